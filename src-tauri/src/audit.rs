@@ -0,0 +1,211 @@
+// ---------------------------------------------------------------------------
+// Audit log — append-only record of actions taken on the user's behalf
+// ---------------------------------------------------------------------------
+// Entries are JSON lines in ~/.nyx/audit.jsonl. Each entry stores the hash of
+// the previous one, so editing or deleting a line breaks the chain and
+// verify_audit reports where. The app and the MCP server both append, so
+// writers hold an OS lock on ~/.nyx/audit.lock around read-and-append.
+// ---------------------------------------------------------------------------
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config;
+
+/// prev_hash of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Serialises appends within this process; `lock_audit_file` covers other
+/// processes.
+static AUDIT_LOCK: std::sync::LazyLock<Mutex<()>> = std::sync::LazyLock::new(|| Mutex::new(()));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEntry {
+    pub seq: u64,
+    /// Unix seconds
+    pub timestamp: u64,
+    /// e.g. "message_sent", "swap_executed", "autonomy_changed"
+    pub action_type: String,
+    pub detail: serde_json::Value,
+    /// "ok" or the error message
+    pub result: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// Outcome of walking the hash chain.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub entries: u64,
+    /// seq of the first entry that failed verification
+    pub broken_at: Option<u64>,
+}
+
+fn audit_path(home: &Path) -> PathBuf {
+    home.join("audit.jsonl")
+}
+
+/// Take an exclusive lock (flock on Unix) on ~/.nyx/audit.lock, held until
+/// the returned file is dropped, so another process can't interleave its
+/// own read-and-append and fork the chain.
+fn lock_audit_file(home: &Path) -> Result<fs::File, String> {
+    let path = home.join("audit.lock");
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open audit lock: {}", e))?;
+    file.lock().map_err(|e| format!("Failed to lock audit log: {}", e))?;
+    Ok(file)
+}
+
+fn entry_hash(
+    seq: u64,
+    timestamp: u64,
+    action_type: &str,
+    detail: &serde_json::Value,
+    result: &str,
+    prev_hash: &str,
+) -> String {
+    let payload = serde_json::json!([seq, timestamp, action_type, detail, result, prev_hash]);
+    hex::encode(Sha256::digest(payload.to_string().as_bytes()))
+}
+
+fn read_entries(home: &Path) -> Result<Vec<AuditEntry>, String> {
+    let content = match fs::read_to_string(audit_path(home)) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read audit log: {}", e)),
+    };
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("Corrupt audit log entry on line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Append an entry to the audit log.
+pub fn record_audit(
+    action_type: &str,
+    detail: serde_json::Value,
+    result: &str,
+) -> Result<AuditEntry, String> {
+    record_audit_in(&config::nyx_home(), action_type, detail, result)
+}
+
+/// `record_audit` for the log kept under `home` instead of `~/.nyx`.
+pub fn record_audit_in(
+    home: &Path,
+    action_type: &str,
+    detail: serde_json::Value,
+    result: &str,
+) -> Result<AuditEntry, String> {
+    let _guard = AUDIT_LOCK.lock().map_err(|e| format!("Audit lock poisoned: {}", e))?;
+    let _file_lock = lock_audit_file(home)?;
+
+    let last = read_entries(home)?.pop();
+    let seq = last.as_ref().map_or(0, |e| e.seq + 1);
+    let prev_hash = last.map_or_else(|| GENESIS_HASH.to_string(), |e| e.hash);
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let hash = entry_hash(seq, timestamp, action_type, &detail, result, &prev_hash);
+    let entry = AuditEntry {
+        seq,
+        timestamp,
+        action_type: action_type.to_string(),
+        detail,
+        result: result.to_string(),
+        prev_hash,
+        hash,
+    };
+
+    let path = audit_path(home);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let line = serde_json::to_string(&entry)
+        .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+    }
+
+    Ok(entry)
+}
+
+/// Record an action, logging (not propagating) audit failures so the action
+/// itself is never blocked by the log.
+pub fn record_audit_lossy(action_type: &str, detail: serde_json::Value, result: &str) {
+    record_audit_lossy_in(&config::nyx_home(), action_type, detail, result)
+}
+
+/// `record_audit_lossy` for the log kept under `home`.
+pub fn record_audit_lossy_in(home: &Path, action_type: &str, detail: serde_json::Value, result: &str) {
+    if let Err(e) = record_audit_in(home, action_type, detail, result) {
+        eprintln!("Audit log write failed ({}): {}", action_type, e);
+    }
+}
+
+/// The most recent `limit` entries, newest first.
+pub fn read_audit(limit: usize) -> Result<Vec<AuditEntry>, String> {
+    read_audit_in(&config::nyx_home(), limit)
+}
+
+/// `read_audit` for the log kept under `home`.
+pub fn read_audit_in(home: &Path, limit: usize) -> Result<Vec<AuditEntry>, String> {
+    let mut entries = read_entries(home)?;
+    entries.reverse();
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+/// Walk the whole log and check every hash and back-link.
+pub fn verify_audit() -> Result<AuditVerification, String> {
+    let entries = read_entries(&config::nyx_home())?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+
+    for (i, e) in entries.iter().enumerate() {
+        let expected =
+            entry_hash(e.seq, e.timestamp, &e.action_type, &e.detail, &e.result, &e.prev_hash);
+        if e.seq != i as u64 || e.prev_hash != prev_hash || e.hash != expected {
+            return Ok(AuditVerification {
+                valid: false,
+                entries: entries.len() as u64,
+                broken_at: Some(e.seq),
+            });
+        }
+        prev_hash = e.hash.clone();
+    }
+
+    Ok(AuditVerification {
+        valid: true,
+        entries: entries.len() as u64,
+        broken_at: None,
+    })
+}
@@ -7,8 +7,12 @@
 // ---------------------------------------------------------------------------
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, Listener, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use nyx_lib::{audit, config};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -16,8 +20,15 @@ use tauri::{AppHandle, Emitter, Listener, Manager, WebviewUrl, WebviewWindowBuil
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserState {
+    /// Browse session this window belongs to
+    #[serde(default)]
+    pub session_key: String,
     pub window_label: String,
+    /// URL the WebView actually shows (tracked from page-load events)
     pub current_url: String,
+    /// document.title of the last fully loaded page
+    #[serde(default)]
+    pub title: String,
     pub is_loading: bool,
 }
 
@@ -30,9 +41,12 @@ pub struct PageContent {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct PageLink {
     pub text: String,
+    /// Absolute URL, resolved against the page URL
+    #[serde(default)]
+    pub url: String,
+    /// Original href attribute, for debugging
     pub href: String,
 }
 
@@ -73,6 +87,8 @@ pub struct BrowserActionResult {
 /// Event payload emitted to the frontend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserEvent {
+    #[serde(default)]
+    pub session_key: String,
     pub kind: String,
     pub url: Option<String>,
     pub title: Option<String>,
@@ -83,35 +99,102 @@ pub struct BrowserEvent {
 // Global state
 // ---------------------------------------------------------------------------
 
-static BROWSER_STATE: std::sync::LazyLock<Mutex<Option<BrowserState>>> =
-    std::sync::LazyLock::new(|| Mutex::new(None));
+/// Open browser windows, keyed by session_key.
+static BROWSER_STATE: std::sync::LazyLock<Mutex<HashMap<String, BrowserState>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Session used when callers don't name one; it owns the original
+/// single "browser" window.
+pub const DEFAULT_SESSION: &str = "agent:default:browse";
 
 const BROWSER_WINDOW_LABEL: &str = "browser";
 
+/// Window label for a session. Tauri labels only allow alphanumerics and
+/// `-/:_`, so the session id is hex-encoded: distinct sessions always get
+/// distinct windows.
+fn window_label(session: &str) -> String {
+    if session == DEFAULT_SESSION {
+        return BROWSER_WINDOW_LABEL.to_string();
+    }
+    let hex: String = session.bytes().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", BROWSER_WINDOW_LABEL, hex)
+}
+
 // ---------------------------------------------------------------------------
 // Window management
 // ---------------------------------------------------------------------------
 
-/// Open (or show) the browser window. Creates it if it doesn't exist.
-pub fn open(app: &AppHandle) -> Result<(), String> {
+/// Open (or show) the session's browser window. Creates it if it doesn't exist.
+pub fn open(app: &AppHandle, session: &str) -> Result<(), String> {
+    let label = window_label(session);
+
     // Check if window already exists
-    if let Some(win) = app.get_webview_window(BROWSER_WINDOW_LABEL) {
+    if let Some(win) = app.get_webview_window(&label) {
         win.show().map_err(|e| format!("Failed to show browser window: {}", e))?;
         win.set_focus().map_err(|e| format!("Failed to focus browser window: {}", e))?;
         return Ok(());
     }
 
     // Create a new secondary window
+    let session_key = session.to_string();
     let builder = WebviewWindowBuilder::new(
         app,
-        BROWSER_WINDOW_LABEL,
+        &label,
         WebviewUrl::External("about:blank".parse().unwrap()),
     )
-    .title("Nyx — Web Browser")
+    .title(if session == DEFAULT_SESSION {
+        "Nyx — Web Browser".to_string()
+    } else {
+        format!("Nyx — Web Browser ({})", session)
+    })
     .inner_size(1200.0, 800.0)
     .min_inner_size(800.0, 500.0)
     .decorations(true)
-    .visible(true);
+    .visible(true)
+    .on_navigation({
+        // Links and redirects inside the page go through the same filter
+        let app = app.clone();
+        let session_key = session_key.clone();
+        move |url| {
+            // Replies from eval_js_async are never real navigations
+            if url.scheme() == EVAL_RESULT_SCHEME {
+                deliver_eval_result(url);
+                return false;
+            }
+            match check_navigation(url) {
+                Ok(()) => true,
+                Err(e) => {
+                    let _ = app.emit(
+                        "browser:event",
+                        BrowserEvent {
+                            session_key: session_key.clone(),
+                            kind: "blocked".to_string(),
+                            url: Some(url.to_string()),
+                            title: None,
+                            message: Some(e),
+                        },
+                    );
+                    false
+                }
+            }
+        }
+    })
+    .on_page_load(move |win, payload| {
+        let url = payload.url().to_string();
+        match payload.event() {
+            PageLoadEvent::Started => update_state(&session_key, |s| {
+                s.current_url = url;
+                s.is_loading = true;
+            }),
+            PageLoadEvent::Finished => {
+                let app = win.app_handle().clone();
+                let session_key = session_key.clone();
+                tauri::async_runtime::spawn(async move {
+                    page_loaded(&app, &session_key, url).await;
+                });
+            }
+        }
+    });
 
     let _win = builder
         .build()
@@ -121,15 +204,22 @@ pub fn open(app: &AppHandle) -> Result<(), String> {
     let mut state = BROWSER_STATE
         .lock()
         .map_err(|_| "Browser state lock poisoned".to_string())?;
-    *state = Some(BrowserState {
-        window_label: BROWSER_WINDOW_LABEL.to_string(),
-        current_url: "about:blank".to_string(),
-        is_loading: false,
-    });
+    state.insert(
+        session.to_string(),
+        BrowserState {
+            session_key: session.to_string(),
+            window_label: label,
+            current_url: "about:blank".to_string(),
+            title: String::new(),
+            is_loading: false,
+        },
+    );
+    drop(state);
 
     let _ = app.emit(
         "browser:event",
         BrowserEvent {
+            session_key: session.to_string(),
             kind: "opened".to_string(),
             url: Some("about:blank".to_string()),
             title: None,
@@ -140,9 +230,45 @@ pub fn open(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Close the browser window.
-pub fn close(app: &AppHandle) -> Result<(), String> {
-    if let Some(win) = app.get_webview_window(BROWSER_WINDOW_LABEL) {
+/// Apply `f` to the session's browser state if its window is open.
+fn update_state(session: &str, f: impl FnOnce(&mut BrowserState)) {
+    if let Ok(mut state) = BROWSER_STATE.lock() {
+        if let Some(s) = state.get_mut(session) {
+            f(s);
+        }
+    }
+}
+
+/// Record a finished page load (including link clicks and redirects inside
+/// the page) and tell the UI the real URL and title.
+async fn page_loaded(app: &AppHandle, session: &str, url: String) {
+    let title = eval_js_async(app, session, "document.title")
+        .await
+        .ok()
+        .filter(|t| !t.starts_with("ok (timeout") && !t.starts_with("{\"error\""))
+        .unwrap_or_default();
+
+    update_state(session, |s| {
+        s.current_url = url.clone();
+        s.title = title.clone();
+        s.is_loading = false;
+    });
+
+    let _ = app.emit(
+        "browser:event",
+        BrowserEvent {
+            session_key: session.to_string(),
+            kind: "loaded".to_string(),
+            url: Some(url),
+            title: Some(title),
+            message: None,
+        },
+    );
+}
+
+/// Close the session's browser window.
+pub fn close(app: &AppHandle, session: &str) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window(&window_label(session)) {
         win.close()
             .map_err(|e| format!("Failed to close browser window: {}", e))?;
     }
@@ -150,11 +276,13 @@ pub fn close(app: &AppHandle) -> Result<(), String> {
     let mut state = BROWSER_STATE
         .lock()
         .map_err(|_| "Browser state lock poisoned".to_string())?;
-    *state = None;
+    state.remove(session);
+    drop(state);
 
     let _ = app.emit(
         "browser:event",
         BrowserEvent {
+            session_key: session.to_string(),
             kind: "closed".to_string(),
             url: None,
             title: None,
@@ -165,12 +293,20 @@ pub fn close(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Get the current browser state.
-pub fn get_state() -> Result<Option<BrowserState>, String> {
+/// Get the session's browser state (None if its window isn't open).
+pub fn get_state(session: &str) -> Result<Option<BrowserState>, String> {
     let state = BROWSER_STATE
         .lock()
         .map_err(|_| "Browser state lock poisoned".to_string())?;
-    Ok(state.clone())
+    Ok(state.get(session).cloned())
+}
+
+/// All open browser sessions.
+pub fn list_states() -> Result<Vec<BrowserState>, String> {
+    let state = BROWSER_STATE
+        .lock()
+        .map_err(|_| "Browser state lock poisoned".to_string())?;
+    Ok(state.values().cloned().collect())
 }
 
 // ---------------------------------------------------------------------------
@@ -178,8 +314,8 @@ pub fn get_state() -> Result<Option<BrowserState>, String> {
 // ---------------------------------------------------------------------------
 
 /// Navigate to a URL.
-pub fn navigate(app: &AppHandle, url: &str) -> Result<(), String> {
-    let win = get_window(app)?;
+pub fn navigate(app: &AppHandle, session: &str, url: &str) -> Result<(), String> {
+    let win = get_window(app, session)?;
 
     // Normalise the URL (add https:// if missing)
     let normalised = if url.starts_with("http://") || url.starts_with("https://") {
@@ -191,11 +327,13 @@ pub fn navigate(app: &AppHandle, url: &str) -> Result<(), String> {
     let parsed: url::Url = normalised
         .parse()
         .map_err(|e| format!("Invalid URL '{}': {}", normalised, e))?;
+    check_navigation(&parsed)?;
 
     // Emit navigating event
     let _ = app.emit(
         "browser:event",
         BrowserEvent {
+            session_key: session.to_string(),
             kind: "navigating".to_string(),
             url: Some(parsed.to_string()),
             title: None,
@@ -206,28 +344,35 @@ pub fn navigate(app: &AppHandle, url: &str) -> Result<(), String> {
     win.navigate(parsed)
         .map_err(|e| format!("Navigation failed: {}", e))?;
 
-    // Update state
-    if let Ok(mut state) = BROWSER_STATE.lock() {
-        if let Some(ref mut s) = *state {
-            s.current_url = normalised;
-            s.is_loading = true;
-        }
-    }
+    // Page-load events correct this if the site redirects
+    update_state(session, |s| {
+        s.current_url = normalised;
+        s.is_loading = true;
+    });
 
     Ok(())
 }
 
+/// Apply the user's allow/blocklist to http(s) URLs. Other schemes
+/// (about:blank, data:) have no host and are allowed.
+fn check_navigation(url: &url::Url) -> Result<(), String> {
+    match (url.scheme(), url.host_str()) {
+        ("http" | "https", Some(host)) => config::check_browser_host(host),
+        _ => Ok(()),
+    }
+}
+
 /// Go back in browser history.
-pub fn go_back(app: &AppHandle) -> Result<(), String> {
-    let win = get_window(app)?;
+pub fn go_back(app: &AppHandle, session: &str) -> Result<(), String> {
+    let win = get_window(app, session)?;
     win.eval("window.history.back()")
         .map_err(|e| format!("go_back failed: {}", e))?;
     Ok(())
 }
 
 /// Go forward in browser history.
-pub fn go_forward(app: &AppHandle) -> Result<(), String> {
-    let win = get_window(app)?;
+pub fn go_forward(app: &AppHandle, session: &str) -> Result<(), String> {
+    let win = get_window(app, session)?;
     win.eval("window.history.forward()")
         .map_err(|e| format!("go_forward failed: {}", e))?;
     Ok(())
@@ -238,7 +383,7 @@ pub fn go_forward(app: &AppHandle) -> Result<(), String> {
 // ---------------------------------------------------------------------------
 
 /// Click the first element matching a CSS selector.
-pub fn click(app: &AppHandle, selector: &str) -> Result<String, String> {
+pub async fn click(app: &AppHandle, session: &str, selector: &str) -> Result<String, String> {
     let js = format!(
         r#"(function() {{
             var el = document.querySelector({sel});
@@ -247,13 +392,16 @@ pub fn click(app: &AppHandle, selector: &str) -> Result<String, String> {
             el.click();
             return JSON.stringify({{ ok: true, tag: el.tagName, text: (el.textContent || '').substring(0, 100) }});
         }})()"#,
-        sel = serde_json::to_string(selector).unwrap_or_else(|_| format!("\"{}\"", selector))
+        sel = js_string(selector, "selector")?
     );
-    eval_js(app, &js)
+    eval_js_async(app, session, &js).await
 }
 
-/// Focus an element and type text into it.
-pub fn type_text(app: &AppHandle, selector: &str, text: &str) -> Result<String, String> {
+/// Focus an element and type text into it. Refuses card numbers, Nyx's
+/// stored secrets and credential or payment fields; see `check_entry`.
+pub async fn type_text(app: &AppHandle, session: &str, selector: &str, text: &str) -> Result<String, String> {
+    check_entry(app, session, "type", selector, text).await?;
+
     let js = format!(
         r#"(function() {{
             var el = document.querySelector({sel});
@@ -268,14 +416,14 @@ pub fn type_text(app: &AppHandle, selector: &str, text: &str) -> Result<String,
             el.dispatchEvent(new Event('change', {{ bubbles: true }}));
             return JSON.stringify({{ ok: true, value: el.value }});
         }})()"#,
-        sel = serde_json::to_string(selector).unwrap_or_else(|_| format!("\"{}\"", selector)),
-        txt = serde_json::to_string(text).unwrap_or_else(|_| format!("\"{}\"", text))
+        sel = js_string(selector, "selector")?,
+        txt = js_string(text, "text")?
     );
-    eval_js(app, &js)
+    eval_js_async(app, session, &js).await
 }
 
 /// Scroll the page.
-pub fn scroll(app: &AppHandle, direction: &str, amount: i32) -> Result<String, String> {
+pub fn scroll(app: &AppHandle, session: &str, direction: &str, amount: i32) -> Result<String, String> {
     let pixels = amount * 300; // each unit ≈ 300px
     let js = match direction {
         "up" => format!("window.scrollBy(0, -{}); 'scrolled up'", pixels),
@@ -284,11 +432,14 @@ pub fn scroll(app: &AppHandle, direction: &str, amount: i32) -> Result<String, S
         "right" => format!("window.scrollBy({}, 0); 'scrolled right'", pixels),
         _ => format!("window.scrollBy(0, {}); 'scrolled down'", pixels),
     };
-    eval_js(app, &js)
+    eval_js(app, session, &js)
 }
 
-/// Select an option in a dropdown.
-pub fn select_option(app: &AppHandle, selector: &str, value: &str) -> Result<String, String> {
+/// Select an option in a dropdown. Refuses the same fields and values as
+/// `type_text`.
+pub async fn select_option(app: &AppHandle, session: &str, selector: &str, value: &str) -> Result<String, String> {
+    check_entry(app, session, "select", selector, value).await?;
+
     let js = format!(
         r#"(function() {{
             var el = document.querySelector({sel});
@@ -297,14 +448,14 @@ pub fn select_option(app: &AppHandle, selector: &str, value: &str) -> Result<Str
             el.dispatchEvent(new Event('change', {{ bubbles: true }}));
             return JSON.stringify({{ ok: true, value: el.value }});
         }})()"#,
-        sel = serde_json::to_string(selector).unwrap_or_else(|_| format!("\"{}\"", selector)),
-        val = serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+        sel = js_string(selector, "selector")?,
+        val = js_string(value, "value")?
     );
-    eval_js(app, &js)
+    eval_js_async(app, session, &js).await
 }
 
 /// Read the current page content (URL, title, visible text).
-pub fn read_page(app: &AppHandle) -> Result<String, String> {
+pub async fn read_page(app: &AppHandle, session: &str) -> Result<String, String> {
     let js = r#"(function() {
         // Get visible text, limiting to avoid huge payloads
         var body = document.body;
@@ -326,11 +477,11 @@ pub fn read_page(app: &AppHandle) -> Result<String, String> {
             text: text
         });
     })()"#;
-    eval_js(app, js)
+    eval_js_async(app, session, js).await
 }
 
-/// Read all links on the page.
-pub fn read_links(app: &AppHandle) -> Result<String, String> {
+/// Read all links on the page, with hrefs resolved to absolute URLs.
+pub async fn read_links(app: &AppHandle, session: &str) -> Result<String, String> {
     let js = r#"(function() {
         var links = [];
         var els = document.querySelectorAll('a[href]');
@@ -340,13 +491,65 @@ pub fn read_links(app: &AppHandle) -> Result<String, String> {
             var href = el.getAttribute('href') || '';
             if (text || href) links.push({ text: text, href: href });
         }
-        return JSON.stringify(links);
+        return JSON.stringify({ base: document.baseURI || window.location.href, links: links });
     })()"#;
-    eval_js(app, js)
+    let raw = eval_js_async(app, session, js).await?;
+
+    let fallback_base = get_state(session)
+        .ok()
+        .flatten()
+        .map(|s| s.current_url)
+        .unwrap_or_default();
+    // An error object or timeout status is passed through unchanged
+    Ok(resolve_links(&raw, &fallback_base).unwrap_or(raw))
+}
+
+/// Resolve a single href against a base URL. Returns None for in-page
+/// `#fragment` anchors, `javascript:` links and unparseable hrefs.
+pub fn resolve_href(base: &url::Url, href: &str) -> Option<url::Url> {
+    let href = href.trim();
+    if href.is_empty() || href.starts_with('#') {
+        return None;
+    }
+    if href.get(..11).is_some_and(|p| p.eq_ignore_ascii_case("javascript:")) {
+        return None;
+    }
+    base.join(href).ok()
+}
+
+/// Turn the read_links JS payload (`{ base, links: [{text, href}] }`) into
+/// a JSON array of PageLink with absolute URLs.
+pub fn resolve_links(raw: &str, fallback_base: &str) -> Result<String, String> {
+    #[derive(Deserialize)]
+    struct RawLinks {
+        base: Option<String>,
+        links: Vec<PageLink>,
+    }
+
+    let payload: RawLinks = serde_json::from_str(raw)
+        .map_err(|e| format!("Failed to parse links payload: {}", e))?;
+    let base_str = payload.base.as_deref().unwrap_or(fallback_base);
+    let base = url::Url::parse(base_str)
+        .map_err(|e| format!("Invalid page URL '{}': {}", base_str, e))?;
+
+    let links: Vec<PageLink> = payload
+        .links
+        .into_iter()
+        .filter_map(|link| {
+            let resolved = resolve_href(&base, &link.href)?;
+            Some(PageLink {
+                text: link.text,
+                url: resolved.to_string(),
+                href: link.href,
+            })
+        })
+        .collect();
+
+    serde_json::to_string(&links).map_err(|e| format!("Failed to serialize links: {}", e))
 }
 
 /// Read form fields on the page.
-pub fn read_forms(app: &AppHandle) -> Result<String, String> {
+pub async fn read_forms(app: &AppHandle, session: &str) -> Result<String, String> {
     let js = r#"(function() {
         var fields = [];
         var els = document.querySelectorAll('input, select, textarea');
@@ -382,12 +585,180 @@ pub fn read_forms(app: &AppHandle) -> Result<String, String> {
         }
         return JSON.stringify(fields);
     })()"#;
-    eval_js(app, js)
+    eval_js_async(app, session, js).await
+}
+
+/// Screenshots kept in ~/.nyx/browser-screenshots/; older ones are pruned.
+const SCREENSHOT_KEEP: usize = 20;
+/// Time for the window manager to raise the browser window before capture.
+const SCREENSHOT_RAISE_MS: u64 = 200;
+
+/// Capture the browser window's visible area to a PNG under
+/// ~/.nyx/browser-screenshots/. The window is raised and kept on top while
+/// capturing so no other app's window ends up in the image; if it can't be
+/// focused the screenshot is refused. Returns `{ path, width, height }` as
+/// JSON, with the PNG's own pixel size.
+pub async fn screenshot(app: &AppHandle, session: &str) -> Result<String, String> {
+    let win = get_window(app, session)?;
+    let dir = screenshot_dir()?;
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = dir.join(format!("{}.png", ts));
+
+    let _ = win.unminimize();
+    win.show().map_err(|e| format!("Failed to show browser window: {}", e))?;
+    win.set_focus().map_err(|e| format!("Failed to focus browser window: {}", e))?;
+    win.set_always_on_top(true)
+        .map_err(|e| format!("Failed to raise browser window: {}", e))?;
+    let captured = capture_window(&win, &path).await;
+    let _ = win.set_always_on_top(false);
+    if let Err(e) = captured {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read screenshot: {}", e))?;
+    let (width, height) = png_dimensions(&bytes)?;
+    prune_screenshots(&dir, SCREENSHOT_KEEP);
+
+    Ok(serde_json::json!({
+        "path": path.to_string_lossy(),
+        "width": width,
+        "height": height,
+    })
+    .to_string())
+}
+
+/// Capture the (already raised) browser window to `path`, refusing if it
+/// isn't the focused window.
+async fn capture_window(win: &tauri::WebviewWindow, path: &std::path::Path) -> Result<(), String> {
+    tokio::time::sleep(std::time::Duration::from_millis(SCREENSHOT_RAISE_MS)).await;
+    let focused = win
+        .is_focused()
+        .map_err(|e| format!("Failed to read browser window focus: {}", e))?;
+    if !focused {
+        return Err("Screenshot refused: the browser window couldn't be brought to the front".to_string());
+    }
+    let pos = win
+        .inner_position()
+        .map_err(|e| format!("Failed to read browser window position: {}", e))?;
+    let size = win
+        .inner_size()
+        .map_err(|e| format!("Failed to read browser window size: {}", e))?;
+    let scale = win.scale_factor().unwrap_or(1.0);
+    capture_region(
+        (pos.x as f64 / scale).round() as i32,
+        (pos.y as f64 / scale).round() as i32,
+        (size.width as f64 / scale).round() as u32,
+        (size.height as f64 / scale).round() as u32,
+        path,
+    )
+    .await
+}
+
+/// ~/.nyx/browser-screenshots/, created 0700 since captures can show
+/// anything on the page.
+fn screenshot_dir() -> Result<std::path::PathBuf, String> {
+    let dir = config::nyx_home().join("browser-screenshots");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to set permissions on {}: {}", dir.display(), e))?;
+    }
+    Ok(dir)
+}
+
+/// Delete all but the newest `keep` screenshots. File names are millisecond
+/// timestamps, so name order is capture order.
+fn prune_screenshots(dir: &std::path::Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut files: Vec<std::path::PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    files.sort();
+    let excess = files.len().saturating_sub(keep);
+    for path in files.into_iter().take(excess) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Width and height from a PNG's IHDR chunk.
+fn png_dimensions(bytes: &[u8]) -> Result<(u32, u32), String> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if bytes.len() < 24 || &bytes[..8] != SIGNATURE || &bytes[12..16] != b"IHDR" {
+        return Err("Screenshot is not a valid PNG".to_string());
+    }
+    let width = u32::from_be_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+    let height = u32::from_be_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+    Ok((width, height))
+}
+
+/// Capture a screen region (logical points) to `path`.
+#[cfg(target_os = "macos")]
+async fn capture_region(x: i32, y: i32, w: u32, h: u32, path: &std::path::Path) -> Result<(), String> {
+    let output = tokio::process::Command::new("screencapture")
+        .args(["-x", "-t", "png", "-R", &format!("{},{},{},{}", x, y, w, h)])
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run screencapture: {}", e))?;
+    if !output.status.success() || !path.exists() {
+        return Err(format!(
+            "Screenshot failed (grant Nyx Screen Recording permission in System Settings): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
 }
 
-/// Execute arbitrary JavaScript in the browser window.
-pub fn execute_js(app: &AppHandle, code: &str) -> Result<String, String> {
-    eval_js(app, code)
+#[cfg(not(target_os = "macos"))]
+async fn capture_region(_x: i32, _y: i32, _w: u32, _h: u32, _path: &std::path::Path) -> Result<(), String> {
+    Err("Browser screenshots are only supported on macOS".to_string())
+}
+
+/// Default and maximum wait_for timeouts.
+const WAIT_FOR_DEFAULT_MS: u64 = 10_000;
+const WAIT_FOR_MAX_MS: u64 = 30_000;
+const WAIT_FOR_POLL_MS: u64 = 250;
+
+/// Poll until an element matching `selector` exists or `timeout_ms` elapses.
+/// Returns `{ found, selector, waited_ms }` as JSON; a timeout is not an error.
+pub async fn wait_for(app: &AppHandle, session: &str, selector: &str, timeout_ms: u64) -> Result<String, String> {
+    let js = format!(
+        "JSON.stringify({{ found: !!document.querySelector({}) }})",
+        js_string(selector, "selector")?
+    );
+    let timeout_ms = timeout_ms.min(WAIT_FOR_MAX_MS);
+    let started = std::time::Instant::now();
+
+    let found = loop {
+        let raw = eval_js_async(app, session, &js).await?;
+        let hit = serde_json::from_str::<serde_json::Value>(&raw)
+            .ok()
+            .and_then(|v| v.get("found").and_then(|f| f.as_bool()))
+            .unwrap_or(false);
+        if hit {
+            break true;
+        }
+        if started.elapsed().as_millis() as u64 >= timeout_ms {
+            break false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(WAIT_FOR_POLL_MS)).await;
+    };
+
+    Ok(serde_json::json!({
+        "found": found,
+        "selector": selector,
+        "waited_ms": started.elapsed().as_millis() as u64,
+    })
+    .to_string())
 }
 
 /// Wait for a specified number of milliseconds (non-blocking on Rust side).
@@ -401,8 +772,11 @@ pub async fn wait(ms: u64) -> Result<(), String> {
 // ---------------------------------------------------------------------------
 
 /// Execute a BrowserAction (from Claude's tool_use) and return the result.
+/// Arbitrary scripts are not an agent action: a script can
+/// read or fill any field, so the credential guard can't vet it.
 pub async fn execute_action(
     app: &AppHandle,
+    session: &str,
     action: &BrowserAction,
 ) -> BrowserActionResult {
     let action_name = action.action.as_str();
@@ -411,6 +785,7 @@ pub async fn execute_action(
     let _ = app.emit(
         "browser:action",
         serde_json::json!({
+            "session_key": session,
             "action": action_name,
             "url": action.url,
             "selector": action.selector,
@@ -421,7 +796,7 @@ pub async fn execute_action(
     let result = match action_name {
         "navigate" => {
             let url = action.url.as_deref().unwrap_or("about:blank");
-            navigate(app, url).map(|_| format!("Navigated to {}", url))
+            navigate(app, session, url).map(|_| format!("Navigated to {}", url))
         }
         "click" => {
             let sel = action
@@ -429,38 +804,42 @@ pub async fn execute_action(
                 .as_deref()
                 .ok_or_else(|| "click requires a 'selector'".to_string());
             match sel {
-                Ok(s) => click(app, s),
+                Ok(s) => click(app, session, s).await,
                 Err(e) => Err(e),
             }
         }
         "type" => {
             let sel = action.selector.as_deref().unwrap_or("input");
             let txt = action.text.as_deref().unwrap_or("");
-            type_text(app, sel, txt)
+            type_text(app, session, sel, txt).await
         }
         "scroll" => {
             let dir = action.direction.as_deref().unwrap_or("down");
             let amt = action.amount.unwrap_or(3);
-            scroll(app, dir, amt)
+            scroll(app, session, dir, amt)
         }
-        "read_page" => read_page(app),
-        "read_links" => read_links(app),
-        "read_forms" => read_forms(app),
+        "read_page" => read_page(app, session).await,
+        "read_links" => read_links(app, session).await,
+        "read_forms" => read_forms(app, session).await,
         "select" => {
             let sel = action.selector.as_deref().unwrap_or("select");
             let val = action.value.as_deref().unwrap_or("");
-            select_option(app, sel, val)
+            select_option(app, session, sel, val).await
         }
-        "back" => go_back(app).map(|_| "Went back".to_string()),
-        "forward" => go_forward(app).map(|_| "Went forward".to_string()),
+        "back" => go_back(app, session).map(|_| "Went back".to_string()),
+        "forward" => go_forward(app, session).map(|_| "Went forward".to_string()),
         "wait" => {
             let ms = action.amount.unwrap_or(2000) as u64;
             wait(ms).await.map(|_| format!("Waited {}ms", ms))
         }
-        "execute_js" => {
-            let code = action.text.as_deref().unwrap_or("");
-            execute_js(app, code)
-        }
+        "wait_for" => match action.selector.as_deref() {
+            Some(sel) => {
+                let ms = action.amount.map(|a| a.max(0) as u64).unwrap_or(WAIT_FOR_DEFAULT_MS);
+                wait_for(app, session, sel, ms).await
+            }
+            None => Err("wait_for requires a 'selector'".to_string()),
+        },
+        "screenshot" => screenshot(app, session).await,
         _ => Err(format!("Unknown browser action: {}", action_name)),
     };
 
@@ -484,17 +863,30 @@ pub async fn execute_action(
 // Tool definition for Claude API
 // ---------------------------------------------------------------------------
 
-/// Returns the browser tool definition to send to Claude.
+/// Whether this platform can capture the browser window (see `capture_region`).
+const SCREENSHOTS_SUPPORTED: bool = cfg!(target_os = "macos");
+
+/// Returns the browser tool definition to send to Claude. `screenshot` is
+/// only offered where it works.
 pub fn tool_definition() -> serde_json::Value {
+    let mut actions = vec![
+        "navigate", "click", "type", "scroll", "read_page", "read_links", "read_forms", "select",
+        "back", "forward", "wait", "wait_for",
+    ];
+    let mut description = "Navigate and interact with websites on the user's behalf. Use this to browse the web, fill forms, click buttons, read page content, and complete tasks like booking travel or ordering groceries.".to_string();
+    if SCREENSHOTS_SUPPORTED {
+        actions.push("screenshot");
+        description.push_str(" Use 'screenshot' to see the page layout when text alone isn't enough (image-only buttons, visual state).");
+    }
     serde_json::json!({
         "name": "browser",
-        "description": "Navigate and interact with websites on the user's behalf. Use this to browse the web, fill forms, click buttons, read page content, and complete tasks like booking travel or ordering groceries.",
+        "description": description,
         "input_schema": {
             "type": "object",
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["navigate", "click", "type", "scroll", "read_page", "read_links", "read_forms", "select", "back", "forward", "wait", "execute_js"],
+                    "enum": actions,
                     "description": "The browser action to perform"
                 },
                 "url": {
@@ -503,11 +895,11 @@ pub fn tool_definition() -> serde_json::Value {
                 },
                 "selector": {
                     "type": "string",
-                    "description": "CSS selector for the target element (for 'click', 'type', 'select' actions)"
+                    "description": "CSS selector for the target element (for 'click', 'type', 'select', 'wait_for' actions)"
                 },
                 "text": {
                     "type": "string",
-                    "description": "Text to type (for 'type' action) or JavaScript code (for 'execute_js' action)"
+                    "description": "Text to type (for 'type' action)"
                 },
                 "direction": {
                     "type": "string",
@@ -520,7 +912,7 @@ pub fn tool_definition() -> serde_json::Value {
                 },
                 "amount": {
                     "type": "integer",
-                    "description": "Scroll amount in units (for 'scroll' action, default 3) or wait time in ms (for 'wait' action, default 2000)"
+                    "description": "Scroll amount in units (for 'scroll' action, default 3), wait time in ms (for 'wait' action, default 2000) or timeout in ms (for 'wait_for' action, default 10000, max 30000)"
                 }
             },
             "required": ["action"]
@@ -529,64 +921,405 @@ pub fn tool_definition() -> serde_json::Value {
 }
 
 // ---------------------------------------------------------------------------
-// Agent loop — Claude controls the browser
+// Agent loop — an LLM controls the browser
 // ---------------------------------------------------------------------------
 
-/// Maximum tool-use iterations per request (safety limit).
-const MAX_ITERATIONS: usize = 25;
-
-/// Read the Anthropic API key from docker.env.
-fn read_anthropic_key() -> Result<String, String> {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let env_path = std::path::PathBuf::from(&home).join("openclaw/docker.env");
-    let content = std::fs::read_to_string(&env_path)
-        .map_err(|e| format!("Failed to read docker.env: {}", e))?;
-    for line in content.lines() {
-        if line.starts_with("ANTHROPIC_API_KEY=") {
-            let key = line.trim_start_matches("ANTHROPIC_API_KEY=").to_string();
-            if !key.is_empty() {
-                return Ok(key);
+/// Default tool-use iterations per request, and the hard ceiling callers
+/// can raise it to (safety limit).
+pub const DEFAULT_MAX_ITERATIONS: usize = 25;
+const MAX_ITERATIONS_CAP: usize = 100;
+
+/// Stop when the agent issues the same action this many times in a row and
+/// gets the same result back — it's almost always stuck on a broken selector.
+const REPEATED_ACTION_LIMIT: usize = 3;
+
+/// Actions that are expected to repeat (paging through a long page, polling
+/// for content), so they never count towards REPEATED_ACTION_LIMIT.
+const REPEATABLE_ACTIONS: &[&str] = &[
+    "scroll", "wait", "wait_for", "read_page", "read_links", "read_forms", "screenshot",
+];
+
+const SYSTEM_PROMPT: &str = "You are Nyx, a privacy-focused AI assistant helping the user browse the web. \
+    You have a browser tool to navigate websites, click elements, fill forms, and read page content. \
+    Work step by step: navigate to the site, read the page, interact with elements as needed. \
+    Always read the page after navigating to understand what's on screen. \
+    If content loads dynamically, use wait_for with a selector before reading or interacting. \
+    For login forms or payment pages, STOP and tell the user to complete those steps manually. \
+    Never enter passwords, credit card numbers, or other sensitive credentials.";
+
+/// Read an API key from docker.env.
+fn read_api_key(var: &str) -> Result<String, String> {
+    config::read_docker_env()?
+        .remove(var)
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| format!("{} not found in docker.env", var))
+}
+
+/// A browser tool call requested by the model.
+struct ToolCall {
+    id: String,
+    input: serde_json::Value,
+}
+
+/// What the model did on one turn.
+enum AgentTurn {
+    Tools(Vec<ToolCall>),
+    Done(String),
+}
+
+/// Result of one tool call, before provider-specific encoding.
+enum ToolOutput {
+    Text(String),
+    Screenshot { path: String },
+}
+
+/// Tool-call protocol of one LLM backend. The loop in `run_agent_loop` only
+/// sees tool calls and results; each provider owns its message history.
+trait BrowseProvider {
+    /// Send the conversation so far and parse the model's reply.
+    async fn next_turn(&mut self, client: &reqwest::Client) -> Result<AgentTurn, String>;
+    /// Append results for the calls returned by the last turn.
+    fn push_tool_results(&mut self, results: Vec<(String, ToolOutput)>);
+}
+
+/// Anthropic Messages API (`tool_use` / `tool_result` blocks).
+struct AnthropicProvider {
+    api_key: String,
+    model: String,
+    messages: Vec<serde_json::Value>,
+}
+
+impl BrowseProvider for AnthropicProvider {
+    async fn next_turn(&mut self, client: &reqwest::Client) -> Result<AgentTurn, String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": 4096,
+            "system": SYSTEM_PROMPT,
+            "tools": [tool_definition()],
+            "messages": self.messages
+        });
+
+        let response = client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Anthropic API request failed: {}", e))?;
+        let resp = read_json_response(response, "Anthropic").await?;
+
+        let blocks = resp
+            .get("content")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let stop_reason = resp
+            .get("stop_reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("end_turn");
+
+        if stop_reason == "tool_use" {
+            let calls = blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|v| v.as_str()) == Some("tool_use"))
+                .map(|b| ToolCall {
+                    id: b.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    input: b.get("input").cloned().unwrap_or(serde_json::json!({})),
+                })
+                .collect();
+            self.messages.push(serde_json::json!({ "role": "assistant", "content": blocks }));
+            return Ok(AgentTurn::Tools(calls));
+        }
+
+        let text = blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|v| v.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|v| v.as_str()))
+            .collect::<String>();
+        Ok(AgentTurn::Done(text))
+    }
+
+    fn push_tool_results(&mut self, results: Vec<(String, ToolOutput)>) {
+        let blocks: Vec<serde_json::Value> = results
+            .into_iter()
+            .map(|(id, output)| {
+                let content = match output {
+                    ToolOutput::Text(text) => serde_json::json!(text),
+                    ToolOutput::Screenshot { path } => match screenshot_base64(&path) {
+                        Ok(data) => serde_json::json!([
+                            {
+                                "type": "image",
+                                "source": { "type": "base64", "media_type": "image/png", "data": data }
+                            },
+                            { "type": "text", "text": format!("Screenshot saved to {}", path) }
+                        ]),
+                        Err(e) => serde_json::json!(format!("Error: {}", e)),
+                    },
+                };
+                serde_json::json!({ "type": "tool_result", "tool_use_id": id, "content": content })
+            })
+            .collect();
+        self.messages.push(serde_json::json!({ "role": "user", "content": blocks }));
+    }
+}
+
+/// OpenAI-compatible chat completions with function tools (OpenAI, Venice,
+/// NEAR AI, and Ollama's /v1 endpoint).
+struct OpenAiCompatProvider {
+    label: &'static str,
+    base_url: &'static str,
+    api_key: Option<String>,
+    model: String,
+    /// Whether screenshots can be sent back as image input
+    vision: bool,
+    messages: Vec<serde_json::Value>,
+}
+
+impl OpenAiCompatProvider {
+    fn new(
+        label: &'static str,
+        base_url: &'static str,
+        api_key: Option<String>,
+        model: String,
+        vision: bool,
+        user_message: &str,
+    ) -> Self {
+        Self {
+            label,
+            base_url,
+            api_key,
+            model,
+            vision,
+            messages: vec![
+                serde_json::json!({ "role": "system", "content": SYSTEM_PROMPT }),
+                serde_json::json!({ "role": "user", "content": user_message }),
+            ],
+        }
+    }
+}
+
+impl BrowseProvider for OpenAiCompatProvider {
+    async fn next_turn(&mut self, client: &reqwest::Client) -> Result<AgentTurn, String> {
+        let tool = tool_definition();
+        let body = serde_json::json!({
+            "model": self.model,
+            "messages": self.messages,
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": tool["name"],
+                    "description": tool["description"],
+                    "parameters": tool["input_schema"],
+                }
+            }],
+            "tool_choice": "auto"
+        });
+
+        let mut request = client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Content-Type", "application/json")
+            .json(&body);
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("{} API request failed: {}", self.label, e))?;
+        let resp = read_json_response(response, self.label).await?;
+
+        let message = resp
+            .pointer("/choices/0/message")
+            .cloned()
+            .ok_or_else(|| format!("{} response has no message", self.label))?;
+        let tool_calls = message
+            .get("tool_calls")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        if !tool_calls.is_empty() {
+            let calls = tool_calls
+                .iter()
+                .map(|c| ToolCall {
+                    id: c.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    // Arguments arrive as a JSON string (Ollama sometimes sends an object)
+                    input: match c.pointer("/function/arguments") {
+                        Some(serde_json::Value::String(s)) => {
+                            serde_json::from_str(s).unwrap_or(serde_json::json!({}))
+                        }
+                        Some(v) => v.clone(),
+                        None => serde_json::json!({}),
+                    },
+                })
+                .collect();
+            self.messages.push(message);
+            return Ok(AgentTurn::Tools(calls));
+        }
+
+        let text = message
+            .get("content")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        Ok(AgentTurn::Done(text))
+    }
+
+    fn push_tool_results(&mut self, results: Vec<(String, ToolOutput)>) {
+        // Tool messages are text-only; screenshots follow as a user image message
+        let mut images = Vec::new();
+        for (id, output) in results {
+            let content = match output {
+                ToolOutput::Text(text) => text,
+                ToolOutput::Screenshot { path } => {
+                    if self.vision {
+                        images.push(path.clone());
+                    }
+                    format!("Screenshot saved to {}", path)
+                }
+            };
+            self.messages.push(serde_json::json!({
+                "role": "tool",
+                "tool_call_id": id,
+                "content": content
+            }));
+        }
+
+        for path in images {
+            if let Ok(data) = screenshot_base64(&path) {
+                self.messages.push(serde_json::json!({
+                    "role": "user",
+                    "content": [
+                        { "type": "text", "text": "Screenshot of the browser window:" },
+                        { "type": "image_url", "image_url": { "url": format!("data:image/png;base64,{}", data) } }
+                    ]
+                }));
             }
         }
     }
-    Err("Anthropic API key not found in docker.env".to_string())
 }
 
-/// Send a message to Claude with the browser tool and run the agent loop.
-/// Claude can issue tool_use calls which are executed against the browser,
-/// and the loop continues until Claude produces a text response or the limit is hit.
+/// Read a JSON body, turning non-2xx statuses into errors.
+async fn read_json_response(response: reqwest::Response, label: &str) -> Result<serde_json::Value, String> {
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {} response: {}", label, e))?;
+    if !status.is_success() {
+        return Err(format!("{} API error ({}): {}", label, status, text));
+    }
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {} response: {}", label, e))
+}
+
+/// Read a saved screenshot as base64.
+fn screenshot_base64(path: &str) -> Result<String, String> {
+    use base64::Engine;
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read screenshot: {}", e))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Send a message to the configured LLM provider with the browser tool and
+/// run the agent loop. Tool calls are executed against the session's
+/// browser window until the model produces a text response, the iteration
+/// limit is hit, or it repeats itself.
 pub async fn send_browse_message(
     app: &AppHandle,
     user_message: String,
-    _session_key: String,
+    session_key: String,
+    max_iterations: Option<usize>,
 ) -> Result<String, String> {
-    let api_key = read_anthropic_key()?;
+    let max_iterations = max_iterations
+        .unwrap_or(DEFAULT_MAX_ITERATIONS)
+        .clamp(1, MAX_ITERATIONS_CAP);
+
+    let caps = config::read_current_config()
+        .map(|c| c.capabilities)
+        .unwrap_or_default();
+    let model = caps.default_model().to_string();
+
+    // Each session drives its own window; open it on first use
+    if get_window(app, &session_key).is_err() {
+        open(app, &session_key)?;
+    }
 
-    let client = reqwest::Client::builder()
+    // Ollama runs on localhost; every other provider is remote
+    let destination = if caps.default_llm_provider == "ollama" {
+        config::HttpDestination::Local
+    } else {
+        config::HttpDestination::Remote
+    };
+    let client = config::http_client_builder(destination)?
         .timeout(std::time::Duration::from_secs(120))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
 
-    let tool_def = tool_definition();
+    let compat = |label, base_url, api_key, vision| {
+        OpenAiCompatProvider::new(label, base_url, api_key, model.clone(), vision, &user_message)
+    };
+    match caps.default_llm_provider.as_str() {
+        "openai" => {
+            let provider = compat("OpenAI", "https://api.openai.com/v1", Some(read_api_key("OPENAI_API_KEY")?), true);
+            run_agent_loop(app, &session_key, &client, provider, max_iterations).await
+        }
+        "venice" => {
+            let provider = compat("Venice", "https://api.venice.ai/api/v1", Some(read_api_key("VENICE_API_KEY")?), false);
+            run_agent_loop(app, &session_key, &client, provider, max_iterations).await
+        }
+        "nearai" => {
+            let provider = compat("NEAR AI", "https://cloud-api.near.ai/v1", Some(read_api_key("NEARAI_API_KEY")?), false);
+            run_agent_loop(app, &session_key, &client, provider, max_iterations).await
+        }
+        "ollama" => {
+            let provider = compat("Ollama", "http://localhost:11434/v1", None, false);
+            run_agent_loop(app, &session_key, &client, provider, max_iterations).await
+        }
+        _ => {
+            let provider = AnthropicProvider {
+                api_key: read_api_key("ANTHROPIC_API_KEY")?,
+                model,
+                messages: vec![serde_json::json!({ "role": "user", "content": user_message })],
+            };
+            run_agent_loop(app, &session_key, &client, provider, max_iterations).await
+        }
+    }
+}
 
-    // Build initial messages
-    let system_prompt = "You are Nyx, a privacy-focused AI assistant helping the user browse the web. \
-        You have a browser tool to navigate websites, click elements, fill forms, and read page content. \
-        Work step by step: navigate to the site, read the page, interact with elements as needed. \
-        Always read the page after navigating to understand what's on screen. \
-        For login forms or payment pages, STOP and tell the user to complete those steps manually. \
-        Never enter passwords, credit card numbers, or other sensitive credentials.";
+/// Drive `provider` until it answers in text, stops repeating itself, or
+/// runs out of iterations.
+async fn run_agent_loop<P: BrowseProvider>(
+    app: &AppHandle,
+    session_key: &str,
+    client: &reqwest::Client,
+    mut provider: P,
+    max_iterations: usize,
+) -> Result<String, String> {
+    let complete = |message: &str| {
+        let _ = app.emit(
+            "browser:event",
+            BrowserEvent {
+                session_key: session_key.to_string(),
+                kind: "complete".to_string(),
+                url: None,
+                title: None,
+                message: Some(message.to_string()),
+            },
+        );
+    };
 
-    let mut messages = vec![serde_json::json!({
-        "role": "user",
-        "content": user_message
-    })];
+    // Last action input with its result, and how many times in a row that
+    // same pair has come back
+    let mut last_attempt: Option<(serde_json::Value, String)> = None;
+    let mut repeat_count = 0usize;
 
-    for iteration in 0..MAX_ITERATIONS {
+    for iteration in 0..max_iterations {
         // Emit iteration event
         let _ = app.emit(
             "browser:event",
             BrowserEvent {
+                session_key: session_key.to_string(),
                 kind: "thinking".to_string(),
                 url: None,
                 title: None,
@@ -594,277 +1327,625 @@ pub async fn send_browse_message(
             },
         );
 
-        let body = serde_json::json!({
-            "model": "claude-sonnet-4-20250514",
-            "max_tokens": 4096,
-            "system": system_prompt,
-            "tools": [tool_def],
-            "messages": messages
-        });
-
-        let response = client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("Anthropic API request failed: {}", e))?;
+        let calls = match provider.next_turn(client).await? {
+            AgentTurn::Done(text) => {
+                complete(&text);
+                return Ok(text);
+            }
+            AgentTurn::Tools(calls) => calls,
+        };
+
+        let mut results = Vec::new();
+        for call in calls {
+            // Parse the browser action
+            let action: BrowserAction = serde_json::from_value(call.input.clone())
+                .unwrap_or(BrowserAction {
+                    action: "read_page".to_string(),
+                    url: None,
+                    selector: None,
+                    text: None,
+                    direction: None,
+                    value: None,
+                    amount: None,
+                });
+
+            // Execute the action
+            let result = execute_action(app, session_key, &action).await;
+
+            if REPEATABLE_ACTIONS.contains(&action.action.as_str()) {
+                last_attempt = None;
+                repeat_count = 0;
+            } else {
+                let attempt = (
+                    call.input.clone(),
+                    format!("{}|{}", result.result, result.error.as_deref().unwrap_or("")),
+                );
+                if last_attempt.as_ref() == Some(&attempt) {
+                    repeat_count += 1;
+                } else {
+                    last_attempt = Some(attempt);
+                    repeat_count = 1;
+                }
+            }
+            if repeat_count >= REPEATED_ACTION_LIMIT {
+                let message = format!(
+                    "I stopped because I tried the same '{}' action {} times in a row and got the same result each time. The page may have changed — please check the browser window and tell me how to continue.",
+                    action.action, repeat_count
+                );
+                complete(&message);
+                return Ok(message);
+            }
 
-        let status = response.status();
-        let text = response
-            .text()
-            .await
-            .map_err(|e| format!("Failed to read API response: {}", e))?;
+            // Small delay after navigation to let page load
+            if action.action == "navigate" {
+                tokio::time::sleep(std::time::Duration::from_millis(2000)).await;
+            } else if action.action == "click" {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            }
 
-        if !status.is_success() {
-            return Err(format!("Anthropic API error ({}): {}", status, text));
+            let output = if !result.success {
+                ToolOutput::Text(format!("Error: {}", result.error.unwrap_or_default()))
+            } else if action.action == "screenshot" {
+                let path = serde_json::from_str::<serde_json::Value>(&result.result)
+                    .ok()
+                    .and_then(|v| v.get("path").and_then(|p| p.as_str()).map(String::from));
+                match path {
+                    Some(path) => ToolOutput::Screenshot { path },
+                    None => ToolOutput::Text(result.result),
+                }
+            } else {
+                ToolOutput::Text(result.result)
+            };
+            results.push((call.id, output));
         }
 
-        let resp: serde_json::Value = serde_json::from_str(&text)
-            .map_err(|e| format!("Failed to parse API response: {}", e))?;
-
-        let stop_reason = resp
-            .get("stop_reason")
-            .and_then(|v| v.as_str())
-            .unwrap_or("end_turn");
-
-        let content = resp.get("content").and_then(|v| v.as_array());
-
-        if stop_reason == "tool_use" {
-            // Extract tool_use blocks and text blocks
-            let content_blocks = content.cloned().unwrap_or_default();
+        provider.push_tool_results(results);
+    }
 
-            // Add the full assistant response to messages
-            messages.push(serde_json::json!({
-                "role": "assistant",
-                "content": content_blocks
-            }));
+    // Hit max iterations
+    complete(&format!(
+        "Reached maximum browsing steps ({}). Here's what I've done so far.",
+        max_iterations
+    ));
+
+    Ok(format!(
+        "I reached the maximum number of browsing steps ({}). The task may not be fully complete — please check the browser window and tell me if you'd like me to continue.",
+        max_iterations
+    ))
+}
 
-            // Process each tool_use block
-            let mut tool_results = Vec::new();
-            for block in &content_blocks {
-                if block.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
-                    let tool_id = block.get("id").and_then(|v| v.as_str()).unwrap_or("");
-                    let input = block.get("input").cloned().unwrap_or(serde_json::json!({}));
-
-                    // Parse the browser action
-                    let action: BrowserAction = serde_json::from_value(input.clone())
-                        .unwrap_or(BrowserAction {
-                            action: "read_page".to_string(),
-                            url: None,
-                            selector: None,
-                            text: None,
-                            direction: None,
-                            value: None,
-                            amount: None,
-                        });
-
-                    // Execute the action
-                    let result = execute_action(app, &action).await;
-
-                    // Small delay after navigation to let page load
-                    if action.action == "navigate" {
-                        tokio::time::sleep(std::time::Duration::from_millis(2000)).await;
-                    } else if action.action == "click" {
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    }
+// ---------------------------------------------------------------------------
+// Credential / payment guard
+// ---------------------------------------------------------------------------
 
-                    let result_text = if result.success {
-                        result.result
-                    } else {
-                        format!("Error: {}", result.error.unwrap_or_default())
-                    };
-
-                    tool_results.push(serde_json::json!({
-                        "type": "tool_result",
-                        "tool_use_id": tool_id,
-                        "content": result_text
-                    }));
-                }
+/// Substrings of a field's name or id that mark it as a credential or card field.
+const SENSITIVE_NAME_MARKERS: &[&str] = &[
+    "password", "passwd", "passcode", "cardnumber", "card_number", "card-number",
+    "ccnum", "cc_num", "cc-num", "cvv", "cvc", "csc", "securitycode", "security_code",
+    "security-code",
+];
+
+/// autocomplete tokens for credential and payment fields.
+const SENSITIVE_AUTOCOMPLETE: &[&str] = &[
+    "current-password", "new-password", "one-time-code", "cc-number", "cc-csc", "cc-exp",
+    "cc-exp-month", "cc-exp-year",
+];
+
+/// Luhn checksum over a run of digits.
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
             }
+        })
+        .sum();
+    sum % 10 == 0
+}
 
-            // Add tool results to messages
-            messages.push(serde_json::json!({
-                "role": "user",
-                "content": tool_results
-            }));
+/// True if `text` contains a 13–19 digit run (spaces and dashes allowed as
+/// separators) that passes the Luhn check.
+fn contains_card_number(text: &str) -> bool {
+    let mut digits: Vec<u32> = Vec::new();
+    let check = |digits: &[u32]| (13..=19).contains(&digits.len()) && luhn_valid(digits);
+
+    for c in text.chars() {
+        if let Some(d) = c.to_digit(10) {
+            digits.push(d);
+        } else if c == ' ' || c == '-' {
+            continue;
         } else {
-            // end_turn — extract the text response
-            let mut final_text = String::new();
-            if let Some(blocks) = content {
-                for block in blocks {
-                    if block.get("type").and_then(|v| v.as_str()) == Some("text") {
-                        if let Some(t) = block.get("text").and_then(|v| v.as_str()) {
-                            final_text.push_str(t);
-                        }
-                    }
-                }
+            if check(&digits) {
+                return true;
             }
+            digits.clear();
+        }
+    }
+    check(&digits)
+}
 
-            let _ = app.emit(
-                "browser:event",
-                BrowserEvent {
-                    kind: "complete".to_string(),
-                    url: None,
-                    title: None,
-                    message: Some(final_text.clone()),
-                },
-            );
+/// docker.env keys whose values are secrets.
+const SECRET_ENV_SUFFIXES: &[&str] = &["_KEY", "_TOKEN", "_SECRET", "_PASSWORD", "_PASSPHRASE"];
+/// Shorter values are too likely to appear in ordinary text.
+const MIN_SECRET_LEN: usize = 8;
+
+/// Secret values from docker.env (API keys, bot and gateway tokens).
+fn stored_secrets() -> Vec<String> {
+    config::read_docker_env()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(key, _)| SECRET_ENV_SUFFIXES.iter().any(|s| key.ends_with(s)))
+        .map(|(_, value)| value)
+        .collect()
+}
 
-            return Ok(final_text);
-        }
+/// Why `text` must not be entered anywhere, judged from the text alone.
+fn sensitive_text(text: &str, secrets: &[String]) -> Option<String> {
+    if contains_card_number(text) {
+        return Some("the text looks like a payment card number".to_string());
+    }
+    let stored = secrets
+        .iter()
+        .any(|s| s.len() >= MIN_SECRET_LEN && text.contains(s.as_str()));
+    if stored {
+        return Some("the text contains one of Nyx's stored secrets".to_string());
     }
+    None
+}
 
-    // Hit max iterations
-    let _ = app.emit(
-        "browser:event",
-        BrowserEvent {
-            kind: "complete".to_string(),
-            url: None,
-            title: None,
-            message: Some("Reached maximum browsing steps (25). Here's what I've done so far.".to_string()),
-        },
+/// Why a selector names a credential or payment field, if it does.
+fn sensitive_selector(selector: &str) -> Option<String> {
+    let lower = selector.to_ascii_lowercase();
+    SENSITIVE_NAME_MARKERS
+        .iter()
+        .chain(SENSITIVE_AUTOCOMPLETE)
+        .find(|m| lower.contains(*m))
+        .map(|m| format!("the selector targets a \"{}\" field", m))
+}
+
+/// Vet text the agent wants to enter. The decisive checks look only at what
+/// Nyx controls — the text and the selector — since a page's scripts can
+/// misreport anything about its own DOM. The field's attributes are still
+/// read so honest login and checkout forms are refused, but the page's
+/// answer can only add a refusal, never lift one.
+async fn check_entry(
+    app: &AppHandle,
+    session: &str,
+    action: &str,
+    selector: &str,
+    text: &str,
+) -> Result<(), String> {
+    let reason = sensitive_text(text, &stored_secrets()).or_else(|| sensitive_selector(selector));
+    if let Some(reason) = reason {
+        return Err(blocked(action, selector, &reason));
+    }
+    if let Some(reason) = sensitive_field(app, session, selector).await? {
+        return Err(blocked(action, selector, &reason));
+    }
+    Ok(())
+}
+
+/// Ask the page about the target field; returns why it's off-limits, if it
+/// is. Advisory only (see `check_entry`). Fails closed: if the attributes
+/// can't be read (script error, timeout, odd result) the field is treated
+/// as sensitive.
+async fn sensitive_field(app: &AppHandle, session: &str, selector: &str) -> Result<Option<String>, String> {
+    let js = format!(
+        r#"(function() {{
+            var el = document.querySelector({sel});
+            if (!el) return JSON.stringify({{ missing: true }});
+            return JSON.stringify({{
+                type: (el.type || '').toLowerCase(),
+                name: (el.name || '').toLowerCase(),
+                id: (el.id || '').toLowerCase(),
+                autocomplete: (el.getAttribute('autocomplete') || '').toLowerCase()
+            }});
+        }})()"#,
+        sel = js_string(selector, "selector")?
     );
+    let unreadable = || Ok(Some("its attributes could not be checked".to_string()));
+    let Ok(raw) = eval_js_async(app, session, &js).await else {
+        return unreadable();
+    };
+    let Ok(attrs) = serde_json::from_str::<serde_json::Value>(&raw) else {
+        return unreadable();
+    };
+    if attrs.get("missing").and_then(|v| v.as_bool()) == Some(true) {
+        return Ok(None);
+    }
+    if !attrs.get("type").is_some_and(|v| v.is_string()) {
+        return unreadable();
+    }
+    let attr = |k: &str| attrs.get(k).and_then(|v| v.as_str()).unwrap_or("").to_string();
+
+    if attr("type") == "password" {
+        return Ok(Some("it is a password field".to_string()));
+    }
+    let autocomplete = attr("autocomplete");
+    if let Some(token) = autocomplete
+        .split_whitespace()
+        .find(|t| SENSITIVE_AUTOCOMPLETE.contains(t))
+    {
+        return Ok(Some(format!("the field is marked autocomplete=\"{}\"", token)));
+    }
+    for key in ["name", "id"] {
+        let value = attr(key);
+        if let Some(marker) = SENSITIVE_NAME_MARKERS.iter().find(|m| value.contains(*m)) {
+            return Ok(Some(format!("the field {} contains \"{}\"", key, marker)));
+        }
+    }
+    Ok(None)
+}
 
-    Ok("I reached the maximum number of browsing steps (25). The task may not be fully complete — please check the browser window and tell me if you'd like me to continue.".to_string())
+/// Audit and describe a refused action. The message is returned to the agent.
+fn blocked(action: &str, selector: &str, reason: &str) -> String {
+    blocked_in(&config::nyx_home(), action, selector, reason)
+}
+
+/// `blocked`, auditing to the log under `home`.
+fn blocked_in(home: &std::path::Path, action: &str, selector: &str, reason: &str) -> String {
+    audit::record_audit_lossy_in(
+        home,
+        "browser_action_blocked",
+        serde_json::json!({ "action": action, "selector": selector, "reason": reason }),
+        "blocked",
+    );
+    format!(
+        "Blocked: {}. Nyx never enters passwords or payment details — ask the user to complete this step in the browser window.",
+        reason
+    )
 }
 
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Encode a value as a JS string literal for embedding in injected scripts.
+/// Values that could break out of the wrapper (`</script>`, line breaks) are
+/// rejected outright rather than escaped.
+fn js_string(value: &str, field: &str) -> Result<String, String> {
+    if value.to_ascii_lowercase().contains("</script") {
+        return Err(format!("Invalid {}: must not contain </script>", field));
+    }
+    if value.contains(['\n', '\r', '\u{2028}', '\u{2029}']) {
+        return Err(format!("Invalid {}: must not contain line breaks", field));
+    }
+    // Encoding a plain string as JSON cannot fail
+    Ok(serde_json::Value::String(value.to_string()).to_string())
+}
+
 /// Get the browser WebviewWindow or error.
-fn get_window(app: &AppHandle) -> Result<tauri::WebviewWindow, String> {
-    app.get_webview_window(BROWSER_WINDOW_LABEL)
+fn get_window(app: &AppHandle, session: &str) -> Result<tauri::WebviewWindow, String> {
+    app.get_webview_window(&window_label(session))
         .ok_or_else(|| "Browser window not open. Call browser_open first.".to_string())
 }
 
-/// Evaluate JavaScript in the browser window and return the result as a string.
-/// Uses Tauri's `eval()` which injects JS. For return values, we use a
-/// message-passing pattern: the JS writes its result to a Tauri event.
-fn eval_js(app: &AppHandle, js: &str) -> Result<String, String> {
-    let win = get_window(app)?;
-
-    // Tauri v2's eval() doesn't return values directly.
-    // Workaround: wrap JS in a function that POSTs the result back via
-    // window.__TAURI__.event.emit(). The caller collects via channel.
-    //
-    // For simplicity in v1 of this feature, we use eval() fire-and-forget
-    // for actions (click, type, scroll) and return success/failure.
-    // For read operations, we inject JS that calls back via Tauri event.
-
-    // Use a sync approach: inject JS that stores result in a known location,
-    // then read it back. This is simpler and avoids async coordination issues.
-    let wrapper = format!(
-        r#"try {{
-            var __nyx_result = (function() {{ return {js}; }})();
-            if (window.__TAURI_INTERNALS__) {{
-                window.__TAURI_INTERNALS__.invoke('__browser_js_result', {{ result: __nyx_result || 'ok' }});
-            }}
-        }} catch(e) {{
-            if (window.__TAURI_INTERNALS__) {{
-                window.__TAURI_INTERNALS__.invoke('__browser_js_result', {{ result: JSON.stringify({{ error: e.message }}) }});
-            }}
-        }}"#,
-        js = js
-    );
-
+/// Evaluate JavaScript in the browser window, fire-and-forget. Used for
+/// actions whose result doesn't matter (scroll); errors thrown by the
+/// script are swallowed.
+fn eval_js(app: &AppHandle, session: &str, js: &str) -> Result<String, String> {
+    let win = get_window(app, session)?;
+    let wrapper = format!("try {{ (function() {{ return {js}; }})(); }} catch(e) {{}}", js = js);
     win.eval(&wrapper)
         .map_err(|e| format!("JS eval failed: {}", e))?;
-
-    // Since eval() is fire-and-forget in Tauri v2, we return a success indicator.
-    // The actual result will come back via the __browser_js_result command or
-    // the frontend can read it. For the agent loop, we'll use a channel-based
-    // approach in the gateway integration.
-    //
-    // For now, we return a placeholder — the gateway agent loop will use
-    // eval_js_async() below for operations that need return values.
     Ok("ok".to_string())
 }
 
-/// Async JS evaluation that waits for the result via a one-shot channel.
-/// This is used by the agent loop where we need the actual return value.
-#[allow(dead_code)]
-pub async fn eval_js_async(app: &AppHandle, js: &str) -> Result<String, String> {
-    let win = get_window(app)?;
+/// Scheme injected scripts navigate to with their result. Browser windows
+/// get no IPC access, so this is the only way back; `on_navigation` cancels
+/// the navigation and hands the result to the waiting call.
+const EVAL_RESULT_SCHEME: &str = "nyx-result";
+
+/// `eval_js_async` calls waiting for a result, keyed by a per-call nonce.
+/// Replies without a pending nonce are dropped, so a page can't answer for
+/// a call it never saw.
+static PENDING_EVALS: std::sync::LazyLock<Mutex<HashMap<String, tokio::sync::oneshot::Sender<String>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Split an eval result URL (`nyx-result://eval/?id=..&r=..` or `&e=..`)
+/// into its nonce and result. Errors come back as `{"error": ..}` JSON.
+fn parse_eval_result(url: &url::Url) -> Option<(String, String)> {
+    if url.scheme() != EVAL_RESULT_SCHEME {
+        return None;
+    }
+    let (mut id, mut result, mut error) = (None, None, None);
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "id" => id = Some(value.into_owned()),
+            "r" => result = Some(value.into_owned()),
+            "e" => error = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    let result = match error {
+        Some(e) => serde_json::json!({ "error": e }).to_string(),
+        None => result?,
+    };
+    Some((id?, result))
+}
 
-    // Create a unique callback ID
-    let cb_id = uuid::Uuid::new_v4().to_string().replace('-', "");
+fn deliver_eval_result(url: &url::Url) {
+    let Some((id, result)) = parse_eval_result(url) else {
+        return;
+    };
+    let sender = PENDING_EVALS.lock().ok().and_then(|mut pending| pending.remove(&id));
+    if let Some(sender) = sender {
+        let _ = sender.send(result);
+    }
+}
 
-    // We'll use the Tauri event system: inject JS that emits an event with the result
+/// Async JS evaluation that waits for the result via a one-shot channel.
+/// Used by every operation whose return value matters (reads, click, type).
+/// The script reports back by navigating to `EVAL_RESULT_SCHEME` with this
+/// call's nonce; see `deliver_eval_result`.
+pub async fn eval_js_async(app: &AppHandle, session: &str, js: &str) -> Result<String, String> {
+    let win = get_window(app, session)?;
+
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    // The nonce is joined with `+`, which page scripts can't hook, and
+    // strict mode keeps hooked builtins from reading this source via `.caller`
     let wrapper = format!(
-        r#"(async function() {{
+        r#"(function() {{
+            'use strict';
+            var __nyx_reply = function(key, value) {{
+                window.location.href = '{scheme}://eval/?id={nonce}&' + key + '=' + encodeURIComponent(value);
+            }};
             try {{
                 var __result = (function() {{ return {js}; }})();
-                // Emit result back to Rust via Tauri event
-                if (window.__TAURI_INTERNALS__) {{
-                    window.__TAURI_INTERNALS__.postMessage(JSON.stringify({{
-                        cmd: 'plugin:event|emit',
-                        event: 'browser:js_result_{cb_id}',
-                        payload: {{ result: typeof __result === 'string' ? __result : JSON.stringify(__result) }}
-                    }}));
-                }}
+                __nyx_reply('r', typeof __result === 'string' ? __result : JSON.stringify(__result));
             }} catch(e) {{
-                if (window.__TAURI_INTERNALS__) {{
-                    window.__TAURI_INTERNALS__.postMessage(JSON.stringify({{
-                        cmd: 'plugin:event|emit',
-                        event: 'browser:js_result_{cb_id}',
-                        payload: {{ error: e.message }}
-                    }}));
-                }}
+                __nyx_reply('e', String(e && e.message));
             }}
         }})()"#,
-        js = js,
-        cb_id = cb_id
+        scheme = EVAL_RESULT_SCHEME,
+        nonce = nonce,
+        js = js
     );
 
-    // Set up a one-shot listener for the result.
-    // Wrap sender in Mutex<Option<>> because Tauri's listen requires Fn (not FnOnce).
     let (tx, rx) = tokio::sync::oneshot::channel::<String>();
-    let tx = std::sync::Mutex::new(Some(tx));
-    let event_name = format!("browser:js_result_{}", cb_id);
-
-    let id = app.listen(&event_name, move |event: tauri::Event| {
-        let payload = event.payload().to_string();
-        // Take the sender (only succeeds once)
-        let sender = tx.lock().ok().and_then(|mut guard| guard.take());
-        if let Some(sender) = sender {
-            // Parse the payload to extract the result
-            if let Ok(val) = serde_json::from_str::<serde_json::Value>(&payload) {
-                if let Some(err) = val.get("error").and_then(|v| v.as_str()) {
-                    let _ = sender.send(format!("{{\"error\":\"{}\"}}", err));
-                } else if let Some(result) = val.get("result").and_then(|v| v.as_str()) {
-                    let _ = sender.send(result.to_string());
-                } else {
-                    let _ = sender.send(payload);
-                }
-            } else {
-                let _ = sender.send(payload);
-            }
+    PENDING_EVALS
+        .lock()
+        .map_err(|_| "Browser eval lock poisoned".to_string())?
+        .insert(nonce.clone(), tx);
+    let forget = || {
+        if let Ok(mut pending) = PENDING_EVALS.lock() {
+            pending.remove(&nonce);
         }
-    });
+    };
 
-    // Inject the JS
-    win.eval(&wrapper)
-        .map_err(|e| format!("JS eval failed: {}", e))?;
+    if let Err(e) = win.eval(&wrapper) {
+        forget();
+        return Err(format!("JS eval failed: {}", e));
+    }
 
     // Wait for the result with a timeout
     match tokio::time::timeout(std::time::Duration::from_secs(10), rx).await {
-        Ok(Ok(result)) => {
-            app.unlisten(id);
-            Ok(result)
-        }
-        Ok(Err(_)) => {
-            app.unlisten(id);
-            Err("JS result channel closed unexpectedly".to_string())
-        }
+        Ok(Ok(result)) => Ok(result),
+        Ok(Err(_)) => Err("JS result channel closed unexpectedly".to_string()),
         Err(_) => {
-            app.unlisten(id);
+            forget();
             // Timeout is not necessarily an error — some actions (click, scroll)
             // don't produce a meaningful return value
             Ok("ok (timeout — action likely completed)".to_string())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_labels_are_distinct_and_valid() {
+        assert_eq!(window_label(DEFAULT_SESSION), "browser");
+        let labels: Vec<String> = ["a.b", "a b", "a-b", "a/b", "", "é"]
+            .iter()
+            .map(|s| window_label(s))
+            .collect();
+        for (i, label) in labels.iter().enumerate() {
+            assert!(label.starts_with("browser-"), "{}", label);
+            assert!(label.chars().all(|c| c.is_ascii_alphanumeric() || "-/:_".contains(c)));
+            assert!(!labels[i + 1..].contains(label), "{} repeats", label);
+        }
+        assert_eq!(window_label("a.b"), "browser-612e62");
+    }
+
+    #[test]
+    fn screenshot_is_only_offered_where_supported() {
+        let tool = tool_definition();
+        let actions = tool["input_schema"]["properties"]["action"]["enum"]
+            .as_array()
+            .unwrap();
+        let offered = actions.iter().any(|a| a == "screenshot");
+        assert_eq!(offered, SCREENSHOTS_SUPPORTED);
+        let described = tool["description"].as_str().unwrap().contains("screenshot");
+        assert_eq!(described, SCREENSHOTS_SUPPORTED);
+    }
+
+    #[test]
+    fn js_string_quotes_and_escapes() {
+        assert_eq!(js_string("plain", "text").unwrap(), r#""plain""#);
+        assert_eq!(js_string(r#"say "hi""#, "text").unwrap(), r#""say \"hi\"""#);
+        assert_eq!(js_string(r"C:\temp\x", "text").unwrap(), r#""C:\\temp\\x""#);
+        assert_eq!(js_string("it's", "text").unwrap(), r#""it's""#);
+        // A closing quote can't end the literal early
+        let encoded = js_string(r#"a"; alert(1); ""#, "text").unwrap();
+        assert_eq!(
+            serde_json::from_str::<String>(&encoded).unwrap(),
+            r#"a"; alert(1); ""#
+        );
+    }
+
+    #[test]
+    fn js_string_rejects_script_breakouts_and_line_breaks() {
+        for value in [
+            "</script>",
+            "x</SCRIPT >",
+            "a\nb",
+            "a\rb",
+            "a\u{2028}b",
+            "a\u{2029}b",
+        ] {
+            assert!(js_string(value, "selector").is_err(), "{:?}", value);
+        }
+        let err = js_string("</script>", "selector").unwrap_err();
+        assert_eq!(err, "Invalid selector: must not contain </script>");
+        assert!(js_string("<script>", "text").is_ok());
+    }
+
+    #[test]
+    fn hrefs_resolve_against_the_page() {
+        let base = url::Url::parse("https://example.com/docs/page.html?q=1").unwrap();
+        let resolve = |href| resolve_href(&base, href).map(|u| u.to_string());
+        assert_eq!(
+            resolve("other.html").as_deref(),
+            Some("https://example.com/docs/other.html")
+        );
+        assert_eq!(resolve("../up").as_deref(), Some("https://example.com/up"));
+        assert_eq!(
+            resolve("/root").as_deref(),
+            Some("https://example.com/root")
+        );
+        assert_eq!(
+            resolve("//cdn.example.net/a.js").as_deref(),
+            Some("https://cdn.example.net/a.js")
+        );
+        assert_eq!(
+            resolve("  https://other.org/x ").as_deref(),
+            Some("https://other.org/x")
+        );
+        assert_eq!(
+            resolve("?page=2").as_deref(),
+            Some("https://example.com/docs/page.html?page=2")
+        );
+
+        assert_eq!(resolve("#section"), None);
+        assert_eq!(resolve(""), None);
+        assert_eq!(resolve("javascript:void(0)"), None);
+        assert_eq!(resolve(" JavaScript:alert(1)"), None);
+    }
+
+    #[test]
+    fn links_payload_resolves_and_drops_script_links() {
+        let raw = r##"{"base": "https://example.com/a/", "links": [
+            {"text": "Next", "href": "b"},
+            {"text": "CDN", "href": "//cdn.example.net/c"},
+            {"text": "Top", "href": "#top"},
+            {"text": "Menu", "href": "javascript:open()"}
+        ]}"##;
+        let links: Vec<PageLink> =
+            serde_json::from_str(&resolve_links(raw, "https://unused.test/").unwrap()).unwrap();
+        let urls: Vec<&str> = links.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            ["https://example.com/a/b", "https://cdn.example.net/c"]
+        );
+        assert_eq!(
+            (links[0].text.as_str(), links[0].href.as_str()),
+            ("Next", "b")
+        );
+
+        // Without a base in the payload the page URL is used
+        let raw = r#"{"links": [{"text": "x", "href": "/y"}]}"#;
+        let links: Vec<PageLink> =
+            serde_json::from_str(&resolve_links(raw, "https://site.test/p").unwrap()).unwrap();
+        assert_eq!(links[0].url, "https://site.test/y");
+
+        assert!(resolve_links(r#"{"error": "boom"}"#, "https://site.test/").is_err());
+        assert!(resolve_links(r#"{"links": []}"#, "not a url").is_err());
+    }
+
+    #[test]
+    fn card_numbers_need_a_valid_luhn_run() {
+        assert!(contains_card_number("4111111111111111"));
+        assert!(contains_card_number("card: 4111 1111 1111 1111, exp 12/30"));
+        assert!(contains_card_number("5500-0000-0000-0004"));
+        // One digit off fails the checksum
+        assert!(!contains_card_number("4111111111111112"));
+        // Too short and too long runs
+        assert!(!contains_card_number("411111111111"));
+        assert!(!contains_card_number("41111111111111111111"));
+        assert!(!contains_card_number("order 12345, call 555-0100"));
+        assert!(!contains_card_number(""));
+    }
+
+    #[test]
+    fn stored_secrets_and_sensitive_selectors_are_refused() {
+        let secrets = vec!["sk-ant-abcdef123456".to_string(), "short".to_string()];
+        assert!(sensitive_text("my key is sk-ant-abcdef123456", &secrets).is_some());
+        assert!(sensitive_text("a short note", &secrets).is_none());
+        assert!(sensitive_text("4111 1111 1111 1111", &[]).is_some());
+        assert!(sensitive_text("hello world", &secrets).is_none());
+
+        assert!(sensitive_selector("input[type=password]").is_some());
+        assert!(sensitive_selector("#CardNumber").is_some());
+        assert!(sensitive_selector("[autocomplete=cc-number]").is_some());
+        assert!(sensitive_selector("input[name=q]").is_none());
+    }
+
+    #[test]
+    fn blocked_actions_are_audited() {
+        let home = std::env::temp_dir().join(format!("nyx-audit-{}", uuid::Uuid::new_v4()));
+        let message = blocked_in(&home, "type", "#pw", "it is a password field");
+        assert!(message.starts_with("Blocked: it is a password field."));
+
+        let entries = audit::read_audit_in(&home, 10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action_type, "browser_action_blocked");
+        assert_eq!(entries[0].result, "blocked");
+        assert_eq!(entries[0].detail["action"], "type");
+        assert_eq!(entries[0].detail["selector"], "#pw");
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn eval_results_need_the_scheme_and_a_nonce() {
+        let url = |s: &str| url::Url::parse(s).unwrap();
+        assert_eq!(
+            parse_eval_result(&url("nyx-result://eval/?id=abc&r=%7B%22ok%22%3Atrue%7D")),
+            Some(("abc".to_string(), r#"{"ok":true}"#.to_string()))
+        );
+        assert_eq!(
+            parse_eval_result(&url("nyx-result://eval/?id=abc&e=boom%2Bbang")),
+            Some(("abc".to_string(), r#"{"error":"boom+bang"}"#.to_string()))
+        );
+        assert_eq!(parse_eval_result(&url("nyx-result://eval/?r=1")), None);
+        assert_eq!(parse_eval_result(&url("nyx-result://eval/?id=abc")), None);
+        assert_eq!(parse_eval_result(&url("https://example.com/?id=abc&r=1")), None);
+    }
+
+    #[test]
+    fn png_dimensions_reads_ihdr() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&1440u32.to_be_bytes());
+        png.extend_from_slice(&900u32.to_be_bytes());
+        assert_eq!(png_dimensions(&png).unwrap(), (1440, 900));
+        assert!(png_dimensions(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn prune_screenshots_keeps_newest() {
+        let dir = std::env::temp_dir().join(format!("nyx-screenshots-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for ts in ["1000", "3000", "2000", "4000"] {
+            std::fs::write(dir.join(format!("{}.png", ts)), b"").unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        prune_screenshots(&dir, 2);
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["3000.png", "4000.png", "notes.txt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
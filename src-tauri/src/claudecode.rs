@@ -27,6 +27,7 @@ pub struct ClaudeCodeStatus {
 
 /// Known paths where the Claude Code CLI might be installed.
 fn known_claude_paths() -> Vec<PathBuf> {
+    // Real HOME, not NYX_HOME: this is the user's own Claude Code install
     let home = std::env::var("HOME").unwrap_or_default();
     vec![
         PathBuf::from("/usr/local/bin/claude"),
@@ -140,6 +141,15 @@ pub fn check_status() -> Result<ClaudeCodeStatus, String> {
     })
 }
 
+/// List the Nyx MCP tools Claude Code can call, with availability under the
+/// current config. Errors if the server isn't registered with Claude Code.
+pub fn list_tools() -> Result<Vec<nyx_lib::mcp::ToolSummary>, String> {
+    if !check_mcp_registered() {
+        return Err("Nyx MCP server is not registered with Claude Code".to_string());
+    }
+    Ok(nyx_lib::mcp::tool_manifest())
+}
+
 /// Get the path to the bundled nyx-mcp binary.
 /// In development: looks for it in the target directory.
 /// In production: looks for it alongside the main app binary.
@@ -21,10 +21,22 @@ pub struct ClawdTalkConfig {
 #[derive(Serialize, Clone, Debug)]
 pub struct ClawdTalkStatus {
     pub configured: bool,
+    /// The ws-client process is running (see `connection_state` for the socket)
     pub connected: bool,
     pub has_api_key: bool,
     pub server: String,
     pub pid: Option<u32>,
+    pub connection_state: ConnectionState,
+}
+
+/// WebSocket state as reported by the ws-client log.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    AuthFailed,
+    Disconnected,
 }
 
 // ---------------------------------------------------------------------------
@@ -58,7 +70,7 @@ pub fn check_status() -> Result<ClawdTalkStatus, String> {
     let configured = config.exists();
 
     let mut has_api_key = false;
-    let mut server = "https://clawdtalk.com".to_string();
+    let mut server = DEFAULT_SERVER.to_string();
 
     if configured {
         if let Ok(content) = fs::read_to_string(&config) {
@@ -78,6 +90,7 @@ pub fn check_status() -> Result<ClawdTalkStatus, String> {
 
     // Check if WebSocket client process is running
     let (connected, pid) = check_process_running();
+    let connection_state = connection_state(connected);
 
     Ok(ClawdTalkStatus {
         configured,
@@ -85,9 +98,49 @@ pub fn check_status() -> Result<ClawdTalkStatus, String> {
         has_api_key,
         server,
         pid,
+        connection_state,
     })
 }
 
+/// Lines of log scanned for the latest connection marker.
+const STATE_LOG_LINES: usize = 200;
+
+/// Map a ws-client log line to the state it reports, if any.
+fn state_marker(line: &str) -> Option<ConnectionState> {
+    if line.contains("INFO: Authenticated") {
+        Some(ConnectionState::Connected)
+    } else if line.contains("Auth failed") {
+        Some(ConnectionState::AuthFailed)
+    } else if line.contains("INFO: Connecting to") || line.contains("Connected, authenticating") {
+        Some(ConnectionState::Connecting)
+    } else if line.contains("INFO: Reconnecting in")
+        || line.contains("INFO: Shutting down")
+        || line.contains("FATAL:")
+    {
+        Some(ConnectionState::Disconnected)
+    } else {
+        None
+    }
+}
+
+/// Derive the socket state from the most recent marker in the log. A dead
+/// process is always disconnected, except that an auth failure is kept so
+/// the user sees why.
+fn connection_state(process_running: bool) -> ConnectionState {
+    let latest = get_logs(STATE_LOG_LINES)
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .find_map(|line| state_marker(line));
+
+    match (process_running, latest) {
+        (_, Some(ConnectionState::AuthFailed)) => ConnectionState::AuthFailed,
+        (false, _) => ConnectionState::Disconnected,
+        (true, Some(state)) => state,
+        (true, None) => ConnectionState::Connecting,
+    }
+}
+
 /// Check if the ws-client process is running via PID file.
 fn check_process_running() -> (bool, Option<u32>) {
     let pidfile = pid_file();
@@ -96,26 +149,76 @@ fn check_process_running() -> (bool, Option<u32>) {
     }
     if let Ok(content) = fs::read_to_string(&pidfile) {
         if let Ok(pid) = content.trim().parse::<u32>() {
-            // Check if process exists
-            let output = std::process::Command::new("kill")
-                .args(["-0", &pid.to_string()])
-                .output();
-            if let Ok(o) = output {
-                if o.status.success() {
-                    return (true, Some(pid));
-                }
+            if pid_alive(pid) && is_ws_client(pid) {
+                return (true, Some(pid));
             }
-            // Stale PID file — clean up
+            // Stale PID file (process gone or PID reused) — clean up
             let _ = fs::remove_file(&pidfile);
         }
     }
     (false, None)
 }
 
+/// `kill -0`: does a process with this PID exist?
+fn pid_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Is `pid` our node ws-client (not an unrelated process that reused the PID)?
+fn is_ws_client(pid: u32) -> bool {
+    std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "command="])
+        .output()
+        .map(|o| {
+            let command = String::from_utf8_lossy(&o.stdout);
+            o.status.success() && command.contains("ws-client.js")
+        })
+        .unwrap_or(false)
+}
+
 // ---------------------------------------------------------------------------
 // Configuration
 // ---------------------------------------------------------------------------
 
+const DEFAULT_SERVER: &str = "https://clawdtalk.com";
+
+/// Check a key against the ClawdTalk API with a read-only request
+/// (`GET /v1/missions`) before it is saved.
+pub async fn validate_api_key(api_key: &str) -> Result<(), String> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err("ClawdTalk API key cannot be empty".to_string());
+    }
+
+    // Honour a server override from an existing config
+    let server = fs::read_to_string(config_path())
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .and_then(|c| c.get("server").and_then(|v| v.as_str()).map(String::from))
+        .unwrap_or_else(|| DEFAULT_SERVER.to_string());
+
+    let client = config::http_client_builder(config::HttpDestination::Remote)?
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let response = client
+        .get(format!("{}/v1/missions", server.trim_end_matches('/')))
+        .bearer_auth(api_key)
+        .send()
+        .await
+        .map_err(|e| format!("Couldn't reach ClawdTalk at {} to verify the key: {}", server, e))?;
+
+    match response.status().as_u16() {
+        401 | 403 => Err("ClawdTalk rejected this API key — check it at clawdtalk.com and try again".to_string()),
+        code if (200..300).contains(&code) => Ok(()),
+        code => Err(format!("ClawdTalk key check failed (HTTP {})", code)),
+    }
+}
+
 /// Write ClawdTalk skill-config.json with the actual API key value.
 /// Shell scripts (call.sh, sms.sh etc.) read this via jq and cannot resolve
 /// env var references, so the raw key must be written directly.
@@ -132,7 +235,7 @@ pub fn write_config(api_key_ref: &str, owner_name: Option<&str>, agent_name: Opt
 
     let config = serde_json::json!({
         "api_key": api_key_ref,
-        "server": "https://clawdtalk.com",
+        "server": DEFAULT_SERVER,
         "owner_name": owner_name,
         "agent_name": agent_name,
         "greeting": greeting,
@@ -141,7 +244,7 @@ pub fn write_config(api_key_ref: &str, owner_name: Option<&str>, agent_name: Opt
 
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(config_path(), content)
+    config::write_atomic(&config_path(), content)
         .map_err(|e| format!("Failed to write config: {}", e))?;
 
     // chmod 600 on config file (contains API key)
@@ -206,21 +309,9 @@ pub async fn start_connection() -> Result<ClawdTalkStatus, String> {
     // Source env vars — we need to resolve ${VAR} in skill-config.json
     // The ws-client.js handles this itself via resolve_config, but we need
     // the env vars available in the process environment
-    let env_path = config::home_dir().join("openclaw/docker.env");
-    let mut env_vars: Vec<(String, String)> = Vec::new();
-    if let Ok(content) = fs::read_to_string(&env_path) {
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-            if let Some(pos) = trimmed.find('=') {
-                let key = trimmed[..pos].trim().to_string();
-                let value = trimmed[pos + 1..].trim().to_string();
-                env_vars.push((key, value));
-            }
-        }
-    }
+    let env_vars: Vec<(String, String)> = config::read_docker_env()
+        .map(|env| env.into_iter().collect())
+        .unwrap_or_default();
 
     // Start ws-client.js via node
     let log = log_file();
@@ -257,21 +348,35 @@ pub async fn start_connection() -> Result<ClawdTalkStatus, String> {
     check_status()
 }
 
-/// Stop the WebSocket connection.
-pub fn stop_connection() -> Result<ClawdTalkStatus, String> {
+/// How long the client gets to disconnect cleanly after SIGTERM.
+const STOP_GRACE_MS: u64 = 5000;
+const STOP_POLL_MS: u64 = 100;
+
+/// Stop the WebSocket connection: SIGTERM, then SIGKILL only if the client
+/// is still alive after the grace period.
+pub async fn stop_connection() -> Result<ClawdTalkStatus, String> {
     let pidfile = pid_file();
     if let Ok(content) = fs::read_to_string(&pidfile) {
         if let Ok(pid) = content.trim().parse::<u32>() {
-            // Graceful kill
-            let _ = std::process::Command::new("kill")
-                .arg(pid.to_string())
-                .output();
-            // Wait briefly
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            // Force kill if still running
-            let _ = std::process::Command::new("kill")
-                .args(["-9", &pid.to_string()])
-                .output();
+            // Never signal a process that merely reused the PID
+            if pid_alive(pid) && is_ws_client(pid) {
+                let _ = std::process::Command::new("kill")
+                    .arg(pid.to_string())
+                    .output();
+
+                let mut waited = 0;
+                while waited < STOP_GRACE_MS && pid_alive(pid) {
+                    tokio::time::sleep(std::time::Duration::from_millis(STOP_POLL_MS)).await;
+                    waited += STOP_POLL_MS;
+                }
+
+                if pid_alive(pid) {
+                    eprintln!("[clawdtalk] ws-client {} ignored SIGTERM for {}ms; sending SIGKILL", pid, STOP_GRACE_MS);
+                    let _ = std::process::Command::new("kill")
+                        .args(["-9", &pid.to_string()])
+                        .output();
+                }
+            }
         }
     }
     let _ = fs::remove_file(&pidfile);
@@ -291,6 +396,80 @@ pub fn get_logs(lines: usize) -> Result<Vec<String>, String> {
     Ok(all_lines[start..].to_vec())
 }
 
+// ---------------------------------------------------------------------------
+// Live log tailing
+// ---------------------------------------------------------------------------
+
+/// The running tail task, if any.
+static LOG_STREAM: std::sync::LazyLock<std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(None));
+
+const LOG_POLL_MS: u64 = 500;
+
+/// Tail `.connect.log` from its current end, calling `on_line` for each
+/// appended line. If the file shrinks (truncated or rotated) reading restarts
+/// from the top. Replaces any stream already running.
+pub fn start_log_stream<F>(on_line: F) -> Result<(), String>
+where
+    F: Fn(String) + Send + 'static,
+{
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = log_file();
+    let mut pos = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+    let handle = tokio::spawn(async move {
+        let mut pending = String::new();
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(LOG_POLL_MS)).await;
+
+            // Reopen each poll so a rotated (replaced) file is picked up
+            let Ok(mut file) = fs::File::open(&path) else {
+                continue;
+            };
+            let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+            if len < pos {
+                pos = 0;
+                pending.clear();
+            }
+            if len == pos || file.seek(SeekFrom::Start(pos)).is_err() {
+                continue;
+            }
+
+            let mut chunk = Vec::new();
+            if file.take(len - pos).read_to_end(&mut chunk).is_err() {
+                continue;
+            }
+            pos += chunk.len() as u64;
+            pending.push_str(&String::from_utf8_lossy(&chunk));
+
+            while let Some(newline) = pending.find('\n') {
+                let line: String = pending.drain(..=newline).collect();
+                on_line(line.trim_end_matches(['\r', '\n']).to_string());
+            }
+        }
+    });
+
+    let mut stream = LOG_STREAM
+        .lock()
+        .map_err(|_| "Log stream lock poisoned".to_string())?;
+    if let Some(previous) = stream.replace(handle) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+/// Stop the live log stream (no-op if none is running).
+pub fn stop_log_stream() -> Result<(), String> {
+    let mut stream = LOG_STREAM
+        .lock()
+        .map_err(|_| "Log stream lock poisoned".to_string())?;
+    if let Some(handle) = stream.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Voice agent config for OpenClaw gateway
 // ---------------------------------------------------------------------------
@@ -375,7 +554,7 @@ pub fn configure_gateway_voice_agent() -> Result<(), String> {
     // Write back
     let updated = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize: {}", e))?;
-    fs::write(&config_path, updated)
+    config::write_atomic(&config_path, updated)
         .map_err(|e| format!("Failed to write openclaw.json: {}", e))?;
 
     Ok(())
@@ -402,7 +581,7 @@ pub fn remove_gateway_voice_agent() -> Result<(), String> {
 
     let updated = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize: {}", e))?;
-    fs::write(&config_path, updated)
+    config::write_atomic(&config_path, updated)
         .map_err(|e| format!("Failed to write: {}", e))?;
 
     Ok(())
@@ -414,20 +593,11 @@ pub fn remove_gateway_voice_agent() -> Result<(), String> {
 
 /// Resolve ${VAR} references in a string using docker.env values.
 fn resolve_env_vars(content: &str) -> String {
-    let env_path = config::home_dir().join("openclaw/docker.env");
     let mut resolved = content.to_string();
 
-    if let Ok(env_content) = fs::read_to_string(&env_path) {
-        for line in env_content.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-            if let Some(pos) = trimmed.find('=') {
-                let key = trimmed[..pos].trim();
-                let value = trimmed[pos + 1..].trim();
-                resolved = resolved.replace(&format!("${{{}}}", key), value);
-            }
+    if let Ok(env) = config::read_docker_env() {
+        for (key, value) in env {
+            resolved = resolved.replace(&format!("${{{}}}", key), &value);
         }
     }
 
@@ -1,6 +1,7 @@
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -15,6 +16,8 @@ pub enum Chain {
     SOL,
     BTC,
     ZEC,
+    LTC,
+    DOGE,
 }
 
 impl std::fmt::Display for Chain {
@@ -25,6 +28,8 @@ impl std::fmt::Display for Chain {
             Chain::SOL => write!(f, "sol"),
             Chain::BTC => write!(f, "btc"),
             Chain::ZEC => write!(f, "zec"),
+            Chain::LTC => write!(f, "ltc"),
+            Chain::DOGE => write!(f, "doge"),
         }
     }
 }
@@ -111,19 +116,100 @@ impl GuardrailsConfig {
     }
 }
 
+impl GuardrailsConfig {
+    /// Limits `proposed` relaxes relative to `self` (higher caps, lower
+    /// minimum health factor, confirmation switched off).
+    pub fn loosened_by(&self, proposed: &GuardrailsConfig) -> Vec<&'static str> {
+        let mut loosened = Vec::new();
+        let caps = [
+            ("max_transaction_usd", self.max_transaction_usd, proposed.max_transaction_usd),
+            ("daily_loss_percent", self.daily_loss_percent, proposed.daily_loss_percent),
+            ("weekly_loss_percent", self.weekly_loss_percent, proposed.weekly_loss_percent),
+            ("daily_tx_limit", self.daily_tx_limit as f64, proposed.daily_tx_limit as f64),
+            ("max_slippage_percent", self.max_slippage_percent, proposed.max_slippage_percent),
+            (
+                "max_concentration_percent",
+                self.max_concentration_percent,
+                proposed.max_concentration_percent,
+            ),
+        ];
+        for (name, current, new) in caps {
+            if new > current {
+                loosened.push(name);
+            }
+        }
+        if proposed.min_health_factor < self.min_health_factor {
+            loosened.push("min_health_factor");
+        }
+        if self.require_confirmation && !proposed.require_confirmation {
+            loosened.push("require_confirmation");
+        }
+        loosened
+    }
+
+    /// Check every limit is within a sane range: a positive transaction cap,
+    /// percentages within 0–100, daily loss no higher than weekly, at least
+    /// one transaction a day and a health factor of at least 1.0. Returns one
+    /// message per problem; empty means valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.max_transaction_usd.is_nan() || self.max_transaction_usd <= 0.0 {
+            problems.push("Max transaction (USD) must be greater than 0".to_string());
+        }
+        let percents = [
+            ("Daily loss limit", self.daily_loss_percent),
+            ("Weekly loss limit", self.weekly_loss_percent),
+            ("Max slippage", self.max_slippage_percent),
+            ("Max concentration", self.max_concentration_percent),
+        ];
+        for (label, value) in percents {
+            if !(0.0..=100.0).contains(&value) {
+                problems.push(format!("{} must be between 0% and 100% (got {})", label, value));
+            }
+        }
+        if self.daily_loss_percent > self.weekly_loss_percent {
+            problems.push("Daily loss limit cannot exceed the weekly loss limit".to_string());
+        }
+        if self.daily_tx_limit == 0 {
+            problems.push("Daily transaction limit must be at least 1".to_string());
+        }
+        if self.min_health_factor.is_nan() || self.min_health_factor < 1.0 {
+            problems.push(format!(
+                "Minimum health factor must be at least 1.0 (got {})",
+                self.min_health_factor
+            ));
+        }
+
+        problems
+    }
+}
+
 impl Default for GuardrailsConfig {
     fn default() -> Self {
         GuardrailsConfig::from_preset(SecurityPreset::Balanced)
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum MessagingAutonomy {
     DraftOnly,
     SendWithConfirm,
     Autonomous,
 }
 
+/// docker.env spelling. read_current_config also accepts the CamelCase
+/// variant names older builds wrote.
+impl std::fmt::Display for MessagingAutonomy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessagingAutonomy::DraftOnly => write!(f, "draft_only"),
+            MessagingAutonomy::SendWithConfirm => write!(f, "send_with_confirm"),
+            MessagingAutonomy::Autonomous => write!(f, "autonomous"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ChannelConfig {
     pub enabled: bool,
@@ -149,6 +235,20 @@ pub struct MessagingConfig {
     pub signal: ChannelConfig,
 }
 
+impl MessagingConfig {
+    /// Look up a channel by name ("gmail", "whatsapp", "telegram", "slack", "signal").
+    pub fn channel(&self, name: &str) -> Option<&ChannelConfig> {
+        match name {
+            "gmail" => Some(&self.gmail),
+            "whatsapp" => Some(&self.whatsapp),
+            "telegram" => Some(&self.telegram),
+            "slack" => Some(&self.slack),
+            "signal" => Some(&self.signal),
+            _ => None,
+        }
+    }
+}
+
 impl Default for MessagingConfig {
     fn default() -> Self {
         MessagingConfig {
@@ -217,6 +317,73 @@ pub struct CapabilitiesConfig {
     /// Selected Ollama model tag (e.g. "qwen3:4b"), None if not using local models
     #[serde(default)]
     pub ollama_model: Option<String>,
+    /// How often the Activity Intelligence observer runs each source
+    #[serde(default)]
+    pub intelligence_intervals: IntelligenceIntervals,
+}
+
+/// Activity Intelligence observer intervals, in minutes. Read when the
+/// observer starts, so changes apply after the next app restart.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct IntelligenceIntervals {
+    pub calendar_mins: u64,
+    pub email_mins: u64,
+    pub messaging_mins: u64,
+    pub suggestion_mins: u64,
+}
+
+impl IntelligenceIntervals {
+    const MIN_MINS: u64 = 5;
+    const MAX_MINS: u64 = 24 * 60;
+
+    /// Every interval limited to 5 minutes – 24 hours.
+    pub fn clamped(self) -> Self {
+        let clamp = |v: u64| v.clamp(Self::MIN_MINS, Self::MAX_MINS);
+        IntelligenceIntervals {
+            calendar_mins: clamp(self.calendar_mins),
+            email_mins: clamp(self.email_mins),
+            messaging_mins: clamp(self.messaging_mins),
+            suggestion_mins: clamp(self.suggestion_mins),
+        }
+    }
+}
+
+impl Default for IntelligenceIntervals {
+    fn default() -> Self {
+        IntelligenceIntervals {
+            calendar_mins: 15,
+            email_mins: 30,
+            messaging_mins: 20,
+            suggestion_mins: 60,
+        }
+    }
+}
+
+/// Ollama model used when `ollama_model` is unset.
+pub const DEFAULT_OLLAMA_MODEL: &str = "qwen3:4b";
+
+impl CapabilitiesConfig {
+    /// The Ollama model the agent will use, if Ollama is the default provider.
+    pub fn active_ollama_model(&self) -> Option<&str> {
+        if self.default_llm_provider == "ollama" {
+            Some(self.ollama_model.as_deref().unwrap_or(DEFAULT_OLLAMA_MODEL))
+        } else {
+            None
+        }
+    }
+
+    /// Model used for `default_llm_provider` (unknown providers fall back to Anthropic).
+    pub fn default_model(&self) -> &str {
+        match self.default_llm_provider.as_str() {
+            "anthropic" => "claude-sonnet-4-20250514",
+            "openai" => "gpt-4o",
+            "venice" => "llama-3.3-70b",
+            "nearai" => "qwen3-30b-a3b",
+            "ollama" => self.ollama_model.as_deref().unwrap_or(DEFAULT_OLLAMA_MODEL),
+            _ => "claude-sonnet-4-20250514",
+        }
+    }
 }
 
 impl Default for CapabilitiesConfig {
@@ -232,6 +399,7 @@ impl Default for CapabilitiesConfig {
             web_browsing: true, // on by default
             default_llm_provider: "anthropic".to_string(),
             ollama_model: None,
+            intelligence_intervals: IntelligenceIntervals::default(),
         }
     }
 }
@@ -287,7 +455,7 @@ pub struct SettingsConfig {
 
 /// Partial update struct — None fields are preserved from existing config.
 /// For keys: None = keep existing, Some("") = clear, Some(val) = update.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Clone, Debug, Default)]
 pub struct SettingsUpdate {
     pub agent_name: Option<String>,
     pub anthropic_key: Option<String>,
@@ -309,6 +477,21 @@ pub struct SettingsSaveResult {
     pub success: bool,
     pub restart_required: bool,
     pub message: String,
+    /// Set when the agent name changed
+    pub soul: Option<SoulPersonalization>,
+}
+
+/// Result of applying (or previewing) the agent name in SOUL.md.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SoulPersonalization {
+    pub soul_found: bool,
+    /// The "You're {old name}" phrase was present
+    pub marker_found: bool,
+    pub replacements: usize,
+    /// SOUL.md already introduces the agent by the new name
+    pub already_applied: bool,
+    /// Shown by the UI when the name could not be applied
+    pub warning: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -320,9 +503,130 @@ fn default_true() -> bool {
     true
 }
 
-/// Get the home directory.
+/// Root for every path Nyx reads or writes: `NYX_HOME` if set (so
+/// integration tests can point the app at a temp directory), else `HOME`.
+/// Panics when neither is set; see `try_home_dir`.
 pub fn home_dir() -> PathBuf {
-    PathBuf::from(std::env::var("HOME").expect("HOME not set"))
+    try_home_dir().unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// `home_dir` for callers that report a missing `HOME` instead of panicking.
+pub fn try_home_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os("NYX_HOME").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| "Failed to find home directory: HOME not set".to_string())
+}
+
+/// `~/.nyx` — app-local state (preferences, intelligence DB).
+pub fn nyx_home() -> PathBuf {
+    home_dir().join(".nyx")
+}
+
+/// `~/.openclaw` — agent state mounted into the container.
+pub fn openclaw_home() -> PathBuf {
+    home_dir().join(".openclaw")
+}
+
+/// `openclaw_home` without the panic when `HOME` is unset.
+pub fn try_openclaw_home() -> Result<PathBuf, String> {
+    Ok(try_home_dir()?.join(".openclaw"))
+}
+
+/// Setup has run: openclaw.json and docker.env both exist.
+pub fn is_setup_complete() -> bool {
+    openclaw_home().join("openclaw.json").exists()
+        && home_dir().join("openclaw/docker.env").exists()
+}
+
+/// Replace `path` with `contents` without ever leaving a truncated file:
+/// write a sibling temp file, fsync it, then rename it over the target. The
+/// temp file is created 0600 and only widened to the existing file's
+/// permissions, so new files (keys, docker.env, caches) are never briefly
+/// world-readable; callers still re-apply their chmod afterwards.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Distinguishes concurrent writers within this process.
+    static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "no file name"))?
+        .to_string_lossy();
+    let tmp = dir.join(format!(
+        ".{}.tmp-{}-{}-{:08x}",
+        name,
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed),
+        rand::thread_rng().gen::<u32>()
+    ));
+
+    let result = (|| {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        if let Ok(meta) = fs::metadata(path) {
+            fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Check an IANA timezone name against the system zoneinfo database.
+pub fn validate_timezone(tz: &str) -> Result<(), String> {
+    if tz == "UTC" {
+        return Ok(());
+    }
+    let well_formed = !tz.is_empty()
+        && !tz.starts_with('/')
+        && !tz.contains("..")
+        && tz.chars().all(|c| c.is_ascii_alphanumeric() || "/_-+".contains(c));
+    if !well_formed {
+        return Err(format!("Invalid timezone '{}'", tz));
+    }
+
+    let zoneinfo_dirs = ["/usr/share/zoneinfo", "/var/db/timezone/zoneinfo"];
+    let dirs_present = zoneinfo_dirs.iter().any(|d| Path::new(d).is_dir());
+    if dirs_present && !zoneinfo_dirs.iter().any(|d| Path::new(d).join(tz).is_file()) {
+        return Err(format!("Unknown timezone '{}' (expected e.g. Europe/London)", tz));
+    }
+    Ok(())
+}
+
+/// Normalize a phone number to E.164 (+ followed by 8-15 digits), stripping
+/// spaces, dashes, dots and parentheses.
+pub fn normalize_phone(phone: &str) -> Result<String, String> {
+    let cleaned: String = phone
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+    let digits = cleaned
+        .strip_prefix('+')
+        .ok_or_else(|| format!("Phone number '{}' must include the country code (e.g. +44...)", phone))?;
+    if !(8..=15).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Phone number '{}' is not a valid international number", phone));
+    }
+    Ok(cleaned)
 }
 
 /// Generate a random 32-byte hex token.
@@ -340,6 +644,25 @@ pub fn generate_token() -> String {
 fn parse_env_file(path: &Path) -> Result<std::collections::HashMap<String, String>, String> {
     let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(parse_env_str(&content))
+}
+
+/// Parse the app's docker.env.
+pub fn read_docker_env() -> Result<std::collections::HashMap<String, String>, String> {
+    read_docker_env_in(&home_dir())
+}
+
+/// `read_docker_env` for the docker.env under `home` instead of `home_dir()`.
+pub fn read_docker_env_in(home: &Path) -> Result<std::collections::HashMap<String, String>, String> {
+    let path = home.join("openclaw/docker.env");
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read docker.env: {}", e))?;
+    Ok(parse_env_str(&content))
+}
+
+/// Parse KEY=VALUE content into a HashMap. Skips comments and empty lines.
+/// Quoted values are unescaped (see `env_quote`).
+fn parse_env_str(content: &str) -> std::collections::HashMap<String, String> {
     let mut map = std::collections::HashMap::new();
     for line in content.lines() {
         let trimmed = line.trim();
@@ -348,11 +671,84 @@ fn parse_env_file(path: &Path) -> Result<std::collections::HashMap<String, Strin
         }
         if let Some(pos) = trimmed.find('=') {
             let key = trimmed[..pos].trim().to_string();
-            let value = trimmed[pos + 1..].trim().to_string();
+            let value = env_unquote(trimmed[pos + 1..].trim());
             map.insert(key, value);
         }
     }
-    Ok(map)
+    map
+}
+
+/// Render a value for docker.env. Values that would otherwise be cut short by
+/// a comment, line break or quote are double-quoted with backslash escapes,
+/// which is also how docker compose reads env_file entries.
+pub fn env_quote(value: &str) -> String {
+    let needs_quotes = value.trim() != value
+        || value.contains(['#', '"', '\'', '\\', '$', '\n', '\r']);
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '$' => out.push_str("\\$"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Inverse of `env_quote`. Single quotes are taken literally; unquoted values
+/// are returned as-is so hand-written files keep working.
+fn env_unquote(raw: &str) -> String {
+    if raw.len() >= 2 && raw.starts_with('\'') && raw.ends_with('\'') {
+        return raw[1..raw.len() - 1].to_string();
+    }
+    if !(raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"')) {
+        return raw.to_string();
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw[1..raw.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Per-channel messaging settings from docker.env.
+fn parse_messaging(env: &std::collections::HashMap<String, String>) -> MessagingConfig {
+    let channel = |name: &str| ChannelConfig {
+        enabled: env
+            .get(&format!("MESSAGING_{}_ENABLED", name))
+            .is_some_and(|v| v == "true"),
+        autonomy: match env.get(&format!("MESSAGING_{}_AUTONOMY", name)).map(|s| s.as_str()) {
+            Some("SendWithConfirm") | Some("send_with_confirm") => MessagingAutonomy::SendWithConfirm,
+            Some("Autonomous") | Some("autonomous") => MessagingAutonomy::Autonomous,
+            _ => MessagingAutonomy::DraftOnly,
+        },
+    };
+    MessagingConfig {
+        gmail: channel("GMAIL"),
+        whatsapp: channel("WHATSAPP"),
+        telegram: channel("TELEGRAM"),
+        slack: channel("SLACK"),
+        signal: channel("SIGNAL"),
+    }
 }
 
 /// Read current configuration from config files. Returns SettingsConfig for the frontend.
@@ -406,39 +802,10 @@ pub fn read_current_config() -> Result<SettingsConfig, String> {
             .and_then(|v| v.parse().ok()).unwrap_or(1.5),
     };
 
-    // Messaging
     let parse_bool = |k: &str| env.get(k).map_or(false, |v| v == "true");
 
-    let parse_autonomy = |k: &str| -> MessagingAutonomy {
-        match env.get(k).map(|s| s.as_str()) {
-            Some("SendWithConfirm") | Some("send_with_confirm") => MessagingAutonomy::SendWithConfirm,
-            Some("Autonomous") | Some("autonomous") => MessagingAutonomy::Autonomous,
-            _ => MessagingAutonomy::DraftOnly,
-        }
-    };
-
-    let messaging = MessagingConfig {
-        gmail: ChannelConfig {
-            enabled: parse_bool("MESSAGING_GMAIL_ENABLED"),
-            autonomy: parse_autonomy("MESSAGING_GMAIL_AUTONOMY"),
-        },
-        whatsapp: ChannelConfig {
-            enabled: parse_bool("MESSAGING_WHATSAPP_ENABLED"),
-            autonomy: parse_autonomy("MESSAGING_WHATSAPP_AUTONOMY"),
-        },
-        telegram: ChannelConfig {
-            enabled: parse_bool("MESSAGING_TELEGRAM_ENABLED"),
-            autonomy: parse_autonomy("MESSAGING_TELEGRAM_AUTONOMY"),
-        },
-        slack: ChannelConfig {
-            enabled: parse_bool("MESSAGING_SLACK_ENABLED"),
-            autonomy: parse_autonomy("MESSAGING_SLACK_AUTONOMY"),
-        },
-        signal: ChannelConfig {
-            enabled: parse_bool("MESSAGING_SIGNAL_ENABLED"),
-            autonomy: parse_autonomy("MESSAGING_SIGNAL_AUTONOMY"),
-        },
-    };
+    // Messaging
+    let messaging = parse_messaging(&env);
 
     // Email notifications — parse from cron/jobs.json
     let email_notifications = read_email_config(&home);
@@ -463,6 +830,19 @@ pub fn read_current_config() -> Result<SettingsConfig, String> {
         ollama_model: env.get("OLLAMA_MODEL")
             .filter(|v| !v.is_empty())
             .cloned(),
+        intelligence_intervals: {
+            let defaults = IntelligenceIntervals::default();
+            let mins = |key: &str, default: u64| {
+                env.get(key).and_then(|v| v.parse().ok()).unwrap_or(default)
+            };
+            IntelligenceIntervals {
+                calendar_mins: mins("INTEL_CALENDAR_INTERVAL_MINS", defaults.calendar_mins),
+                email_mins: mins("INTEL_EMAIL_INTERVAL_MINS", defaults.email_mins),
+                messaging_mins: mins("INTEL_MESSAGING_INTERVAL_MINS", defaults.messaging_mins),
+                suggestion_mins: mins("INTEL_SUGGESTION_INTERVAL_MINS", defaults.suggestion_mins),
+            }
+            .clamped()
+        },
     };
 
     // WhatsApp phone from openclaw.json
@@ -547,8 +927,330 @@ fn read_email_config(home: &Path) -> EmailNotificationsConfig {
     config
 }
 
+/// Result of merging a SettingsUpdate over the current on-disk config.
+struct MergedSettings {
+    config: SetupConfig,
+    restart_required: bool,
+    previous_agent_name: String,
+}
+
 /// Apply settings update. Reads existing config, merges changes, writes all files.
+/// The previous files are backed up first so a failed save can be rolled back.
 pub fn save_settings(update: SettingsUpdate) -> Result<SettingsSaveResult, String> {
+    let MergedSettings {
+        config: setup_config,
+        restart_required,
+        previous_agent_name,
+    } = merge_settings(&update)?;
+    let agent_name = setup_config.agent_name.clone();
+
+    // Snapshot the current files so a bad save can be rolled back
+    backup_config_files()?;
+
+    // Write all config files
+    write_docker_env(&setup_config)?;
+    write_openclaw_config(&setup_config)?;
+    write_guardrails(&setup_config.guardrails)?;
+    write_cron_jobs(&setup_config)?;
+
+    // Update SOUL.md if agent name changed
+    let soul = if agent_name != previous_agent_name {
+        Some(apply_soul_personalization(&previous_agent_name, &agent_name)?)
+    } else {
+        None
+    };
+
+    Ok(SettingsSaveResult {
+        success: true,
+        restart_required,
+        message: if restart_required {
+            "Settings saved. Container restart required for changes to take effect.".to_string()
+        } else {
+            "Settings saved.".to_string()
+        },
+        soul,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Config backups
+// ---------------------------------------------------------------------------
+
+/// Snapshots kept under `~/.openclaw/backups/`; older ones are pruned.
+pub const CONFIG_BACKUP_KEEP: usize = 10;
+
+/// Files save_settings rewrites, as paths relative to home. Each is stored in
+/// the snapshot under its file name.
+const BACKUP_FILES: &[&str] = &[
+    "openclaw/docker.env",
+    ".openclaw/openclaw.json",
+    ".openclaw/secrets/defi_guardrails.env",
+    ".openclaw/cron/jobs.json",
+    "openclaw/workspace/SOUL.md",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigBackup {
+    /// Directory name, e.g. "20260214T093000Z"
+    pub timestamp: String,
+    /// File names present in the snapshot
+    pub files: Vec<String>,
+}
+
+fn backups_dir() -> PathBuf {
+    openclaw_home().join("backups")
+}
+
+fn backup_file_name(rel: &str) -> &str {
+    rel.rsplit('/').next().unwrap_or(rel)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path, mode: u32) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|e| format!("Failed to set permissions on {}: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path, _mode: u32) -> Result<(), String> {
+    Ok(())
+}
+
+/// Lists the files a snapshot was taken with, so a restore can tell a
+/// damaged snapshot from one that never had a file.
+const BACKUP_MANIFEST: &str = "manifest.json";
+
+/// Copy the current config files into a new timestamped snapshot and prune
+/// old ones. Returns None when there was nothing on disk to back up.
+pub fn backup_config_files() -> Result<Option<ConfigBackup>, String> {
+    backup_config_files_keeping(None)
+}
+
+/// `backup_config_files`, never pruning the snapshot named `keep`.
+fn backup_config_files_keeping(keep: Option<&str>) -> Result<Option<ConfigBackup>, String> {
+    let home = home_dir();
+    let existing: Vec<&str> = BACKUP_FILES
+        .iter()
+        .copied()
+        .filter(|rel| home.join(rel).is_file())
+        .collect();
+    if existing.is_empty() {
+        return Ok(None);
+    }
+
+    let root = backups_dir();
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create backups dir: {}", e))?;
+    restrict_permissions(&root, 0o700)?;
+
+    // Several saves within one second get a numeric suffix instead of colliding
+    let base = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut timestamp = base.clone();
+    let mut n = 1;
+    while root.join(&timestamp).exists() {
+        timestamp = format!("{}-{}", base, n);
+        n += 1;
+    }
+    let dir = root.join(&timestamp);
+    fs::create_dir(&dir).map_err(|e| format!("Failed to create backup dir: {}", e))?;
+    restrict_permissions(&dir, 0o700)?;
+
+    let mut files = Vec::new();
+    for rel in existing {
+        let name = backup_file_name(rel);
+        let dest = dir.join(name);
+        fs::copy(home.join(rel), &dest)
+            .map_err(|e| format!("Failed to back up {}: {}", name, e))?;
+        restrict_permissions(&dest, 0o600)?;
+        files.push(name.to_string());
+    }
+    let manifest = serde_json::to_string_pretty(&files)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    write_atomic(&dir.join(BACKUP_MANIFEST), manifest)
+        .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+
+    prune_config_backups(keep)?;
+    Ok(Some(ConfigBackup { timestamp, files }))
+}
+
+/// Available snapshots, newest first.
+pub fn list_config_backups() -> Result<Vec<ConfigBackup>, String> {
+    let entries = match fs::read_dir(backups_dir()) {
+        Ok(e) => e,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read backups dir: {}", e)),
+    };
+
+    let mut backups: Vec<ConfigBackup> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let dir = e.path();
+            let files = BACKUP_FILES
+                .iter()
+                .map(|rel| backup_file_name(rel))
+                .filter(|name| dir.join(name).is_file())
+                .map(|name| name.to_string())
+                .collect();
+            ConfigBackup {
+                timestamp: e.file_name().to_string_lossy().to_string(),
+                files,
+            }
+        })
+        .collect();
+    // Timestamps are fixed-width, so lexical order is chronological
+    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(backups)
+}
+
+fn prune_config_backups(keep: Option<&str>) -> Result<(), String> {
+    let old = list_config_backups()?.into_iter().skip(CONFIG_BACKUP_KEEP);
+    for old in old.filter(|b| Some(b.timestamp.as_str()) != keep) {
+        fs::remove_dir_all(backups_dir().join(&old.timestamp))
+            .map_err(|e| format!("Failed to remove old backup {}: {}", old.timestamp, e))?;
+    }
+    Ok(())
+}
+
+/// Put the files from a snapshot back in place. The snapshot is read into
+/// memory and checked against its manifest before anything changes; then
+/// the current files are snapshotted (never pruning the one being restored)
+/// so a restore can itself be undone.
+pub fn restore_config_backup(timestamp: &str) -> Result<ConfigBackup, String> {
+    if timestamp.is_empty()
+        || !timestamp.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(format!("Invalid backup timestamp: {}", timestamp));
+    }
+    let dir = backups_dir().join(timestamp);
+    if !dir.is_dir() {
+        return Err(format!("No config backup named {}", timestamp));
+    }
+
+    // Snapshots from before manifests were written list what is present
+    let expected: Vec<String> = match fs::read_to_string(dir.join(BACKUP_MANIFEST)) {
+        Ok(c) => serde_json::from_str(&c)
+            .map_err(|e| format!("Failed to parse backup manifest: {}", e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => BACKUP_FILES
+            .iter()
+            .map(|rel| backup_file_name(rel))
+            .filter(|name| dir.join(name).is_file())
+            .map(|name| name.to_string())
+            .collect(),
+        Err(e) => return Err(format!("Failed to read backup manifest: {}", e)),
+    };
+    if expected.is_empty() {
+        return Err(format!("Config backup {} contains no files", timestamp));
+    }
+    let mut contents = Vec::new();
+    for rel in BACKUP_FILES {
+        let name = backup_file_name(rel);
+        if !expected.iter().any(|f| f == name) {
+            continue;
+        }
+        let content = fs::read(dir.join(name)).map_err(|e| {
+            format!("Config backup {} is missing {}: {}", timestamp, name, e)
+        })?;
+        contents.push((*rel, name, content));
+    }
+
+    backup_config_files_keeping(Some(timestamp))?;
+
+    let home = home_dir();
+    let mut files = Vec::new();
+    for (rel, name, content) in contents {
+        let dest = home.join(rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        write_atomic(&dest, content).map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+        if name.ends_with(".env") {
+            restrict_permissions(&dest, 0o600)?;
+        }
+        files.push(name.to_string());
+    }
+
+    Ok(ConfigBackup {
+        timestamp: timestamp.to_string(),
+        files,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// SOUL.md personalization
+// ---------------------------------------------------------------------------
+
+fn soul_path() -> PathBuf {
+    home_dir().join("openclaw/workspace/SOUL.md")
+}
+
+/// Replace the "You're {from}" marker with "You're {to}" in SOUL.md content.
+/// Returns the new content and a report (without the file-level fields).
+pub fn personalize_soul_content(content: &str, from: &str, to: &str) -> (String, SoulPersonalization) {
+    let marker = format!("You're {}", from);
+    let target = format!("You're {}", to);
+    let replacements = if from == to { 0 } else { content.matches(&marker).count() };
+    let updated = if replacements > 0 { content.replace(&marker, &target) } else { content.to_string() };
+    let already_applied = replacements == 0 && content.contains(&target);
+
+    let warning = if replacements == 0 && !already_applied {
+        Some(format!(
+            "Agent name not applied to SOUL.md: the phrase \"{}\" was not found. \
+             Edit SOUL.md to introduce the agent as {}.",
+            marker, to
+        ))
+    } else {
+        None
+    };
+
+    let report = SoulPersonalization {
+        soul_found: true,
+        marker_found: replacements > 0,
+        replacements,
+        already_applied,
+        warning,
+    };
+    (updated, report)
+}
+
+fn missing_soul_report() -> SoulPersonalization {
+    SoulPersonalization {
+        warning: Some("SOUL.md not found in the workspace; agent name not applied.".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Rename the agent in SOUL.md, reporting whether the marker was found.
+pub fn apply_soul_personalization(from: &str, to: &str) -> Result<SoulPersonalization, String> {
+    let path = soul_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(missing_soul_report()),
+        Err(e) => return Err(format!("Failed to read SOUL.md: {}", e)),
+    };
+
+    let (updated, report) = personalize_soul_content(&content, from, to);
+    if report.replacements > 0 {
+        write_atomic(&path, updated).map_err(|e| format!("Failed to write SOUL.md: {}", e))?;
+    }
+    Ok(report)
+}
+
+/// Preview renaming the agent from its current name to `agent_name`, without writing.
+pub fn preview_soul_personalization(agent_name: &str) -> Result<SoulPersonalization, String> {
+    let current = read_current_config()
+        .map(|c| c.agent_name)
+        .unwrap_or_else(|_| "Nyx".to_string());
+    match fs::read_to_string(soul_path()) {
+        Ok(content) => Ok(personalize_soul_content(&content, &current, agent_name).1),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(missing_soul_report()),
+        Err(e) => Err(format!("Failed to read SOUL.md: {}", e)),
+    }
+}
+
+/// Merge an update over the existing config without writing anything.
+fn merge_settings(update: &SettingsUpdate) -> Result<MergedSettings, String> {
     let home = home_dir();
     let env_path = home.join("openclaw/docker.env");
     let env = parse_env_file(&env_path)?;
@@ -622,6 +1324,325 @@ pub fn save_settings(update: SettingsUpdate) -> Result<SettingsSaveResult, Strin
         .unwrap_or_else(generate_token);
 
     // Reconstruct wallets from existing env
+    let wallets = wallets_from_env(&env);
+    let active_wallet_id = env.get("ACTIVE_WALLET_ID").cloned();
+
+    // Build full SetupConfig
+    let setup_config = SetupConfig {
+        agent_name: agent_name.clone(),
+        anthropic_key,
+        openai_key,
+        venice_key,
+        nearai_key,
+        perplexity_key,
+        telegram_token,
+        slack_token,
+        whatsapp_phone,
+        gateway_token,
+        wallets,
+        active_wallet_id,
+        guardrails,
+        messaging,
+        google_authenticated: existing.google_authenticated,
+        email_notifications,
+        capabilities,
+    };
+
+    Ok(MergedSettings {
+        config: setup_config,
+        restart_required,
+        previous_agent_name: existing.agent_name,
+    })
+}
+
+/// Merge a setup wizard config over an existing install without losing
+/// anything: the gateway token, wallets the wizard didn't pass, and API keys
+/// or tokens left blank are all kept from docker.env. Everything else the
+/// wizard set wins. Returns the merged config and the previous agent name.
+pub fn merge_setup_config(incoming: SetupConfig) -> Result<(SetupConfig, String), String> {
+    let env = parse_env_file(&home_dir().join("openclaw/docker.env"))?;
+    let existing = read_current_config()?;
+
+    let keep = |new: Option<String>, key: &str| {
+        new.filter(|v| !v.is_empty())
+            .or_else(|| env.get(key).filter(|v| !v.is_empty()).cloned())
+    };
+
+    // Existing wallets are never dropped or replaced; new ones are appended
+    let mut wallets = wallets_from_env(&env);
+    for w in incoming.wallets {
+        let known = wallets
+            .iter()
+            .any(|e| e.id == w.id || (e.chain == w.chain && e.address == w.address));
+        if !known {
+            wallets.push(w);
+        }
+    }
+    let active_wallet_id = incoming
+        .active_wallet_id
+        .filter(|id| wallets.iter().any(|w| &w.id == id))
+        .or_else(|| env.get("ACTIVE_WALLET_ID").filter(|v| !v.is_empty()).cloned());
+
+    let merged = SetupConfig {
+        agent_name: incoming.agent_name,
+        anthropic_key: keep(Some(incoming.anthropic_key), "ANTHROPIC_API_KEY").unwrap_or_default(),
+        openai_key: keep(incoming.openai_key, "OPENAI_API_KEY"),
+        venice_key: keep(incoming.venice_key, "VENICE_API_KEY"),
+        nearai_key: keep(incoming.nearai_key, "NEARAI_API_KEY"),
+        perplexity_key: keep(incoming.perplexity_key, "PERPLEXITY_API_KEY"),
+        telegram_token: keep(incoming.telegram_token, "TELEGRAM_BOT_TOKEN"),
+        slack_token: keep(incoming.slack_token, "SLACK_BOT_TOKEN"),
+        whatsapp_phone: incoming
+            .whatsapp_phone
+            .filter(|p| !p.is_empty())
+            .or(existing.whatsapp_phone),
+        gateway_token: env
+            .get("OPENCLAW_GATEWAY_TOKEN")
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .unwrap_or(incoming.gateway_token),
+        wallets,
+        active_wallet_id,
+        guardrails: incoming.guardrails,
+        messaging: incoming.messaging,
+        google_authenticated: incoming.google_authenticated || existing.google_authenticated,
+        email_notifications: incoming.email_notifications,
+        capabilities: incoming.capabilities,
+    };
+
+    Ok((merged, existing.agent_name))
+}
+
+// ---------------------------------------------------------------------------
+// Capability change preview
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ListDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ListDiff {
+    fn between(before: &[String], after: &[String]) -> Self {
+        ListDiff {
+            added: after.iter().filter(|v| !before.contains(v)).cloned().collect(),
+            removed: before.iter().filter(|v| !after.contains(v)).cloned().collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// What a capability change would do to the generated config. Env entries
+/// list key names only — values (API keys, addresses) are never included.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct CapabilityDiff {
+    pub safe_bins: ListDiff,
+    pub allow_bundled: ListDiff,
+    pub skill_entries: ListDiff,
+    /// Enabled cron job ids
+    pub cron_jobs: ListDiff,
+    pub env_keys: ListDiff,
+    pub env_changed: Vec<String>,
+    pub restart_required: bool,
+}
+
+/// Compute what would change if `caps` replaced the current capabilities,
+/// using the same generators as the writers. Nothing is written.
+pub fn preview_capability_change(caps: CapabilitiesConfig) -> Result<CapabilityDiff, String> {
+    let current = merge_settings(&SettingsUpdate::default())?.config;
+    let proposed = merge_settings(&SettingsUpdate {
+        capabilities: Some(caps),
+        ..SettingsUpdate::default()
+    })?
+    .config;
+
+    let string_list = |value: &serde_json::Value, pointer: &str| -> Vec<String> {
+        value
+            .pointer(pointer)
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|i| i.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let object_keys = |value: &serde_json::Value, pointer: &str| -> Vec<String> {
+        value
+            .pointer(pointer)
+            .and_then(|v| v.as_object())
+            .map(|m| m.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let enabled_jobs = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .get("jobs")
+            .and_then(|v| v.as_array())
+            .map(|jobs| {
+                jobs.iter()
+                    .filter(|j| j.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false))
+                    .filter_map(|j| j.get("id").and_then(|v| v.as_str()).map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let before = build_openclaw_config(&current);
+    let after = build_openclaw_config(&proposed);
+    let cron_before = build_cron_jobs(&current);
+    let cron_after = build_cron_jobs(&proposed);
+
+    let (env_keys, env_changed) = env_key_diff(&current, &proposed);
+
+    let diff = CapabilityDiff {
+        safe_bins: ListDiff::between(
+            &string_list(&before, "/tools/exec/safeBins"),
+            &string_list(&after, "/tools/exec/safeBins"),
+        ),
+        allow_bundled: ListDiff::between(
+            &string_list(&before, "/skills/allowBundled"),
+            &string_list(&after, "/skills/allowBundled"),
+        ),
+        skill_entries: ListDiff::between(
+            &object_keys(&before, "/skills/entries"),
+            &object_keys(&after, "/skills/entries"),
+        ),
+        cron_jobs: ListDiff::between(&enabled_jobs(&cron_before), &enabled_jobs(&cron_after)),
+        restart_required: before != after || !env_keys.is_empty() || !env_changed.is_empty(),
+        env_keys,
+        env_changed,
+    };
+
+    Ok(diff)
+}
+
+/// docker.env keys added/removed between two configs, plus keys whose value
+/// changed. Names only.
+fn env_key_diff(before: &SetupConfig, after: &SetupConfig) -> (ListDiff, Vec<String>) {
+    let env_before = parse_env_str(&build_docker_env(before));
+    let env_after = parse_env_str(&build_docker_env(after));
+    let mut keys_before: Vec<String> = env_before.keys().cloned().collect();
+    let mut keys_after: Vec<String> = env_after.keys().cloned().collect();
+    keys_before.sort();
+    keys_after.sort();
+    let changed = keys_after
+        .iter()
+        .filter(|k| env_before.get(*k).is_some_and(|v| Some(v) != env_after.get(*k)))
+        .cloned()
+        .collect();
+    (ListDiff::between(&keys_before, &keys_after), changed)
+}
+
+/// What save_settings would change, without writing. Like CapabilityDiff,
+/// only names and paths are reported, never values.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SettingsDiff {
+    pub env_keys: ListDiff,
+    pub env_changed: Vec<String>,
+    /// JSON pointers of openclaw.json values that differ, e.g. "/llm/default"
+    pub openclaw_fields: Vec<String>,
+    pub cron_jobs: ListDiff,
+    /// Ids of cron jobs present before and after whose definition changed
+    pub cron_changed: Vec<String>,
+    pub guardrails_changed: bool,
+    /// Set when the agent name changes
+    pub soul: Option<SoulPersonalization>,
+    pub restart_required: bool,
+}
+
+/// Collect the pointers under which `before` and `after` differ. Objects are
+/// walked; anything else (arrays included) is compared as a whole.
+fn json_diff_paths(
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    path: &str,
+    out: &mut Vec<String>,
+) {
+    match (before.as_object(), after.as_object()) {
+        (Some(a), Some(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            let null = serde_json::Value::Null;
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                let (x, y) = (a.get(key).unwrap_or(&null), b.get(key).unwrap_or(&null));
+                json_diff_paths(x, y, &child, out);
+            }
+        }
+        _ if before != after => {
+            out.push(if path.is_empty() { "/".to_string() } else { path.to_string() })
+        }
+        _ => {}
+    }
+}
+
+/// Merge `update` the way save_settings does and report what would change.
+/// Nothing is written.
+pub fn preview_settings(update: SettingsUpdate) -> Result<SettingsDiff, String> {
+    let current = merge_settings(&SettingsUpdate::default())?.config;
+    let MergedSettings {
+        config: proposed,
+        restart_required,
+        previous_agent_name,
+    } = merge_settings(&update)?;
+
+    let (env_keys, env_changed) = env_key_diff(&current, &proposed);
+
+    let mut openclaw_fields = Vec::new();
+    json_diff_paths(
+        &build_openclaw_config(&current),
+        &build_openclaw_config(&proposed),
+        "",
+        &mut openclaw_fields,
+    );
+
+    let jobs_by_id = |doc: &serde_json::Value| -> HashMap<String, serde_json::Value> {
+        doc.get("jobs")
+            .and_then(|v| v.as_array())
+            .map(|jobs| {
+                jobs.iter()
+                    .filter_map(|j| Some((j.get("id")?.as_str()?.to_string(), j.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let jobs_before = jobs_by_id(&build_cron_jobs(&current));
+    let jobs_after = jobs_by_id(&build_cron_jobs(&proposed));
+    let mut ids_before: Vec<String> = jobs_before.keys().cloned().collect();
+    let mut ids_after: Vec<String> = jobs_after.keys().cloned().collect();
+    ids_before.sort();
+    ids_after.sort();
+    let cron_changed = ids_after
+        .iter()
+        .filter(|id| jobs_before.get(*id).is_some_and(|j| Some(j) != jobs_after.get(*id)))
+        .cloned()
+        .collect();
+
+    let guardrails_changed = serde_json::to_value(&current.guardrails).ok()
+        != serde_json::to_value(&proposed.guardrails).ok();
+
+    let soul = if proposed.agent_name != previous_agent_name {
+        Some(preview_soul_personalization(&proposed.agent_name)?)
+    } else {
+        None
+    };
+
+    Ok(SettingsDiff {
+        env_keys,
+        env_changed,
+        openclaw_fields,
+        cron_jobs: ListDiff::between(&ids_before, &ids_after),
+        cron_changed,
+        guardrails_changed,
+        soul,
+        restart_required,
+    })
+}
+
+/// Rebuild the wallet list from docker.env `WALLET_{i}_*` entries. Files
+/// written before ids were persisted fall back to `wallet_{i}`.
+fn wallets_from_env(env: &HashMap<String, String>) -> Vec<WalletConfig> {
     let wallet_count: usize = env.get("WALLET_COUNT")
         .and_then(|v| v.parse().ok()).unwrap_or(0);
     let mut wallets = Vec::new();
@@ -634,124 +1655,557 @@ pub fn save_settings(update: SettingsUpdate) -> Result<SettingsSaveResult, Strin
             "sol" => Chain::SOL,
             "btc" => Chain::BTC,
             "zec" => Chain::ZEC,
-            _ => Chain::NEAR,
+            "ltc" => Chain::LTC,
+            "doge" => Chain::DOGE,
+            other => {
+                // Don't guess — a wallet relabelled as NEAR would be worse than a missing one
+                eprintln!("Skipping WALLET_{}: unknown chain {:?}", i, other);
+                continue;
+            }
         };
         wallets.push(WalletConfig {
-            id: format!("wallet_{}", i),
+            id: env.get(&format!("WALLET_{}_ID", i))
+                .filter(|v| !v.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("wallet_{}", i)),
             chain,
             address: env.get(&format!("WALLET_{}_ADDRESS", i)).cloned().unwrap_or_default(),
             label: env.get(&format!("WALLET_{}_LABEL", i)).cloned().unwrap_or_default(),
             has_private_key: true,
             is_active: env.get(&format!("WALLET_{}_ACTIVE", i))
-                .map_or(false, |v| v == "true"),
+                .is_some_and(|v| v == "true"),
         });
     }
-    let active_wallet_id = env.get("ACTIVE_WALLET_ID").cloned();
+    wallets
+}
+
+/// Wallets currently configured in docker.env.
+pub fn read_configured_wallets() -> Result<Vec<WalletConfig>, String> {
+    let env = parse_env_file(&home_dir().join("openclaw/docker.env"))?;
+    Ok(wallets_from_env(&env))
+}
+
+// ---------------------------------------------------------------------------
+// ZEC / NEAR address helpers (used by shield/unshield commands)
+// ---------------------------------------------------------------------------
+
+/// Get the configured ZEC wallet address from docker.env wallets.
+pub fn get_zec_address() -> Option<String> {
+    let home = home_dir();
+    let env_path = home.join("openclaw/docker.env");
+    let env = parse_env_file(&env_path).ok()?;
+
+    let wallet_count: usize = env.get("WALLET_COUNT")
+        .and_then(|v| v.parse().ok()).unwrap_or(0);
+
+    for i in 0..wallet_count {
+        let chain = env.get(&format!("WALLET_{}_CHAIN", i)).cloned().unwrap_or_default();
+        if chain == "zec" {
+            if let Some(addr) = env.get(&format!("WALLET_{}_ADDRESS", i)) {
+                if !addr.is_empty() {
+                    return Some(addr.clone());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Get the configured NEAR account ID from docker.env wallets.
+pub fn get_near_account() -> Option<String> {
+    let home = home_dir();
+    let env_path = home.join("openclaw/docker.env");
+    let env = parse_env_file(&env_path).ok()?;
+
+    // Check for explicit NEAR_ACCOUNT_ID first
+    if let Some(account) = env.get("NEAR_ACCOUNT_ID") {
+        if !account.is_empty() {
+            return Some(account.clone());
+        }
+    }
+
+    // Fall back to first NEAR wallet address
+    let wallet_count: usize = env.get("WALLET_COUNT")
+        .and_then(|v| v.parse().ok()).unwrap_or(0);
+
+    for i in 0..wallet_count {
+        let chain = env.get(&format!("WALLET_{}_CHAIN", i)).cloned().unwrap_or_default();
+        if chain == "near" {
+            if let Some(addr) = env.get(&format!("WALLET_{}_ADDRESS", i)) {
+                if !addr.is_empty() {
+                    return Some(addr.clone());
+                }
+            }
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// App preferences (~/.nyx/preferences.json — local to the desktop app,
+// never mounted into the container)
+// ---------------------------------------------------------------------------
+
+/// Default NEAR Intents asset id for ZEC.
+pub const DEFAULT_ZEC_ASSET_ID: &str = "nep141:zec.omft.near";
+
+/// Public RPC endpoints used for balance lookups unless overridden.
+pub const DEFAULT_NEAR_RPC_URL: &str = "https://rpc.mainnet.near.org";
+pub const DEFAULT_ETH_RPC_URL: &str = "https://ethereum-rpc.publicnode.com";
+
+/// Gateway send retries after a connection error, and the delay before the
+/// first retry (doubled for each one after), unless overridden.
+pub const DEFAULT_GATEWAY_SEND_RETRIES: u32 = 3;
+pub const DEFAULT_GATEWAY_RETRY_BACKOFF_MS: u64 = 2000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NyxPreferences {
+    /// Override for the ZEC intents asset id (advanced recovery only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zec_asset_id: Option<String>,
+    /// User-edited LLM prices, keyed by model-name fragment. Replaces the defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_prices: Option<HashMap<String, ModelPrice>>,
+    /// Hosts the browser agent may (or may not) visit. Absent = allow all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser_navigation: Option<BrowserNavigationFilter>,
+    /// Per-chain RPC URL overrides for balance lookups, keyed by chain
+    /// ("near", "eth").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_endpoints: Option<HashMap<String, String>>,
+    /// Proxy for the app's outbound HTTP requests. Absent = connect directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyConfig>,
+    /// Retry policy for gateway sends. Absent = the defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway_retry: Option<GatewayRetryConfig>,
+    /// Activity Intelligence suggestion limits. Absent = the defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intelligence_tuning: Option<IntelligenceTuning>,
+}
+
+/// Outbound proxy, e.g. `socks5h://127.0.0.1:9050` for Tor (`socks5h`
+/// resolves DNS through the proxy; `socks5` resolves locally).
+///
+/// Honored by requests the desktop app makes itself: 1Click (tokens, quotes,
+/// status), chain RPC balance lookups, the OpenClaw image registry check,
+/// ClawdTalk and the browser agent's model calls. Source verification pages are
+/// fetched by the agent inside the container, whose traffic goes through the
+/// egress-proxy service instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub url: String,
+    /// Connect to the local gateway and Ollama directly (default). A Tor
+    /// SOCKS proxy can't reach localhost.
+    #[serde(default = "default_true")]
+    pub bypass_localhost: bool,
+}
+
+/// How gateway sends retry connection errors (never HTTP error responses)
+/// while a just-started container comes up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GatewayRetryConfig {
+    #[serde(default = "default_gateway_send_retries")]
+    pub send_retries: u32,
+    /// Delay before the first retry; doubled for each one after
+    #[serde(default = "default_gateway_retry_backoff_ms")]
+    pub backoff_ms: u64,
+}
+
+impl Default for GatewayRetryConfig {
+    fn default() -> Self {
+        Self {
+            send_retries: DEFAULT_GATEWAY_SEND_RETRIES,
+            backoff_ms: DEFAULT_GATEWAY_RETRY_BACKOFF_MS,
+        }
+    }
+}
+
+fn default_gateway_send_retries() -> u32 {
+    DEFAULT_GATEWAY_SEND_RETRIES
+}
+
+fn default_gateway_retry_backoff_ms() -> u64 {
+    DEFAULT_GATEWAY_RETRY_BACKOFF_MS
+}
+
+/// Per-detector limits: how many suggestions a single run may surface and
+/// how long they stay pending before expiring.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DetectorTuning {
+    pub max_count: u32,
+    pub expiry_days: u32,
+}
+
+impl DetectorTuning {
+    const fn new(max_count: u32, expiry_days: u32) -> Self {
+        DetectorTuning { max_count, expiry_days }
+    }
+}
+
+/// User-tunable Activity Intelligence suggestion limits.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IntelligenceTuning {
+    pub catch_up: DetectorTuning,
+    pub respond: DetectorTuning,
+    pub reachout: DetectorTuning,
+    pub schedule_meeting: DetectorTuning,
+    pub going_cold: DetectorTuning,
+}
+
+impl Default for IntelligenceTuning {
+    fn default() -> Self {
+        IntelligenceTuning {
+            catch_up: DetectorTuning::new(5, 7),
+            respond: DetectorTuning::new(5, 3),
+            reachout: DetectorTuning::new(5, 3),
+            schedule_meeting: DetectorTuning::new(5, 7),
+            going_cold: DetectorTuning::new(5, 7),
+        }
+    }
+}
+
+/// Host patterns for browser navigation: `example.com` matches that host,
+/// `*.example.com` matches it and every subdomain. The blocklist wins; an
+/// empty allowlist allows everything not blocked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BrowserNavigationFilter {
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    #[serde(default)]
+    pub blocklist: Vec<String>,
+}
+
+/// USD per million tokens. Used only for spend estimates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+/// Rate applied to models that match no entry in the price table.
+pub const DEFAULT_MODEL_PRICE: ModelPrice = ModelPrice {
+    input_per_mtok: 3.0,
+    output_per_mtok: 15.0,
+};
+
+/// Rough public list prices (USD / 1M tokens). Estimates only — providers
+/// change prices and apply caching discounts this table doesn't model.
+pub fn default_model_prices() -> HashMap<String, ModelPrice> {
+    let p = |input: f64, output: f64| ModelPrice { input_per_mtok: input, output_per_mtok: output };
+    HashMap::from([
+        ("claude-opus".to_string(), p(15.0, 75.0)),
+        ("claude-sonnet".to_string(), p(3.0, 15.0)),
+        ("claude-haiku".to_string(), p(1.0, 5.0)),
+        ("gpt-5".to_string(), p(1.25, 10.0)),
+        ("gpt-4.1".to_string(), p(2.0, 8.0)),
+        ("gpt-4o".to_string(), p(2.5, 10.0)),
+        ("gpt-4o-mini".to_string(), p(0.15, 0.6)),
+        ("sonar".to_string(), p(1.0, 1.0)),
+        ("ollama".to_string(), p(0.0, 0.0)),
+    ])
+}
+
+/// Current price table (user override or defaults).
+pub fn get_model_prices() -> HashMap<String, ModelPrice> {
+    read_preferences()
+        .model_prices
+        .unwrap_or_else(default_model_prices)
+}
+
+/// Replace the price table. `None` restores the defaults.
+pub fn set_model_prices(prices: Option<HashMap<String, ModelPrice>>) -> Result<(), String> {
+    if let Some(ref table) = prices {
+        for (model, price) in table {
+            if model.trim().is_empty() {
+                return Err("Model name in price table cannot be empty".to_string());
+            }
+            let valid = |v: f64| v.is_finite() && v >= 0.0;
+            if !valid(price.input_per_mtok) || !valid(price.output_per_mtok) {
+                return Err(format!("Invalid price for '{}': must be a non-negative number", model));
+            }
+        }
+    }
+    let mut prefs = load_preferences()?;
+    prefs.model_prices = prices;
+    write_preferences(&prefs)
+}
+
+/// Does `host` match a filter pattern (see `BrowserNavigationFilter`)?
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+        None => host == pattern,
+    }
+}
+
+/// Normalise a pattern to a lowercase host (optionally `*.`-prefixed).
+fn normalize_host_pattern(pattern: &str) -> Result<String, String> {
+    let p = pattern.trim().trim_end_matches('.').to_lowercase();
+    let host = p.strip_prefix("*.").unwrap_or(&p);
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        && !host.starts_with('.')
+        && !host.contains("..");
+    if !valid {
+        return Err(format!(
+            "Invalid host pattern '{}': use a host like example.com or *.example.com",
+            pattern
+        ));
+    }
+    Ok(p)
+}
+
+/// Current browser navigation filter (allow-all if unset). An unreadable
+/// preferences file is an error, not allow-all.
+pub fn get_browser_filter() -> Result<BrowserNavigationFilter, String> {
+    Ok(load_preferences()?.browser_navigation.unwrap_or_default())
+}
+
+/// Replace the browser navigation filter. Patterns are normalised and
+/// de-duplicated; empty lists restore allow-all.
+pub fn set_browser_filter(filter: BrowserNavigationFilter) -> Result<BrowserNavigationFilter, String> {
+    let clean = |list: Vec<String>| -> Result<Vec<String>, String> {
+        let mut out: Vec<String> = Vec::new();
+        for p in list {
+            let p = normalize_host_pattern(&p)?;
+            if !out.contains(&p) {
+                out.push(p);
+            }
+        }
+        Ok(out)
+    };
+    let filter = BrowserNavigationFilter {
+        allowlist: clean(filter.allowlist)?,
+        blocklist: clean(filter.blocklist)?,
+    };
+
+    let mut prefs = load_preferences()?;
+    prefs.browser_navigation = if filter.allowlist.is_empty() && filter.blocklist.is_empty() {
+        None
+    } else {
+        Some(filter.clone())
+    };
+    write_preferences(&prefs)?;
+    Ok(filter)
+}
+
+/// Check a navigation target host against the filter.
+pub fn check_browser_host(host: &str) -> Result<(), String> {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let filter = get_browser_filter()?;
+
+    if let Some(p) = filter.blocklist.iter().find(|p| host_matches(p, &host)) {
+        return Err(format!("Navigation to {} is blocked (matches '{}')", host, p));
+    }
+    if !filter.allowlist.is_empty() && !filter.allowlist.iter().any(|p| host_matches(p, &host)) {
+        return Err(format!("Navigation to {} is not on the browser allowlist", host));
+    }
+    Ok(())
+}
 
-    // Build full SetupConfig
-    let setup_config = SetupConfig {
-        agent_name: agent_name.clone(),
-        anthropic_key,
-        openai_key,
-        venice_key,
-        nearai_key,
-        perplexity_key,
-        telegram_token,
-        slack_token,
-        whatsapp_phone,
-        gateway_token,
-        wallets,
-        active_wallet_id,
-        guardrails,
-        messaging,
-        google_authenticated: existing.google_authenticated,
-        email_notifications,
-        capabilities,
+fn preferences_path() -> PathBuf {
+    nyx_home().join("preferences.json")
+}
+
+/// Read app preferences for display settings. Missing or unreadable files
+/// yield defaults; anything security-relevant or about to be written back
+/// uses `load_preferences` instead.
+pub fn read_preferences() -> NyxPreferences {
+    load_preferences().unwrap_or_default()
+}
+
+/// Read app preferences. A missing file yields defaults; a file that can't
+/// be read or parsed is an error, so callers don't overwrite it or silently
+/// drop settings like the proxy.
+pub fn load_preferences() -> Result<NyxPreferences, String> {
+    let content = match fs::read_to_string(preferences_path()) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(NyxPreferences::default()),
+        Err(e) => return Err(format!("Failed to read preferences: {}", e)),
     };
+    serde_json::from_str(&content).map_err(|e| {
+        format!(
+            "Failed to parse {}: {}. Fix or remove the file to continue.",
+            preferences_path().display(),
+            e
+        )
+    })
+}
 
-    // Write all config files
-    write_docker_env(&setup_config)?;
-    write_openclaw_config(&setup_config)?;
-    write_guardrails(&setup_config.guardrails)?;
-    write_cron_jobs(&setup_config)?;
+/// Write app preferences. Callers read them with `load_preferences` first.
+pub fn write_preferences(prefs: &NyxPreferences) -> Result<(), String> {
+    let path = preferences_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(prefs)
+        .map_err(|e| format!("Failed to serialize preferences: {}", e))?;
+    write_atomic(&path, content).map_err(|e| format!("Failed to write preferences: {}", e))
+}
 
-    // Update SOUL.md if agent name changed
-    if update.agent_name.is_some() && update.agent_name.as_deref() != Some(&existing.agent_name) {
-        let soul_path = home.join("openclaw/workspace/SOUL.md");
-        if let Ok(soul_content) = fs::read_to_string(&soul_path) {
-            let updated_soul = soul_content.replace(
-                &format!("You're {}", existing.agent_name),
-                &format!("You're {}", agent_name),
-            );
-            let _ = fs::write(&soul_path, updated_soul);
-        }
+/// Get the ZEC intents asset id (override or default).
+pub fn get_zec_asset_id() -> String {
+    read_preferences()
+        .zec_asset_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| DEFAULT_ZEC_ASSET_ID.to_string())
+}
+
+/// Override the ZEC intents asset id. `None` restores the default.
+pub fn set_zec_asset_id(asset_id: Option<String>) -> Result<String, String> {
+    let asset_id = asset_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+
+    if let Some(ref id) = asset_id {
+        validate_intents_asset_id(id)?;
     }
 
-    Ok(SettingsSaveResult {
-        success: true,
-        restart_required,
-        message: if restart_required {
-            "Settings saved. Container restart required for changes to take effect.".to_string()
-        } else {
-            "Settings saved.".to_string()
-        },
-    })
+    let mut prefs = load_preferences()?;
+    prefs.zec_asset_id = asset_id;
+    write_preferences(&prefs)?;
+
+    Ok(get_zec_asset_id())
 }
 
-// ---------------------------------------------------------------------------
-// ZEC / NEAR address helpers (used by shield/unshield commands)
-// ---------------------------------------------------------------------------
+/// Check an intents asset id has the `nep141:<contract>` or `btc:<id>` shape.
+fn validate_intents_asset_id(id: &str) -> Result<(), String> {
+    let rest = id
+        .strip_prefix("nep141:")
+        .or_else(|| id.strip_prefix("btc:"))
+        .ok_or_else(|| format!("Invalid asset id '{}': must start with nep141: or btc:", id))?;
 
-/// Get the configured ZEC wallet address from docker.env wallets.
-pub fn get_zec_address() -> Option<String> {
-    let home = home_dir();
-    let env_path = home.join("openclaw/docker.env");
-    let env = parse_env_file(&env_path).ok()?;
+    if rest.is_empty() || rest.chars().any(|c| c.is_whitespace()) {
+        return Err(format!("Invalid asset id '{}': missing or malformed identifier", id));
+    }
+    Ok(())
+}
 
-    let wallet_count: usize = env.get("WALLET_COUNT")
-        .and_then(|v| v.parse().ok()).unwrap_or(0);
+/// RPC endpoint for a chain (override or default). None for chains without
+/// balance lookup support.
+pub fn get_rpc_endpoint(chain: &Chain) -> Option<String> {
+    let default = match chain {
+        Chain::NEAR => DEFAULT_NEAR_RPC_URL,
+        Chain::ETH => DEFAULT_ETH_RPC_URL,
+        _ => return None,
+    };
+    let url = read_preferences()
+        .rpc_endpoints
+        .and_then(|mut m| m.remove(&chain.to_string()))
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| default.to_string());
+    Some(url)
+}
 
-    for i in 0..wallet_count {
-        let chain = env.get(&format!("WALLET_{}_CHAIN", i)).cloned().unwrap_or_default();
-        if chain == "zec" {
-            if let Some(addr) = env.get(&format!("WALLET_{}_ADDRESS", i)) {
-                if !addr.is_empty() {
-                    return Some(addr.clone());
-                }
-            }
+/// Override a chain's RPC endpoint. `None` restores the default.
+pub fn set_rpc_endpoint(chain: &Chain, url: Option<String>) -> Result<Option<String>, String> {
+    if !matches!(chain, Chain::NEAR | Chain::ETH) {
+        return Err(format!("Balance lookup is not supported for {}", chain));
+    }
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(ref u) = url {
+        let parsed = url::Url::parse(u).map_err(|e| format!("Invalid RPC URL '{}': {}", u, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Invalid RPC URL '{}': must be http or https", u));
         }
     }
-    None
+
+    let mut prefs = load_preferences()?;
+    let mut endpoints = prefs.rpc_endpoints.take().unwrap_or_default();
+    match url {
+        Some(u) => endpoints.insert(chain.to_string(), u),
+        None => endpoints.remove(&chain.to_string()),
+    };
+    prefs.rpc_endpoints = (!endpoints.is_empty()).then_some(endpoints);
+    write_preferences(&prefs)?;
+
+    Ok(get_rpc_endpoint(chain))
 }
 
-/// Get the configured NEAR account ID from docker.env wallets.
-pub fn get_near_account() -> Option<String> {
-    let home = home_dir();
-    let env_path = home.join("openclaw/docker.env");
-    let env = parse_env_file(&env_path).ok()?;
+/// Gateway retry policy (override or defaults).
+pub fn get_gateway_retry() -> GatewayRetryConfig {
+    read_preferences().gateway_retry.unwrap_or_default()
+}
 
-    // Check for explicit NEAR_ACCOUNT_ID first
-    if let Some(account) = env.get("NEAR_ACCOUNT_ID") {
-        if !account.is_empty() {
-            return Some(account.clone());
+/// Override the gateway retry policy. `None` restores the defaults.
+pub fn set_gateway_retry(retry: Option<GatewayRetryConfig>) -> Result<GatewayRetryConfig, String> {
+    if let Some(ref r) = retry {
+        if r.send_retries > 10 {
+            return Err("Gateway retries must be between 0 and 10".to_string());
+        }
+        if !(100..=60_000).contains(&r.backoff_ms) {
+            return Err("Gateway retry backoff must be between 100 and 60000 ms".to_string());
         }
     }
 
-    // Fall back to first NEAR wallet address
-    let wallet_count: usize = env.get("WALLET_COUNT")
-        .and_then(|v| v.parse().ok()).unwrap_or(0);
+    let mut prefs = load_preferences()?;
+    prefs.gateway_retry = retry;
+    write_preferences(&prefs)?;
 
-    for i in 0..wallet_count {
-        let chain = env.get(&format!("WALLET_{}_CHAIN", i)).cloned().unwrap_or_default();
-        if chain == "near" {
-            if let Some(addr) = env.get(&format!("WALLET_{}_ADDRESS", i)) {
-                if !addr.is_empty() {
-                    return Some(addr.clone());
-                }
-            }
+    Ok(get_gateway_retry())
+}
+
+/// Where an HTTP client connects, for proxy routing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpDestination {
+    /// Third-party services on the internet
+    Remote,
+    /// The gateway and Ollama on localhost
+    Local,
+}
+
+/// Current proxy setting (None = direct). Fails closed: if preferences.json
+/// can't be loaded there may be a proxy in it, so this is an error rather
+/// than a direct connection.
+pub fn get_proxy() -> Result<Option<ProxyConfig>, String> {
+    Ok(load_preferences()?.proxy.filter(|p| !p.url.is_empty()))
+}
+
+/// Set the outbound proxy. `None` (or an empty URL) connects directly.
+pub fn set_proxy(proxy: Option<ProxyConfig>) -> Result<Option<ProxyConfig>, String> {
+    let proxy = proxy
+        .map(|p| ProxyConfig { url: p.url.trim().to_string(), ..p })
+        .filter(|p| !p.url.is_empty());
+    if let Some(ref p) = proxy {
+        let parsed = url::Url::parse(&p.url)
+            .map_err(|e| format!("Invalid proxy URL '{}': {}", p.url, e))?;
+        if !matches!(parsed.scheme(), "socks5" | "socks5h" | "http" | "https") {
+            return Err(format!(
+                "Invalid proxy URL '{}': must be socks5, socks5h, http or https",
+                p.url
+            ));
+        }
+        if parsed.host_str().unwrap_or("").is_empty() || parsed.port_or_known_default().is_none() {
+            return Err(format!("Invalid proxy URL '{}': missing host or port", p.url));
         }
     }
-    None
+
+    let mut prefs = load_preferences()?;
+    prefs.proxy = proxy;
+    write_preferences(&prefs)?;
+
+    get_proxy()
+}
+
+/// A reqwest client builder routed through the configured proxy. Local
+/// destinations skip the proxy when `bypass_localhost` is set; with no proxy
+/// configured the builder is left as reqwest's default.
+pub fn http_client_builder(destination: HttpDestination) -> Result<reqwest::ClientBuilder, String> {
+    let builder = reqwest::Client::builder();
+    let Some(proxy) = get_proxy()? else {
+        return Ok(builder);
+    };
+    if destination == HttpDestination::Local && proxy.bypass_localhost {
+        return Ok(builder.no_proxy());
+    }
+    let proxy = reqwest::Proxy::all(&proxy.url)
+        .map_err(|e| format!("Invalid proxy URL '{}': {}", proxy.url, e))?;
+    Ok(builder.proxy(proxy))
 }
 
 // ---------------------------------------------------------------------------
@@ -798,36 +2252,155 @@ pub fn create_directories() -> Result<(), String> {
 // docker.env
 // ---------------------------------------------------------------------------
 
+/// OpenClaw image this build is tested against — the one place the version is
+/// pinned. docker.env, the image pre-pull and the installed
+/// docker-compose.yml all take it from here.
+pub const OPENCLAW_IMAGE_REPO: &str = "ghcr.io/openclaw/openclaw";
+pub const OPENCLAW_IMAGE_TAG: &str = "2026.2.21";
+
+/// `repo:tag` of the pinned OpenClaw image.
+pub fn openclaw_image() -> String {
+    format!("{}:{}", OPENCLAW_IMAGE_REPO, OPENCLAW_IMAGE_TAG)
+}
+
+/// Point every OpenClaw `image:` line in a compose file at the pinned image.
+fn pin_compose_image(compose: &str) -> String {
+    let prefix = format!("image: {}:", OPENCLAW_IMAGE_REPO);
+    let mut out: String = compose
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with(&prefix) {
+                let indent = &line[..line.len() - trimmed.len()];
+                format!("{}image: {}", indent, openclaw_image())
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if compose.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
 /// Generate docker.env from config.
 pub fn write_docker_env(config: &SetupConfig) -> Result<(), String> {
     let home = home_dir();
     let path = home.join("openclaw/docker.env");
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let content = merge_docker_env(&existing, &build_docker_env(config));
+
+    write_atomic(&path, content)
+        .map_err(|e| format!("Failed to write docker.env: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to set docker.env permissions: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Keys build_docker_env only writes conditionally. Together with everything
+/// it did write (and the WALLET_ prefix) these are owned by Nyx, so a stale
+/// value is dropped rather than carried over.
+const MANAGED_ENV_KEYS: &[&str] = &[
+    "OPENAI_API_KEY",
+    "VENICE_API_KEY",
+    "NEARAI_API_KEY",
+    "PERPLEXITY_API_KEY",
+    "TELEGRAM_BOT_TOKEN",
+    "SLACK_BOT_TOKEN",
+    "ACTIVE_WALLET_ID",
+    "MAX_SINGLE_TX_USD",
+    "DAILY_LOSS_LIMIT_PCT",
+    "WEEKLY_LOSS_LIMIT_PCT",
+    "MAX_CONCENTRATION_PCT",
+    "BURROW_MIN_HEALTH_FACTOR",
+    "MAX_SLIPPAGE_PCT",
+    "MAX_DAILY_TXS",
+    "REQUIRE_CONFIRMATION",
+    "NEAR_ACCOUNT_ID",
+    "NEAR_NETWORK_ID",
+    "SOLVER_RELAY_URL",
+];
+
+const PRESERVED_ENV_HEADER: &str = "# Other settings (kept as-is)";
+
+/// Append the entries of `existing` that Nyx doesn't manage to `generated`,
+/// each with the comment lines directly above it, so hand-added variables
+/// (HTTP_PROXY, CLAWDTALK_API_KEY, ...) survive a settings save.
+fn merge_docker_env(existing: &str, generated: &str) -> String {
+    let generated_keys = parse_env_str(generated);
+    let is_managed = |key: &str| {
+        generated_keys.contains_key(key)
+            || MANAGED_ENV_KEYS.contains(&key)
+            || key.starts_with("WALLET_")
+    };
+
+    let mut preserved: Vec<&str> = Vec::new();
+    let mut comments: Vec<&str> = Vec::new();
+    for line in existing.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            comments.clear();
+        } else if trimmed.starts_with('#') {
+            if trimmed != PRESERVED_ENV_HEADER {
+                comments.push(line);
+            }
+        } else if let Some(pos) = trimmed.find('=') {
+            if !is_managed(trimmed[..pos].trim()) {
+                preserved.append(&mut comments);
+                preserved.push(line);
+            }
+            comments.clear();
+        }
+    }
+
+    if preserved.is_empty() {
+        return generated.to_string();
+    }
+    format!(
+        "{}\n\n{}\n{}\n",
+        generated.trim_end(),
+        PRESERVED_ENV_HEADER,
+        preserved.join("\n")
+    )
+}
 
+/// Render docker.env content for a config.
+pub fn build_docker_env(config: &SetupConfig) -> String {
     let mut content = format!(
         "# Nyx Docker Environment\n\
          OPENCLAW_GATEWAY_TOKEN={}\n\
-         OPENCLAW_IMAGE=ghcr.io/openclaw/openclaw:2026.2.21\n\
+         OPENCLAW_IMAGE={}\n\
          ANTHROPIC_API_KEY={}\n",
-        config.gateway_token, config.anthropic_key
+        env_quote(&config.gateway_token),
+        openclaw_image(),
+        env_quote(&config.anthropic_key)
     );
 
     if let Some(ref key) = config.openai_key {
-        content.push_str(&format!("OPENAI_API_KEY={}\n", key));
+        content.push_str(&format!("OPENAI_API_KEY={}\n", env_quote(key)));
     }
     if let Some(ref key) = config.venice_key {
-        content.push_str(&format!("VENICE_API_KEY={}\n", key));
+        content.push_str(&format!("VENICE_API_KEY={}\n", env_quote(key)));
     }
     if let Some(ref key) = config.nearai_key {
-        content.push_str(&format!("NEARAI_API_KEY={}\n", key));
+        content.push_str(&format!("NEARAI_API_KEY={}\n", env_quote(key)));
     }
     if let Some(ref key) = config.perplexity_key {
-        content.push_str(&format!("PERPLEXITY_API_KEY={}\n", key));
+        content.push_str(&format!("PERPLEXITY_API_KEY={}\n", env_quote(key)));
     }
     if let Some(ref token) = config.telegram_token {
-        content.push_str(&format!("TELEGRAM_BOT_TOKEN={}\n", token));
+        content.push_str(&format!("TELEGRAM_BOT_TOKEN={}\n", env_quote(token)));
     }
     if let Some(ref token) = config.slack_token {
-        content.push_str(&format!("SLACK_BOT_TOKEN={}\n", token));
+        content.push_str(&format!("SLACK_BOT_TOKEN={}\n", env_quote(token)));
     }
 
     // Wallet credentials — injected at container boundary, never mounted as files
@@ -836,13 +2409,14 @@ pub fn write_docker_env(config: &SetupConfig) -> Result<(), String> {
         config.wallets.len()
     ));
     for (i, w) in config.wallets.iter().enumerate() {
+        content.push_str(&format!("WALLET_{}_ID={}\n", i, env_quote(&w.id)));
         content.push_str(&format!("WALLET_{}_CHAIN={}\n", i, w.chain));
-        content.push_str(&format!("WALLET_{}_ADDRESS={}\n", i, w.address));
-        content.push_str(&format!("WALLET_{}_LABEL={}\n", i, w.label));
+        content.push_str(&format!("WALLET_{}_ADDRESS={}\n", i, env_quote(&w.address)));
+        content.push_str(&format!("WALLET_{}_LABEL={}\n", i, env_quote(&w.label)));
         content.push_str(&format!("WALLET_{}_ACTIVE={}\n", i, w.is_active));
     }
     if let Some(ref active_id) = config.active_wallet_id {
-        content.push_str(&format!("ACTIVE_WALLET_ID={}\n", active_id));
+        content.push_str(&format!("ACTIVE_WALLET_ID={}\n", env_quote(active_id)));
     }
 
     // DeFi guardrails — from config (only if DeFi capability enabled)
@@ -880,24 +2454,25 @@ pub fn write_docker_env(config: &SetupConfig) -> Result<(), String> {
              NEAR_ACCOUNT_ID={}\n\
              NEAR_NETWORK_ID=mainnet\n\
              SOLVER_RELAY_URL=https://solver-relay.near.org\n",
-            near_wallet.address
+            env_quote(&near_wallet.address)
         ));
     }
 
     // Messaging env vars (with autonomy)
     let m = &config.messaging;
+    let intervals = caps.intelligence_intervals.clamped();
     content.push_str(&format!(
         "\n# Messaging\n\
          MESSAGING_GMAIL_ENABLED={}\n\
-         MESSAGING_GMAIL_AUTONOMY={:?}\n\
+         MESSAGING_GMAIL_AUTONOMY={}\n\
          MESSAGING_WHATSAPP_ENABLED={}\n\
-         MESSAGING_WHATSAPP_AUTONOMY={:?}\n\
+         MESSAGING_WHATSAPP_AUTONOMY={}\n\
          MESSAGING_TELEGRAM_ENABLED={}\n\
-         MESSAGING_TELEGRAM_AUTONOMY={:?}\n\
+         MESSAGING_TELEGRAM_AUTONOMY={}\n\
          MESSAGING_SLACK_ENABLED={}\n\
-         MESSAGING_SLACK_AUTONOMY={:?}\n\
+         MESSAGING_SLACK_AUTONOMY={}\n\
          MESSAGING_SIGNAL_ENABLED={}\n\
-         MESSAGING_SIGNAL_AUTONOMY={:?}\n\
+         MESSAGING_SIGNAL_AUTONOMY={}\n\
          GOOGLE_AUTHENTICATED={}\n\
          \n# Privacy\n\
          ZEC_PRIVACY_DEFAULT=true\n\
@@ -912,7 +2487,12 @@ pub fn write_docker_env(config: &SetupConfig) -> Result<(), String> {
          CAPABILITY_ACTIVITY_INTEL={}\n\
          CAPABILITY_WEB_BROWSING={}\n\
          DEFAULT_LLM_PROVIDER={}\n\
-         OLLAMA_MODEL={}\n",
+         OLLAMA_MODEL={}\n\
+         \n# Activity Intelligence observer (minutes)\n\
+         INTEL_CALENDAR_INTERVAL_MINS={}\n\
+         INTEL_EMAIL_INTERVAL_MINS={}\n\
+         INTEL_MESSAGING_INTERVAL_MINS={}\n\
+         INTEL_SUGGESTION_INTERVAL_MINS={}\n",
         m.gmail.enabled, m.gmail.autonomy,
         m.whatsapp.enabled, m.whatsapp.autonomy,
         m.telegram.enabled, m.telegram.autonomy,
@@ -927,21 +2507,15 @@ pub fn write_docker_env(config: &SetupConfig) -> Result<(), String> {
         caps.source_intelligence,
         caps.activity_intelligence,
         caps.web_browsing,
-        caps.default_llm_provider,
-        caps.ollama_model.as_deref().unwrap_or(""),
+        env_quote(&caps.default_llm_provider),
+        env_quote(caps.ollama_model.as_deref().unwrap_or("")),
+        intervals.calendar_mins,
+        intervals.email_mins,
+        intervals.messaging_mins,
+        intervals.suggestion_mins,
     ));
 
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write docker.env: {}", e))?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
-            .map_err(|e| format!("Failed to set docker.env permissions: {}", e))?;
-    }
-
-    Ok(())
+    content
 }
 
 // ---------------------------------------------------------------------------
@@ -952,7 +2526,19 @@ pub fn write_docker_env(config: &SetupConfig) -> Result<(), String> {
 pub fn write_openclaw_config(config: &SetupConfig) -> Result<(), String> {
     let home = home_dir();
     let path = home.join(".openclaw/openclaw.json");
+    let config_json = build_openclaw_config(config);
+
+    let content = serde_json::to_string_pretty(&config_json)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write_atomic(&path, content)
+        .map_err(|e| format!("Failed to write openclaw.json: {}", e))?;
+
+    Ok(())
+}
 
+/// Build the openclaw.json document for a config.
+pub fn build_openclaw_config(config: &SetupConfig) -> serde_json::Value {
+    let home = home_dir();
     let has_perplexity = config.perplexity_key.is_some();
     let has_telegram = config.telegram_token.is_some();
     let has_slack = config.slack_token.is_some();
@@ -1089,18 +2675,19 @@ pub fn write_openclaw_config(config: &SetupConfig) -> Result<(), String> {
             "model": "qwen3-30b-a3b"
         }));
     }
+    // Search-grounded model for research; also backs tools.web.search below
+    if has_perplexity {
+        providers.insert("perplexity".to_string(), json!({
+            "enabled": true,
+            "baseUrl": "https://api.perplexity.ai",
+            "model": "sonar"
+        }));
+    }
 
     // Resolve the default model based on provider
-    let default_model = match default_provider.as_str() {
-        "anthropic" => "claude-sonnet-4-20250514",
-        "openai" => "gpt-4o",
-        "venice" => "llama-3.3-70b",
-        "nearai" => "qwen3-30b-a3b",
-        "ollama" => caps.ollama_model.as_deref().unwrap_or("qwen3:4b"),
-        _ => "claude-sonnet-4-20250514",
-    };
+    let default_model = caps.default_model();
 
-    let config_json = json!({
+    json!({
         "agents": {
             "defaults": {
                 "workspace": "/home/node/.openclaw/workspace",
@@ -1200,14 +2787,7 @@ pub fn write_openclaw_config(config: &SetupConfig) -> Result<(), String> {
             "entries": skill_entries
         },
         "plugins": { "entries": plugins }
-    });
-
-    let content = serde_json::to_string_pretty(&config_json)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write openclaw.json: {}", e))?;
-
-    Ok(())
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -1239,7 +2819,7 @@ pub fn write_guardrails(guardrails: &GuardrailsConfig) -> Result<(), String> {
         guardrails.require_confirmation,
     );
 
-    fs::write(&path, content)
+    write_atomic(&path, content)
         .map_err(|e| format!("Failed to write guardrails: {}", e))?;
 
     #[cfg(unix)]
@@ -1260,7 +2840,18 @@ pub fn write_guardrails(guardrails: &GuardrailsConfig) -> Result<(), String> {
 pub fn write_cron_jobs(config: &SetupConfig) -> Result<(), String> {
     let home = home_dir();
     let path = home.join(".openclaw/cron/jobs.json");
+    let jobs = build_cron_jobs(config);
+
+    let content = serde_json::to_string_pretty(&jobs)
+        .map_err(|e| format!("Failed to serialize cron jobs: {}", e))?;
+    write_atomic(&path, content)
+        .map_err(|e| format!("Failed to write cron jobs: {}", e))?;
+
+    Ok(())
+}
 
+/// Build the cron jobs.json document for a config.
+pub fn build_cron_jobs(config: &SetupConfig) -> serde_json::Value {
     let e = &config.email_notifications;
     let caps = &config.capabilities;
     let email_enabled = config.google_authenticated && e.enabled && caps.email_intelligence;
@@ -1282,7 +2873,7 @@ pub fn write_cron_jobs(config: &SetupConfig) -> Result<(), String> {
     let triage_cron = format!("0 {}-{} * * *", e.triage_start_hour, e.triage_end_hour);
     let digest_cron = format!("{} {} * * *", e.digest_minute, e.digest_hour);
 
-    let jobs = json!({
+    json!({
         "version": 2,
         "jobs": [
             {
@@ -1384,14 +2975,7 @@ pub fn write_cron_jobs(config: &SetupConfig) -> Result<(), String> {
                 "delivery": { "mode": "none" }
             }
         ]
-    });
-
-    let content = serde_json::to_string_pretty(&jobs)
-        .map_err(|e| format!("Failed to serialize cron jobs: {}", e))?;
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write cron jobs: {}", e))?;
-
-    Ok(())
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -1399,14 +2983,18 @@ pub fn write_cron_jobs(config: &SetupConfig) -> Result<(), String> {
 // ---------------------------------------------------------------------------
 
 /// Copy bundled resources to user directories.
-pub fn copy_resources(resources_dir: &Path) -> Result<(), String> {
+pub fn copy_resources(resources_dir: &Path, overwrite_workspace: bool) -> Result<(), String> {
     let home = home_dir();
 
-    // Copy workspace files
-    copy_dir_contents(
-        &resources_dir.join("workspace"),
-        &home.join("openclaw/workspace"),
-    )?;
+    // Copy workspace files. These are the user's to edit (SOUL.md etc.), so
+    // existing ones are only replaced when `overwrite_workspace` is set.
+    let workspace_src = resources_dir.join("workspace");
+    let workspace_dst = home.join("openclaw/workspace");
+    if overwrite_workspace {
+        copy_dir_contents(&workspace_src, &workspace_dst)?;
+    } else {
+        copy_missing_dir_contents(&workspace_src, &workspace_dst)?;
+    }
 
     // Copy skills
     copy_dir_contents(
@@ -1471,7 +3059,9 @@ pub fn copy_resources(resources_dir: &Path) -> Result<(), String> {
     let compose_src = resources_dir.join("docker-compose.yml");
     let compose_dst = home.join("openclaw/docker-compose.yml");
     if compose_src.exists() {
-        fs::copy(&compose_src, &compose_dst)
+        let compose = fs::read_to_string(&compose_src)
+            .map_err(|e| format!("Failed to read docker-compose.yml: {}", e))?;
+        write_atomic(&compose_dst, pin_compose_image(&compose))
             .map_err(|e| format!("Failed to copy docker-compose.yml: {}", e))?;
     }
 
@@ -1500,6 +3090,32 @@ pub fn copy_resources(resources_dir: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Like `copy_dir_contents`, but files that already exist are left alone.
+fn copy_missing_dir_contents(src: &Path, dst: &Path) -> Result<(), String> {
+    if !src.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(dst)
+        .map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+
+    for entry in fs::read_dir(src)
+        .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?
+    {
+        let entry = entry.map_err(|e| format!("Dir entry error: {}", e))?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+
+        if src_path.is_dir() {
+            copy_missing_dir_contents(&src_path, &dst_path)?;
+        } else if !dst_path.exists() {
+            fs::copy(&src_path, &dst_path)
+                .map_err(|e| format!("Failed to copy {}: {}", src_path.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
 fn copy_dir_contents(src: &Path, dst: &Path) -> Result<(), String> {
     if !src.exists() {
         return Ok(());
@@ -1524,3 +3140,220 @@ fn copy_dir_contents(src: &Path, dst: &Path) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_config() -> SetupConfig {
+        SetupConfig {
+            agent_name: "Nyx".to_string(),
+            anthropic_key: "sk-ant-test".to_string(),
+            openai_key: None,
+            venice_key: None,
+            nearai_key: None,
+            perplexity_key: None,
+            telegram_token: None,
+            slack_token: None,
+            whatsapp_phone: None,
+            gateway_token: "gateway-token".to_string(),
+            wallets: Vec::new(),
+            active_wallet_id: None,
+            guardrails: GuardrailsConfig::default(),
+            messaging: MessagingConfig::default(),
+            google_authenticated: false,
+            email_notifications: EmailNotificationsConfig::default(),
+            capabilities: CapabilitiesConfig::default(),
+        }
+    }
+
+    /// Save `config` over `existing` docker.env content and read it back.
+    fn save_and_read(existing: &str, config: &SetupConfig) -> std::collections::HashMap<String, String> {
+        parse_env_str(&merge_docker_env(existing, &build_docker_env(config)))
+    }
+
+    #[test]
+    fn messaging_autonomy_survives_save_and_read() {
+        for autonomy in [
+            MessagingAutonomy::DraftOnly,
+            MessagingAutonomy::SendWithConfirm,
+            MessagingAutonomy::Autonomous,
+        ] {
+            let mut config = setup_config();
+            config.messaging.whatsapp = ChannelConfig { enabled: true, autonomy: autonomy.clone() };
+            config.messaging.slack.autonomy = autonomy.clone();
+
+            let messaging = parse_messaging(&save_and_read("", &config));
+            assert_eq!(messaging.whatsapp.autonomy, autonomy);
+            assert!(messaging.whatsapp.enabled);
+            assert_eq!(messaging.slack.autonomy, autonomy);
+            assert!(!messaging.slack.enabled);
+            assert_eq!(messaging.gmail.autonomy, MessagingAutonomy::DraftOnly);
+        }
+    }
+
+    #[test]
+    fn messaging_autonomy_save_replaces_previous_value() {
+        let existing = "MESSAGING_TELEGRAM_AUTONOMY=autonomous\nMESSAGING_TELEGRAM_ENABLED=true\n";
+        let messaging = parse_messaging(&save_and_read(existing, &setup_config()));
+        assert_eq!(messaging.telegram.autonomy, MessagingAutonomy::DraftOnly);
+        assert!(!messaging.telegram.enabled);
+    }
+
+    #[test]
+    fn messaging_autonomy_reads_legacy_spelling() {
+        let env = parse_env_str("MESSAGING_SIGNAL_AUTONOMY=SendWithConfirm\nMESSAGING_GMAIL_AUTONOMY=bogus\n");
+        let messaging = parse_messaging(&env);
+        assert_eq!(messaging.signal.autonomy, MessagingAutonomy::SendWithConfirm);
+        assert_eq!(messaging.gmail.autonomy, MessagingAutonomy::DraftOnly);
+    }
+
+    /// Values that need quoting in docker.env.
+    const TRICKY_VALUES: &[&str] = &[
+        "My # wallet = test",
+        "say \"hi\"",
+        "it's",
+        "'single'",
+        "$HOME and ${PATH}",
+        r"C:\keys\near\",
+        "line one\nline two\r\n",
+        "  padded  ",
+        "\ttabbed",
+        "#",
+        "=",
+        "\\n is not a newline",
+    ];
+
+    #[test]
+    fn env_quote_round_trips() {
+        for value in TRICKY_VALUES {
+            let quoted = env_quote(value);
+            assert!(!quoted.contains('\n'), "{:?} spans lines: {:?}", value, quoted);
+            assert_eq!(env_unquote(&quoted), *value, "quoted as {:?}", quoted);
+        }
+        assert_eq!(env_quote("sk-ant-api03_abc=="), "sk-ant-api03_abc==");
+        assert_eq!(env_unquote("plain"), "plain");
+    }
+
+    #[test]
+    fn env_file_round_trips_tricky_values() {
+        let mut content = String::from("# comment\n\n");
+        for (i, value) in TRICKY_VALUES.iter().enumerate() {
+            content.push_str(&format!("KEY_{}={}\n", i, env_quote(value)));
+        }
+        let env = parse_env_str(&content);
+        for (i, value) in TRICKY_VALUES.iter().enumerate() {
+            assert_eq!(env.get(&format!("KEY_{}", i)).map(String::as_str), Some(*value));
+        }
+        assert_eq!(env.len(), TRICKY_VALUES.len());
+    }
+
+    #[test]
+    fn wallet_label_round_trips_through_docker_env() {
+        let mut config = setup_config();
+        config.wallets.push(WalletConfig {
+            id: "w1".to_string(),
+            chain: Chain::NEAR,
+            address: "alice.near".to_string(),
+            label: "My # wallet = test".to_string(),
+            has_private_key: true,
+            is_active: true,
+        });
+        config.anthropic_key = "sk-ant-$weird#key".to_string();
+
+        let env = save_and_read("", &config);
+        assert_eq!(env["WALLET_0_LABEL"], "My # wallet = test");
+        assert_eq!(env["ANTHROPIC_API_KEY"], "sk-ant-$weird#key");
+        assert_eq!(env["NEAR_ACCOUNT_ID"], "alice.near");
+    }
+
+    #[test]
+    fn read_docker_env_unquotes_values() {
+        let home = std::env::temp_dir().join(format!("nyx-home-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(home.join("openclaw")).unwrap();
+        let content: String = TRICKY_VALUES
+            .iter()
+            .enumerate()
+            .map(|(i, value)| format!("KEY_{}={}\n", i, env_quote(value)))
+            .collect();
+        fs::write(home.join("openclaw/docker.env"), content).unwrap();
+
+        let env = read_docker_env_in(&home);
+        fs::remove_dir_all(&home).unwrap();
+
+        let env = env.unwrap();
+        for (i, value) in TRICKY_VALUES.iter().enumerate() {
+            assert_eq!(env[&format!("KEY_{}", i)], *value);
+        }
+    }
+
+    /// Held by every test that sets or removes environment variables, since
+    /// the environment is shared by all test threads.
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn nyx_home_redirects_reads_and_writes() {
+        let _env = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let real_prefs = std::env::var_os("HOME")
+            .map(|h| PathBuf::from(h).join(".nyx/preferences.json"));
+        let real_before = real_prefs.as_ref().map(fs::read);
+
+        let home = std::env::temp_dir().join(format!("nyx-home-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(home.join("openclaw")).unwrap();
+        fs::write(home.join("openclaw/docker.env"), "NEAR_ACCOUNT_ID=alice.near\n").unwrap();
+
+        std::env::set_var("NYX_HOME", &home);
+        let paths = (home_dir(), nyx_home(), openclaw_home());
+        let written = write_preferences(&NyxPreferences::default());
+        let loaded = load_preferences();
+        let env = read_docker_env();
+        std::env::remove_var("NYX_HOME");
+
+        assert_eq!(paths, (home.clone(), home.join(".nyx"), home.join(".openclaw")));
+        written.unwrap();
+        loaded.unwrap();
+        assert!(home.join(".nyx/preferences.json").exists());
+        assert_eq!(env.unwrap()["NEAR_ACCOUNT_ID"], "alice.near");
+        fs::remove_dir_all(&home).unwrap();
+
+        let real_after = real_prefs.as_ref().map(fs::read);
+        assert_eq!(
+            real_before.map(|r| r.ok()),
+            real_after.map(|r| r.ok()),
+            "the real ~/.nyx/preferences.json changed"
+        );
+    }
+
+    #[test]
+    fn intelligence_tuning_lives_in_preferences() {
+        let prefs: NyxPreferences = serde_json::from_str("{}").unwrap();
+        assert!(prefs.intelligence_tuning.is_none());
+
+        let prefs: NyxPreferences = serde_json::from_str(
+            r#"{"intelligence_tuning": {"respond": {"max_count": 9, "expiry_days": 2}}}"#,
+        )
+        .unwrap();
+        let tuning = prefs.intelligence_tuning.unwrap();
+        assert_eq!((tuning.respond.max_count, tuning.respond.expiry_days), (9, 2));
+        // Detectors left out keep their defaults
+        assert_eq!(tuning.going_cold.max_count, IntelligenceTuning::default().going_cold.max_count);
+
+        // A malformed entry is an error, not a silent reset
+        let bad = serde_json::from_str::<NyxPreferences>(
+            r#"{"intelligence_tuning": {"respond": {"max_count": "lots"}}}"#,
+        );
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn gateway_retry_defaults_fill_missing_fields() {
+        let prefs: NyxPreferences = serde_json::from_str("{}").unwrap();
+        assert_eq!(prefs.gateway_retry.unwrap_or_default(), GatewayRetryConfig::default());
+
+        let prefs: NyxPreferences =
+            serde_json::from_str(r#"{"gateway_retry": {"send_retries": 5}}"#).unwrap();
+        let retry = prefs.gateway_retry.unwrap();
+        assert_eq!(retry.send_retries, 5);
+        assert_eq!(retry.backoff_ms, DEFAULT_GATEWAY_RETRY_BACKOFF_MS);
+    }
+}
@@ -1,12 +1,27 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+use crate::config;
+use crate::gateway;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DockerCheck {
     pub installed: bool,
     pub running: bool,
     pub version: Option<String>,
     pub download_url: Option<String>,
+    /// Image this build pins (`config::openclaw_image`)
+    pub pinned_image: String,
+    /// Image of the gateway container, if it's running
+    pub running_image: Option<String>,
+    /// OpenClaw image referenced by the installed docker-compose.yml
+    pub compose_image: Option<String>,
+    /// OPENCLAW_IMAGE from docker.env
+    pub env_image: Option<String>,
+    /// Newest version tag on the registry, when it could be reached
+    pub latest_tag: Option<String>,
+    /// `latest_tag` is newer than the tag in use (running, else pinned)
+    pub update_available: bool,
 }
 
 /// Detailed Docker status: installed, running, version, download link.
@@ -42,14 +57,109 @@ pub async fn check_docker_detailed() -> Result<DockerCheck, String> {
         None
     };
 
+    let pinned_image = config::openclaw_image();
+    let running_image = if running { gateway_running_image().await } else { None };
+    let latest_tag = if installed { latest_registry_tag().await.ok() } else { None };
+    let in_use_tag = running_image
+        .as_deref()
+        .and_then(|image| image.rsplit_once(':'))
+        .map_or(config::OPENCLAW_IMAGE_TAG, |(_, tag)| tag);
+    let update_available = matches!(
+        (latest_tag.as_deref().and_then(parse_version), parse_version(in_use_tag)),
+        (Some(latest), Some(current)) if latest > current
+    );
+
     Ok(DockerCheck {
         installed,
         running,
         version,
         download_url,
+        pinned_image,
+        running_image,
+        compose_image: compose_image(),
+        env_image: config::read_docker_env().ok().and_then(|mut env| env.remove("OPENCLAW_IMAGE")),
+        latest_tag,
+        update_available,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Image version
+// ---------------------------------------------------------------------------
+
+/// Image of the gateway container while it's running.
+async fn gateway_running_image() -> Option<String> {
+    let id = gateway_container_id().await.ok()?;
+    let output = Command::new("docker")
+        .args(["inspect", "--format", "{{if .State.Running}}{{.Config.Image}}{{end}}", &id])
+        .output()
+        .ok()?;
+    let image = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !image.is_empty()).then_some(image)
+}
+
+/// First OpenClaw `image:` in the installed docker-compose.yml.
+fn compose_image() -> Option<String> {
+    let content = std::fs::read_to_string(compose_file_path()).ok()?;
+    content.lines().find_map(|line| {
+        let image = line.trim().strip_prefix("image:")?.trim().trim_matches('"');
+        image.starts_with(config::OPENCLAW_IMAGE_REPO).then(|| image.to_string())
     })
 }
 
+/// "2026.2.21" -> [2026, 2, 21]. Non-numeric tags (latest, sha-...) are None.
+fn parse_version(tag: &str) -> Option<Vec<u64>> {
+    tag.trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Highest version tag of the OpenClaw image on its registry. Uses the
+/// anonymous pull token GHCR hands out for public images.
+async fn latest_registry_tag() -> Result<String, String> {
+    let (registry, repo) = config::OPENCLAW_IMAGE_REPO
+        .split_once('/')
+        .ok_or_else(|| "Malformed image repository".to_string())?;
+    let client = config::http_client_builder(config::HttpDestination::Remote)?
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let token: serde_json::Value = client
+        .get(format!("https://{}/token?scope=repository:{}:pull", registry, repo))
+        .send()
+        .await
+        .map_err(|e| format!("Registry token request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse registry token: {}", e))?;
+    let token = token
+        .get("token")
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "Registry returned no token".to_string())?;
+
+    let tags: serde_json::Value = client
+        .get(format!("https://{}/v2/{}/tags/list?n=1000", registry, repo))
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| format!("Registry tag list request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse registry tags: {}", e))?;
+
+    tags.get("tags")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str())
+        .filter_map(|t| parse_version(t).map(|v| (v, t)))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, tag)| tag.to_string())
+        .ok_or_else(|| "No version tags found on registry".to_string())
+}
+
 /// Get the Docker Desktop download URL based on the current macOS architecture.
 pub fn get_docker_download_url() -> String {
     let arch = std::env::consts::ARCH;
@@ -70,28 +180,84 @@ pub async fn is_docker_running() -> Result<bool, String> {
     Ok(output.status.success())
 }
 
-/// Start the openclaw-gateway container.
+/// How long start_container waits for the gateway to answer after `up`.
+pub const START_READY_TIMEOUT_SECS: u64 = 90;
+
+/// Start the openclaw-gateway container and wait until its gateway answers,
+/// so callers can send messages straight away.
 pub async fn start_container() -> Result<(), String> {
-    let home = dirs_next().ok_or("Cannot determine home directory")?;
-    let compose_file = format!("{}/openclaw/docker-compose.yml", home);
+    let compose_file = compose_file_path();
 
     let output = Command::new("docker")
         .args(["compose", "-f", &compose_file, "up", "-d", "openclaw-gateway"])
         .output()
         .map_err(|e| format!("Failed to start container: {}", e))?;
 
-    if output.status.success() {
-        Ok(())
-    } else {
+    if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Container start failed: {}", stderr))
+        return Err(format!("Container start failed: {}", stderr));
     }
+
+    wait_until_ready(START_READY_TIMEOUT_SECS).await
+}
+
+/// Poll the gateway port until it answers. Fails early if the container
+/// exits or its healthcheck reports unhealthy, rather than waiting out the
+/// timeout. (The compose healthcheck runs only every 60s, so the port is
+/// the primary signal.)
+pub async fn wait_until_ready(timeout_secs: u64) -> Result<(), String> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    loop {
+        if gateway::wait_for_gateway(2).await.is_ok() {
+            return Ok(());
+        }
+
+        if let Some((status, exit_code, health)) = container_state().await {
+            if matches!(status.as_str(), "exited" | "dead") {
+                return Err(format!(
+                    "OpenClaw container exited during startup (exit code {}) — see the container logs",
+                    exit_code
+                ));
+            }
+            if health == "unhealthy" {
+                return Err("OpenClaw container is unhealthy — see the container logs".to_string());
+            }
+        }
+
+        if std::time::Instant::now() >= deadline {
+            return Err(format!(
+                "OpenClaw gateway did not become ready within {}s",
+                timeout_secs
+            ));
+        }
+    }
+}
+
+/// (status, exit code, health) of the gateway container. Health is empty
+/// when the container has no healthcheck.
+async fn container_state() -> Option<(String, i64, String)> {
+    let id = gateway_container_id().await.ok()?;
+    let output = Command::new("docker")
+        .args([
+            "inspect",
+            "--format",
+            "{{.State.Status}} {{.State.ExitCode}} {{if .State.Health}}{{.State.Health.Status}}{{end}}",
+            &id,
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    let mut parts = text.split_whitespace();
+    let status = parts.next()?.to_string();
+    let exit_code = parts.next()?.parse().unwrap_or(0);
+    let health = parts.next().unwrap_or("").to_string();
+    Some((status, exit_code, health))
 }
 
 /// Stop the openclaw-gateway container.
 pub async fn stop_container() -> Result<(), String> {
-    let home = dirs_next().ok_or("Cannot determine home directory")?;
-    let compose_file = format!("{}/openclaw/docker-compose.yml", home);
+    let compose_file = compose_file_path();
 
     let output = Command::new("docker")
         .args(["compose", "-f", &compose_file, "stop", "openclaw-gateway"])
@@ -121,12 +287,73 @@ pub async fn container_status() -> Result<String, String> {
     }
 }
 
-/// Restart the openclaw-gateway container (stop + start).
+/// Seconds compose gives the gateway to shut down before SIGKILL on restart.
+const RESTART_STOP_TIMEOUT_SECS: u32 = 30;
+
+/// Restart progress reported to the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartProgress {
+    /// "restarting", "waiting", "ready" or "failed"
+    pub stage: String,
+    pub message: String,
+}
+
+/// `docker` arguments for a graceful in-place restart of the gateway. Kept
+/// separate from execution so the command line can be checked without docker.
+pub fn restart_args(compose_file: &str) -> Vec<String> {
+    [
+        "compose",
+        "-f",
+        compose_file,
+        "restart",
+        "--timeout",
+        &RESTART_STOP_TIMEOUT_SECS.to_string(),
+        GATEWAY_SERVICE,
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect()
+}
+
+/// Restart the openclaw-gateway container in place.
 pub async fn restart_container() -> Result<(), String> {
-    stop_container().await?;
-    // Brief pause for clean shutdown
-    std::thread::sleep(std::time::Duration::from_secs(2));
-    start_container().await
+    restart_container_with_progress(|_| {}).await
+}
+
+/// `docker compose restart` the gateway — the container (and everything
+/// mounted from ~/.openclaw) is kept, unlike down/up — then wait for it to
+/// answer. Each step is reported through `on_progress`.
+pub async fn restart_container_with_progress<F>(on_progress: F) -> Result<(), String>
+where
+    F: Fn(RestartProgress),
+{
+    let progress = |stage: &str, message: String| {
+        on_progress(RestartProgress {
+            stage: stage.to_string(),
+            message,
+        })
+    };
+    let fail = |message: String| {
+        progress("failed", message.clone());
+        message
+    };
+
+    progress("restarting", "Restarting the OpenClaw container".to_string());
+    let output = tokio::process::Command::new("docker")
+        .args(restart_args(&compose_file_path()))
+        .output()
+        .await
+        .map_err(|e| fail(format!("Failed to restart container: {}", e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(fail(format!("Container restart failed: {}", stderr)));
+    }
+
+    progress("waiting", "Waiting for the gateway to come back".to_string());
+    wait_until_ready(START_READY_TIMEOUT_SECS).await.map_err(fail)?;
+
+    progress("ready", "Gateway is back up".to_string());
+    Ok(())
 }
 
 /// Download and install Docker Desktop from the official DMG.
@@ -240,6 +467,194 @@ pub async fn pull_image(image: &str) -> Result<(), String> {
     }
 }
 
-fn dirs_next() -> Option<String> {
-    std::env::var("HOME").ok()
+// ---------------------------------------------------------------------------
+// Container logs
+// ---------------------------------------------------------------------------
+
+const GATEWAY_SERVICE: &str = "openclaw-gateway";
+
+/// Upper bound for `get_logs` so a huge tail can't stall the UI.
+pub const MAX_LOG_TAIL: usize = 5000;
+
+/// Id of the gateway container, running or not. Asks compose first (the
+/// project name varies with the directory), then falls back to the compose
+/// service label and finally the fixed container_name.
+pub async fn gateway_container_id() -> Result<String, String> {
+    let first_line = |out: std::process::Output| {
+        out.status
+            .success()
+            .then(|| String::from_utf8_lossy(&out.stdout).lines().next().map(|l| l.trim().to_string()))
+            .flatten()
+            .filter(|id| !id.is_empty())
+    };
+
+    let compose_file = compose_file_path();
+    if let Some(id) = Command::new("docker")
+        .args(["compose", "-f", &compose_file, "ps", "-a", "-q", GATEWAY_SERVICE])
+        .output()
+        .ok()
+        .and_then(first_line)
+    {
+        return Ok(id);
+    }
+
+    let label = format!("label=com.docker.compose.service={}", GATEWAY_SERVICE);
+    if let Some(id) = Command::new("docker")
+        .args(["ps", "-a", "-q", "--filter", &label])
+        .output()
+        .ok()
+        .and_then(first_line)
+    {
+        return Ok(id);
+    }
+
+    // `name=` filters match substrings, so check the exact name with inspect
+    Command::new("docker")
+        .args(["inspect", "--format", "{{.Id}}", GATEWAY_SERVICE])
+        .output()
+        .ok()
+        .and_then(first_line)
+        .ok_or_else(|| "OpenClaw container not found — has it been started?".to_string())
+}
+
+/// Recent gateway container output. `since` takes anything `docker logs
+/// --since` does ("10m", an RFC 3339 time, a Unix timestamp). stdout and
+/// stderr are merged, so relative order between them is approximate.
+pub async fn get_logs(tail: usize, since: Option<String>) -> Result<String, String> {
+    let id = gateway_container_id().await?;
+    let tail = tail.clamp(1, MAX_LOG_TAIL).to_string();
+
+    let mut args = vec!["logs", "--timestamps", "--tail", tail.as_str()];
+    if let Some(ref since) = since {
+        args.extend(["--since", since.as_str()]);
+    }
+    args.push(id.as_str());
+
+    let output = Command::new("docker")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to read container logs: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("docker logs failed: {}", stderr.trim()));
+    }
+
+    let mut logs = String::from_utf8_lossy(&output.stdout).to_string();
+    logs.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(logs)
+}
+
+/// One line from the followed container output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerLogLine {
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+/// The running `docker logs -f` task, if any.
+static LOG_STREAM: std::sync::LazyLock<std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(None));
+
+/// Wait before re-attaching after the container stops or restarts.
+const LOG_REATTACH_SECS: u64 = 2;
+
+/// Follow the gateway container's output, calling `on_line` for each new
+/// line. Re-attaches when the container restarts. Replaces any running stream.
+pub fn stream_logs<F>(on_line: F) -> Result<(), String>
+where
+    F: Fn(DockerLogLine) + Send + Sync + 'static,
+{
+    let handle = tokio::spawn(async move {
+        loop {
+            if let Ok(id) = gateway_container_id().await {
+                let child = tokio::process::Command::new("docker")
+                    .args(["logs", "-f", "--tail", "0", &id])
+                    .stdout(std::process::Stdio::piped())
+                    .stderr(std::process::Stdio::piped())
+                    .kill_on_drop(true)
+                    .spawn();
+                if let Ok(mut child) = child {
+                    tokio::join!(
+                        forward_lines(child.stdout.take(), "stdout", &on_line),
+                        forward_lines(child.stderr.take(), "stderr", &on_line),
+                    );
+                    let _ = child.wait().await;
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(LOG_REATTACH_SECS)).await;
+        }
+    });
+
+    let mut stream = LOG_STREAM
+        .lock()
+        .map_err(|_| "Log stream lock poisoned".to_string())?;
+    if let Some(previous) = stream.replace(handle) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+async fn forward_lines<R, F>(pipe: Option<R>, stream: &str, on_line: &F)
+where
+    R: tokio::io::AsyncRead + Unpin,
+    F: Fn(DockerLogLine),
+{
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let Some(pipe) = pipe else { return };
+    let mut lines = BufReader::new(pipe).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        on_line(DockerLogLine {
+            stream: stream.to_string(),
+            line,
+        });
+    }
+}
+
+/// Stop following container logs (no-op if not streaming).
+pub fn stop_log_stream() -> Result<(), String> {
+    let mut stream = LOG_STREAM
+        .lock()
+        .map_err(|_| "Log stream lock poisoned".to_string())?;
+    if let Some(handle) = stream.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+fn compose_file_path() -> String {
+    config::home_dir()
+        .join("openclaw/docker-compose.yml")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restart_args_restart_in_place() {
+        assert_eq!(
+            restart_args("/home/me/openclaw/docker-compose.yml"),
+            [
+                "compose",
+                "-f",
+                "/home/me/openclaw/docker-compose.yml",
+                "restart",
+                "--timeout",
+                "30",
+                GATEWAY_SERVICE,
+            ]
+        );
+    }
+
+    #[test]
+    fn restart_args_never_recreate_the_container() {
+        let args = restart_args("docker-compose.yml");
+        for verb in ["down", "up", "rm", "stop", "--force-recreate", "-v", "--volumes"] {
+            assert!(!args.iter().any(|a| a == verb), "restart must not use {}", verb);
+        }
+    }
 }
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::config;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -21,11 +23,52 @@ pub struct SessionInfo {
     pub folder: Option<String>,
 }
 
+/// Token usage reported by the gateway for a single chat completion.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ChatUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// Assistant reply plus token usage (None if the gateway omitted it).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatReply {
+    pub text: String,
+    pub usage: Option<ChatUsage>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatFolders {
     pub folders: Vec<ChatFolder>,
     pub session_folders: HashMap<String, String>,
     pub session_titles: HashMap<String, String>,
+    /// Per-session context token budgets
+    #[serde(default)]
+    pub session_budgets: HashMap<String, SessionBudget>,
+    /// Old session key -> continuation session key
+    #[serde(default)]
+    pub session_continuations: HashMap<String, String>,
+}
+
+/// Context budget for a single session.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionBudget {
+    pub max_context_tokens: u64,
+    /// Start a linked continuation session (with a summary) instead of only warning
+    #[serde(default)]
+    pub auto_continue: bool,
+}
+
+/// Reported when a session nears or exceeds its context budget.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub session_key: String,
+    pub used_tokens: u64,
+    pub max_context_tokens: u64,
+    /// Set when an auto-continuation session was created
+    pub continued_as: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -45,6 +88,8 @@ impl Default for ChatFolders {
             ],
             session_folders: HashMap::new(),
             session_titles: HashMap::new(),
+            session_budgets: HashMap::new(),
+            session_continuations: HashMap::new(),
         }
     }
 }
@@ -55,19 +100,9 @@ impl Default for ChatFolders {
 
 /// Read gateway token from docker.env.
 fn read_gateway_token() -> Result<String, String> {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let env_path = PathBuf::from(&home).join("openclaw/docker.env");
-
-    let content = fs::read_to_string(&env_path)
-        .map_err(|e| format!("Failed to read docker.env: {}", e))?;
-
-    for line in content.lines() {
-        if line.starts_with("OPENCLAW_GATEWAY_TOKEN=") {
-            return Ok(line.trim_start_matches("OPENCLAW_GATEWAY_TOKEN=").to_string());
-        }
-    }
-
-    Err("Gateway token not found in docker.env".to_string())
+    config::read_docker_env()?
+        .remove("OPENCLAW_GATEWAY_TOKEN")
+        .ok_or_else(|| "Gateway token not found in docker.env".to_string())
 }
 
 /// Extract the assistant reply from an OpenAI chat completion response.
@@ -85,14 +120,64 @@ fn extract_openai_reply(text: &str) -> String {
     text.to_string()
 }
 
+/// Extract the `usage` block from an OpenAI chat completion response.
+fn extract_openai_usage(text: &str) -> Option<ChatUsage> {
+    let json = serde_json::from_str::<serde_json::Value>(text).ok()?;
+    let usage = json.get("usage")?;
+    let field = |name: &str| usage.get(name).and_then(|v| v.as_u64());
+
+    let prompt_tokens = field("prompt_tokens").unwrap_or(0);
+    let completion_tokens = field("completion_tokens").unwrap_or(0);
+    let total_tokens = field("total_tokens").unwrap_or(prompt_tokens + completion_tokens);
+
+    Some(ChatUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens,
+    })
+}
+
+/// Raw sessions.json entries keyed by session key.
+type RawSessions = HashMap<String, serde_json::Value>;
+
+/// `~/.openclaw/agents/default/sessions` — the gateway's session store.
+fn sessions_dir() -> PathBuf {
+    sessions_dir_in(&config::openclaw_home())
+}
+
+/// The session store under an openclaw home.
+fn sessions_dir_in(openclaw: &Path) -> PathBuf {
+    openclaw.join("agents/default/sessions")
+}
+
+fn read_raw_sessions() -> Result<RawSessions, String> {
+    read_raw_sessions_in(&sessions_dir())
+}
+
+fn read_raw_sessions_in(dir: &Path) -> Result<RawSessions, String> {
+    let sessions_path = dir.join("sessions.json");
+
+    let content = fs::read_to_string(&sessions_path)
+        .map_err(|e| format!("Failed to read sessions.json: {}", e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse sessions.json: {}", e))
+}
+
 fn folders_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(&home).join(".openclaw/agents/default/chat_folders.json")
+    folders_path_in(&config::openclaw_home())
+}
+
+fn folders_path_in(openclaw: &Path) -> PathBuf {
+    openclaw.join("agents/default/chat_folders.json")
 }
 
 fn load_folders() -> ChatFolders {
-    let path = folders_path();
-    if let Ok(content) = fs::read_to_string(&path) {
+    load_folders_from(&folders_path())
+}
+
+fn load_folders_from(path: &Path) -> ChatFolders {
+    if let Ok(content) = fs::read_to_string(path) {
         serde_json::from_str(&content).unwrap_or_default()
     } else {
         ChatFolders::default()
@@ -100,10 +185,13 @@ fn load_folders() -> ChatFolders {
 }
 
 fn save_folders(folders: &ChatFolders) -> Result<(), String> {
-    let path = folders_path();
+    save_folders_to(&folders_path(), folders)
+}
+
+fn save_folders_to(path: &Path, folders: &ChatFolders) -> Result<(), String> {
     let content = serde_json::to_string_pretty(folders)
         .map_err(|e| format!("Failed to serialize folders: {}", e))?;
-    fs::write(&path, content)
+    config::write_atomic(path, content)
         .map_err(|e| format!("Failed to write folders: {}", e))
 }
 
@@ -118,14 +206,83 @@ pub async fn send_message(message: String) -> Result<String, String> {
 
 /// Send a message to a specific session via the gateway's OpenAI-compatible endpoint.
 pub async fn send_message_to_session(message: String, session_key: String) -> Result<String, String> {
-    let token = read_gateway_token()?;
+    send_message_with_usage(message, session_key)
+        .await
+        .map(|reply| reply.text)
+}
+
+/// Like `send_message_to_session`, but also returns the token usage.
+pub async fn send_message_with_usage(message: String, session_key: String) -> Result<ChatReply, String> {
+    send_with_timeout(message, &session_key, GATEWAY_TIMEOUT_SECS)
+        .await
+        .map_err(String::from)
+}
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(180))
+/// Default gateway chat timeout.
+const GATEWAY_TIMEOUT_SECS: u64 = 180;
+const GATEWAY_BASE_URL: &str = "http://127.0.0.1:18789";
+/// How long to wait for a just-started container's gateway to accept connections.
+const GATEWAY_READY_TIMEOUT_SECS: u64 = 30;
+
+/// Gateway send failure, keeping timeouts distinct from other errors.
+enum SendError {
+    Timeout(u64),
+    Failed(String),
+}
+
+impl From<SendError> for String {
+    fn from(e: SendError) -> Self {
+        match e {
+            SendError::Timeout(secs) => format!("Gateway request timed out after {}s", secs),
+            SendError::Failed(msg) => msg,
+        }
+    }
+}
+
+/// Wait until the gateway accepts HTTP connections. Any response counts —
+/// only connection failures mean the container is still booting.
+pub async fn wait_for_gateway(timeout_secs: u64) -> Result<(), String> {
+    let client = config::http_client_builder(config::HttpDestination::Local)?
+        .timeout(std::time::Duration::from_secs(2))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    let mut delay_ms = 250;
 
-    let url = "http://127.0.0.1:18789/v1/chat/completions";
+    loop {
+        match client.get(GATEWAY_BASE_URL).send().await {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                if std::time::Instant::now() >= deadline {
+                    return Err(format!(
+                        "Gateway not reachable at {} after {}s — is the Nyx container running? ({})",
+                        GATEWAY_BASE_URL, timeout_secs, e
+                    ));
+                }
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        delay_ms = (delay_ms * 2).min(2000);
+    }
+}
+
+async fn send_with_timeout(
+    message: String,
+    session_key: &str,
+    timeout_secs: u64,
+) -> Result<ChatReply, SendError> {
+    let token = read_gateway_token().map_err(SendError::Failed)?;
+    wait_for_gateway(GATEWAY_READY_TIMEOUT_SECS)
+        .await
+        .map_err(SendError::Failed)?;
+
+    let client = config::http_client_builder(config::HttpDestination::Local)
+        .map_err(SendError::Failed)?
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .build()
+        .map_err(|e| SendError::Failed(format!("HTTP client error: {}", e)))?;
+
+    let url = format!("{}/v1/chat/completions", GATEWAY_BASE_URL);
 
     let body = serde_json::json!({
         "model": "default",
@@ -134,55 +291,337 @@ pub async fn send_message_to_session(message: String, session_key: String) -> Re
         ]
     });
 
-    let response = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .header("X-OpenClaw-Session-Key", &session_key)
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("Gateway request failed: {}", e))?;
+    let retry = config::get_gateway_retry();
+    let mut attempt = 0;
+    let response = loop {
+        let result = client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("X-OpenClaw-Session-Key", session_key)
+            .json(&body)
+            .send()
+            .await;
+        match result {
+            Ok(response) => break response,
+            // The gateway can drop connections for a moment right after it
+            // starts listening; retrying is safe since nothing was delivered.
+            Err(e) if e.is_connect() && attempt < retry.send_retries => {
+                let delay = retry.backoff_ms.saturating_mul(1 << attempt.min(16));
+                attempt += 1;
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            }
+            Err(e) if e.is_timeout() => return Err(SendError::Timeout(timeout_secs)),
+            Err(e) => return Err(SendError::Failed(format!("Gateway request failed: {}", e))),
+        }
+    };
 
     let status = response.status();
-    let text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let text = response.text().await.map_err(|e| {
+        if e.is_timeout() {
+            SendError::Timeout(timeout_secs)
+        } else {
+            SendError::Failed(format!("Failed to read response: {}", e))
+        }
+    })?;
 
     if status.is_success() {
-        Ok(extract_openai_reply(&text))
+        Ok(ChatReply {
+            text: extract_openai_reply(&text),
+            usage: extract_openai_usage(&text),
+        })
     } else {
-        Err(format!("Gateway error ({}): {}", status, text))
+        Err(SendError::Failed(format!("Gateway error ({}): {}", status, text)))
     }
 }
 
+// ---------------------------------------------------------------------------
+// Streaming chat
+// ---------------------------------------------------------------------------
+
+/// Splits an SSE byte stream into complete `data:` payloads. Bytes after the
+/// last newline are held until the next chunk, so lines (and multi-byte
+/// characters) split across chunk boundaries are reassembled.
+#[derive(Default)]
+struct SseBuffer {
+    pending: Vec<u8>,
+}
+
+impl SseBuffer {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(data) = line.trim_end_matches(['\r', '\n']).strip_prefix("data:") {
+                payloads.push(data.trim_start().to_string());
+            }
+        }
+        payloads
+    }
+}
+
+/// What one SSE payload contributed to the reply.
+enum StreamEvent {
+    Token(String),
+    Usage(ChatUsage),
+    Done,
+    Error(String),
+    Ignore,
+}
+
+fn parse_stream_payload(data: &str) -> StreamEvent {
+    if data == "[DONE]" {
+        return StreamEvent::Done;
+    }
+    let json: serde_json::Value = match serde_json::from_str(data) {
+        Ok(v) => v,
+        Err(_) => return StreamEvent::Ignore,
+    };
+    if let Some(err) = json.get("error") {
+        let msg = err
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| err.to_string());
+        return StreamEvent::Error(msg);
+    }
+    if let Some(token) = json
+        .pointer("/choices/0/delta/content")
+        .and_then(|v| v.as_str())
+        .filter(|t| !t.is_empty())
+    {
+        return StreamEvent::Token(token.to_string());
+    }
+    match extract_openai_usage(data) {
+        Some(usage) => StreamEvent::Usage(usage),
+        None => StreamEvent::Ignore,
+    }
+}
+
+/// Send a message with `"stream": true`, calling `on_token` for each content
+/// delta as it arrives. Returns the assembled reply once the gateway sends
+/// `[DONE]`. The timeout applies to the gap between chunks, not the whole run,
+/// so long agent replies aren't cut off while they're still producing output.
+pub async fn send_message_stream<F>(
+    message: String,
+    session_key: String,
+    mut on_token: F,
+) -> Result<ChatReply, String>
+where
+    F: FnMut(&str),
+{
+    let token = read_gateway_token()?;
+    wait_for_gateway(GATEWAY_READY_TIMEOUT_SECS).await?;
+
+    let client = config::http_client_builder(config::HttpDestination::Local)?
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let body = serde_json::json!({
+        "model": "default",
+        "stream": true,
+        "stream_options": { "include_usage": true },
+        "messages": [
+            { "role": "user", "content": message }
+        ]
+    });
+
+    let idle = std::time::Duration::from_secs(GATEWAY_TIMEOUT_SECS);
+    let mut response = tokio::time::timeout(
+        idle,
+        client
+            .post(format!("{}/v1/chat/completions", GATEWAY_BASE_URL))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream")
+            .header("X-OpenClaw-Session-Key", &session_key)
+            .json(&body)
+            .send(),
+    )
+    .await
+    .map_err(|_| String::from(SendError::Timeout(GATEWAY_TIMEOUT_SECS)))?
+    .map_err(|e| format!("Gateway request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Gateway error ({}): {}", status, text));
+    }
+
+    let mut buffer = SseBuffer::default();
+    let mut text = String::new();
+    let mut usage = None;
+
+    loop {
+        let chunk = tokio::time::timeout(idle, response.chunk())
+            .await
+            .map_err(|_| String::from(SendError::Timeout(GATEWAY_TIMEOUT_SECS)))?
+            .map_err(|e| format!("Failed to read response stream: {}", e))?;
+        let chunk = match chunk {
+            Some(c) => c,
+            // Connection closed without [DONE] — keep what arrived
+            None => break,
+        };
+        for payload in buffer.push(&chunk) {
+            match parse_stream_payload(&payload) {
+                StreamEvent::Token(t) => {
+                    on_token(&t);
+                    text.push_str(&t);
+                }
+                StreamEvent::Usage(u) => usage = Some(u),
+                StreamEvent::Done => return Ok(ChatReply { text, usage }),
+                StreamEvent::Error(e) => return Err(format!("Gateway stream error: {}", e)),
+                StreamEvent::Ignore => {}
+            }
+        }
+    }
+
+    Ok(ChatReply { text, usage })
+}
+
+// ---------------------------------------------------------------------------
+// Context budget
+// ---------------------------------------------------------------------------
+
+/// Warn once a session has used this fraction of its budget.
+const BUDGET_WARN_RATIO: f64 = 0.8;
+/// Auto-continue (when enabled) once this fraction is used.
+const BUDGET_CONTINUE_RATIO: f64 = 0.9;
+
+/// Set or clear (None) the context budget for a session.
+pub fn set_session_budget(
+    session_key: String,
+    max_context_tokens: Option<u64>,
+    auto_continue: bool,
+) -> Result<(), String> {
+    let mut data = load_folders();
+    match max_context_tokens {
+        Some(0) => return Err("max_context_tokens must be greater than 0".to_string()),
+        Some(max) => {
+            data.session_budgets.insert(
+                session_key,
+                SessionBudget { max_context_tokens: max, auto_continue },
+            );
+        }
+        None => {
+            data.session_budgets.remove(&session_key);
+        }
+    }
+    save_folders(&data)
+}
+
+/// Compare a session's token use against its budget. Returns None when no
+/// budget is set or use is below the warning threshold. If the session is
+/// over the continuation threshold and auto_continue is on, a linked session
+/// seeded with a summary is created.
+pub async fn check_session_budget(
+    session_key: &str,
+    usage: Option<&ChatUsage>,
+) -> Result<Option<BudgetStatus>, String> {
+    let data = load_folders();
+    let budget = match data.session_budgets.get(session_key) {
+        Some(b) => b.clone(),
+        None => return Ok(None),
+    };
+
+    // sessions.json lags behind the latest reply, so also use the prompt size
+    // the gateway just reported (≈ the context currently carried).
+    let tracked = list_sessions()
+        .ok()
+        .and_then(|sessions| sessions.into_iter().find(|s| s.session_key == session_key))
+        .and_then(|s| s.total_tokens)
+        .unwrap_or(0);
+    let reported = usage.map(|u| u.prompt_tokens + u.completion_tokens).unwrap_or(0);
+    let used_tokens = tracked.max(reported);
+
+    let should_continue = match budget_verdict(used_tokens, &budget) {
+        Some(c) => c,
+        None => return Ok(None),
+    };
+
+    let continued_as = match data.session_continuations.get(session_key) {
+        Some(existing) => Some(existing.clone()),
+        None if should_continue => Some(continue_session(session_key).await?),
+        None => None,
+    };
+
+    Ok(Some(BudgetStatus {
+        session_key: session_key.to_string(),
+        used_tokens,
+        max_context_tokens: budget.max_context_tokens,
+        continued_as,
+    }))
+}
+
+/// None while `used_tokens` is below the warning threshold; otherwise
+/// whether the session should be continued in a linked session.
+fn budget_verdict(used_tokens: u64, budget: &SessionBudget) -> Option<bool> {
+    let ratio = used_tokens as f64 / budget.max_context_tokens as f64;
+    if ratio < BUDGET_WARN_RATIO {
+        return None;
+    }
+    Some(budget.auto_continue && ratio >= BUDGET_CONTINUE_RATIO)
+}
+
+/// Summarise a session and start a linked continuation carrying the summary.
+async fn continue_session(session_key: &str) -> Result<String, String> {
+    let summary = send_message_to_session(
+        "Summarise our conversation so far in under 200 words: key facts, decisions, \
+         and open tasks. Reply with the summary only."
+            .to_string(),
+        session_key.to_string(),
+    )
+    .await?;
+
+    let data = load_folders();
+    let title = data
+        .session_titles
+        .get(session_key)
+        .cloned()
+        .unwrap_or_else(|| session_key.strip_prefix("agent:default:").unwrap_or(session_key).to_string());
+    let folder = data.session_folders.get(session_key).cloned();
+
+    let new_key = create_session(Some(format!("{} (cont.)", title)), folder)?;
+
+    let mut data = load_folders();
+    let budget = data.session_budgets.get(session_key).cloned();
+    if let Some(budget) = budget {
+        data.session_budgets.insert(new_key.clone(), budget);
+    }
+    data.session_continuations
+        .insert(session_key.to_string(), new_key.clone());
+    save_folders(&data)?;
+
+    send_message_to_session(
+        format!(
+            "This conversation continues a previous session that ran out of context. \
+             Summary of what came before:\n\n{}",
+            summary
+        ),
+        new_key.clone(),
+    )
+    .await?;
+
+    Ok(new_key)
+}
+
 // ---------------------------------------------------------------------------
 // Session management
 // ---------------------------------------------------------------------------
 
 /// List all chat sessions from sessions.json, enriched with folder metadata.
 pub fn list_sessions() -> Result<Vec<SessionInfo>, String> {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let sessions_path = PathBuf::from(&home)
-        .join(".openclaw/agents/default/sessions/sessions.json");
-
-    let content = fs::read_to_string(&sessions_path)
-        .map_err(|e| format!("Failed to read sessions.json: {}", e))?;
-
-    let raw: HashMap<String, serde_json::Value> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse sessions.json: {}", e))?;
+    let raw = read_raw_sessions()?;
 
     let folders_data = load_folders();
 
     let mut sessions: Vec<SessionInfo> = raw
         .iter()
-        .filter(|(key, _)| {
-            // Only include user chat sessions, skip internal ones
-            key.starts_with("agent:default:")
-                && !key.contains("veritas")
-                && !key.contains("cron")
-        })
+        // Only include user chat sessions, skip internal ones
+        .filter(|(key, _)| key.starts_with("agent:default:") && !is_internal_session(key))
         .map(|(key, val)| {
             let short_key = key.strip_prefix("agent:default:").unwrap_or(key);
             SessionInfo {
@@ -206,6 +645,180 @@ pub fn list_sessions() -> Result<Vec<SessionInfo>, String> {
     Ok(sessions)
 }
 
+/// Sessions the app uses behind the scenes (source analysis, scheduled jobs).
+fn is_internal_session(key: &str) -> bool {
+    key.contains("veritas") || key.contains("cron")
+}
+
+/// Delete a chat session: its sessions.json entry, its title/folder/budget
+/// metadata and, when `drop_transcript` is set, the gateway's transcript file
+/// so the agent does not resume the conversation under the same key.
+///
+/// sessions.json belongs to the gateway, which rewrites it while running and
+/// has no API for removing a session, so a session it knows about can only
+/// be deleted while the container is stopped.
+pub async fn delete_session(session_key: String, drop_transcript: bool) -> Result<(), String> {
+    if is_internal_session(&session_key) {
+        return Err(format!("{} is an internal session and can't be deleted", session_key));
+    }
+
+    let openclaw = config::openclaw_home();
+    let in_index = match read_raw_sessions_in(&sessions_dir_in(&openclaw)) {
+        Ok(raw) => raw.contains_key(&session_key),
+        // Never messaged — only local metadata exists
+        Err(_) if !sessions_dir_in(&openclaw).join("sessions.json").exists() => false,
+        Err(e) => return Err(e),
+    };
+    if in_index && crate::docker::container_status().await? != "stopped" {
+        return Err(
+            "Stop the agent before deleting this chat: the gateway is using its session store"
+                .to_string(),
+        );
+    }
+    delete_session_in(&openclaw, &session_key, drop_transcript)
+}
+
+/// The file work of `delete_session`, under an openclaw home.
+fn delete_session_in(openclaw: &Path, session_key: &str, drop_transcript: bool) -> Result<(), String> {
+    let dir = sessions_dir_in(openclaw);
+    let sessions_path = dir.join("sessions.json");
+    let mut raw = match read_raw_sessions_in(&dir) {
+        Ok(raw) => raw,
+        Err(_) if !sessions_path.exists() => HashMap::new(),
+        Err(e) => return Err(e),
+    };
+    if let Some(entry) = raw.remove(session_key) {
+        let content = serde_json::to_string_pretty(&raw)
+            .map_err(|e| format!("Failed to serialize sessions: {}", e))?;
+        config::write_atomic(&sessions_path, content)
+            .map_err(|e| format!("Failed to write sessions.json: {}", e))?;
+
+        if drop_transcript {
+            if let Some(id) = entry.get("sessionId").and_then(|v| v.as_str()) {
+                let transcript = dir.join(format!("{}.jsonl", id));
+                if transcript.exists() {
+                    fs::remove_file(&transcript)
+                        .map_err(|e| format!("Failed to remove session transcript: {}", e))?;
+                }
+            }
+        }
+    }
+
+    let folders_path = folders_path_in(openclaw);
+    let mut folders_data = load_folders_from(&folders_path);
+    folders_data.session_titles.remove(session_key);
+    folders_data.session_folders.remove(session_key);
+    folders_data.session_budgets.remove(session_key);
+    folders_data.session_continuations.remove(session_key);
+    folders_data.session_continuations.retain(|_, next| next != session_key);
+    save_folders_to(&folders_path, &folders_data)
+}
+
+/// Text of a transcript message: a plain string or the `text` parts of a
+/// content array (tool calls and images are skipped).
+fn transcript_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        _ => String::new(),
+    }
+}
+
+/// Format unix milliseconds as "YYYY-MM-DD HH:MM UTC".
+fn format_timestamp_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60
+    )
+}
+
+fn transcript_timestamp(entry: &serde_json::Value) -> Option<String> {
+    let ts = entry.get("timestamp").or_else(|| entry.pointer("/message/timestamp"))?;
+    match ts {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => n.as_u64().map(format_timestamp_ms),
+        _ => None,
+    }
+}
+
+/// Render a session transcript as Markdown. Sessions without an on-disk
+/// transcript (never messaged, or already deleted) export a header only.
+pub fn export_session(session_key: String) -> Result<String, String> {
+    let title = load_folders()
+        .session_titles
+        .get(&session_key)
+        .cloned()
+        .unwrap_or_else(|| session_key.clone());
+    let mut md = format!("# {}\n\n_Session `{}`_\n", title, session_key);
+
+    let session_id = read_raw_sessions()
+        .ok()
+        .and_then(|raw| {
+            raw.get(&session_key)
+                .and_then(|v| v.get("sessionId"))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        });
+    let transcript = session_id
+        .map(|id| sessions_dir().join(format!("{}.jsonl", id)))
+        .filter(|p| p.exists());
+    let content = match transcript {
+        Some(path) => fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read session transcript: {}", e))?,
+        None => {
+            md.push_str("\n_No transcript is stored for this session._\n");
+            return Ok(md);
+        }
+    };
+
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let entry: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        // Entries are either {role, content} or {type: "message", message: {role, content}}
+        let message = entry.get("message").unwrap_or(&entry);
+        let role = match message.get("role").and_then(|r| r.as_str()) {
+            Some(r @ ("user" | "assistant")) => r,
+            _ => continue,
+        };
+        let text = transcript_text(message.get("content").unwrap_or(&serde_json::Value::Null));
+        if text.trim().is_empty() {
+            continue;
+        }
+        let heading = if role == "user" { "You" } else { "Nyx" };
+        match transcript_timestamp(&entry) {
+            Some(ts) => md.push_str(&format!("\n## {} — {}\n\n", heading, ts)),
+            None => md.push_str(&format!("\n## {}\n\n", heading)),
+        }
+        md.push_str(text.trim());
+        md.push('\n');
+    }
+
+    Ok(md)
+}
+
 /// Create a new session key for a conversation.
 pub fn create_session(title: Option<String>, folder: Option<String>) -> Result<String, String> {
     let id = uuid::Uuid::new_v4().to_string().replace('-', "")[..12].to_string();
@@ -270,20 +883,188 @@ pub fn delete_folder(folder_id: String) -> Result<(), String> {
     let mut data = load_folders();
     data.folders.retain(|f| f.id != folder_id);
     data.session_folders.retain(|_, v| v != &folder_id);
+    compact_folder_order(&mut data.folders);
+    save_folders(&data)
+}
+
+/// Renumber `order` as 0..n, keeping the current relative order.
+fn compact_folder_order(folders: &mut [ChatFolder]) {
+    folders.sort_by_key(|f| f.order);
+    for (i, f) in folders.iter_mut().enumerate() {
+        f.order = i as u32;
+    }
+}
+
+/// Reorder folders to match `ordered_ids`, which must list every folder id
+/// exactly once.
+pub fn reorder_folders(ordered_ids: Vec<String>) -> Result<(), String> {
+    let mut data = load_folders();
+
+    let mut expected: Vec<&str> = data.folders.iter().map(|f| f.id.as_str()).collect();
+    let mut given: Vec<&str> = ordered_ids.iter().map(String::as_str).collect();
+    expected.sort_unstable();
+    given.sort_unstable();
+    if expected != given {
+        return Err("Folder order must list every existing folder exactly once".to_string());
+    }
+
+    for f in data.folders.iter_mut() {
+        if let Some(pos) = ordered_ids.iter().position(|id| *id == f.id) {
+            f.order = pos as u32;
+        }
+    }
+    data.folders.sort_by_key(|f| f.order);
     save_folders(&data)
 }
 
 // ---------------------------------------------------------------------------
-// Verify source (unchanged)
+// Spend estimate
 // ---------------------------------------------------------------------------
 
-/// Verify a source URL for credibility via the Veritas analysis prompt.
-/// Uses a dedicated session key so analysis doesn't pollute chat history.
-pub async fn verify_source(url: String) -> Result<String, String> {
-    let prompt = format!(
+/// Estimated spend for one model over the range.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSpend {
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// No price-table entry matched; the default rate was used
+    pub default_rate: bool,
+}
+
+/// Estimated LLM spend from per-session token counts. Always an estimate.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendEstimate {
+    pub range_days: u32,
+    pub total_usd: f64,
+    /// total_usd scaled to 30 days
+    pub projected_monthly_usd: f64,
+    pub by_model: Vec<ModelSpend>,
+    pub is_estimate: bool,
+}
+
+/// Find the price for a model: the longest table key contained in the name.
+fn price_for_model<'a>(
+    model: &str,
+    prices: &'a HashMap<String, config::ModelPrice>,
+) -> Option<&'a config::ModelPrice> {
+    let model = model.to_lowercase();
+    prices
+        .iter()
+        .filter(|(key, _)| model.contains(&key.to_lowercase()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, price)| price)
+}
+
+/// Estimate spend over the last `range_days`, priced per model.
+/// Token counts are per session, so a session counts in full if it was
+/// active within the range.
+pub fn estimate_spend(range_days: u32) -> Result<SpendEstimate, String> {
+    if range_days == 0 {
+        return Err("Range must be at least 1 day".to_string());
+    }
+    let raw = read_raw_sessions()?;
+    let prices = config::get_model_prices();
+
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let cutoff_ms = now_ms.saturating_sub(range_days as u64 * 86_400_000);
+    let by_model = spend_by_model(&raw, &prices, cutoff_ms);
+    let total_usd: f64 = by_model.iter().map(|m| m.cost_usd).sum();
+
+    Ok(SpendEstimate {
+        range_days,
+        total_usd,
+        projected_monthly_usd: total_usd * 30.0 / range_days as f64,
+        by_model,
+        is_estimate: true,
+    })
+}
+
+/// Priced token totals per model for sessions updated at or after
+/// `cutoff_ms`, most expensive first.
+fn spend_by_model(
+    raw: &RawSessions,
+    prices: &HashMap<String, config::ModelPrice>,
+    cutoff_ms: u64,
+) -> Vec<ModelSpend> {
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for val in raw.values() {
+        let updated_at = val.get("updatedAt").and_then(|v| v.as_u64()).unwrap_or(0);
+        if updated_at < cutoff_ms {
+            continue;
+        }
+        let model = val
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let entry = totals.entry(model).or_default();
+        entry.0 += val.get("inputTokens").and_then(|v| v.as_u64()).unwrap_or(0);
+        entry.1 += val.get("outputTokens").and_then(|v| v.as_u64()).unwrap_or(0);
+    }
+
+    let mut by_model: Vec<ModelSpend> = totals
+        .into_iter()
+        .map(|(model, (input_tokens, output_tokens))| {
+            let (price, default_rate) = match price_for_model(&model, prices) {
+                Some(p) => (p.clone(), false),
+                None => (config::DEFAULT_MODEL_PRICE, true),
+            };
+            let cost_usd = input_tokens as f64 / 1_000_000.0 * price.input_per_mtok
+                + output_tokens as f64 / 1_000_000.0 * price.output_per_mtok;
+            ModelSpend { model, input_tokens, output_tokens, cost_usd, default_rate }
+        })
+        .collect();
+    by_model.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    by_model
+}
+
+// ---------------------------------------------------------------------------
+// Verify source
+// ---------------------------------------------------------------------------
+
+/// Marker the agent returns when it could not fetch the source in time.
+const FETCH_TIMEOUT_MARKER: &str = "FETCH_TIMEOUT";
+/// Time allowed for the analysis itself, on top of the fetch budget.
+const ANALYSIS_BUDGET_SECS: u64 = 120;
+
+/// Client-side limits for a verify_source run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerifyOptions {
+    /// Seconds the agent may spend fetching the URL (default 30, max 120)
+    pub fetch_timeout_secs: Option<u64>,
+    /// Maximum page content considered, in KB (default 200, max 2048)
+    pub max_content_kb: Option<u32>,
+}
+
+impl VerifyOptions {
+    fn fetch_timeout(&self) -> u64 {
+        self.fetch_timeout_secs.unwrap_or(30).clamp(5, 120)
+    }
+
+    fn max_content(&self) -> u32 {
+        self.max_content_kb.unwrap_or(200).clamp(16, 2048)
+    }
+
+    /// Client-side limit for the whole request: fetch plus analysis, capped
+    /// at the gateway's own timeout.
+    fn request_timeout(&self) -> u64 {
+        (self.fetch_timeout() + ANALYSIS_BUDGET_SECS).min(GATEWAY_TIMEOUT_SECS)
+    }
+}
+
+fn veritas_prompt(url: &str, opts: &VerifyOptions) -> String {
+    format!(
         r#"Analyze the credibility of this source: {}
 
-Fetch the content at the URL and evaluate it across these 6 dimensions (score 0-100 each):
+Fetch the content at the URL and evaluate it across these 6 dimensions (score 0-100 each).
+Fetch limits: give up fetching after {} seconds and consider only the first {} KB of page content.
+If the page cannot be fetched within the time limit, reply with exactly {} and nothing else.
 
 1. SOURCE_REPUTATION: Domain authority, publication history, editorial standards
 2. AUTHOR_CREDIBILITY: Author track record, expertise, transparency
@@ -317,9 +1098,392 @@ Return ONLY a JSON object with no markdown fences, no extra text — raw JSON on
   "summary": "<2-3 sentence credibility assessment>",
   "limitations": "<any caveats about this analysis>"
 }}"#,
-        url
-    );
+        url,
+        opts.fetch_timeout(),
+        opts.max_content(),
+        FETCH_TIMEOUT_MARKER
+    )
+}
+
+/// Per-dimension credibility scores (0–100).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct VeritasScores {
+    pub source_reputation: f64,
+    pub author_credibility: f64,
+    pub corroboration: f64,
+    pub evidence_quality: f64,
+    pub consistency: f64,
+    pub presentation: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VeritasClaim {
+    pub claim: String,
+    /// "verified", "unverified", "disputed" or "misleading"
+    pub status: String,
+}
+
+/// Parsed Veritas analysis, matching the schema in `veritas_prompt`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VeritasReport {
+    #[serde(default)]
+    pub url: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub domain: Option<String>,
+    pub published_date: Option<String>,
+    pub scores: VeritasScores,
+    pub overall_score: f64,
+    pub grade: String,
+    #[serde(default)]
+    pub claims: Vec<VeritasClaim>,
+    #[serde(default)]
+    pub summary: String,
+    pub limitations: Option<String>,
+}
+
+/// Strip markdown code fences (```json ... ```) and any prose around the
+/// outermost JSON object.
+fn strip_json_fences(raw: &str) -> &str {
+    let trimmed = raw.trim();
+    match (trimmed.find('{'), trimmed.rfind('}')) {
+        (Some(start), Some(end)) if end > start => &trimmed[start..=end],
+        _ => trimmed,
+    }
+}
+
+/// Parse a Veritas reply into a report. The error carries the raw reply.
+pub fn parse_veritas_report(raw: &str) -> Result<VeritasReport, String> {
+    serde_json::from_str(strip_json_fences(raw))
+        .map_err(|e| format!("Failed to parse Veritas report: {}\nRaw reply:\n{}", e, raw))
+}
+
+/// Like `verify_source`, but parses the analysis into a `VeritasReport`.
+pub async fn verify_source_structured(
+    url: String,
+    opts: VerifyOptions,
+) -> Result<VeritasReport, String> {
+    let raw = verify_source(url, opts).await?;
+    parse_veritas_report(&raw)
+}
+
+/// Verify a source URL for credibility via the Veritas analysis prompt.
+/// Uses a dedicated session key so analysis doesn't pollute chat history.
+pub async fn verify_source(url: String, opts: VerifyOptions) -> Result<String, String> {
+    let prompt = veritas_prompt(&url, &opts);
+    let timeout = opts.request_timeout();
+
+    let reply = match send_with_timeout(prompt, "agent:default:veritas", timeout).await {
+        Ok(reply) => reply.text,
+        Err(SendError::Timeout(secs)) => {
+            return Err(format!(
+                "Source verification timed out after {}s (fetch limit {}s)",
+                secs,
+                opts.fetch_timeout()
+            ))
+        }
+        Err(SendError::Failed(e)) => return Err(format!("Source analysis failed: {}", e)),
+    };
+
+    check_fetch_timeout(&url, reply, &opts)
+}
+
+/// Turn the agent's FETCH_TIMEOUT_MARKER reply into an error.
+fn check_fetch_timeout(url: &str, reply: String, opts: &VerifyOptions) -> Result<String, String> {
+    if reply.trim() == FETCH_TIMEOUT_MARKER {
+        return Err(format!(
+            "Timed out fetching {} (limit {}s)",
+            url,
+            opts.fetch_timeout()
+        ));
+    }
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_is_read_from_completions() {
+        let body = r#"{"choices": [], "usage": {"prompt_tokens": 120, "completion_tokens": 30, "total_tokens": 150}}"#;
+        assert_eq!(
+            extract_openai_usage(body),
+            Some(ChatUsage {
+                prompt_tokens: 120,
+                completion_tokens: 30,
+                total_tokens: 150
+            })
+        );
+        // A missing total is the sum of the parts
+        let body = r#"{"usage": {"prompt_tokens": 7, "completion_tokens": 5}}"#;
+        assert_eq!(extract_openai_usage(body).unwrap().total_tokens, 12);
+        assert_eq!(extract_openai_usage(r#"{"choices": []}"#), None);
+        assert_eq!(extract_openai_usage("not json"), None);
+    }
+
+    #[test]
+    fn sse_buffer_reassembles_split_lines() {
+        let mut buf = SseBuffer::default();
+        assert!(buf.push(b"data: {\"a\"").is_empty());
+        assert_eq!(
+            buf.push(b":1}\r\n\ndata: [DONE]\n"),
+            vec!["{\"a\":1}", "[DONE]"]
+        );
+
+        // "é" split across chunks
+        let line = "data: caf\u{e9}\n".as_bytes();
+        let (head, tail) = line.split_at(line.len() - 2);
+        assert!(buf.push(head).is_empty());
+        assert_eq!(buf.push(tail), vec!["caf\u{e9}"]);
+
+        assert!(buf.push(b": keep-alive\nevent: ping\n").is_empty());
+    }
+
+    #[test]
+    fn stream_payloads_are_classified() {
+        assert!(matches!(parse_stream_payload("[DONE]"), StreamEvent::Done));
+        assert!(matches!(
+            parse_stream_payload(r#"{"choices": [{"delta": {"content": "Hi"}}]}"#),
+            StreamEvent::Token(t) if t == "Hi"
+        ));
+        assert!(matches!(
+            parse_stream_payload(r#"{"error": {"message": "rate limited"}}"#),
+            StreamEvent::Error(e) if e == "rate limited"
+        ));
+        assert!(matches!(
+            parse_stream_payload(r#"{"choices": [], "usage": {"prompt_tokens": 3, "completion_tokens": 4}}"#),
+            StreamEvent::Usage(u) if u.total_tokens == 7
+        ));
+        assert!(matches!(
+            parse_stream_payload(r#"{"choices": [{"delta": {"content": ""}}]}"#),
+            StreamEvent::Ignore
+        ));
+        assert!(matches!(
+            parse_stream_payload("garbage"),
+            StreamEvent::Ignore
+        ));
+    }
+
+    #[test]
+    fn budget_warns_then_continues() {
+        let budget = SessionBudget {
+            max_context_tokens: 1000,
+            auto_continue: true,
+        };
+        assert_eq!(budget_verdict(799, &budget), None);
+        assert_eq!(budget_verdict(800, &budget), Some(false));
+        assert_eq!(budget_verdict(899, &budget), Some(false));
+        assert_eq!(budget_verdict(900, &budget), Some(true));
+        assert_eq!(budget_verdict(5000, &budget), Some(true));
+
+        let warn_only = SessionBudget {
+            max_context_tokens: 1000,
+            auto_continue: false,
+        };
+        assert_eq!(budget_verdict(950, &warn_only), Some(false));
+    }
+
+    fn prices() -> HashMap<String, config::ModelPrice> {
+        let price = |input_per_mtok, output_per_mtok| config::ModelPrice {
+            input_per_mtok,
+            output_per_mtok,
+        };
+        HashMap::from([
+            ("claude-sonnet".to_string(), price(3.0, 15.0)),
+            ("claude-sonnet-4-5".to_string(), price(2.0, 10.0)),
+            ("gpt-4o".to_string(), price(2.5, 10.0)),
+        ])
+    }
+
+    #[test]
+    fn longest_matching_price_key_wins() {
+        let prices = prices();
+        assert_eq!(
+            price_for_model("anthropic/Claude-Sonnet-4-5", &prices)
+                .unwrap()
+                .input_per_mtok,
+            2.0
+        );
+        assert_eq!(
+            price_for_model("claude-sonnet-4", &prices)
+                .unwrap()
+                .input_per_mtok,
+            3.0
+        );
+        assert!(price_for_model("llama-3", &prices).is_none());
+    }
+
+    fn sessions(entries: &[(&str, u64, u64, u64)]) -> RawSessions {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(i, (model, input, output, updated))| {
+                (
+                    format!("agent:main:{}", i),
+                    serde_json::json!({
+                        "model": model,
+                        "inputTokens": input,
+                        "outputTokens": output,
+                        "updatedAt": updated,
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spend_is_linear_in_tokens() {
+        let prices = prices();
+        let once = spend_by_model(&sessions(&[("gpt-4o", 1_000_000, 100_000, 10)]), &prices, 0);
+        assert!((once[0].cost_usd - 3.5).abs() < 1e-9);
+
+        let twice = spend_by_model(&sessions(&[("gpt-4o", 2_000_000, 200_000, 10)]), &prices, 0);
+        assert!((twice[0].cost_usd - 2.0 * once[0].cost_usd).abs() < 1e-9);
+
+        // Two sessions on one model add up to the same as one with both
+        let split = spend_by_model(
+            &sessions(&[
+                ("gpt-4o", 1_000_000, 100_000, 10),
+                ("gpt-4o", 1_000_000, 100_000, 10),
+            ]),
+            &prices,
+            0,
+        );
+        assert_eq!(split.len(), 1);
+        assert!((split[0].cost_usd - twice[0].cost_usd).abs() < 1e-9);
+    }
+
+    #[test]
+    fn spend_flags_unknown_models_and_skips_old_sessions() {
+        let raw = sessions(&[
+            ("mystery-7b", 1_000_000, 0, 10),
+            ("gpt-4o", 1_000_000, 0, 1),
+        ]);
+        let spend = spend_by_model(&raw, &prices(), 5);
+        assert_eq!(spend.len(), 1);
+        assert_eq!(spend[0].model, "mystery-7b");
+        assert!(spend[0].default_rate);
+        assert!((spend[0].cost_usd - config::DEFAULT_MODEL_PRICE.input_per_mtok).abs() < 1e-9);
+
+        let spend = spend_by_model(&raw, &prices(), 0);
+        assert!(
+            !spend
+                .iter()
+                .find(|m| m.model == "gpt-4o")
+                .unwrap()
+                .default_rate
+        );
+    }
 
-    // Use the shared chat completions function with a dedicated Veritas session
-    send_message_to_session(prompt, "agent:default:veritas".to_string()).await
+    #[test]
+    fn verify_limits_are_clamped_and_capped() {
+        let opts = VerifyOptions::default();
+        assert_eq!((opts.fetch_timeout(), opts.max_content()), (30, 200));
+        assert_eq!(opts.request_timeout(), 30 + ANALYSIS_BUDGET_SECS);
+
+        let opts = VerifyOptions {
+            fetch_timeout_secs: Some(1),
+            max_content_kb: Some(1),
+        };
+        assert_eq!((opts.fetch_timeout(), opts.max_content()), (5, 16));
+
+        let opts = VerifyOptions {
+            fetch_timeout_secs: Some(999),
+            max_content_kb: Some(99_999),
+        };
+        assert_eq!((opts.fetch_timeout(), opts.max_content()), (120, 2048));
+        assert_eq!(opts.request_timeout(), GATEWAY_TIMEOUT_SECS);
+
+        let prompt = veritas_prompt("https://example.com", &opts);
+        assert!(prompt.contains("after 120 seconds"));
+        assert!(prompt.contains("first 2048 KB"));
+        assert!(prompt.contains(FETCH_TIMEOUT_MARKER));
+    }
+
+    #[test]
+    fn fetch_timeout_marker_becomes_an_error() {
+        let opts = VerifyOptions::default();
+        let err = check_fetch_timeout(
+            "https://example.com",
+            format!("  {}\n", FETCH_TIMEOUT_MARKER),
+            &opts,
+        )
+        .unwrap_err();
+        assert_eq!(err, "Timed out fetching https://example.com (limit 30s)");
+
+        // The marker inside a real report is not a timeout
+        let reply = format!(r#"{{"summary": "mentions {}"}}"#, FETCH_TIMEOUT_MARKER);
+        assert_eq!(
+            check_fetch_timeout("https://example.com", reply.clone(), &opts),
+            Ok(reply)
+        );
+    }
+
+    #[test]
+    fn veritas_reports_parse_through_fences_and_prose() {
+        let raw = r#"Here is the analysis:
+```json
+{"url": "https://example.com", "title": "T", "scores": {"source_reputation": 80, "corroboration": 60},
+ "overall_score": 72.5, "grade": "B", "claims": [{"claim": "X", "status": "verified"}], "summary": "ok"}
+```"#;
+        let report = parse_veritas_report(raw).unwrap();
+        assert_eq!(report.grade, "B");
+        assert_eq!(report.overall_score, 72.5);
+        assert_eq!(report.scores.source_reputation, 80.0);
+        // Missing dimensions default to zero
+        assert_eq!(report.scores.presentation, 0.0);
+        assert_eq!(report.claims[0].status, "verified");
+
+        let err = parse_veritas_report("I couldn't analyze that").unwrap_err();
+        assert!(
+            err.contains("Raw reply:\nI couldn't analyze that"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn delete_session_removes_index_entry_transcript_and_metadata() {
+        let openclaw = std::env::temp_dir().join(format!("nyx-openclaw-{}", uuid::Uuid::new_v4()));
+        let dir = sessions_dir_in(&openclaw);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("sessions.json"),
+            r#"{"agent:main:a": {"sessionId": "s-a"}, "agent:main:b": {"sessionId": "s-b"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("s-a.jsonl"), "{}\n").unwrap();
+        fs::write(dir.join("s-b.jsonl"), "{}\n").unwrap();
+
+        let mut folders = ChatFolders::default();
+        folders.session_titles.insert("agent:main:a".into(), "Trip".into());
+        folders.session_titles.insert("agent:main:b".into(), "Work".into());
+        folders.session_folders.insert("agent:main:a".into(), "f1".into());
+        folders
+            .session_continuations
+            .insert("agent:main:old".into(), "agent:main:a".into());
+        save_folders_to(&folders_path_in(&openclaw), &folders).unwrap();
+
+        delete_session_in(&openclaw, "agent:main:a", true).unwrap();
+
+        let raw = read_raw_sessions_in(&dir).unwrap();
+        assert!(!raw.contains_key("agent:main:a"));
+        assert!(raw.contains_key("agent:main:b"));
+        assert!(!dir.join("s-a.jsonl").exists());
+        assert!(dir.join("s-b.jsonl").exists());
+
+        let folders = load_folders_from(&folders_path_in(&openclaw));
+        assert_eq!(folders.session_titles.len(), 1);
+        assert!(folders.session_folders.is_empty());
+        assert!(folders.session_continuations.is_empty());
+
+        // Keeping the transcript leaves the file for the gateway
+        delete_session_in(&openclaw, "agent:main:b", false).unwrap();
+        assert!(read_raw_sessions_in(&dir).unwrap().is_empty());
+        assert!(dir.join("s-b.jsonl").exists());
+
+        fs::remove_dir_all(&openclaw).unwrap();
+    }
 }
@@ -0,0 +1,227 @@
+// ---------------------------------------------------------------------------
+// gog CLI — binary resolution and auth status (shared, no Tauri dependency)
+// The Tauri binary adds installing the bundled binary on top of these.
+// ---------------------------------------------------------------------------
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config;
+
+/// How long the signed-in accounts are reused before asking gog again.
+const ACCOUNT_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GogStatus {
+    pub installed: bool,
+    pub authenticated: bool,
+    pub version: Option<String>,
+}
+
+/// Check if the `gog` CLI binary is available.
+pub async fn check_gog_available() -> Result<GogStatus, String> {
+    // Try ~/openclaw/bin/gog first, then PATH
+    let gog_path = gog_binary_path();
+
+    let output = Command::new(&gog_path)
+        .args(["--version"])
+        .output();
+
+    match output {
+        Ok(out) if out.status.success() => {
+            let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            // Check if authenticated by trying to list calendars
+            let auth_check = Command::new(&gog_path)
+                .args(["calendar", "list", "--limit", "1"])
+                .output();
+
+            let authenticated = auth_check
+                .map(|o| o.status.success())
+                .unwrap_or(false);
+
+            Ok(GogStatus {
+                installed: true,
+                authenticated,
+                version: if version.is_empty() { None } else { Some(version) },
+            })
+        }
+        _ => Ok(GogStatus {
+            installed: false,
+            authenticated: false,
+            version: None,
+        }),
+    }
+}
+
+/// Run `gog auth` to initiate OAuth browser flow.
+/// Returns when the auth process completes (user finishes in browser).
+pub async fn run_gog_auth() -> Result<bool, String> {
+    let gog_path = gog_binary_path();
+
+    let output = Command::new(&gog_path)
+        .args(["auth"])
+        .output()
+        .map_err(|e| format!("Failed to run gog auth: {}", e))?;
+
+    if output.status.success() {
+        // The signed-in accounts may have changed
+        clear_account_cache();
+        Ok(true)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("gog auth failed: {}", stderr))
+    }
+}
+
+/// Check if gog is authenticated (quick check).
+pub async fn check_gog_authenticated() -> Result<bool, String> {
+    let gog_path = gog_binary_path();
+
+    let output = Command::new(&gog_path)
+        .args(["calendar", "list", "--limit", "1"])
+        .output();
+
+    Ok(output.map(|o| o.status.success()).unwrap_or(false))
+}
+
+/// The gog binary: ~/openclaw/bin/gog when installed there, else `gog` on PATH.
+pub fn gog_binary_path() -> String {
+    let home = config::home_dir();
+    let local_path = format!("{}/openclaw/bin/gog", home.display());
+
+    // Prefer the bundled gog binary if it exists
+    if std::path::Path::new(&local_path).exists() {
+        local_path
+    } else {
+        "gog".to_string() // Fall back to PATH
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Signed-in accounts
+// ---------------------------------------------------------------------------
+
+/// A Google account gog is signed in as.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GogAccount {
+    pub email: String,
+}
+
+type AccountCache<T> = Mutex<Option<(Instant, Result<T, String>)>>;
+
+static WHOAMI_CACHE: std::sync::LazyLock<AccountCache<GogAccount>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+static ACCOUNTS_CACHE: std::sync::LazyLock<AccountCache<Vec<GogAccount>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+/// Reuse a result younger than `ACCOUNT_CACHE_TTL`, else run `fetch` and cache it.
+fn cached<T: Clone>(
+    cache: &AccountCache<T>,
+    fetch: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    if let Ok(cache) = cache.lock() {
+        if let Some((at, result)) = cache.as_ref() {
+            if at.elapsed() < ACCOUNT_CACHE_TTL {
+                return result.clone();
+            }
+        }
+    }
+
+    let result = fetch();
+    if let Ok(mut cache) = cache.lock() {
+        *cache = Some((Instant::now(), result.clone()));
+    }
+    result
+}
+
+/// The default account from `gog auth whoami`, cached for a few minutes so
+/// callers like the email observer don't shell out on every run.
+pub fn gog_whoami() -> Result<GogAccount, String> {
+    cached(&WHOAMI_CACHE, fetch_whoami)
+}
+
+/// Every account from `gog accounts list` (cached like `gog_whoami`). Falls
+/// back to just the default account when gog can't list them.
+pub fn gog_accounts() -> Result<Vec<GogAccount>, String> {
+    cached(&ACCOUNTS_CACHE, || match fetch_accounts() {
+        Ok(accounts) if !accounts.is_empty() => Ok(accounts),
+        _ => gog_whoami().map(|account| vec![account]),
+    })
+}
+
+/// Forget the cached accounts (e.g. after signing in again).
+pub fn clear_account_cache() {
+    if let Ok(mut cache) = WHOAMI_CACHE.lock() {
+        *cache = None;
+    }
+    if let Ok(mut cache) = ACCOUNTS_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+/// Run gog with JSON output and no prompts, returning stdout.
+fn gog_json(args: &[&str]) -> Result<Vec<u8>, String> {
+    let label = args.join(" ");
+    let output = Command::new(gog_binary_path())
+        .args(args)
+        .args(["--json", "--no-input"])
+        .output()
+        .map_err(|e| format!("Failed to run gog {}: {}", label, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("gog {} failed: {}", label, stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+fn fetch_whoami() -> Result<GogAccount, String> {
+    let stdout = gog_json(&["auth", "whoami"])?;
+    let account: GogAccount = serde_json::from_slice(&stdout)
+        .map_err(|e| format!("Failed to parse gog auth whoami: {}", e))?;
+    if account.email.is_empty() {
+        return Err("gog auth whoami returned no email".to_string());
+    }
+    Ok(account)
+}
+
+/// One entry of `gog accounts list`: an account object or a bare email.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountEntry {
+    Account(GogAccount),
+    Email(String),
+}
+
+/// `gog accounts list --json` may return a wrapper object or a direct array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountList {
+    Wrapped { accounts: Vec<AccountEntry> },
+    Bare(Vec<AccountEntry>),
+}
+
+fn fetch_accounts() -> Result<Vec<GogAccount>, String> {
+    let stdout = gog_json(&["accounts", "list"])?;
+    let entries = match serde_json::from_slice::<AccountList>(&stdout)
+        .map_err(|e| format!("Failed to parse gog accounts list: {}", e))?
+    {
+        AccountList::Wrapped { accounts } => accounts,
+        AccountList::Bare(accounts) => accounts,
+    };
+
+    let mut accounts: Vec<GogAccount> = Vec::new();
+    for entry in entries {
+        let email = match entry {
+            AccountEntry::Account(a) => a.email,
+            AccountEntry::Email(e) => e,
+        };
+        let email = email.trim().to_lowercase();
+        if !email.is_empty() && !accounts.iter().any(|a| a.email == email) {
+            accounts.push(GogAccount { email });
+        }
+    }
+    Ok(accounts)
+}
@@ -0,0 +1,231 @@
+// ---------------------------------------------------------------------------
+// System health — one report across every subsystem, for support requests
+// ---------------------------------------------------------------------------
+
+use serde::Serialize;
+
+use crate::clawdtalk;
+use crate::config;
+use crate::docker;
+use crate::gateway;
+use crate::gog;
+use crate::ollama;
+
+/// Seconds the gateway probe waits for a connection.
+const GATEWAY_PROBE_SECS: u64 = 3;
+
+/// Ordered from best to worst so the overall status is the max.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Degraded,
+    Error,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HealthCheck {
+    /// "setup", "docker", "container", "gateway", "ollama", "gog" or "clawdtalk"
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SystemHealth {
+    /// The worst status of any check
+    pub status: CheckStatus,
+    pub checks: Vec<HealthCheck>,
+    /// Unix seconds
+    pub checked_at: u64,
+}
+
+fn check(name: &str, status: CheckStatus, message: impl Into<String>) -> HealthCheck {
+    HealthCheck {
+        name: name.to_string(),
+        status,
+        message: message.into(),
+    }
+}
+
+fn check_setup() -> HealthCheck {
+    if config::is_setup_complete() {
+        check("setup", CheckStatus::Ok, "Setup complete")
+    } else {
+        check(
+            "setup",
+            CheckStatus::Error,
+            "Setup not completed (openclaw.json or docker.env missing)",
+        )
+    }
+}
+
+async fn check_docker_and_container() -> (HealthCheck, HealthCheck) {
+    match docker::is_docker_running().await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                check(
+                    "docker",
+                    CheckStatus::Error,
+                    "Docker is installed but not running",
+                ),
+                check(
+                    "container",
+                    CheckStatus::Error,
+                    "Skipped: Docker is not running",
+                ),
+            )
+        }
+        Err(e) => {
+            return (
+                check("docker", CheckStatus::Error, e),
+                check(
+                    "container",
+                    CheckStatus::Error,
+                    "Skipped: Docker is not available",
+                ),
+            )
+        }
+    }
+    let docker = check("docker", CheckStatus::Ok, "Docker is running");
+
+    let container = match docker::container_status().await {
+        Ok(status) if status == "stopped" => check(
+            "container",
+            CheckStatus::Error,
+            "OpenClaw container is not running",
+        ),
+        Ok(status) if status.contains("unhealthy") => check(
+            "container",
+            CheckStatus::Error,
+            format!("OpenClaw container is unhealthy ({})", status),
+        ),
+        Ok(status) if status.contains("starting") || status.contains("Restarting") => check(
+            "container",
+            CheckStatus::Degraded,
+            format!("OpenClaw container is starting ({})", status),
+        ),
+        Ok(status) => check("container", CheckStatus::Ok, status),
+        Err(e) => check("container", CheckStatus::Error, e),
+    };
+    (docker, container)
+}
+
+async fn check_gateway() -> HealthCheck {
+    match gateway::wait_for_gateway(GATEWAY_PROBE_SECS).await {
+        Ok(()) => check("gateway", CheckStatus::Ok, "Gateway is reachable"),
+        Err(e) => check("gateway", CheckStatus::Error, e),
+    }
+}
+
+async fn check_ollama() -> HealthCheck {
+    match ollama::check_ollama().await {
+        Ok(status) if status.available => {
+            let version = status
+                .version
+                .map(|v| format!(" {}", v))
+                .unwrap_or_default();
+            check(
+                "ollama",
+                CheckStatus::Ok,
+                format!("Ollama{} is running", version),
+            )
+        }
+        Ok(_) => check(
+            "ollama",
+            CheckStatus::Degraded,
+            "Ollama is not running (local models unavailable)",
+        ),
+        Err(e) => check("ollama", CheckStatus::Degraded, e),
+    }
+}
+
+async fn check_gog() -> HealthCheck {
+    match gog::check_gog_available().await {
+        Ok(status) if status.installed && status.authenticated => {
+            check("gog", CheckStatus::Ok, "gog is installed and authenticated")
+        }
+        Ok(status) if status.installed => check(
+            "gog",
+            CheckStatus::Degraded,
+            "gog is installed but not authenticated with Google",
+        ),
+        Ok(_) => check(
+            "gog",
+            CheckStatus::Degraded,
+            "gog is not installed (Google integration unavailable)",
+        ),
+        Err(e) => check("gog", CheckStatus::Degraded, e),
+    }
+}
+
+fn check_clawdtalk() -> HealthCheck {
+    match clawdtalk::check_status() {
+        Ok(status) if !status.configured => {
+            check("clawdtalk", CheckStatus::Ok, "ClawdTalk is not configured")
+        }
+        Ok(status) if !status.has_api_key => check(
+            "clawdtalk",
+            CheckStatus::Error,
+            "ClawdTalk is configured without an API key",
+        ),
+        Ok(status) => match status.connection_state {
+            clawdtalk::ConnectionState::Connected => {
+                check("clawdtalk", CheckStatus::Ok, "ClawdTalk is connected")
+            }
+            clawdtalk::ConnectionState::Connecting => check(
+                "clawdtalk",
+                CheckStatus::Degraded,
+                "ClawdTalk is connecting",
+            ),
+            clawdtalk::ConnectionState::AuthFailed => check(
+                "clawdtalk",
+                CheckStatus::Error,
+                "ClawdTalk rejected the API key",
+            ),
+            clawdtalk::ConnectionState::Disconnected => check(
+                "clawdtalk",
+                CheckStatus::Degraded,
+                "ClawdTalk is configured but not connected",
+            ),
+        },
+        Err(e) => check("clawdtalk", CheckStatus::Error, e),
+    }
+}
+
+/// Run every check. Each is independent, so one failing subsystem never
+/// hides the others.
+pub async fn system_health() -> SystemHealth {
+    let ((docker, container), gateway, ollama, gog) = tokio::join!(
+        check_docker_and_container(),
+        check_gateway(),
+        check_ollama(),
+        check_gog()
+    );
+
+    let checks = vec![
+        check_setup(),
+        docker,
+        container,
+        gateway,
+        ollama,
+        gog,
+        check_clawdtalk(),
+    ];
+    let status = checks
+        .iter()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(CheckStatus::Ok);
+    let checked_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    SystemHealth {
+        status,
+        checks,
+        checked_at,
+    }
+}
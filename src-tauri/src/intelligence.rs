@@ -8,9 +8,12 @@
 
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config;
+use crate::gog;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -77,6 +80,8 @@ pub struct AutonomySetting {
     pub total_dismissed: i64,
 }
 
+pub use crate::config::{DetectorTuning, IntelligenceTuning};
+
 // ---------------------------------------------------------------------------
 // gog JSON output structures (Google Calendar API + Gmail API pass-through)
 // ---------------------------------------------------------------------------
@@ -157,8 +162,7 @@ struct GogGmailSearchResponse {
 // ---------------------------------------------------------------------------
 
 fn db_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-    PathBuf::from(home).join(".nyx").join("intelligence.db")
+    config::nyx_home().join("intelligence.db")
 }
 
 fn open_db() -> Result<Connection, String> {
@@ -176,8 +180,10 @@ fn open_db() -> Result<Connection, String> {
 // ---------------------------------------------------------------------------
 
 pub fn init_db() -> Result<(), String> {
-    let conn = open_db()?;
+    init_schema(&open_db()?)
+}
 
+fn init_schema(conn: &Connection) -> Result<(), String> {
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS contacts (
@@ -190,6 +196,8 @@ pub fn init_db() -> Result<(), String> {
             avg_response_time_mins REAL,
             preferred_channel TEXT,
             tags TEXT,
+            interaction_cadence_days REAL,
+            last_interaction_at TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL
         );
@@ -210,7 +218,7 @@ pub fn init_db() -> Result<(), String> {
         CREATE TABLE IF NOT EXISTS email_observations (
             id INTEGER PRIMARY KEY,
             thread_id TEXT NOT NULL,
-            message_id TEXT UNIQUE,
+            message_id TEXT,
             from_email TEXT NOT NULL,
             to_emails TEXT,
             subject TEXT,
@@ -219,7 +227,8 @@ pub fn init_db() -> Result<(), String> {
             replied INTEGER DEFAULT 0,
             reply_time_mins REAL,
             labels TEXT,
-            observed_at TEXT NOT NULL
+            observed_at TEXT NOT NULL,
+            account TEXT
         );
 
         CREATE TABLE IF NOT EXISTS suggestions (
@@ -285,21 +294,88 @@ pub fn init_db() -> Result<(), String> {
     )
     .map_err(|e| format!("Failed to initialise intelligence schema: {}", e))?;
 
-    Ok(())
+    run_migrations(conn)
 }
 
-// ---------------------------------------------------------------------------
-// gog CLI helper
-// ---------------------------------------------------------------------------
+/// Ordered schema migrations. Step N brings the database to version N
+/// (`PRAGMA user_version`). Append new steps; never edit or reorder
+/// existing ones.
+const MIGRATIONS: &[&str] = &[
+    // 1–2: contact cadence tracking (already in CREATE TABLE for new databases)
+    "ALTER TABLE contacts ADD COLUMN interaction_cadence_days REAL",
+    "ALTER TABLE contacts ADD COLUMN last_interaction_at TEXT",
+    // 3: suggestion snoozing
+    "ALTER TABLE suggestions ADD COLUMN snooze_until TEXT",
+    // 4: resurfacing scans snoozed rows by time
+    "CREATE INDEX IF NOT EXISTS idx_suggestions_snooze ON suggestions(status, snooze_until)",
+    // 5–6: owning gog account per email (already in CREATE TABLE for new databases)
+    "ALTER TABLE email_observations ADD COLUMN account TEXT",
+    "CREATE INDEX IF NOT EXISTS idx_email_account ON email_observations(account)",
+    // 7: the same message can be seen from two accounts (e.g. mail between
+    // them), so message_id alone is no longer unique. SQLite can't drop a
+    // column constraint, so the table is rebuilt without it.
+    "CREATE TABLE email_observations_new (
+         id INTEGER PRIMARY KEY,
+         thread_id TEXT NOT NULL,
+         message_id TEXT,
+         from_email TEXT NOT NULL,
+         to_emails TEXT,
+         subject TEXT,
+         timestamp TEXT NOT NULL,
+         is_inbound INTEGER,
+         replied INTEGER DEFAULT 0,
+         reply_time_mins REAL,
+         labels TEXT,
+         observed_at TEXT NOT NULL,
+         account TEXT
+     );
+     INSERT INTO email_observations_new
+         SELECT id, thread_id, message_id, from_email, to_emails, subject, timestamp,
+                is_inbound, replied, reply_time_mins, labels, observed_at, account
+         FROM email_observations;
+     DROP TABLE email_observations;
+     ALTER TABLE email_observations_new RENAME TO email_observations;
+     CREATE INDEX IF NOT EXISTS idx_email_timestamp ON email_observations(timestamp);
+     CREATE INDEX IF NOT EXISTS idx_email_from ON email_observations(from_email);
+     CREATE INDEX IF NOT EXISTS idx_email_replied ON email_observations(is_inbound, replied);
+     CREATE INDEX IF NOT EXISTS idx_email_account ON email_observations(account);",
+    // 8: one row per message per account. IFNULL so rows without an account
+    // (single-account installs) still dedupe; NULLs never collide in SQLite.
+    "CREATE UNIQUE INDEX IF NOT EXISTS idx_email_message_account
+         ON email_observations(message_id, IFNULL(account, ''))",
+];
 
-fn gog_binary_path() -> String {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let local_path = format!("{}/openclaw/bin/gog", home);
-    if std::path::Path::new(&local_path).exists() {
-        local_path
-    } else {
-        "gog".to_string()
+/// Apply every migration newer than the database's `user_version`.
+/// Unversioned databases (version 0) may already have some columns, either
+/// from CREATE TABLE or from releases that altered tables outside this list;
+/// for those only, "duplicate column" counts as applied. Schema changes go
+/// in MIGRATIONS, never in ad-hoc ALTERs.
+///
+/// Each step runs in its own transaction together with its version bump,
+/// so a failed step leaves the database exactly at the previous version.
+fn run_migrations(conn: &Connection) -> Result<(), String> {
+    let current: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("Failed to read schema version: {}", e))? as usize;
+
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(current) {
+        let version = i + 1;
+        // Dropping `tx` without committing rolls the step back
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to start schema migration {}: {}", version, e))?;
+        if let Err(e) = tx.execute_batch(step) {
+            if current > 0 || !e.to_string().contains("duplicate column name") {
+                return Err(format!("Schema migration {} failed: {}", version, e));
+            }
+        }
+        // PRAGMA doesn't take bound parameters
+        tx.execute_batch(&format!("PRAGMA user_version = {}", version))
+            .map_err(|e| format!("Failed to record schema version {}: {}", version, e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit schema migration {}: {}", version, e))?;
     }
+    Ok(())
 }
 
 fn now_iso() -> String {
@@ -364,6 +440,25 @@ fn is_leap(y: u64) -> bool {
     (y % 4 == 0 && y % 100 != 0) || y % 400 == 0
 }
 
+/// The user's configured timezone (from the email digest settings), used
+/// only for human-readable dates — storage stays UTC.
+fn display_timezone() -> chrono_tz::Tz {
+    config::read_current_config()
+        .ok()
+        .and_then(|c| c.email_notifications.timezone.parse().ok())
+        .unwrap_or(chrono_tz::UTC)
+}
+
+/// The calendar date (YYYY-MM-DD) of a stored timestamp in `tz`. Values
+/// that aren't full RFC 3339 timestamps (e.g. all-day event dates) are
+/// shown as stored.
+fn local_date(timestamp: &str, tz: chrono_tz::Tz) -> String {
+    match chrono::DateTime::parse_from_rfc3339(timestamp) {
+        Ok(dt) => dt.with_timezone(&tz).format("%Y-%m-%d").to_string(),
+        Err(_) => timestamp.get(..10).unwrap_or(timestamp).to_string(),
+    }
+}
+
 /// Returns ISO date string for N days ago (YYYY-MM-DD).
 fn days_ago(n: u64) -> String {
     use std::time::SystemTime;
@@ -393,11 +488,11 @@ fn days_ahead(n: u64) -> String {
 // ---------------------------------------------------------------------------
 
 pub fn observe_calendar() -> Result<u32, String> {
-    let gog = gog_binary_path();
+    let gog_path = gog::gog_binary_path();
     let from = days_ago(7);
     let to = days_ahead(14);
 
-    let output = Command::new(&gog)
+    let output = Command::new(&gog_path)
         .args([
             "calendar", "events", "primary",
             "--from", &from,
@@ -524,16 +619,58 @@ pub fn observe_calendar() -> Result<u32, String> {
 // ---------------------------------------------------------------------------
 
 pub fn observe_email() -> Result<u32, String> {
-    let gog = gog_binary_path();
+    let conn = open_db()?;
 
-    let output = Command::new(&gog)
-        .args([
-            "gmail", "search",
-            "newer_than:24h",
-            "--max", "100",
-            "--json",
-            "--no-input",
-        ])
+    // Each signed-in account is its own mailbox. If gog can't name any,
+    // search the default one and guess the user's address from the data.
+    let accounts: Vec<Option<String>> = match gog::gog_accounts() {
+        Ok(accounts) => accounts.into_iter().map(|a| Some(a.email)).collect(),
+        Err(_) => vec![None],
+    };
+
+    let mut count = 0u32;
+    let mut first_error = None;
+    let mut any_ok = false;
+    for account in &accounts {
+        match observe_account_email(&conn, account.as_deref()) {
+            Ok(n) => {
+                count += n;
+                any_ok = true;
+            }
+            Err(e) => {
+                let label = account.as_deref().unwrap_or("default account");
+                eprintln!("[intelligence] Email observation for {} failed: {}", label, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let (false, Some(e)) = (any_ok, first_error) {
+        return Err(e);
+    }
+
+    // After processing all messages, detect reply patterns
+    detect_reply_patterns(&conn)?;
+
+    Ok(count)
+}
+
+/// Observe the last day of one mailbox. `account` is passed to gog as
+/// `--account`; messages not sent from it count as inbound.
+fn observe_account_email(conn: &Connection, account: Option<&str>) -> Result<u32, String> {
+    let gog_path = gog::gog_binary_path();
+
+    let mut cmd = Command::new(&gog_path);
+    cmd.args([
+        "gmail", "search",
+        "newer_than:24h",
+        "--max", "100",
+        "--json",
+        "--no-input",
+    ]);
+    if let Some(account) = account {
+        cmd.args(["--account", account]);
+    }
+    let output = cmd
         .output()
         .map_err(|e| format!("Failed to run gog gmail: {}", e))?;
 
@@ -556,12 +693,11 @@ pub fn observe_email() -> Result<u32, String> {
         Vec::new()
     };
 
-    let conn = open_db()?;
     let now = now_iso();
     let mut count = 0u32;
 
-    // Try to determine the user's own email for is_inbound classification
-    let user_email = detect_user_email(&conn);
+    // The mailbox owner's address decides is_inbound
+    let user_email = account.map(String::from).or_else(|| detect_user_email(conn));
 
     for thread in &threads {
         let thread_id = match &thread.id {
@@ -633,11 +769,11 @@ pub fn observe_email() -> Result<u32, String> {
                 let to_json = serde_json::to_string(&to_emails)
                     .unwrap_or_else(|_| "[]".to_string());
 
-                // Insert email observation (skip if already seen)
+                // Insert email observation (skip if this account already saw it)
                 let result = conn.execute(
                     "INSERT OR IGNORE INTO email_observations
-                     (thread_id, message_id, from_email, to_emails, subject, timestamp, is_inbound, labels, observed_at)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                     (thread_id, message_id, from_email, to_emails, subject, timestamp, is_inbound, labels, observed_at, account)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
                     params![
                         thread_id,
                         message_id,
@@ -648,6 +784,7 @@ pub fn observe_email() -> Result<u32, String> {
                         is_inbound,
                         labels_json,
                         now,
+                        account,
                     ],
                 );
 
@@ -659,20 +796,17 @@ pub fn observe_email() -> Result<u32, String> {
 
                 // Upsert contacts from email participants
                 if !from_email.is_empty() {
-                    upsert_contact(&conn, &from_email, None, "email", &now)?;
+                    upsert_contact(conn, &from_email, None, "email", &now)?;
                 }
                 for to in &to_emails {
                     if !to.is_empty() {
-                        upsert_contact(&conn, to, None, "email", &now)?;
+                        upsert_contact(conn, to, None, "email", &now)?;
                     }
                 }
             }
         }
     }
 
-    // After processing all messages, detect reply patterns
-    detect_reply_patterns(&conn)?;
-
     Ok(count)
 }
 
@@ -730,9 +864,8 @@ const URGENCY_KEYWORDS: &[&str] = &[
 /// Reads the main session's JSONL file and extracts user messages
 /// from the last `hours` hours. Classifies each by topic keywords.
 pub fn observe_messaging(hours: u32) -> Result<u32, String> {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let sessions_path = std::path::PathBuf::from(&home)
-        .join(".openclaw/agents/default/sessions/sessions.json");
+    let sessions_dir = config::openclaw_home().join("agents/default/sessions");
+    let sessions_path = sessions_dir.join("sessions.json");
 
     let sessions_content = std::fs::read_to_string(&sessions_path)
         .map_err(|e| format!("Failed to read sessions.json: {}", e))?;
@@ -748,9 +881,7 @@ pub fn observe_messaging(hours: u32) -> Result<u32, String> {
         .and_then(|v| v.as_str())
         .ok_or_else(|| "Main session not found in sessions.json".to_string())?;
 
-    let session_file = std::path::PathBuf::from(&home)
-        .join(".openclaw/agents/default/sessions")
-        .join(format!("{}.jsonl", main_session_id));
+    let session_file = sessions_dir.join(format!("{}.jsonl", main_session_id));
 
     if !session_file.exists() {
         return Ok(0);
@@ -917,17 +1048,24 @@ fn upsert_contact(
 // Reply pattern detection
 // ---------------------------------------------------------------------------
 
+/// Reply times above this (30 days) are treated as bad data, not slow replies.
+const MAX_REPLY_TIME_MINS: f64 = 30.0 * 24.0 * 60.0;
+
 fn detect_reply_patterns(conn: &Connection) -> Result<(), String> {
     // Find inbound emails that haven't been checked for replies yet
     // A reply exists if the same thread has a subsequent outbound message
-    conn.execute_batch(
+    // from the same account.
+    // Order by JULIANDAY rather than the raw strings so timestamps carrying
+    // different UTC offsets still compare correctly.
+    conn.execute_batch(&format!(
         "UPDATE email_observations SET replied = 1
          WHERE is_inbound = 1 AND replied = 0
          AND EXISTS (
              SELECT 1 FROM email_observations e2
              WHERE e2.thread_id = email_observations.thread_id
+             AND e2.account IS email_observations.account
              AND e2.is_inbound = 0
-             AND e2.timestamp > email_observations.timestamp
+             AND JULIANDAY(e2.timestamp) > JULIANDAY(email_observations.timestamp)
          );
 
          -- Calculate reply time for newly detected replies
@@ -937,11 +1075,20 @@ fn detect_reply_patterns(conn: &Connection) -> Result<(), String> {
              )
              FROM email_observations e2
              WHERE e2.thread_id = email_observations.thread_id
+             AND e2.account IS email_observations.account
              AND e2.is_inbound = 0
-             AND e2.timestamp > email_observations.timestamp
+             AND JULIANDAY(e2.timestamp) > JULIANDAY(email_observations.timestamp)
          )
          WHERE is_inbound = 1 AND replied = 1 AND reply_time_mins IS NULL;
 
+         -- Discard non-positive or implausible reply times (unparseable or
+         -- mis-synthesised timestamps); -1 marks them so they aren't recomputed
+         UPDATE email_observations SET reply_time_mins = -1
+         WHERE is_inbound = 1 AND replied = 1
+         AND reply_time_mins IS NOT NULL
+         AND reply_time_mins != -1
+         AND (reply_time_mins <= 0 OR reply_time_mins > {max});
+
          -- Update contact avg response times
          UPDATE contacts SET avg_response_time_mins = (
              SELECT AVG(reply_time_mins)
@@ -949,14 +1096,15 @@ fn detect_reply_patterns(conn: &Connection) -> Result<(), String> {
              WHERE from_email = contacts.email
              AND is_inbound = 1
              AND replied = 1
-             AND reply_time_mins IS NOT NULL
              AND reply_time_mins > 0
+             AND reply_time_mins <= {max}
          )
          WHERE email IN (
              SELECT DISTINCT from_email FROM email_observations
              WHERE is_inbound = 1 AND replied = 1 AND reply_time_mins IS NOT NULL
          );",
-    )
+        max = MAX_REPLY_TIME_MINS
+    ))
     .map_err(|e| format!("Failed to detect reply patterns: {}", e))?;
 
     Ok(())
@@ -968,19 +1116,8 @@ fn detect_reply_patterns(conn: &Connection) -> Result<(), String> {
 
 fn detect_user_email(conn: &Connection) -> Option<String> {
     // First, check if we have a gog account
-    let gog = gog_binary_path();
-    if let Ok(output) = Command::new(&gog)
-        .args(["auth", "whoami", "--json", "--no-input"])
-        .output()
-    {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Ok(val) = serde_json::from_str::<serde_json::Value>(&stdout) {
-                if let Some(email) = val.get("email").and_then(|v| v.as_str()) {
-                    return Some(email.to_string());
-                }
-            }
-        }
+    if let Ok(account) = gog::gog_whoami() {
+        return Some(account.email);
     }
 
     // Fallback: the email that appears most frequently in "from" field for outbound-looking messages
@@ -1091,6 +1228,128 @@ pub fn get_recent_contacts(limit: u32) -> Result<Vec<ContactSummary>, String> {
     Ok(contacts)
 }
 
+// ---------------------------------------------------------------------------
+// Contact tags
+// ---------------------------------------------------------------------------
+
+/// Lowercase, trim and de-duplicate tags, dropping empty ones. Order is kept.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+fn read_contact_tags(conn: &Connection, email: &str) -> Result<Vec<String>, String> {
+    let raw: Option<Option<String>> = conn
+        .query_row(
+            "SELECT tags FROM contacts WHERE email = ?1",
+            params![email],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to query contact: {}", e))?;
+
+    match raw {
+        None => Err(format!("Unknown contact: {}", email)),
+        Some(t) => Ok(t.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default()),
+    }
+}
+
+fn write_contact_tags(conn: &Connection, email: &str, tags: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string(tags)
+        .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+    conn.execute(
+        "UPDATE contacts SET tags = ?1 WHERE email = ?2",
+        params![json, email],
+    )
+    .map_err(|e| format!("Failed to update tags: {}", e))?;
+    Ok(())
+}
+
+/// Replace a contact's tags. Returns the stored (normalised) list.
+pub fn set_contact_tags(email: &str, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let conn = open_db()?;
+    read_contact_tags(&conn, email)?;
+    let tags = normalize_tags(tags);
+    write_contact_tags(&conn, email, &tags)?;
+    Ok(tags)
+}
+
+/// Add one tag to a contact (no-op if already present).
+pub fn add_contact_tag(email: &str, tag: &str) -> Result<Vec<String>, String> {
+    let conn = open_db()?;
+    let mut tags = read_contact_tags(&conn, email)?;
+    tags.push(tag.to_string());
+    let tags = normalize_tags(tags);
+    write_contact_tags(&conn, email, &tags)?;
+    Ok(tags)
+}
+
+/// Remove one tag from a contact (no-op if absent).
+pub fn remove_contact_tag(email: &str, tag: &str) -> Result<Vec<String>, String> {
+    let conn = open_db()?;
+    let tag = tag.trim().to_lowercase();
+    let tags: Vec<String> = normalize_tags(read_contact_tags(&conn, email)?)
+        .into_iter()
+        .filter(|t| *t != tag)
+        .collect();
+    write_contact_tags(&conn, email, &tags)?;
+    Ok(tags)
+}
+
+/// Contacts carrying `tag`, most active first.
+pub fn list_contacts_by_tag(tag: &str) -> Result<Vec<ContactSummary>, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag must not be empty".to_string());
+    }
+    let conn = open_db()?;
+
+    // LIKE narrows the scan; the exact match happens after parsing the JSON.
+    let pattern = format!("%{}%", serde_json::to_string(&tag).unwrap_or_default());
+    let mut stmt = conn
+        .prepare(
+            "SELECT email, name, interaction_count, last_seen, preferred_channel, tags
+             FROM contacts
+             WHERE tags LIKE ?1
+             ORDER BY interaction_count DESC, last_seen DESC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![pattern], |row| {
+            let tags_raw: Option<String> = row.get(5)?;
+            let tags: Vec<String> = tags_raw
+                .and_then(|t| serde_json::from_str(&t).ok())
+                .unwrap_or_default();
+
+            Ok(ContactSummary {
+                email: row.get(0)?,
+                name: row.get(1)?,
+                interaction_count: row.get(2)?,
+                last_seen: row.get(3)?,
+                preferred_channel: row.get(4)?,
+                tags,
+            })
+        })
+        .map_err(|e| format!("Failed to query contacts: {}", e))?;
+
+    let mut contacts = Vec::new();
+    for row in rows {
+        let contact = row.map_err(|e| format!("Row error: {}", e))?;
+        if contact.tags.iter().any(|t| t.to_lowercase() == tag) {
+            contacts.push(contact);
+        }
+    }
+
+    Ok(contacts)
+}
+
 #[allow(dead_code)] // Used by MCP tools and future dashboard
 pub fn get_unanswered_emails(hours: u32) -> Result<Vec<serde_json::Value>, String> {
     let conn = open_db()?;
@@ -1293,6 +1552,14 @@ pub fn get_activity_stats() -> Result<ActivityStats, String> {
 pub fn get_suggestions() -> Result<Vec<Suggestion>, String> {
     let conn = open_db()?;
 
+    // Resurface snoozed suggestions whose snooze has ended
+    conn.execute(
+        "UPDATE suggestions SET status = 'pending', snooze_until = NULL
+         WHERE status = 'snoozed' AND snooze_until <= ?1",
+        params![now_iso()],
+    )
+    .map_err(|e| format!("Failed to resurface snoozed suggestions: {}", e))?;
+
     let mut stmt = conn
         .prepare(
             "SELECT id, type, title, description, contact_email, confidence,
@@ -1351,6 +1618,42 @@ pub fn dismiss_suggestion(id: i64) -> Result<(), String> {
     )
     .ok(); // Non-critical
 
+    discard_undo_snapshot();
+    Ok(())
+}
+
+/// Hide a pending suggestion until `until_iso` (RFC 3339). If it would expire
+/// while snoozed, its expiry moves to a week after it resurfaces.
+pub fn snooze_suggestion(id: i64, until_iso: &str) -> Result<(), String> {
+    let until = chrono::DateTime::parse_from_rfc3339(until_iso)
+        .map_err(|e| format!("Invalid snooze time '{}': {}", until_iso, e))?
+        .with_timezone(&chrono::Utc);
+    if until <= chrono::Utc::now() {
+        return Err("Snooze time must be in the future".to_string());
+    }
+    // Same shape as now_iso() so string comparisons in SQL hold
+    let until_str = until.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let min_expiry = (until + chrono::Duration::days(7))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+
+    let conn = open_db()?;
+    let updated = conn
+        .execute(
+            "UPDATE suggestions SET status = 'snoozed', snooze_until = ?1,
+                 expires_at = CASE
+                     WHEN expires_at IS NOT NULL AND expires_at < ?2 THEN ?2
+                     ELSE expires_at
+                 END
+             WHERE id = ?3 AND status IN ('pending', 'snoozed')",
+            params![until_str, min_expiry, id],
+        )
+        .map_err(|e| format!("Failed to snooze suggestion: {}", e))?;
+    if updated == 0 {
+        return Err(format!("No pending suggestion with id {}", id));
+    }
+
+    discard_undo_snapshot();
     Ok(())
 }
 
@@ -1372,6 +1675,8 @@ pub fn accept_suggestion(id: i64) -> Result<Suggestion, String> {
     )
     .ok(); // Non-critical
 
+    discard_undo_snapshot();
+
     // Return the suggestion
     conn.query_row(
         "SELECT id, type, title, description, contact_email, confidence,
@@ -1444,12 +1749,34 @@ pub fn set_autonomy_level(activity_type: &str, level: &str) -> Result<(), String
     let conn = open_db()?;
     let now = now_iso();
 
+    let previous: Option<String> = conn
+        .query_row(
+            "SELECT level FROM autonomy_settings WHERE activity_type = ?1",
+            params![activity_type],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read autonomy level: {}", e))?;
+
     conn.execute(
         "UPDATE autonomy_settings SET level = ?1, promoted_at = ?2 WHERE activity_type = ?3",
         params![level, now, activity_type],
     )
     .map_err(|e| format!("Failed to update autonomy level: {}", e))?;
 
+    if previous.as_deref() != Some(level) {
+        crate::audit::record_audit_lossy(
+            "autonomy_changed",
+            serde_json::json!({
+                "activityType": activity_type,
+                "from": previous,
+                "to": level,
+            }),
+            "ok",
+        );
+    }
+
+    discard_undo_snapshot();
     Ok(())
 }
 
@@ -1458,7 +1785,8 @@ pub fn set_autonomy_level(activity_type: &str, level: &str) -> Result<(), String
 // ---------------------------------------------------------------------------
 
 /// Detect contacts with 3+ interactions in 14 days but no contact in 5+ days.
-pub fn detect_frequent_contacts() -> Result<Vec<Suggestion>, String> {
+pub fn detect_frequent_contacts(tuning: &DetectorTuning) -> Result<Vec<Suggestion>, String> {
+    let tz = display_timezone();
     let conn = open_db()?;
     let fourteen_days = days_ago(14);
     let five_days = days_ago(5);
@@ -1473,15 +1801,15 @@ pub fn detect_frequent_contacts() -> Result<Vec<Suggestion>, String> {
              AND c.interaction_count >= 3
              AND c.email NOT IN (
                  SELECT COALESCE(contact_email, '') FROM suggestions
-                 WHERE type = 'catch_up' AND status = 'pending'
+                 WHERE type = 'catch_up' AND status IN ('pending', 'snoozed')
              )
              ORDER BY c.interaction_count DESC
-             LIMIT 5",
+             LIMIT ?3",
         )
         .map_err(|e| format!("Query failed: {}", e))?;
 
     let rows = stmt
-        .query_map(params![fourteen_days, five_days], |row| {
+        .query_map(params![fourteen_days, five_days, tuning.max_count], |row| {
             let email: String = row.get(0)?;
             let name: Option<String> = row.get(1)?;
             let count: i64 = row.get(2)?;
@@ -1496,7 +1824,7 @@ pub fn detect_frequent_contacts() -> Result<Vec<Suggestion>, String> {
                 title: format!("Catch up with {}", display),
                 description: format!(
                     "{} has had {} interactions recently but hasn't been in touch since {}.",
-                    display, count, &last[..10.min(last.len())]
+                    display, count, local_date(&last, tz)
                 ),
                 contact_email: Some(email),
                 confidence,
@@ -1508,7 +1836,116 @@ pub fn detect_frequent_contacts() -> Result<Vec<Suggestion>, String> {
                 status: "pending".to_string(),
                 created_at: now.clone(),
                 acted_at: None,
-                expires_at: Some(days_ahead(7)),
+                expires_at: Some(days_ahead(tuning.expiry_days as u64)),
+            })
+        })
+        .map_err(|e| format!("Query failed: {}", e))?;
+
+    let mut suggestions = Vec::new();
+    for row in rows {
+        suggestions.push(row.map_err(|e| format!("Row error: {}", e))?);
+    }
+
+    Ok(suggestions)
+}
+
+/// Recompute each contact's historical cadence (average days between distinct
+/// interaction days) from observed emails and past meetings. Contacts need at
+/// least 3 distinct interaction days for a meaningful baseline.
+fn update_contact_cadence(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "UPDATE contacts SET
+            interaction_cadence_days = pc.cadence,
+            last_interaction_at = pc.last_ts
+         FROM (
+             SELECT email,
+                    (JULIANDAY(MAX(ts)) - JULIANDAY(MIN(ts)))
+                        / (COUNT(DISTINCT DATE(ts)) - 1) AS cadence,
+                    MAX(ts) AS last_ts
+             FROM (
+                 SELECT from_email AS email, timestamp AS ts
+                 FROM email_observations
+                 UNION ALL
+                 SELECT je.value, e.timestamp
+                 FROM email_observations e, json_each(e.to_emails) je
+                 UNION ALL
+                 SELECT je.value, ce.start_time
+                 FROM calendar_events ce, json_each(ce.attendees) je
+                 WHERE ce.start_time <= ?1
+             )
+             GROUP BY email
+             HAVING COUNT(DISTINCT DATE(ts)) >= 3
+         ) AS pc
+         WHERE contacts.email = pc.email",
+        params![now_iso()],
+    )
+    .map_err(|e| format!("Failed to update contact cadence: {}", e))?;
+
+    Ok(())
+}
+
+/// Detect contacts whose current silence far exceeds their own historical
+/// cadence (e.g. a weekly contact gone quiet for three weeks).
+pub fn detect_going_cold(tuning: &DetectorTuning) -> Result<Vec<Suggestion>, String> {
+    let tz = display_timezone();
+    let conn = open_db()?;
+    update_contact_cadence(&conn)?;
+    let now = now_iso();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT email, name, interaction_cadence_days, last_interaction_at, gap_days
+             FROM (
+                 SELECT c.email, c.name, c.interaction_cadence_days, c.last_interaction_at,
+                        JULIANDAY(?1) - JULIANDAY(c.last_interaction_at) AS gap_days
+                 FROM contacts c
+                 WHERE c.interaction_cadence_days BETWEEN 1 AND 45
+                 AND c.last_interaction_at IS NOT NULL
+             )
+             WHERE gap_days >= 7
+             AND gap_days >= interaction_cadence_days * 2.5
+             AND email NOT IN (
+                 SELECT COALESCE(contact_email, '') FROM suggestions
+                 WHERE type = 'going_cold' AND status IN ('pending', 'snoozed')
+             )
+             ORDER BY gap_days / interaction_cadence_days DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| format!("Query failed: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![now, tuning.max_count], |row| {
+            let email: String = row.get(0)?;
+            let name: Option<String> = row.get(1)?;
+            let cadence: f64 = row.get(2)?;
+            let last: String = row.get(3)?;
+            let gap: f64 = row.get(4)?;
+
+            let display = name.as_deref().unwrap_or(&email);
+            // Scale with how far past the baseline we are: 2.5x → 0.5, 6.5x+ → 0.9
+            let deviation = gap / cadence;
+            let confidence = (0.5 + (deviation - 2.5) * 0.1).clamp(0.5, 0.9);
+
+            Ok(Suggestion {
+                id: 0,
+                suggestion_type: "going_cold".to_string(),
+                title: format!("Reconnect with {}", display),
+                description: format!(
+                    "You usually interact with {} every {:.0} days, but it's been {:.0} days since {}.",
+                    display, cadence, gap, local_date(&last, tz)
+                ),
+                contact_email: Some(email),
+                confidence,
+                context: Some(serde_json::json!({
+                    "cadence_days": cadence,
+                    "gap_days": gap,
+                    "last_interaction_at": last,
+                })
+                .to_string()),
+                status: "pending".to_string(),
+                created_at: now.clone(),
+                acted_at: None,
+                expires_at: Some(days_ahead(tuning.expiry_days as u64)),
             })
         })
         .map_err(|e| format!("Query failed: {}", e))?;
@@ -1522,7 +1959,8 @@ pub fn detect_frequent_contacts() -> Result<Vec<Suggestion>, String> {
 }
 
 /// Detect inbound emails from known contacts with no reply in 24+ hours.
-pub fn detect_unanswered_threads() -> Result<Vec<Suggestion>, String> {
+pub fn detect_unanswered_threads(tuning: &DetectorTuning) -> Result<Vec<Suggestion>, String> {
+    let tz = display_timezone();
     let conn = open_db()?;
     let one_day = days_ago(1);
     let seven_days = days_ago(7);
@@ -1539,15 +1977,15 @@ pub fn detect_unanswered_threads() -> Result<Vec<Suggestion>, String> {
              AND e.timestamp >= ?2
              AND e.from_email NOT IN (
                  SELECT COALESCE(contact_email, '') FROM suggestions
-                 WHERE type = 'respond' AND status = 'pending'
+                 WHERE type = 'respond' AND status IN ('pending', 'snoozed')
              )
              ORDER BY c.interaction_count DESC, e.timestamp ASC
-             LIMIT 5",
+             LIMIT ?3",
         )
         .map_err(|e| format!("Query failed: {}", e))?;
 
     let rows = stmt
-        .query_map(params![one_day, seven_days], |row| {
+        .query_map(params![one_day, seven_days, tuning.max_count], |row| {
             let email: String = row.get(0)?;
             let subject: Option<String> = row.get(1)?;
             let ts: String = row.get(2)?;
@@ -1563,7 +2001,7 @@ pub fn detect_unanswered_threads() -> Result<Vec<Suggestion>, String> {
                 title: format!("Reply to {} about \"{}\"", display, truncate(subj, 40)),
                 description: format!(
                     "You haven't replied to {} about \"{}\". Sent {}.",
-                    display, subj, &ts[..10.min(ts.len())]
+                    display, subj, local_date(&ts, tz)
                 ),
                 contact_email: Some(email),
                 confidence: 0.7,
@@ -1576,7 +2014,7 @@ pub fn detect_unanswered_threads() -> Result<Vec<Suggestion>, String> {
                 status: "pending".to_string(),
                 created_at: now.clone(),
                 acted_at: None,
-                expires_at: Some(days_ahead(3)),
+                expires_at: Some(days_ahead(tuning.expiry_days as u64)),
             })
         })
         .map_err(|e| format!("Query failed: {}", e))?;
@@ -1590,7 +2028,8 @@ pub fn detect_unanswered_threads() -> Result<Vec<Suggestion>, String> {
 }
 
 /// Detect contacts who sent 2+ emails in 7 days with no response.
-pub fn detect_reachout_attempts() -> Result<Vec<Suggestion>, String> {
+pub fn detect_reachout_attempts(tuning: &DetectorTuning) -> Result<Vec<Suggestion>, String> {
+    let tz = display_timezone();
     let conn = open_db()?;
     let seven_days = days_ago(7);
     let now = now_iso();
@@ -1607,15 +2046,15 @@ pub fn detect_reachout_attempts() -> Result<Vec<Suggestion>, String> {
              HAVING cnt >= 2
              AND e.from_email NOT IN (
                  SELECT COALESCE(contact_email, '') FROM suggestions
-                 WHERE type = 'reachout' AND status = 'pending'
+                 WHERE type = 'reachout' AND status IN ('pending', 'snoozed')
              )
              ORDER BY cnt DESC
-             LIMIT 5",
+             LIMIT ?2",
         )
         .map_err(|e| format!("Query failed: {}", e))?;
 
     let rows = stmt
-        .query_map(params![seven_days], |row| {
+        .query_map(params![seven_days, tuning.max_count], |row| {
             let email: String = row.get(0)?;
             let count: i64 = row.get(1)?;
             let name: Option<String> = row.get(2)?;
@@ -1629,7 +2068,7 @@ pub fn detect_reachout_attempts() -> Result<Vec<Suggestion>, String> {
                 title: format!("{} has been trying to reach you", display),
                 description: format!(
                     "{} has sent {} unanswered emails in the last 7 days. Latest: {}.",
-                    display, count, &latest[..10.min(latest.len())]
+                    display, count, local_date(&latest, tz)
                 ),
                 contact_email: Some(email),
                 confidence: 0.85,
@@ -1641,7 +2080,7 @@ pub fn detect_reachout_attempts() -> Result<Vec<Suggestion>, String> {
                 status: "pending".to_string(),
                 created_at: now.clone(),
                 acted_at: None,
-                expires_at: Some(days_ahead(3)),
+                expires_at: Some(days_ahead(tuning.expiry_days as u64)),
             })
         })
         .map_err(|e| format!("Query failed: {}", e))?;
@@ -1655,7 +2094,7 @@ pub fn detect_reachout_attempts() -> Result<Vec<Suggestion>, String> {
 }
 
 /// Detect recurring meeting attendees with no recent event.
-pub fn detect_meeting_patterns() -> Result<Vec<Suggestion>, String> {
+pub fn detect_meeting_patterns(tuning: &DetectorTuning) -> Result<Vec<Suggestion>, String> {
     let conn = open_db()?;
     let thirty_days = days_ago(30);
     let fourteen_days = days_ago(14);
@@ -1686,15 +2125,15 @@ pub fn detect_meeting_patterns() -> Result<Vec<Suggestion>, String> {
              )
              AND attendee_email NOT IN (
                  SELECT COALESCE(contact_email, '') FROM suggestions
-                 WHERE type = 'schedule_meeting' AND status = 'pending'
+                 WHERE type = 'schedule_meeting' AND status IN ('pending', 'snoozed')
              )
              ORDER BY meeting_count DESC
-             LIMIT 5",
+             LIMIT ?3",
         )
         .map_err(|e| format!("Query failed: {}", e))?;
 
     let rows = stmt
-        .query_map(params![thirty_days, fourteen_days], |row| {
+        .query_map(params![thirty_days, fourteen_days, tuning.max_count], |row| {
             let email: String = row.get(0)?;
             let count: i64 = row.get(1)?;
             let name: Option<String> = row.get(2)?;
@@ -1718,7 +2157,7 @@ pub fn detect_meeting_patterns() -> Result<Vec<Suggestion>, String> {
                 status: "pending".to_string(),
                 created_at: now.clone(),
                 acted_at: None,
-                expires_at: Some(days_ahead(7)),
+                expires_at: Some(days_ahead(tuning.expiry_days as u64)),
             })
         })
         .map_err(|e| format!("Query failed: {}", e))?;
@@ -1731,21 +2170,47 @@ pub fn detect_meeting_patterns() -> Result<Vec<Suggestion>, String> {
     Ok(suggestions)
 }
 
+/// Suggestions created by an on-demand detector run, plus everything pending.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SuggestionRun {
+    pub created: Vec<Suggestion>,
+    pub pending: Vec<Suggestion>,
+}
+
+/// Run the detectors now instead of waiting for the hourly tick.
+pub fn run_suggestions_now() -> Result<SuggestionRun, String> {
+    init_db()?;
+    let before: std::collections::HashSet<i64> =
+        get_suggestions()?.into_iter().map(|s| s.id).collect();
+    generate_suggestions()?;
+    let pending = get_suggestions()?;
+    let created = pending
+        .iter()
+        .filter(|s| !before.contains(&s.id))
+        .cloned()
+        .collect();
+    Ok(SuggestionRun { created, pending })
+}
+
 /// Master function: run all detectors, deduplicate, insert into suggestions table.
 pub fn generate_suggestions() -> Result<u32, String> {
+    let tuning = get_tuning();
     let mut all: Vec<Suggestion> = Vec::new();
 
     // Run each detector, ignoring errors (best-effort)
-    if let Ok(mut s) = detect_reachout_attempts() {
+    if let Ok(mut s) = detect_reachout_attempts(&tuning.reachout) {
+        all.append(&mut s);
+    }
+    if let Ok(mut s) = detect_unanswered_threads(&tuning.respond) {
         all.append(&mut s);
     }
-    if let Ok(mut s) = detect_unanswered_threads() {
+    if let Ok(mut s) = detect_frequent_contacts(&tuning.catch_up) {
         all.append(&mut s);
     }
-    if let Ok(mut s) = detect_frequent_contacts() {
+    if let Ok(mut s) = detect_meeting_patterns(&tuning.schedule_meeting) {
         all.append(&mut s);
     }
-    if let Ok(mut s) = detect_meeting_patterns() {
+    if let Ok(mut s) = detect_going_cold(&tuning.going_cold) {
         all.append(&mut s);
     }
 
@@ -1771,11 +2236,11 @@ pub fn generate_suggestions() -> Result<u32, String> {
             continue;
         }
 
-        // Deduplicate: skip if a similar pending suggestion already exists
+        // Deduplicate: skip if a similar pending (or snoozed) suggestion already exists
         let exists: bool = conn
             .query_row(
                 "SELECT COUNT(*) FROM suggestions
-                 WHERE type = ?1 AND contact_email = ?2 AND status = 'pending'",
+                 WHERE type = ?1 AND contact_email = ?2 AND status IN ('pending', 'snoozed')",
                 params![suggestion.suggestion_type, suggestion.contact_email],
                 |row| row.get::<_, i64>(0),
             )
@@ -1828,6 +2293,143 @@ pub fn generate_suggestions() -> Result<u32, String> {
     Ok(count)
 }
 
+// ---------------------------------------------------------------------------
+// Contact pruning
+// ---------------------------------------------------------------------------
+
+/// Defaults used by the observer's daily prune.
+pub const PRUNE_STALE_DAYS: u32 = 180;
+pub const PRUNE_MIN_INTERACTIONS: u32 = 3;
+
+/// Rows removed by `prune_contacts`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PruneReport {
+    pub contacts: u32,
+    pub email_observations: u32,
+    pub suggestions: u32,
+}
+
+/// Forget contacts not seen for `older_than_days` with fewer than
+/// `min_interactions` interactions, along with the email observations they
+/// sent and their open suggestions. Tagged contacts are always kept.
+pub fn prune_contacts(older_than_days: u32, min_interactions: u32) -> Result<PruneReport, String> {
+    if older_than_days == 0 {
+        return Err("older_than_days must be at least 1".to_string());
+    }
+    let mut conn = open_db()?;
+    let cutoff = days_ago(older_than_days as u64);
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start prune: {}", e))?;
+
+    tx.execute_batch(
+        "CREATE TEMP TABLE IF NOT EXISTS prune_targets (email TEXT PRIMARY KEY);
+         DELETE FROM prune_targets;",
+    )
+    .map_err(|e| format!("Failed to prepare prune: {}", e))?;
+    tx.execute(
+        "INSERT INTO prune_targets (email)
+         SELECT email FROM contacts
+         WHERE last_seen < ?1
+           AND interaction_count < ?2
+           AND (tags IS NULL OR tags = '' OR tags = '[]')",
+        params![cutoff, min_interactions],
+    )
+    .map_err(|e| format!("Failed to select stale contacts: {}", e))?;
+
+    let email_observations = tx
+        .execute(
+            "DELETE FROM email_observations
+             WHERE from_email IN (SELECT email FROM prune_targets)",
+            [],
+        )
+        .map_err(|e| format!("Failed to prune email observations: {}", e))?;
+    let suggestions = tx
+        .execute(
+            "DELETE FROM suggestions
+             WHERE status IN ('pending', 'snoozed')
+               AND contact_email IN (SELECT email FROM prune_targets)",
+            [],
+        )
+        .map_err(|e| format!("Failed to prune suggestions: {}", e))?;
+    let contacts = tx
+        .execute(
+            "DELETE FROM contacts WHERE email IN (SELECT email FROM prune_targets)",
+            [],
+        )
+        .map_err(|e| format!("Failed to prune contacts: {}", e))?;
+
+    tx.execute_batch("DROP TABLE IF EXISTS prune_targets;")
+        .map_err(|e| format!("Failed to finish prune: {}", e))?;
+    tx.commit().map_err(|e| format!("Failed to commit prune: {}", e))?;
+
+    Ok(PruneReport {
+        contacts: contacts as u32,
+        email_observations: email_observations as u32,
+        suggestions: suggestions as u32,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Suggestion tuning
+// ---------------------------------------------------------------------------
+
+/// Where releases before the preferences file kept the tuning.
+fn legacy_tuning_path() -> PathBuf {
+    db_path().with_file_name("intelligence-tuning.json")
+}
+
+/// Read the suggestion tuning from preferences.json (or the legacy file when
+/// it was never saved there), falling back to defaults. A file that can't be
+/// read or parsed is logged rather than silently ignored.
+pub fn get_tuning() -> IntelligenceTuning {
+    match config::load_preferences() {
+        Ok(prefs) => match prefs.intelligence_tuning {
+            Some(tuning) => tuning,
+            None => read_legacy_tuning().unwrap_or_default(),
+        },
+        Err(e) => {
+            eprintln!("Using default suggestion tuning: {}", e);
+            IntelligenceTuning::default()
+        }
+    }
+}
+
+fn read_legacy_tuning() -> Option<IntelligenceTuning> {
+    let path = legacy_tuning_path();
+    let content = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| eprintln!("Ignoring malformed {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Validate and persist the suggestion tuning.
+pub fn set_tuning(tuning: &IntelligenceTuning) -> Result<(), String> {
+    let detectors = [
+        ("catch_up", &tuning.catch_up),
+        ("respond", &tuning.respond),
+        ("reachout", &tuning.reachout),
+        ("schedule_meeting", &tuning.schedule_meeting),
+        ("going_cold", &tuning.going_cold),
+    ];
+    for (name, d) in detectors {
+        if d.max_count == 0 || d.max_count > 50 {
+            return Err(format!("{}: max_count must be between 1 and 50", name));
+        }
+        if d.expiry_days == 0 || d.expiry_days > 90 {
+            return Err(format!("{}: expiry_days must be between 1 and 90", name));
+        }
+    }
+
+    let mut prefs = config::load_preferences()?;
+    prefs.intelligence_tuning = Some(tuning.clone());
+    config::write_preferences(&prefs)?;
+    // Superseded by preferences.json
+    let _ = std::fs::remove_file(legacy_tuning_path());
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Trust building — check if any activity type qualifies for promotion
 // ---------------------------------------------------------------------------
@@ -1879,10 +2481,328 @@ pub fn next_autonomy_level(current: &str) -> Option<&'static str> {
 // Data management
 // ---------------------------------------------------------------------------
 
+/// Tables with an `id` whose rows are restored from the undo snapshot.
+/// Rows written after the operation (id above the recorded maximum) are
+/// kept; a snapshot row whose unique key is already present is skipped.
+const UNDO_ROW_TABLES: &[&str] = &[
+    "calendar_events",
+    "email_observations",
+    "messaging_observations",
+    "suggestions",
+];
+
+/// How long an undo snapshot stays valid after the destructive operation.
+const UNDO_WINDOW_SECS: u64 = 10 * 60;
+
+fn undo_path() -> PathBuf {
+    db_path().with_file_name("intel-undo.db")
+}
+
+/// Snapshot the intelligence tables to ~/.nyx/intel-undo.db so the next
+/// destructive operation can be reverted with `undo_last_action`.
+fn snapshot_for_undo(conn: &Connection) -> Result<(), String> {
+    // VACUUM INTO refuses to overwrite, so drop any older snapshot first
+    discard_undo_snapshot();
+    snapshot_to(conn, &undo_path())
+}
+
+fn snapshot_to(conn: &Connection, path: &Path) -> Result<(), String> {
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![path.to_string_lossy().to_string()],
+    )
+    .map_err(|e| format!("Failed to snapshot intelligence data: {}", e))?;
+
+    Ok(())
+}
+
+/// Call once the destructive operation has committed. Without a baseline
+/// the snapshot can't be merged safely, so it is dropped instead.
+fn finish_undo_snapshot(conn: &Connection) {
+    if record_undo_baseline(conn, &undo_path()).is_err() {
+        discard_undo_snapshot();
+    }
+}
+
+/// Record in the snapshot what the operation left behind: the highest row id
+/// per table and each contact's interaction count. Undo uses them to tell
+/// later observations apart from the operation's own writes.
+fn record_undo_baseline(conn: &Connection, path: &Path) -> Result<(), String> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS undo",
+        params![path.to_string_lossy().to_string()],
+    )
+    .map_err(|e| format!("Failed to open undo snapshot: {}", e))?;
+
+    let mut batch = String::from(
+        "BEGIN;
+         CREATE TABLE undo.undo_baseline_ids (tbl TEXT PRIMARY KEY, max_id INTEGER NOT NULL);
+         CREATE TABLE undo.undo_baseline_contacts AS
+             SELECT email, interaction_count FROM main.contacts;",
+    );
+    for table in UNDO_ROW_TABLES.iter().chain(["contacts"].iter()) {
+        batch.push_str(&format!(
+            "INSERT INTO undo.undo_baseline_ids VALUES ('{t}', (SELECT IFNULL(MAX(id), 0) FROM main.{t}));",
+            t = table
+        ));
+    }
+    batch.push_str("COMMIT;");
+
+    let result = conn.execute_batch(&batch);
+    if result.is_err() {
+        let _ = conn.execute_batch("ROLLBACK;");
+    }
+    let _ = conn.execute_batch("DETACH DATABASE undo;");
+    result.map_err(|e| format!("Failed to record undo baseline: {}", e))
+}
+
+/// Drop the undo snapshot (called after any unrelated successful write).
+fn discard_undo_snapshot() {
+    let _ = std::fs::remove_file(undo_path());
+}
+
+/// Restore the snapshot taken before the last destructive operation. The
+/// snapshot is merged over the current data, so anything observed since
+/// the operation is kept.
+pub fn undo_last_action() -> Result<(), String> {
+    let path = undo_path();
+    let modified = std::fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .map_err(|_| "Nothing to undo".to_string())?;
+
+    let age = std::time::SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default()
+        .as_secs();
+    if age > UNDO_WINDOW_SECS {
+        discard_undo_snapshot();
+        return Err(format!(
+            "Undo window expired ({} minutes)",
+            UNDO_WINDOW_SECS / 60
+        ));
+    }
+
+    merge_snapshot(&open_db()?, &path)?;
+    discard_undo_snapshot();
+    Ok(())
+}
+
+/// Merge the snapshot at `path` into the live tables in one transaction.
+/// Rows the operation wrote are replaced by the snapshot's; rows observed
+/// since are kept. A contact in both gets the snapshot row plus whatever
+/// interactions were counted after the operation.
+fn merge_snapshot(conn: &Connection, path: &Path) -> Result<(), String> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS undo",
+        params![path.to_string_lossy().to_string()],
+    )
+    .map_err(|e| format!("Failed to open undo snapshot: {}", e))?;
+
+    let merged = (|| {
+        let has_baseline: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM undo.sqlite_master WHERE name = 'undo_baseline_ids'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to read undo snapshot: {}", e))?;
+        if !has_baseline {
+            return Err("Nothing to undo".to_string());
+        }
+
+        // Interactions counted since the operation, per contact
+        let since = "(COALESCE(c.interaction_count, 0) - COALESCE(b.interaction_count, 0))";
+        let mut batch = format!(
+            "BEGIN;
+             UPDATE main.contacts AS c SET
+                 name = COALESCE(u.name, c.name),
+                 first_seen = u.first_seen,
+                 last_seen = CASE WHEN {since} > 0 THEN MAX(u.last_seen, c.last_seen) ELSE u.last_seen END,
+                 interaction_count = COALESCE(u.interaction_count, 0) + MAX({since}, 0),
+                 avg_response_time_mins = COALESCE(u.avg_response_time_mins, c.avg_response_time_mins),
+                 preferred_channel = COALESCE(u.preferred_channel, c.preferred_channel),
+                 tags = CASE WHEN u.tags IS NULL OR u.tags IN ('', '[]') THEN c.tags ELSE u.tags END,
+                 interaction_cadence_days = COALESCE(u.interaction_cadence_days, c.interaction_cadence_days),
+                 last_interaction_at = CASE WHEN {since} > 0
+                     THEN COALESCE(MAX(u.last_interaction_at, c.last_interaction_at),
+                                   u.last_interaction_at, c.last_interaction_at)
+                     ELSE u.last_interaction_at END,
+                 created_at = u.created_at,
+                 updated_at = MAX(u.updated_at, c.updated_at)
+             FROM undo.contacts AS u
+             LEFT JOIN undo.undo_baseline_contacts AS b ON b.email = u.email
+             WHERE u.email = c.email;
+             -- Contacts the operation added (e.g. by import) lose what it added
+             DELETE FROM main.contacts
+             WHERE email NOT IN (SELECT email FROM undo.contacts)
+               AND interaction_count <= (SELECT b.interaction_count FROM undo.undo_baseline_contacts AS b
+                                         WHERE b.email = main.contacts.email);
+             UPDATE main.contacts AS c SET interaction_count = c.interaction_count - b.interaction_count
+             FROM undo.undo_baseline_contacts AS b
+             WHERE b.email = c.email AND c.email NOT IN (SELECT email FROM undo.contacts);",
+            since = since
+        );
+        batch.push_str(&format!(
+            "INSERT INTO main.contacts ({c}) SELECT {c} FROM undo.contacts
+             WHERE email NOT IN (SELECT email FROM main.contacts);",
+            c = shared_columns(conn, "contacts")?
+        ));
+
+        for table in UNDO_ROW_TABLES {
+            let cols = shared_columns(conn, table)?;
+            batch.push_str(&format!(
+                "DELETE FROM main.{t}
+                 WHERE id <= (SELECT max_id FROM undo.undo_baseline_ids WHERE tbl = '{t}');",
+                t = table
+            ));
+            if *table == "suggestions" {
+                // No unique key; skip ones regenerated since the operation
+                batch.push_str(&format!(
+                    "INSERT INTO main.suggestions ({c}) SELECT {c} FROM undo.suggestions AS u
+                     WHERE NOT EXISTS (
+                         SELECT 1 FROM main.suggestions AS s
+                         WHERE s.type = u.type AND s.title = u.title AND s.status = u.status
+                           AND IFNULL(s.contact_email, '') = IFNULL(u.contact_email, '')
+                     );",
+                    c = cols
+                ));
+            } else {
+                batch.push_str(&format!(
+                    "INSERT OR IGNORE INTO main.{t} ({c}) SELECT {c} FROM undo.{t};",
+                    t = table,
+                    c = cols
+                ));
+            }
+        }
+
+        // Accepting or dismissing discards the snapshot, so autonomy settings
+        // can't have changed since; restore them as they were
+        batch.push_str(&format!(
+            "DELETE FROM main.autonomy_settings;
+             INSERT INTO main.autonomy_settings ({c}) SELECT {c} FROM undo.autonomy_settings;
+             COMMIT;",
+            c = shared_columns(conn, "autonomy_settings")?
+        ));
+
+        let result = conn.execute_batch(&batch);
+        if result.is_err() {
+            let _ = conn.execute_batch("ROLLBACK;");
+        }
+        result.map_err(|e| format!("Failed to restore intelligence data: {}", e))
+    })();
+
+    let _ = conn.execute_batch("DETACH DATABASE undo;");
+    merged
+}
+
+/// Comma-separated columns `table` has in both the live database and the
+/// attached snapshot, without the row id (the live table assigns new ones).
+fn shared_columns(conn: &Connection, table: &str) -> Result<String, String> {
+    let columns = |schema: &str| -> Result<Vec<String>, String> {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA {}.table_info({})", schema, table))
+            .map_err(|e| format!("Failed to read {} columns: {}", table, e))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| format!("Failed to read {} columns: {}", table, e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read {} columns: {}", table, e))
+    };
+    let snapshot = columns("undo")?;
+    let shared: Vec<String> = columns("main")?
+        .into_iter()
+        .filter(|c| c != "id" && snapshot.contains(c))
+        .collect();
+    if shared.is_empty() {
+        return Err(format!("Undo snapshot has no {} table", table));
+    }
+    Ok(shared.join(", "))
+}
+
+/// Fold `merge_email`'s contact into `keep_email`: interaction counts are
+/// added, the seen range widened, tags combined and gaps in name, channel
+/// and cadence filled. Suggestions about the merged contact move to the
+/// kept one, then the merged row is deleted. Undoable like a clear.
+pub fn merge_contacts(keep_email: &str, merge_email: &str) -> Result<ContactSummary, String> {
+    if keep_email == merge_email {
+        return Err("Can't merge a contact into itself".to_string());
+    }
+    let mut conn = open_db()?;
+    read_contact_tags(&conn, keep_email)?;
+    read_contact_tags(&conn, merge_email)?;
+
+    snapshot_for_undo(&conn)?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start merge: {}", e))?;
+    merge_contact_rows(&tx, keep_email, merge_email)?;
+    tx.commit().map_err(|e| format!("Failed to commit merge: {}", e))?;
+    finish_undo_snapshot(&conn);
+
+    conn.query_row(
+        "SELECT email, name, interaction_count, last_seen, preferred_channel, tags
+         FROM contacts WHERE email = ?1",
+        params![keep_email],
+        |row| {
+            let tags: Option<String> = row.get(5)?;
+            Ok(ContactSummary {
+                email: row.get(0)?,
+                name: row.get(1)?,
+                interaction_count: row.get(2)?,
+                last_seen: row.get(3)?,
+                preferred_channel: row.get(4)?,
+                tags: tags.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default(),
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to read merged contact: {}", e))
+}
+
+fn merge_contact_rows(conn: &Connection, keep_email: &str, merge_email: &str) -> Result<(), String> {
+    let mut tags = read_contact_tags(conn, keep_email)?;
+    tags.extend(read_contact_tags(conn, merge_email)?);
+    let tags = serde_json::to_string(&normalize_tags(tags))
+        .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+
+    conn.execute(
+        "UPDATE contacts AS k SET
+             name = COALESCE(k.name, m.name),
+             first_seen = MIN(k.first_seen, m.first_seen),
+             last_seen = MAX(k.last_seen, m.last_seen),
+             interaction_count = COALESCE(k.interaction_count, 0) + COALESCE(m.interaction_count, 0),
+             avg_response_time_mins = COALESCE(k.avg_response_time_mins, m.avg_response_time_mins),
+             preferred_channel = COALESCE(k.preferred_channel, m.preferred_channel),
+             tags = ?3,
+             interaction_cadence_days = COALESCE(k.interaction_cadence_days, m.interaction_cadence_days),
+             last_interaction_at = COALESCE(MAX(k.last_interaction_at, m.last_interaction_at),
+                                            k.last_interaction_at, m.last_interaction_at),
+             created_at = MIN(k.created_at, m.created_at),
+             updated_at = ?4
+         FROM contacts AS m WHERE k.email = ?1 AND m.email = ?2",
+        params![keep_email, merge_email, tags, now_iso()],
+    )
+    .map_err(|e| format!("Failed to merge contacts: {}", e))?;
+    conn.execute(
+        "UPDATE suggestions SET contact_email = ?1 WHERE contact_email = ?2",
+        params![keep_email, merge_email],
+    )
+    .map_err(|e| format!("Failed to move suggestions: {}", e))?;
+    conn.execute("DELETE FROM contacts WHERE email = ?1", params![merge_email])
+        .map_err(|e| format!("Failed to remove merged contact: {}", e))?;
+    Ok(())
+}
+
 /// Delete all intelligence data (for when user disables the feature).
+/// A snapshot is kept so the deletion can be undone for a short window.
 pub fn clear_all_data() -> Result<(), String> {
     let conn = open_db()?;
+    snapshot_for_undo(&conn)?;
+    clear_tables(&conn)?;
+    finish_undo_snapshot(&conn);
+    Ok(())
+}
 
+fn clear_tables(conn: &Connection) -> Result<(), String> {
     conn.execute_batch(
         "DELETE FROM email_observations;
          DELETE FROM calendar_events;
@@ -1892,16 +2812,343 @@ pub fn clear_all_data() -> Result<(), String> {
          -- Reset autonomy counters but keep level settings
          UPDATE autonomy_settings SET total_accepted = 0, total_dismissed = 0;",
     )
-    .map_err(|e| format!("Failed to clear intelligence data: {}", e))?;
+    .map_err(|e| format!("Failed to clear intelligence data: {}", e))
+}
+
+/// Tables (and key columns) a backup must contain to be restorable.
+const REQUIRED_SCHEMA: &[(&str, &str)] = &[
+    ("contacts", "email"),
+    ("calendar_events", "event_id"),
+    ("email_observations", "message_id"),
+    ("suggestions", "type"),
+    ("messaging_observations", "message_id"),
+    ("autonomy_settings", "activity_type"),
+];
+
+/// Write a consistent copy of intelligence.db to `dest` using SQLite's online
+/// backup API (safe while the observer is writing).
+pub fn backup_db(dest: &str) -> Result<(), String> {
+    let conn = open_db()?;
+    conn.backup(rusqlite::DatabaseName::Main, dest, None)
+        .map_err(|e| format!("Failed to back up intelligence.db: {}", e))
+}
 
+/// Check a database file is an intelligence database this version can
+/// migrate: it has the tables the current schema expects and its schema
+/// version is not newer than `MIGRATIONS`.
+fn validate_backup(src: &str) -> Result<(), String> {
+    let conn = Connection::open_with_flags(src, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+    validate_backup_schema(&conn)
+}
+
+fn validate_backup_schema(conn: &Connection) -> Result<(), String> {
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Backup is not an intelligence database: {}", e))?;
+    if version < 0 || version as usize > MIGRATIONS.len() {
+        return Err(format!(
+            "Backup schema version {} is newer than this version of Nyx supports ({})",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+
+    for (table, column) in REQUIRED_SCHEMA {
+        let present: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+                params![table, column],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Backup is not an intelligence database: {}", e))?;
+        if present == 0 {
+            return Err(format!(
+                "Backup is missing {}.{} — not a compatible intelligence database",
+                table, column
+            ));
+        }
+    }
     Ok(())
 }
 
+/// Replace intelligence.db with a backup. The observer is paused for the
+/// duration so it can't write into a half-restored database.
+pub fn restore_db(src: &str) -> Result<(), String> {
+    if !Path::new(src).is_file() {
+        return Err(format!("Backup not found: {}", src));
+    }
+    validate_backup(src)?;
+
+    OBSERVER_PAUSED.store(true, Ordering::SeqCst);
+    let result = (|| {
+        let mut conn = open_db()?;
+        conn.restore(rusqlite::DatabaseName::Main, src, None::<fn(rusqlite::backup::Progress)>)
+            .map_err(|e| format!("Failed to restore intelligence.db: {}", e))?;
+        drop(conn);
+        // Bring an older backup up to the current schema
+        init_db()
+    })();
+    OBSERVER_PAUSED.store(false, Ordering::SeqCst);
+
+    if result.is_ok() {
+        discard_undo_snapshot();
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// JSON export / import
+// ---------------------------------------------------------------------------
+
+/// Bumped when the export layout changes incompatibly.
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Tables and the exact columns exported from each. Columns are listed
+/// explicitly so nothing new leaks into exports by accident: email
+/// observations are metadata only (sender, recipients, subject, timing,
+/// labels) and message bodies are never stored or exported.
+const EXPORT_TABLES: &[(&str, &[&str])] = &[
+    ("contacts", &[
+        "email", "name", "first_seen", "last_seen", "interaction_count",
+        "avg_response_time_mins", "preferred_channel", "tags",
+        "interaction_cadence_days", "last_interaction_at", "created_at", "updated_at",
+    ]),
+    ("calendar_events", &[
+        "event_id", "summary", "start_time", "end_time", "attendees", "location",
+        "is_recurring", "organizer_email", "observed_at",
+    ]),
+    ("email_observations", &[
+        "thread_id", "message_id", "from_email", "to_emails", "subject", "timestamp",
+        "is_inbound", "replied", "reply_time_mins", "labels", "observed_at", "account",
+    ]),
+    ("suggestions", &[
+        "id", "type", "title", "description", "contact_email", "confidence", "context",
+        "status", "created_at", "acted_at", "expires_at", "snooze_until",
+    ]),
+    ("autonomy_settings", &[
+        "activity_type", "level", "promoted_at", "total_accepted", "total_dismissed",
+    ]),
+];
+
+/// Portable dump of the on-device model. Each table is a list of
+/// column → value objects. Email bodies are never part of an export — only
+/// the metadata columns in `EXPORT_TABLES`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntelligenceExport {
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default)]
+    pub contacts: Vec<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    pub calendar_events: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Metadata only; bodies are never exported
+    #[serde(default)]
+    pub email_observations: Vec<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    pub suggestions: Vec<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    pub autonomy_settings: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl IntelligenceExport {
+    fn table(&self, name: &str) -> &[serde_json::Map<String, serde_json::Value>] {
+        match name {
+            "contacts" => &self.contacts,
+            "calendar_events" => &self.calendar_events,
+            "email_observations" => &self.email_observations,
+            "suggestions" => &self.suggestions,
+            "autonomy_settings" => &self.autonomy_settings,
+            _ => &[],
+        }
+    }
+
+    fn table_mut(&mut self, name: &str) -> Option<&mut Vec<serde_json::Map<String, serde_json::Value>>> {
+        match name {
+            "contacts" => Some(&mut self.contacts),
+            "calendar_events" => Some(&mut self.calendar_events),
+            "email_observations" => Some(&mut self.email_observations),
+            "suggestions" => Some(&mut self.suggestions),
+            "autonomy_settings" => Some(&mut self.autonomy_settings),
+            _ => None,
+        }
+    }
+}
+
+fn sql_to_json(value: rusqlite::types::ValueRef<'_>) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => serde_json::json!(i),
+        ValueRef::Real(f) => serde_json::json!(f),
+        ValueRef::Text(t) => serde_json::Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => serde_json::Value::String(hex::encode(b)),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+/// Dump contacts, calendar events, email metadata, suggestions and autonomy
+/// settings as JSON. Messaging observations are not exported.
+pub fn export_all() -> Result<serde_json::Value, String> {
+    let conn = open_db()?;
+    let mut export = IntelligenceExport {
+        version: EXPORT_FORMAT_VERSION,
+        exported_at: now_iso(),
+        contacts: Vec::new(),
+        calendar_events: Vec::new(),
+        email_observations: Vec::new(),
+        suggestions: Vec::new(),
+        autonomy_settings: Vec::new(),
+    };
+
+    for (table, columns) in EXPORT_TABLES {
+        let sql = format!("SELECT {} FROM {}", columns.join(", "), table);
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to prepare export of {}: {}", table, e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                let mut obj = serde_json::Map::new();
+                for (i, col) in columns.iter().enumerate() {
+                    obj.insert(col.to_string(), sql_to_json(row.get_ref(i)?));
+                }
+                Ok(obj)
+            })
+            .map_err(|e| format!("Failed to export {}: {}", table, e))?;
+
+        let out = export
+            .table_mut(table)
+            .ok_or_else(|| format!("No export slot for {}", table))?;
+        for row in rows {
+            out.push(row.map_err(|e| format!("Row error: {}", e))?);
+        }
+    }
+
+    serde_json::to_value(&export).map_err(|e| format!("Failed to serialize export: {}", e))
+}
+
+/// Replace the exported tables with the contents of an `export_all` dump, in
+/// one transaction. Unknown columns are ignored; missing ones become NULL
+/// (or the column default). The previous data is kept for undo.
+pub fn import_all(json: serde_json::Value) -> Result<(), String> {
+    let export: IntelligenceExport = serde_json::from_value(json)
+        .map_err(|e| format!("Invalid intelligence export: {}", e))?;
+    if export.version > EXPORT_FORMAT_VERSION {
+        return Err(format!(
+            "Export format {} is newer than this version of Nyx supports ({})",
+            export.version, EXPORT_FORMAT_VERSION
+        ));
+    }
+
+    OBSERVER_PAUSED.store(true, Ordering::SeqCst);
+    let result = (|| {
+        let mut conn = open_db()?;
+        snapshot_for_undo(&conn)?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start import: {}", e))?;
+
+        for (table, columns) in EXPORT_TABLES {
+            tx.execute(&format!("DELETE FROM {}", table), [])
+                .map_err(|e| format!("Failed to clear {}: {}", table, e))?;
+
+            for row in export.table(table) {
+                let present: Vec<&str> = columns
+                    .iter()
+                    .copied()
+                    .filter(|c| row.contains_key(*c))
+                    .collect();
+                if present.is_empty() {
+                    continue;
+                }
+                let placeholders: Vec<String> =
+                    (1..=present.len()).map(|i| format!("?{}", i)).collect();
+                let sql = format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table,
+                    present.join(", "),
+                    placeholders.join(", ")
+                );
+                let values: Vec<rusqlite::types::Value> =
+                    present.iter().map(|c| json_to_sql(&row[*c])).collect();
+                tx.execute(&sql, rusqlite::params_from_iter(values))
+                    .map_err(|e| format!("Failed to import into {}: {}", table, e))?;
+            }
+        }
+
+        tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+        finish_undo_snapshot(&conn);
+        Ok(())
+    })();
+    OBSERVER_PAUSED.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Write `export_all` to `dest` as pretty-printed JSON (0600 on Unix).
+pub fn export_to_file(dest: &str) -> Result<(), String> {
+    let export = export_all()?;
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize export: {}", e))?;
+    write_private(Path::new(dest), json.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", dest, e))
+}
+
+/// Write `contents` to `path` as a 0600 file. An existing file is truncated
+/// and tightened to 0600 before anything is written to it.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Read a JSON export from `src` and import it.
+pub fn import_from_file(src: &str) -> Result<(), String> {
+    let content = std::fs::read_to_string(src)
+        .map_err(|e| format!("Failed to read {}: {}", src, e))?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", src, e))?;
+    import_all(json)
+}
+
 // ---------------------------------------------------------------------------
 // Background observer
 // ---------------------------------------------------------------------------
 
-pub fn start_observer(app: AppHandle) {
+/// Set while a restore is swapping the database; observer ticks are skipped.
+static OBSERVER_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Run the observe/suggest loop in the background. `emit(event, payload)`
+/// forwards `intelligence:*` events to the UI.
+pub fn start_observer<F>(emit: F)
+where
+    F: Fn(&str, serde_json::Value) + Send + 'static,
+{
     tokio::spawn(async move {
         // Wait a few seconds for app to finish initialising
         tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
@@ -1912,20 +3159,25 @@ pub fn start_observer(app: AppHandle) {
             return;
         }
 
-        let mut calendar_interval =
-            tokio::time::interval(tokio::time::Duration::from_secs(15 * 60));
-        let mut email_interval =
-            tokio::time::interval(tokio::time::Duration::from_secs(30 * 60));
-        let mut messaging_interval =
-            tokio::time::interval(tokio::time::Duration::from_secs(20 * 60));
-        let mut suggestion_interval =
-            tokio::time::interval(tokio::time::Duration::from_secs(60 * 60));
+        // Intervals are fixed for the observer's lifetime; changes apply on restart
+        let intervals = config::read_current_config()
+            .map(|c| c.capabilities.intelligence_intervals)
+            .unwrap_or_default()
+            .clamped();
+        let every = |mins: u64| tokio::time::interval(tokio::time::Duration::from_secs(mins * 60));
+
+        let mut calendar_interval = every(intervals.calendar_mins);
+        let mut email_interval = every(intervals.email_mins);
+        let mut messaging_interval = every(intervals.messaging_mins);
+        let mut suggestion_interval = every(intervals.suggestion_mins);
+        let mut prune_interval = every(24 * 60);
 
         // Tick once immediately to skip the first instant tick
         calendar_interval.tick().await;
         email_interval.tick().await;
         messaging_interval.tick().await;
         suggestion_interval.tick().await;
+        prune_interval.tick().await;
 
         loop {
             tokio::select! {
@@ -1937,7 +3189,7 @@ pub fn start_observer(app: AppHandle) {
                     match observe_calendar() {
                         Ok(count) => {
                             if count > 0 {
-                                let _ = app.emit("intelligence:update", serde_json::json!({
+                                emit("intelligence:update", serde_json::json!({
                                     "source": "calendar",
                                     "count": count,
                                 }));
@@ -1953,7 +3205,7 @@ pub fn start_observer(app: AppHandle) {
                     match observe_email() {
                         Ok(count) => {
                             if count > 0 {
-                                let _ = app.emit("intelligence:update", serde_json::json!({
+                                emit("intelligence:update", serde_json::json!({
                                     "source": "email",
                                     "count": count,
                                 }));
@@ -1970,7 +3222,7 @@ pub fn start_observer(app: AppHandle) {
                     match observe_messaging(4) {
                         Ok(count) => {
                             if count > 0 {
-                                let _ = app.emit("intelligence:update", serde_json::json!({
+                                emit("intelligence:update", serde_json::json!({
                                     "source": "messaging",
                                     "count": count,
                                 }));
@@ -1986,7 +3238,7 @@ pub fn start_observer(app: AppHandle) {
                     match generate_suggestions() {
                         Ok(count) => {
                             if count > 0 {
-                                let _ = app.emit("intelligence:suggestions", serde_json::json!({
+                                emit("intelligence:suggestions", serde_json::json!({
                                     "new_count": count,
                                 }));
                             }
@@ -1994,15 +3246,35 @@ pub fn start_observer(app: AppHandle) {
                         Err(e) => eprintln!("[intelligence] Suggestion generation failed: {}", e),
                     }
                 }
+                _ = prune_interval.tick() => {
+                    if !is_intelligence_enabled() {
+                        continue;
+                    }
+                    match prune_contacts(PRUNE_STALE_DAYS, PRUNE_MIN_INTERACTIONS) {
+                        Ok(report) => {
+                            if report.contacts > 0 {
+                                emit("intelligence:update", serde_json::json!({
+                                    "source": "prune",
+                                    "count": report.contacts,
+                                }));
+                            }
+                        }
+                        Err(e) => eprintln!("[intelligence] Contact pruning failed: {}", e),
+                    }
+                }
             }
         }
     });
 }
 
-/// Check if the activity_intelligence capability is enabled.
+/// Check if the activity_intelligence capability is enabled (and no restore
+/// is in progress).
 fn is_intelligence_enabled() -> bool {
-    // Read from the settings config (config is in nyx_lib)
-    match nyx_lib::config::read_current_config() {
+    if OBSERVER_PAUSED.load(Ordering::SeqCst) {
+        return false;
+    }
+    // Read from the settings config
+    match config::read_current_config() {
         Ok(settings) => settings.capabilities.activity_intelligence,
         Err(_) => false,
     }
@@ -2023,3 +3295,336 @@ fn truncate(s: &str, max: usize) -> &str {
         &s[..end]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_email(conn: &Connection, message_id: &str, account: Option<&str>) -> usize {
+        conn.execute(
+            "INSERT OR IGNORE INTO email_observations
+             (thread_id, message_id, from_email, timestamp, observed_at, account)
+             VALUES ('t1', ?1, 'a@example.com', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z', ?2)",
+            params![message_id, account],
+        )
+        .unwrap()
+    }
+
+    fn email_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM email_observations", [], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn same_message_is_kept_once_per_account() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+
+        assert_eq!(insert_email(&conn, "m1", Some("me@work.com")), 1);
+        assert_eq!(insert_email(&conn, "m1", Some("me@home.com")), 1);
+        assert_eq!(insert_email(&conn, "m1", Some("me@work.com")), 0);
+        assert_eq!(insert_email(&conn, "m2", None), 1);
+        assert_eq!(insert_email(&conn, "m2", None), 0);
+        assert_eq!(email_count(&conn), 3);
+    }
+
+    #[test]
+    fn duplicate_columns_only_tolerated_when_unversioned() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+
+        // Pre-migration databases already have the cadence columns
+        conn.execute_batch("PRAGMA user_version = 0").unwrap();
+        init_schema(&conn).unwrap();
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+
+        // A versioned database should never have a column its version lacks
+        conn.execute_batch("PRAGMA user_version = 2").unwrap();
+        let err = init_schema(&conn).unwrap_err();
+        assert!(err.contains("Schema migration 3"), "{}", err);
+    }
+
+    #[test]
+    fn migration_drops_message_id_uniqueness() {
+        let conn = Connection::open_in_memory().unwrap();
+        // email_observations as it was at schema version 6
+        conn.execute_batch(
+            "CREATE TABLE email_observations (
+                id INTEGER PRIMARY KEY,
+                thread_id TEXT NOT NULL,
+                message_id TEXT UNIQUE,
+                from_email TEXT NOT NULL,
+                to_emails TEXT,
+                subject TEXT,
+                timestamp TEXT NOT NULL,
+                is_inbound INTEGER,
+                replied INTEGER DEFAULT 0,
+                reply_time_mins REAL,
+                labels TEXT,
+                observed_at TEXT NOT NULL,
+                account TEXT
+            );
+            PRAGMA user_version = 6;",
+        )
+        .unwrap();
+        insert_email(&conn, "m1", Some("me@work.com"));
+
+        init_schema(&conn).unwrap();
+
+        assert_eq!(email_count(&conn), 1);
+        assert_eq!(insert_email(&conn, "m1", Some("me@home.com")), 1);
+        assert_eq!(insert_email(&conn, "m1", Some("me@work.com")), 0);
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    /// Create email_observations as it was at schema version 6 and mark the
+    /// database as that version.
+    fn create_version_6_emails(conn: &Connection) {
+        conn.execute_batch(
+            "CREATE TABLE email_observations (
+                id INTEGER PRIMARY KEY,
+                thread_id TEXT NOT NULL,
+                message_id TEXT UNIQUE,
+                from_email TEXT NOT NULL,
+                to_emails TEXT,
+                subject TEXT,
+                timestamp TEXT NOT NULL,
+                is_inbound INTEGER,
+                replied INTEGER DEFAULT 0,
+                reply_time_mins REAL,
+                labels TEXT,
+                observed_at TEXT NOT NULL,
+                account TEXT
+            );
+            CREATE INDEX idx_email_timestamp ON email_observations(timestamp);
+            CREATE INDEX idx_email_account ON email_observations(account);
+            PRAGMA user_version = 6;",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn version_6_database_on_disk_upgrades_with_its_rows() {
+        let path = std::env::temp_dir().join(format!("nyx-intel-{}.db", uuid::Uuid::new_v4()));
+        {
+            let conn = Connection::open(&path).unwrap();
+            create_version_6_emails(&conn);
+            conn.execute(
+                "INSERT INTO email_observations
+                 (thread_id, message_id, from_email, to_emails, subject, timestamp, is_inbound,
+                  replied, reply_time_mins, labels, observed_at, account)
+                 VALUES ('t9', 'm9', 'b@example.com', 'me@work.com', 'Lunch?',
+                         '2026-02-01T10:00:00Z', 1, 1, 42.5, 'INBOX', '2026-02-01T11:00:00Z', 'me@work.com')",
+                [],
+            )
+            .unwrap();
+            insert_email(&conn, "m1", Some("me@work.com"));
+            insert_email(&conn, "m2", None);
+        }
+
+        let conn = Connection::open(&path).unwrap();
+        init_schema(&conn).unwrap();
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+        assert_eq!(email_count(&conn), 3);
+
+        let row: (String, String, i64, f64, String, String) = conn
+            .query_row(
+                "SELECT thread_id, subject, replied, reply_time_mins, labels, account
+                 FROM email_observations WHERE message_id = 'm9'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            row,
+            (
+                "t9".to_string(),
+                "Lunch?".to_string(),
+                1,
+                42.5,
+                "INBOX".to_string(),
+                "me@work.com".to_string()
+            )
+        );
+        assert_eq!(insert_email(&conn, "m1", Some("me@home.com")), 1);
+
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn failed_migration_rolls_back_to_the_previous_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_version_6_emails(&conn);
+        run_migrations(&conn).unwrap();
+
+        // Duplicates the version 8 unique index would reject
+        conn.execute_batch(
+            "DROP INDEX idx_email_message_account;
+             PRAGMA user_version = 7;",
+        )
+        .unwrap();
+        insert_email(&conn, "m1", Some("me@work.com"));
+        conn.execute(
+            "INSERT INTO email_observations (thread_id, message_id, from_email, timestamp, observed_at, account)
+             VALUES ('t2', 'm1', 'a@example.com', '2026-01-02T00:00:00Z', '2026-01-02T00:00:00Z', 'me@work.com')",
+            [],
+        )
+        .unwrap();
+
+        let err = run_migrations(&conn).unwrap_err();
+        assert!(err.contains("Schema migration 8"), "{}", err);
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(version, 7);
+        assert!(conn.is_autocommit());
+        assert_eq!(email_count(&conn), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_private_tightens_an_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("nyx-export-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "old contents that are longer").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"{}").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn insert_contact(conn: &Connection, email: &str, count: i64, seen: &str) {
+        conn.execute(
+            "INSERT INTO contacts (email, first_seen, last_seen, interaction_count, created_at, updated_at)
+             VALUES (?1, ?2, ?2, ?3, ?2, ?2)",
+            params![email, seen, count],
+        )
+        .unwrap();
+    }
+
+    fn contact_count(conn: &Connection, email: &str) -> Option<i64> {
+        conn.query_row(
+            "SELECT interaction_count FROM contacts WHERE email = ?1",
+            params![email],
+            |r| r.get(0),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    fn temp_snapshot() -> PathBuf {
+        std::env::temp_dir().join(format!("nyx-intel-undo-{}.db", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn undo_after_clear_keeps_later_observations() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        insert_contact(&conn, "alice@example.com", 10, "2026-01-01T00:00:00Z");
+        insert_contact(&conn, "bob@example.com", 3, "2026-01-02T00:00:00Z");
+        insert_email(&conn, "m1", None);
+
+        let snapshot = temp_snapshot();
+        snapshot_to(&conn, &snapshot).unwrap();
+        clear_tables(&conn).unwrap();
+        record_undo_baseline(&conn, &snapshot).unwrap();
+
+        // The observer keeps running after the clear
+        insert_contact(&conn, "alice@example.com", 1, "2026-02-01T00:00:00Z");
+        insert_contact(&conn, "carol@example.com", 1, "2026-02-01T00:00:00Z");
+        insert_email(&conn, "m1", None);
+        insert_email(&conn, "m2", None);
+
+        merge_snapshot(&conn, &snapshot).unwrap();
+        std::fs::remove_file(&snapshot).unwrap();
+
+        assert_eq!(contact_count(&conn, "alice@example.com"), Some(11));
+        assert_eq!(contact_count(&conn, "bob@example.com"), Some(3));
+        assert_eq!(contact_count(&conn, "carol@example.com"), Some(1));
+        assert_eq!(email_count(&conn), 2);
+    }
+
+    #[test]
+    fn undo_reverts_contact_merge() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        insert_contact(&conn, "alice@example.com", 10, "2026-01-01T00:00:00Z");
+        insert_contact(&conn, "alice@work.com", 4, "2026-01-03T00:00:00Z");
+        conn.execute(
+            "INSERT INTO suggestions (type, title, description, contact_email, created_at)
+             VALUES ('catch_up', 'Catch up', 'd', 'alice@work.com', '2026-01-03T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        let snapshot = temp_snapshot();
+        snapshot_to(&conn, &snapshot).unwrap();
+        merge_contact_rows(&conn, "alice@example.com", "alice@work.com").unwrap();
+        record_undo_baseline(&conn, &snapshot).unwrap();
+        assert_eq!(contact_count(&conn, "alice@example.com"), Some(14));
+        assert_eq!(contact_count(&conn, "alice@work.com"), None);
+
+        // One more interaction after the merge
+        conn.execute(
+            "UPDATE contacts SET interaction_count = interaction_count + 1 WHERE email = 'alice@example.com'",
+            [],
+        )
+        .unwrap();
+
+        merge_snapshot(&conn, &snapshot).unwrap();
+        std::fs::remove_file(&snapshot).unwrap();
+
+        assert_eq!(contact_count(&conn, "alice@example.com"), Some(11));
+        assert_eq!(contact_count(&conn, "alice@work.com"), Some(4));
+        let owner: String = conn
+            .query_row("SELECT contact_email FROM suggestions", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(owner, "alice@work.com");
+    }
+
+    #[test]
+    fn undo_without_baseline_is_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        insert_contact(&conn, "alice@example.com", 10, "2026-01-01T00:00:00Z");
+
+        let snapshot = temp_snapshot();
+        snapshot_to(&conn, &snapshot).unwrap();
+        let err = merge_snapshot(&conn, &snapshot).unwrap_err();
+        std::fs::remove_file(&snapshot).unwrap();
+        assert_eq!(err, "Nothing to undo");
+        assert_eq!(contact_count(&conn, "alice@example.com"), Some(10));
+    }
+
+    #[test]
+    fn backup_validation_checks_schema_and_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        assert!(validate_backup_schema(&conn).is_ok());
+
+        conn.execute_batch(&format!("PRAGMA user_version = {}", MIGRATIONS.len() + 1))
+            .unwrap();
+        let err = validate_backup_schema(&conn).unwrap_err();
+        assert!(err.contains("newer than this version"), "{}", err);
+
+        let other = Connection::open_in_memory().unwrap();
+        other
+            .execute_batch("CREATE TABLE contacts (id INTEGER PRIMARY KEY, email TEXT);")
+            .unwrap();
+        let err = validate_backup_schema(&other).unwrap_err();
+        assert!(err.contains("not a compatible intelligence database"), "{}", err);
+    }
+}
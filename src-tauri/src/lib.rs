@@ -2,9 +2,16 @@
 // nyx_lib — shared modules used by both the Tauri GUI and the MCP server
 // ---------------------------------------------------------------------------
 
+pub mod audit;
+pub mod clawdtalk;
 pub mod config;
 pub mod docker;
 pub mod gateway;
+pub mod gog;
+pub mod health;
+pub mod intelligence;
+pub mod messaging;
+pub mod ollama;
 pub mod oneclick;
 pub mod wallet;
 
@@ -12,5 +19,9 @@ pub mod wallet;
 // The Tauri binary adds the file-watcher on top of these.
 pub mod portfolio_data;
 
+// PTY output buffer + on-disk snapshots. The Tauri binary owns the sessions;
+// the MCP server reads the snapshots.
+pub mod pty_output;
+
 // MCP server implementation
 pub mod mcp;
@@ -2,23 +2,28 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 // Shared modules from nyx_lib (used by both Tauri GUI and MCP server)
+use nyx_lib::audit;
+use nyx_lib::clawdtalk;
 use nyx_lib::config;
 use nyx_lib::docker;
 use nyx_lib::gateway;
+use nyx_lib::health;
+use nyx_lib::intelligence;
+use nyx_lib::messaging;
+use nyx_lib::ollama;
 use nyx_lib::oneclick;
 use nyx_lib::wallet;
 
 // Tauri-only modules (UI-specific or have Tauri dependencies)
 mod browser;
-mod clawdtalk;
 mod claudecode;
 mod google;
-mod intelligence;
-mod ollama;
 mod portfolio;
 mod pty;
 mod setup;
 
+use tauri::Emitter;
+
 // ---------------------------------------------------------------------------
 // Docker commands
 // ---------------------------------------------------------------------------
@@ -41,7 +46,7 @@ async fn install_docker() -> Result<String, String> {
 /// Pre-pull the OpenClaw Docker image in the background.
 #[tauri::command]
 async fn docker_prepull() -> Result<(), String> {
-    docker::pull_image("ghcr.io/openclaw/openclaw:2026.2.21").await
+    docker::pull_image(&config::openclaw_image()).await
 }
 
 // ---------------------------------------------------------------------------
@@ -59,11 +64,12 @@ async fn run_setup(
     anthropic_key: String,
     openai_key: Option<String>,
     telegram_token: Option<String>,
-) -> Result<String, String> {
+) -> Result<setup::SetupResult, String> {
     setup::run_setup(app_handle, anthropic_key, openai_key, telegram_token).await
 }
 
-/// Extended setup command that accepts the full v2 configuration.
+/// Extended setup command that accepts the full v2 configuration. On an
+/// existing install it merges rather than overwrites unless `force` is set.
 #[tauri::command]
 async fn run_setup_v2(
     app_handle: tauri::AppHandle,
@@ -84,20 +90,9 @@ async fn run_setup_v2(
     google_authenticated: bool,
     email_notifications: Option<config::EmailNotificationsConfig>,
     capabilities: Option<config::CapabilitiesConfig>,
-) -> Result<String, String> {
-    // Resolve guardrails from preset name or custom config
-    let guardrails = match guardrails_custom {
-        Some(custom) => custom,
-        None => {
-            let preset = match guardrails_preset.as_str() {
-                "conservative" => config::SecurityPreset::Conservative,
-                "autonomous" => config::SecurityPreset::Autonomous,
-                _ => config::SecurityPreset::Balanced,
-            };
-            config::GuardrailsConfig::from_preset(preset)
-        }
-    };
-
+    force: Option<bool>,
+) -> Result<setup::SetupV2Result, String> {
+    let guardrails = resolve_guardrails(&guardrails_preset, guardrails_custom);
     let email_config = email_notifications.unwrap_or_default();
     let caps = capabilities.unwrap_or_default();
     let name = agent_name.unwrap_or_else(|| "Nyx".to_string());
@@ -120,10 +115,76 @@ async fn run_setup_v2(
         google_authenticated,
         email_config,
         caps,
+        force.unwrap_or(false),
     )
     .await
 }
 
+/// Dry-run validation of the v2 setup inputs. Writes nothing; returns a list
+/// of problems for the wizard to show (empty = ready to run setup).
+#[tauri::command]
+fn validate_setup_v2(
+    agent_name: Option<String>,
+    anthropic_key: String,
+    openai_key: Option<String>,
+    venice_key: Option<String>,
+    nearai_key: Option<String>,
+    perplexity_key: Option<String>,
+    telegram_token: Option<String>,
+    slack_token: Option<String>,
+    whatsapp_phone: Option<String>,
+    wallets: Vec<config::WalletConfig>,
+    active_wallet_id: Option<String>,
+    guardrails_preset: String,
+    guardrails_custom: Option<config::GuardrailsConfig>,
+    messaging: config::MessagingConfig,
+    google_authenticated: bool,
+    email_notifications: Option<config::EmailNotificationsConfig>,
+    capabilities: Option<config::CapabilitiesConfig>,
+) -> Result<Vec<String>, String> {
+    let guardrails = resolve_guardrails(&guardrails_preset, guardrails_custom);
+    let email_config = email_notifications.unwrap_or_default();
+    let caps = capabilities.unwrap_or_default();
+    let name = agent_name.unwrap_or_else(|| "Nyx".to_string());
+
+    Ok(setup::validate_setup_v2(
+        &name,
+        &anthropic_key,
+        openai_key.as_deref(),
+        venice_key.as_deref(),
+        nearai_key.as_deref(),
+        perplexity_key.as_deref(),
+        telegram_token.as_deref(),
+        slack_token.as_deref(),
+        whatsapp_phone.as_deref(),
+        &wallets,
+        active_wallet_id.as_deref(),
+        &guardrails,
+        &messaging,
+        google_authenticated,
+        &email_config,
+        &caps,
+    ))
+}
+
+/// Resolve guardrails from a preset name or a custom config.
+fn resolve_guardrails(
+    preset: &str,
+    custom: Option<config::GuardrailsConfig>,
+) -> config::GuardrailsConfig {
+    match custom {
+        Some(custom) => custom,
+        None => {
+            let preset = match preset {
+                "conservative" => config::SecurityPreset::Conservative,
+                "autonomous" => config::SecurityPreset::Autonomous,
+                _ => config::SecurityPreset::Balanced,
+            };
+            config::GuardrailsConfig::from_preset(preset)
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Wallet commands
 // ---------------------------------------------------------------------------
@@ -140,6 +201,16 @@ async fn generate_near_wallet_full() -> Result<(wallet::WalletInfo, config::Wall
     wallet::generate_near_wallet().await
 }
 
+/// Re-derive a NEAR wallet from its recovery phrase and save its key.
+/// `index` selects the account (default 0).
+#[tauri::command]
+fn recover_near_wallet(
+    mnemonic: String,
+    index: Option<u32>,
+) -> Result<(wallet::WalletInfo, config::WalletConfig), String> {
+    wallet::recover_near_wallet(&mnemonic, index.unwrap_or(0))
+}
+
 /// Validate a wallet address for a given chain.
 #[tauri::command]
 fn validate_wallet_address(chain: config::Chain, address: String) -> Result<(), String> {
@@ -156,6 +227,107 @@ fn import_wallet(
     wallet::import_wallet(chain, address, label)
 }
 
+/// Key files on disk with no matching configured wallet.
+#[tauri::command]
+fn list_orphaned_wallet_keys() -> Result<Vec<String>, String> {
+    wallet::list_orphaned_wallet_keys()
+}
+
+/// Securely delete orphaned key files. Returns the removed ids.
+#[tauri::command]
+fn prune_orphaned_wallet_keys(confirm: bool) -> Result<Vec<String>, String> {
+    wallet::prune_orphaned_wallet_keys(confirm)
+}
+
+/// Sign a message with a wallet's key to prove ownership.
+#[tauri::command]
+fn sign_wallet_message(
+    wallet_id: String,
+    message: String,
+    passphrase: Option<String>,
+) -> Result<wallet::SignedMessage, String> {
+    wallet::sign_message(&wallet_id, &message, passphrase.as_deref())
+}
+
+#[tauri::command]
+fn verify_wallet_signature(
+    public_key: String,
+    message: String,
+    signature: String,
+) -> Result<bool, String> {
+    wallet::verify_signature(&public_key, &message, &signature)
+}
+
+/// On-chain native balance of an address (NEAR and ETH).
+#[tauri::command]
+async fn get_wallet_balance(chain: config::Chain, address: String) -> Result<wallet::Balance, String> {
+    wallet::get_balance(&chain, &address).await
+}
+
+/// Override the RPC endpoint used for a chain's balance lookups. Empty or
+/// None restores the default.
+#[tauri::command]
+fn set_rpc_endpoint(chain: config::Chain, url: Option<String>) -> Result<Option<String>, String> {
+    config::set_rpc_endpoint(&chain, url)
+}
+
+/// Current outbound proxy (None = direct connections).
+#[tauri::command]
+fn get_proxy() -> Result<Option<config::ProxyConfig>, String> {
+    config::get_proxy()
+}
+
+/// Set the outbound proxy (e.g. socks5h://127.0.0.1:9050 for Tor). None or an
+/// empty URL restores direct connections.
+#[tauri::command]
+fn set_proxy(proxy: Option<config::ProxyConfig>) -> Result<Option<config::ProxyConfig>, String> {
+    config::set_proxy(proxy)
+}
+
+/// Current retry policy for gateway sends.
+#[tauri::command]
+fn get_gateway_retry() -> config::GatewayRetryConfig {
+    config::get_gateway_retry()
+}
+
+/// Override the gateway retry policy. None restores the defaults.
+#[tauri::command]
+fn set_gateway_retry(retry: Option<config::GatewayRetryConfig>) -> Result<config::GatewayRetryConfig, String> {
+    config::set_gateway_retry(retry)
+}
+
+/// Whether a wallet's key file is passphrase-encrypted (None = no key file).
+#[tauri::command]
+fn is_wallet_key_encrypted(wallet_id: String) -> Result<Option<bool>, String> {
+    wallet::is_wallet_key_encrypted(&wallet_id)
+}
+
+/// Check a wallet passphrase. Never rewrites the key file; plaintext keys are
+/// encrypted only through `encrypt_wallet_key_file`. The key material itself
+/// never leaves the backend.
+#[tauri::command]
+fn unlock_wallet_key(wallet_id: String, passphrase: String) -> Result<(), String> {
+    if wallet::is_wallet_key_encrypted(&wallet_id)? == Some(false) {
+        return Err(format!(
+            "Wallet key {} is not encrypted; set a passphrase first",
+            wallet_id
+        ));
+    }
+    wallet::load_wallet_key(&wallet_id, Some(&passphrase))?
+        .map(|_| ())
+        .ok_or_else(|| format!("No key file for wallet {}", wallet_id))
+}
+
+/// Encrypt a plaintext wallet key file. The passphrase is entered twice.
+#[tauri::command]
+fn encrypt_wallet_key_file(
+    wallet_id: String,
+    passphrase: String,
+    passphrase_confirm: String,
+) -> Result<(), String> {
+    wallet::encrypt_wallet_key_file(&wallet_id, &passphrase, &passphrase_confirm)
+}
+
 // ---------------------------------------------------------------------------
 // Security preset commands
 // ---------------------------------------------------------------------------
@@ -206,13 +378,137 @@ async fn get_portfolio() -> Result<portfolio::PortfolioData, String> {
 }
 
 #[tauri::command]
-async fn send_chat_message(message: String) -> Result<String, String> {
-    gateway::send_message(message).await
+fn pause_portfolio_watcher() -> Result<(), String> {
+    portfolio::pause_watcher()
+}
+
+#[tauri::command]
+async fn resume_portfolio_watcher() -> Result<(), String> {
+    portfolio::resume_watcher().await
+}
+
+#[tauri::command]
+fn portfolio_watcher_status() -> Result<portfolio::WatcherStatus, String> {
+    portfolio::watcher_status()
 }
 
 #[tauri::command]
-async fn send_chat_message_to_session(message: String, session_key: String) -> Result<String, String> {
-    gateway::send_message_to_session(message, session_key).await
+async fn send_chat_message(app: tauri::AppHandle, message: String) -> Result<String, String> {
+    send_chat_message_to_session(app, message, "agent:default:main".to_string()).await
+}
+
+/// Send a chat message and emit a `chat:usage` event with the token deltas.
+#[tauri::command]
+async fn send_chat_message_to_session(
+    app: tauri::AppHandle,
+    message: String,
+    session_key: String,
+) -> Result<String, String> {
+    let reply = gateway::send_message_with_usage(message, session_key.clone()).await?;
+    emit_reply_usage(&app, &session_key, &reply).await;
+    Ok(reply.text)
+}
+
+/// Stream a chat reply: tokens are sent on `on_token` as they arrive and the
+/// full text is returned at the end. Emits the same events as
+/// `send_chat_message_to_session`.
+#[tauri::command]
+async fn send_chat_message_stream(
+    app: tauri::AppHandle,
+    message: String,
+    session_key: String,
+    on_token: tauri::ipc::Channel<String>,
+) -> Result<String, String> {
+    let reply = gateway::send_message_stream(message, session_key.clone(), |token| {
+        let _ = on_token.send(token.to_string());
+    })
+    .await?;
+    emit_reply_usage(&app, &session_key, &reply).await;
+    Ok(reply.text)
+}
+
+/// Emit `chat:usage` and, if the session is near its budget, `chat:context-budget`.
+async fn emit_reply_usage(app: &tauri::AppHandle, session_key: &str, reply: &gateway::ChatReply) {
+    if let Some(ref usage) = reply.usage {
+        let _ = app.emit(
+            "chat:usage",
+            serde_json::json!({
+                "sessionKey": session_key,
+                "usage": usage,
+            }),
+        );
+    }
+    match gateway::check_session_budget(session_key, reply.usage.as_ref()).await {
+        Ok(Some(status)) => {
+            let _ = app.emit("chat:context-budget", &status);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Context budget check failed: {}", e),
+    }
+}
+
+/// Set (or clear with null) a session's context token budget.
+#[tauri::command]
+fn set_session_context_budget(
+    session_key: String,
+    max_context_tokens: Option<u64>,
+    auto_continue: bool,
+) -> Result<(), String> {
+    gateway::set_session_budget(session_key, max_context_tokens, auto_continue)
+}
+
+/// Estimate LLM spend over the last `range_days` from session token counts.
+#[tauri::command]
+fn estimate_spend(range_days: u32) -> Result<gateway::SpendEstimate, String> {
+    gateway::estimate_spend(range_days)
+}
+
+#[tauri::command]
+fn get_model_prices() -> std::collections::HashMap<String, config::ModelPrice> {
+    config::get_model_prices()
+}
+
+/// Replace the spend-estimate price table. Pass null to restore defaults.
+#[tauri::command]
+fn set_model_prices(
+    prices: Option<std::collections::HashMap<String, config::ModelPrice>>,
+) -> Result<(), String> {
+    config::set_model_prices(prices)
+}
+
+// ---------------------------------------------------------------------------
+// Audit log
+// ---------------------------------------------------------------------------
+
+/// Most recent audit entries, newest first (default 100).
+#[tauri::command]
+fn get_audit_log(limit: Option<usize>) -> Result<Vec<audit::AuditEntry>, String> {
+    audit::read_audit(limit.unwrap_or(100))
+}
+
+/// Check the audit log's hash chain for tampering.
+#[tauri::command]
+fn verify_audit_log() -> Result<audit::AuditVerification, String> {
+    audit::verify_audit()
+}
+
+// ---------------------------------------------------------------------------
+// Pending messages (send-with-confirm)
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+fn list_pending_messages() -> Vec<messaging::PendingMessage> {
+    messaging::list_pending_messages()
+}
+
+#[tauri::command]
+async fn approve_message(id: String) -> Result<String, String> {
+    messaging::approve_message(&id).await
+}
+
+#[tauri::command]
+fn reject_message(id: String) -> Result<(), String> {
+    messaging::reject_message(&id)
 }
 
 // ---------------------------------------------------------------------------
@@ -234,6 +530,24 @@ fn rename_chat_session(session_key: String, title: String) -> Result<(), String>
     gateway::rename_session(session_key, title)
 }
 
+/// Delete a chat session. The gateway transcript is removed too unless
+/// `keep_transcript` is set. Refused while the agent is running.
+#[tauri::command]
+async fn delete_chat_session(session_key: String, keep_transcript: Option<bool>) -> Result<(), String> {
+    gateway::delete_session(session_key, !keep_transcript.unwrap_or(false)).await
+}
+
+/// Export a session as Markdown, writing it to `path` when given.
+#[tauri::command]
+fn export_chat_session(session_key: String, path: Option<String>) -> Result<String, String> {
+    let markdown = gateway::export_session(session_key)?;
+    if let Some(path) = path {
+        std::fs::write(&path, &markdown)
+            .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(markdown)
+}
+
 #[tauri::command]
 fn move_session_to_folder(session_key: String, folder_id: Option<String>) -> Result<(), String> {
     gateway::move_session_to_folder(session_key, folder_id)
@@ -260,8 +574,18 @@ fn delete_chat_folder(folder_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn verify_source(url: String) -> Result<String, String> {
-    gateway::verify_source(url).await
+fn reorder_chat_folders(ordered_ids: Vec<String>) -> Result<(), String> {
+    gateway::reorder_folders(ordered_ids)
+}
+
+#[tauri::command]
+async fn verify_source(
+    url: String,
+    fetch_timeout_secs: Option<u64>,
+    max_content_kb: Option<u32>,
+) -> Result<String, String> {
+    let opts = gateway::VerifyOptions { fetch_timeout_secs, max_content_kb };
+    gateway::verify_source(url, opts).await
 }
 
 // ---------------------------------------------------------------------------
@@ -281,7 +605,10 @@ async fn get_cross_chain_quote(
     recipient: String,
     refund_to: String,
     dry_run: Option<bool>,
+    swap_type: Option<oneclick::SwapType>,
+    slippage_bps: Option<u32>,
 ) -> Result<oneclick::QuoteResponse, String> {
+    let overrides = oneclick::QuoteOverrides { slippage_bps, deadline_mins: None, swap_type };
     oneclick::get_quote(
         &asset_in,
         &asset_out,
@@ -289,6 +616,7 @@ async fn get_cross_chain_quote(
         &recipient,
         &refund_to,
         dry_run.unwrap_or(true),
+        overrides,
     )
     .await
 }
@@ -298,59 +626,162 @@ async fn get_swap_status(swap_id: String) -> Result<oneclick::SwapStatus, String
     oneclick::get_status(&swap_id).await
 }
 
+/// Poll a swap until it finishes, emitting `swap:progress` on each change.
+#[tauri::command]
+async fn watch_swap_status(
+    app: tauri::AppHandle,
+    swap_id: String,
+    timeout_secs: Option<u64>,
+) -> Result<oneclick::SwapStatus, String> {
+    let timeout = timeout_secs.unwrap_or(oneclick::DEFAULT_POLL_TIMEOUT_SECS);
+    oneclick::poll_until_complete(&swap_id, timeout, |status| {
+        let _ = app.emit("swap:progress", status);
+    })
+    .await
+}
+
+/// Executed shield/unshield swaps, newest first.
+#[tauri::command]
+fn list_swaps(limit: Option<u32>) -> Result<Vec<oneclick::SwapRecord>, String> {
+    oneclick::list_swaps(limit.unwrap_or(50))
+}
+
+/// Agent swap quotes awaiting the user's confirmation phrase.
+#[tauri::command]
+fn list_pending_swaps() -> Result<Vec<oneclick::PendingQuote>, String> {
+    oneclick::list_pending_quotes()
+}
+
+/// Reject a pending agent swap so it can't be executed.
 #[tauri::command]
-fn resolve_asset_id(chain: String, symbol: String) -> Result<String, String> {
-    oneclick::resolve_asset_id(&chain, &symbol)
+fn dismiss_pending_swap(correlation_id: String) -> Result<(), String> {
+    oneclick::dismiss_pending_quote(&correlation_id)
+}
+
+/// Resolve a chain+symbol pair, refreshing the token list first if it's stale.
+#[tauri::command]
+async fn resolve_asset_id(chain: String, symbol: String) -> Result<String, String> {
+    let _ = oneclick::get_tokens().await;
+    oneclick::resolve_asset_id(&chain, &symbol).await
 }
 
 /// Get a quote to shield assets into ZEC (any supported asset → ZEC).
+/// `amount` is a decimal amount ("1.5"), converted to base units here.
 #[tauri::command]
 async fn get_zec_shield_quote(
     from_asset: String,
     amount: String,
+    slippage_bps: Option<u32>,
+    deadline_mins: Option<u32>,
+    swap_type: Option<oneclick::SwapType>,
 ) -> Result<oneclick::QuoteResponse, String> {
     let zec_address = config::get_zec_address()
         .ok_or_else(|| "No ZEC address configured. Add a ZEC wallet in Settings.".to_string())?;
     let refund_to = config::get_near_account()
         .unwrap_or_else(|| "nyx.near".to_string());
-    oneclick::get_zec_quote(&from_asset, &amount, &zec_address, &refund_to).await
+    let overrides = oneclick::QuoteOverrides { slippage_bps, deadline_mins, swap_type };
+    let amount = oneclick::swap_amount_to_base_units(
+        &from_asset,
+        &config::get_zec_asset_id(),
+        &amount,
+        swap_type,
+    )?;
+    oneclick::get_zec_quote(&from_asset, &amount, &zec_address, &refund_to, overrides).await
 }
 
 /// Get a quote to unshield from ZEC to any asset (ZEC → any supported asset).
+/// `zec_amount` is a decimal amount, converted to base units here.
 #[tauri::command]
 async fn get_zec_unshield_quote(
     to_asset: String,
     zec_amount: String,
     recipient: String,
+    slippage_bps: Option<u32>,
+    deadline_mins: Option<u32>,
+    swap_type: Option<oneclick::SwapType>,
 ) -> Result<oneclick::QuoteResponse, String> {
     let zec_refund = config::get_zec_address()
         .ok_or_else(|| "No ZEC address configured. Add a ZEC wallet in Settings.".to_string())?;
-    oneclick::get_quote_from_zec(&to_asset, &zec_amount, &recipient, &zec_refund).await
+    let overrides = oneclick::QuoteOverrides { slippage_bps, deadline_mins, swap_type };
+    let zec_amount = oneclick::swap_amount_to_base_units(
+        &config::get_zec_asset_id(),
+        &to_asset,
+        &zec_amount,
+        swap_type,
+    )?;
+    oneclick::get_quote_from_zec(&to_asset, &zec_amount, &recipient, &zec_refund, overrides).await
+}
+
+/// Re-request an expired (or about to expire) dry-run quote.
+#[tauri::command]
+async fn refresh_quote(quote: oneclick::QuoteResponse) -> Result<oneclick::QuoteResponse, String> {
+    oneclick::refresh_quote(&quote).await
 }
 
 /// Execute a shield swap (any supported asset → shielded ZEC). Live, not dry run.
+/// Swaps over the transaction limit fail with `guardrailExceeded` until retried
+/// with `confirm_over_limit`.
 #[tauri::command]
 async fn execute_zec_shield(
     from_asset: String,
     amount: String,
-) -> Result<oneclick::QuoteResponse, String> {
+    slippage_bps: Option<u32>,
+    deadline_mins: Option<u32>,
+    swap_type: Option<oneclick::SwapType>,
+    confirm_over_limit: Option<bool>,
+) -> Result<oneclick::QuoteResponse, oneclick::SwapError> {
     let zec_address = config::get_zec_address()
         .ok_or_else(|| "No ZEC address configured. Add a ZEC wallet in Settings.".to_string())?;
     let refund_to = config::get_near_account()
         .unwrap_or_else(|| "nyx.near".to_string());
-    oneclick::execute_zec_shield(&from_asset, &amount, &zec_address, &refund_to).await
+    let overrides = oneclick::QuoteOverrides { slippage_bps, deadline_mins, swap_type };
+    let amount = oneclick::swap_amount_to_base_units(
+        &from_asset,
+        &config::get_zec_asset_id(),
+        &amount,
+        swap_type,
+    )?;
+    oneclick::execute_zec_shield(
+        &from_asset,
+        &amount,
+        &zec_address,
+        &refund_to,
+        overrides,
+        confirm_over_limit.unwrap_or(false),
+    )
+    .await
 }
 
 /// Execute an unshield swap (ZEC → any supported asset). Live, not dry run.
+/// Same transaction-limit check as `execute_zec_shield`.
 #[tauri::command]
 async fn execute_zec_unshield(
     to_asset: String,
     zec_amount: String,
     recipient: String,
-) -> Result<oneclick::QuoteResponse, String> {
+    slippage_bps: Option<u32>,
+    deadline_mins: Option<u32>,
+    swap_type: Option<oneclick::SwapType>,
+    confirm_over_limit: Option<bool>,
+) -> Result<oneclick::QuoteResponse, oneclick::SwapError> {
     let zec_refund = config::get_zec_address()
         .ok_or_else(|| "No ZEC address configured. Add a ZEC wallet in Settings.".to_string())?;
-    oneclick::execute_zec_unshield(&to_asset, &zec_amount, &recipient, &zec_refund).await
+    let overrides = oneclick::QuoteOverrides { slippage_bps, deadline_mins, swap_type };
+    let zec_amount = oneclick::swap_amount_to_base_units(
+        &config::get_zec_asset_id(),
+        &to_asset,
+        &zec_amount,
+        swap_type,
+    )?;
+    oneclick::execute_zec_unshield(
+        &to_asset,
+        &zec_amount,
+        &recipient,
+        &zec_refund,
+        overrides,
+        confirm_over_limit.unwrap_or(false),
+    )
+    .await
 }
 
 /// Get the list of assets that can be shielded to ZEC.
@@ -359,6 +790,12 @@ fn get_shieldable_assets() -> Vec<oneclick::ShieldableAsset> {
     oneclick::get_shieldable_assets()
 }
 
+/// Override the ZEC intents asset id (advanced recovery). Pass null to reset.
+#[tauri::command]
+fn set_zec_asset_id(asset_id: Option<String>) -> Result<String, String> {
+    config::set_zec_asset_id(asset_id)
+}
+
 // ---------------------------------------------------------------------------
 // Container lifecycle
 // ---------------------------------------------------------------------------
@@ -378,6 +815,32 @@ async fn docker_status() -> Result<String, String> {
     docker::container_status().await
 }
 
+/// One report across Docker, the container, gateway, Ollama, gog, ClawdTalk
+/// and setup.
+#[tauri::command]
+async fn system_health() -> health::SystemHealth {
+    health::system_health().await
+}
+
+/// Recent OpenClaw container output (default last 200 lines).
+#[tauri::command]
+async fn docker_logs(tail: Option<usize>, since: Option<String>) -> Result<String, String> {
+    docker::get_logs(tail.unwrap_or(200), since).await
+}
+
+/// Follow container output as `docker:log` events.
+#[tauri::command]
+async fn docker_start_log_stream(app: tauri::AppHandle) -> Result<(), String> {
+    docker::stream_logs(move |line| {
+        let _ = app.emit("docker:log", line);
+    })
+}
+
+#[tauri::command]
+fn docker_stop_log_stream() -> Result<(), String> {
+    docker::stop_log_stream()
+}
+
 // ---------------------------------------------------------------------------
 // Ollama (local models)
 // ---------------------------------------------------------------------------
@@ -402,6 +865,18 @@ async fn pull_ollama_model(model: String) -> Result<String, String> {
     ollama::pull_model(model).await
 }
 
+/// Pull a model, sending `{status, completed, total}` on `on_progress` as it downloads.
+#[tauri::command]
+async fn pull_ollama_model_stream(
+    model: String,
+    on_progress: tauri::ipc::Channel<ollama::PullProgress>,
+) -> Result<String, String> {
+    ollama::pull_model_stream(model, |progress| {
+        let _ = on_progress.send(progress.clone());
+    })
+    .await
+}
+
 #[tauri::command]
 async fn delete_ollama_model(model: String) -> Result<String, String> {
     ollama::delete_model(model).await
@@ -416,6 +891,26 @@ async fn chat_ollama(
     ollama::chat_ollama(model, message, history).await
 }
 
+/// Stream an Ollama reply on `on_token`; stop it with `cancel_ollama_chat(request_id)`.
+#[tauri::command]
+async fn chat_ollama_stream(
+    model: String,
+    message: String,
+    history: Vec<ollama::ChatMessage>,
+    request_id: String,
+    on_token: tauri::ipc::Channel<String>,
+) -> Result<String, String> {
+    ollama::chat_ollama_stream(model, message, history, request_id, |token| {
+        let _ = on_token.send(token.to_string());
+    })
+    .await
+}
+
+#[tauri::command]
+fn cancel_ollama_chat(request_id: String) -> bool {
+    ollama::cancel_chat(&request_id)
+}
+
 #[tauri::command]
 async fn get_system_ram() -> Result<u64, String> {
     ollama::get_system_ram().await
@@ -428,8 +923,7 @@ async fn get_system_ram() -> Result<u64, String> {
 /// Read the configured agent name from openclaw.json (fallback: "Nyx").
 #[tauri::command]
 fn get_agent_name() -> Result<String, String> {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let config_path = std::path::PathBuf::from(&home).join(".openclaw/openclaw.json");
+    let config_path = config::openclaw_home().join("openclaw.json");
     let content = match std::fs::read_to_string(&config_path) {
         Ok(c) => c,
         Err(_) => return Ok("Nyx".to_string()),
@@ -455,14 +949,70 @@ fn read_current_config() -> Result<config::SettingsConfig, String> {
     config::read_current_config()
 }
 
+/// Check whether renaming the agent would be applied to SOUL.md.
 #[tauri::command]
-fn save_settings(update: config::SettingsUpdate) -> Result<config::SettingsSaveResult, String> {
+fn preview_soul_personalization(agent_name: String) -> Result<config::SoulPersonalization, String> {
+    config::preview_soul_personalization(&agent_name)
+}
+
+#[tauri::command]
+async fn save_settings(
+    update: config::SettingsUpdate,
+    auto_pull_model: Option<bool>,
+) -> Result<config::SettingsSaveResult, String> {
+    // Don't point the agent at a local model it can't load
+    if let Some(model) = update.capabilities.as_ref().and_then(|c| c.active_ollama_model()) {
+        ollama::ensure_model_installed(model, auto_pull_model.unwrap_or(false)).await?;
+    }
     config::save_settings(update)
 }
 
+/// Show what save_settings would change for `update`, without writing.
+#[tauri::command]
+fn preview_settings(update: config::SettingsUpdate) -> Result<config::SettingsDiff, String> {
+    config::preview_settings(update)
+}
+
+/// Snapshots taken before each settings save, newest first.
+#[tauri::command]
+fn list_config_backups() -> Result<Vec<config::ConfigBackup>, String> {
+    config::list_config_backups()
+}
+
+/// Roll the config files back to a snapshot. Needs a container restart.
+#[tauri::command]
+fn restore_config_backup(timestamp: String) -> Result<config::ConfigBackup, String> {
+    config::restore_config_backup(&timestamp)
+}
+
+/// Preview which safeBins, skills, cron jobs and env keys a capability change
+/// would touch, without writing anything.
+#[tauri::command]
+fn preview_capability_change(
+    caps: config::CapabilitiesConfig,
+) -> Result<config::CapabilityDiff, String> {
+    config::preview_capability_change(caps)
+}
+
+/// Restart the container in place, emitting `docker:restart` progress events.
+#[tauri::command]
+async fn restart_container(app: tauri::AppHandle) -> Result<(), String> {
+    docker::restart_container_with_progress(|progress| {
+        let _ = app.emit("docker:restart", progress);
+    })
+    .await
+}
+
+/// Install or remove the launch-at-login entry (LaunchAgent on macOS,
+/// systemd user unit on Linux, Startup folder script on Windows).
 #[tauri::command]
-async fn restart_container() -> Result<(), String> {
-    docker::restart_container().await
+fn set_autostart(enabled: bool) -> Result<String, String> {
+    setup::set_autostart(enabled)
+}
+
+#[tauri::command]
+fn is_autostart_enabled() -> Result<bool, String> {
+    setup::is_autostart_enabled()
 }
 
 // ---------------------------------------------------------------------------
@@ -484,18 +1034,28 @@ async fn claude_code_unregister_mcp() -> Result<(), String> {
     claudecode::unregister_mcp_server().await
 }
 
+/// Nyx MCP tools visible to Claude Code, with per-tool availability.
+#[tauri::command]
+fn claude_code_list_tools() -> Result<Vec<nyx_lib::mcp::ToolSummary>, String> {
+    claudecode::list_tools()
+}
+
 // ---------------------------------------------------------------------------
 // PTY (embedded terminal)
 // ---------------------------------------------------------------------------
 
+/// Spawn a PTY session. `cwd` must exist; `env` is merged over the inherited
+/// environment.
 #[tauri::command]
 fn pty_spawn(
     app: tauri::AppHandle,
     command: Option<String>,
     cols: Option<u16>,
     rows: Option<u16>,
+    cwd: Option<String>,
+    env: Option<std::collections::HashMap<String, String>>,
 ) -> Result<String, String> {
-    pty::spawn(app, command, cols.unwrap_or(120), rows.unwrap_or(36))
+    pty::spawn(app, command, cols.unwrap_or(120), rows.unwrap_or(36), cwd, env)
 }
 
 #[tauri::command]
@@ -513,6 +1073,24 @@ fn pty_kill(session_id: String) -> Result<(), String> {
     pty::kill(&session_id)
 }
 
+/// Sessions that haven't been killed, with their command and whether they're alive.
+#[tauri::command]
+fn pty_list() -> Result<Vec<pty::PtySessionInfo>, String> {
+    pty::list()
+}
+
+/// The last `lines` lines (default 100) of a session's output, as plain text.
+#[tauri::command]
+fn pty_read_output(
+    session_id: String,
+    lines: Option<usize>,
+) -> Result<nyx_lib::pty_output::PtyOutput, String> {
+    pty::read_output(
+        &session_id,
+        lines.unwrap_or(nyx_lib::pty_output::DEFAULT_OUTPUT_LINES),
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Activity Intelligence
 // ---------------------------------------------------------------------------
@@ -527,6 +1105,11 @@ fn dismiss_intelligence_suggestion(id: i64) -> Result<(), String> {
     intelligence::dismiss_suggestion(id)
 }
 
+#[tauri::command]
+fn snooze_intelligence_suggestion(id: i64, until: String) -> Result<(), String> {
+    intelligence::snooze_suggestion(id, &until)
+}
+
 #[tauri::command]
 fn accept_intelligence_suggestion(id: i64) -> Result<intelligence::Suggestion, String> {
     intelligence::accept_suggestion(id)
@@ -537,6 +1120,36 @@ fn get_contact_insights(email: String) -> Result<intelligence::ContactInsight, S
     intelligence::get_contact_insights(&email)
 }
 
+/// Replace a contact's tags (e.g. "vip", "investor").
+#[tauri::command]
+fn set_contact_tags(email: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    intelligence::set_contact_tags(&email, tags)
+}
+
+#[tauri::command]
+fn add_contact_tag(email: String, tag: String) -> Result<Vec<String>, String> {
+    intelligence::add_contact_tag(&email, &tag)
+}
+
+#[tauri::command]
+fn remove_contact_tag(email: String, tag: String) -> Result<Vec<String>, String> {
+    intelligence::remove_contact_tag(&email, &tag)
+}
+
+#[tauri::command]
+fn list_contacts_by_tag(tag: String) -> Result<Vec<intelligence::ContactSummary>, String> {
+    intelligence::list_contacts_by_tag(&tag)
+}
+
+/// Fold a duplicate contact into another (undoable).
+#[tauri::command]
+fn merge_contacts(
+    keep_email: String,
+    merge_email: String,
+) -> Result<intelligence::ContactSummary, String> {
+    intelligence::merge_contacts(&keep_email, &merge_email)
+}
+
 #[tauri::command]
 fn get_activity_stats() -> Result<intelligence::ActivityStats, String> {
     intelligence::get_activity_stats()
@@ -552,91 +1165,199 @@ fn set_autonomy_level(activity_type: String, level: String) -> Result<(), String
     intelligence::set_autonomy_level(&activity_type, &level)
 }
 
+#[tauri::command]
+fn get_intelligence_tuning() -> intelligence::IntelligenceTuning {
+    intelligence::get_tuning()
+}
+
+#[tauri::command]
+fn set_intelligence_tuning(tuning: intelligence::IntelligenceTuning) -> Result<(), String> {
+    intelligence::set_tuning(&tuning)
+}
+
 #[tauri::command]
 fn clear_intelligence_data() -> Result<(), String> {
     intelligence::clear_all_data()
 }
 
+/// Back up intelligence.db to `dest` using SQLite's online backup API.
+#[tauri::command]
+fn backup_intelligence_db(dest: String) -> Result<(), String> {
+    intelligence::backup_db(&dest)
+}
+
+/// Restore intelligence.db from a backup file (observer paused meanwhile).
+#[tauri::command]
+fn restore_intelligence_db(src: String) -> Result<(), String> {
+    intelligence::restore_db(&src)
+}
+
+/// Export intelligence data (metadata only, never email bodies) as JSON.
+#[tauri::command]
+fn export_intelligence_data(dest: String) -> Result<(), String> {
+    intelligence::export_to_file(&dest)
+}
+
+/// Replace intelligence data with a JSON export (undoable).
+#[tauri::command]
+fn import_intelligence_data(src: String) -> Result<(), String> {
+    intelligence::import_from_file(&src)
+}
+
+/// Forget stale one-off contacts (defaults match the observer's daily prune).
+#[tauri::command]
+fn prune_intelligence_contacts(
+    older_than_days: Option<u32>,
+    min_interactions: Option<u32>,
+) -> Result<intelligence::PruneReport, String> {
+    intelligence::prune_contacts(
+        older_than_days.unwrap_or(intelligence::PRUNE_STALE_DAYS),
+        min_interactions.unwrap_or(intelligence::PRUNE_MIN_INTERACTIONS),
+    )
+}
+
+#[tauri::command]
+fn undo_last_intelligence_action() -> Result<(), String> {
+    intelligence::undo_last_action()
+}
+
 // ---------------------------------------------------------------------------
 // Web Browser (agent-controlled browsing)
 // ---------------------------------------------------------------------------
 
 #[tauri::command]
-fn browser_open(app: tauri::AppHandle) -> Result<(), String> {
-    browser::open(&app)
+fn browser_open(app: tauri::AppHandle, session_key: Option<String>) -> Result<(), String> {
+    browser::open(&app, browser_session(&session_key))
 }
 
 #[tauri::command]
-fn browser_close(app: tauri::AppHandle) -> Result<(), String> {
-    browser::close(&app)
+fn browser_close(app: tauri::AppHandle, session_key: Option<String>) -> Result<(), String> {
+    browser::close(&app, browser_session(&session_key))
 }
 
 #[tauri::command]
-fn browser_state() -> Result<Option<browser::BrowserState>, String> {
-    browser::get_state()
+fn browser_state(session_key: Option<String>) -> Result<Option<browser::BrowserState>, String> {
+    browser::get_state(browser_session(&session_key))
 }
 
+/// Every open browser window, one per browse session.
 #[tauri::command]
-fn browser_navigate(app: tauri::AppHandle, url: String) -> Result<(), String> {
-    browser::navigate(&app, &url)
+fn browser_sessions() -> Result<Vec<browser::BrowserState>, String> {
+    browser::list_states()
 }
 
 #[tauri::command]
-fn browser_go_back(app: tauri::AppHandle) -> Result<(), String> {
-    browser::go_back(&app)
+fn browser_navigate(app: tauri::AppHandle, url: String, session_key: Option<String>) -> Result<(), String> {
+    browser::navigate(&app, browser_session(&session_key), &url)
 }
 
 #[tauri::command]
-fn browser_go_forward(app: tauri::AppHandle) -> Result<(), String> {
-    browser::go_forward(&app)
+fn browser_go_back(app: tauri::AppHandle, session_key: Option<String>) -> Result<(), String> {
+    browser::go_back(&app, browser_session(&session_key))
 }
 
 #[tauri::command]
-fn browser_click(app: tauri::AppHandle, selector: String) -> Result<String, String> {
-    browser::click(&app, &selector)
+fn browser_go_forward(app: tauri::AppHandle, session_key: Option<String>) -> Result<(), String> {
+    browser::go_forward(&app, browser_session(&session_key))
 }
 
 #[tauri::command]
-fn browser_type_text(app: tauri::AppHandle, selector: String, text: String) -> Result<String, String> {
-    browser::type_text(&app, &selector, &text)
+async fn browser_click(
+    app: tauri::AppHandle,
+    selector: String,
+    session_key: Option<String>,
+) -> Result<String, String> {
+    browser::click(&app, browser_session(&session_key), &selector).await
 }
 
 #[tauri::command]
-fn browser_scroll(app: tauri::AppHandle, direction: String, amount: Option<i32>) -> Result<String, String> {
-    browser::scroll(&app, &direction, amount.unwrap_or(3))
+async fn browser_type_text(
+    app: tauri::AppHandle,
+    selector: String,
+    text: String,
+    session_key: Option<String>,
+) -> Result<String, String> {
+    browser::type_text(&app, browser_session(&session_key), &selector, &text).await
 }
 
 #[tauri::command]
-fn browser_read_page(app: tauri::AppHandle) -> Result<String, String> {
-    browser::read_page(&app)
+fn browser_scroll(
+    app: tauri::AppHandle,
+    direction: String,
+    amount: Option<i32>,
+    session_key: Option<String>,
+) -> Result<String, String> {
+    browser::scroll(&app, browser_session(&session_key), &direction, amount.unwrap_or(3))
 }
 
 #[tauri::command]
-fn browser_read_links(app: tauri::AppHandle) -> Result<String, String> {
-    browser::read_links(&app)
+async fn browser_read_page(app: tauri::AppHandle, session_key: Option<String>) -> Result<String, String> {
+    browser::read_page(&app, browser_session(&session_key)).await
 }
 
 #[tauri::command]
-fn browser_read_forms(app: tauri::AppHandle) -> Result<String, String> {
-    browser::read_forms(&app)
+async fn browser_read_links(app: tauri::AppHandle, session_key: Option<String>) -> Result<String, String> {
+    browser::read_links(&app, browser_session(&session_key)).await
 }
 
 #[tauri::command]
-fn browser_select_option(app: tauri::AppHandle, selector: String, value: String) -> Result<String, String> {
-    browser::select_option(&app, &selector, &value)
+async fn browser_read_forms(app: tauri::AppHandle, session_key: Option<String>) -> Result<String, String> {
+    browser::read_forms(&app, browser_session(&session_key)).await
 }
 
 #[tauri::command]
-fn browser_execute_js(app: tauri::AppHandle, code: String) -> Result<String, String> {
-    browser::execute_js(&app, &code)
+async fn browser_select_option(
+    app: tauri::AppHandle,
+    selector: String,
+    value: String,
+    session_key: Option<String>,
+) -> Result<String, String> {
+    browser::select_option(&app, browser_session(&session_key), &selector, &value).await
+}
+
+/// Wait until `selector` exists on the page (default timeout 10s).
+#[tauri::command]
+async fn browser_wait_for(
+    app: tauri::AppHandle,
+    selector: String,
+    timeout_ms: Option<u64>,
+    session_key: Option<String>,
+) -> Result<String, String> {
+    browser::wait_for(&app, browser_session(&session_key), &selector, timeout_ms.unwrap_or(10_000)).await
+}
+
+/// Capture the browser window to a PNG; returns `{ path, width, height }`.
+#[tauri::command]
+async fn browser_screenshot(app: tauri::AppHandle, session_key: Option<String>) -> Result<String, String> {
+    browser::screenshot(&app, browser_session(&session_key)).await
 }
 
 #[tauri::command]
 async fn browser_execute_action(
     app: tauri::AppHandle,
     action: browser::BrowserAction,
+    session_key: Option<String>,
 ) -> Result<browser::BrowserActionResult, String> {
-    Ok(browser::execute_action(&app, &action).await)
+    Ok(browser::execute_action(&app, browser_session(&session_key), &action).await)
+}
+
+#[tauri::command]
+fn get_browser_filter() -> Result<config::BrowserNavigationFilter, String> {
+    config::get_browser_filter()
+}
+
+/// Set the browser host allow/blocklist (`*.example.com` wildcards).
+#[tauri::command]
+fn set_browser_filter(
+    allowlist: Vec<String>,
+    blocklist: Vec<String>,
+) -> Result<config::BrowserNavigationFilter, String> {
+    config::set_browser_filter(config::BrowserNavigationFilter { allowlist, blocklist })
+}
+
+/// Browse session for a command; omitted means the original single window.
+fn browser_session(session_key: &Option<String>) -> &str {
+    session_key.as_deref().unwrap_or(browser::DEFAULT_SESSION)
 }
 
 /// Send a message with browser tool to the agent and run the full agent loop.
@@ -645,9 +1366,10 @@ async fn browser_send_message(
     app: tauri::AppHandle,
     message: String,
     session_key: Option<String>,
+    max_iterations: Option<usize>,
 ) -> Result<String, String> {
-    let key = session_key.unwrap_or_else(|| "agent:default:browse".to_string());
-    browser::send_browse_message(&app, message, key).await
+    let key = session_key.unwrap_or_else(|| browser::DEFAULT_SESSION.to_string());
+    browser::send_browse_message(&app, message, key, max_iterations).await
 }
 
 // ---------------------------------------------------------------------------
@@ -660,7 +1382,11 @@ fn clawdtalk_status() -> Result<clawdtalk::ClawdTalkStatus, String> {
 }
 
 #[tauri::command]
-fn clawdtalk_configure(api_key: String) -> Result<(), String> {
+async fn clawdtalk_configure(api_key: String) -> Result<(), String> {
+    // Fail fast on a bad key instead of at the first call
+    clawdtalk::validate_api_key(&api_key).await?;
+    let api_key = api_key.trim().to_string();
+
     // Store the raw API key in docker.env, reference via env var in skill config
     let home = config::home_dir();
     let env_path = home.join("openclaw/docker.env");
@@ -676,7 +1402,7 @@ fn clawdtalk_configure(api_key: String) -> Result<(), String> {
         content.lines()
             .map(|l| {
                 if l.trim().starts_with("CLAWDTALK_API_KEY=") {
-                    format!("CLAWDTALK_API_KEY={}", api_key)
+                    format!("CLAWDTALK_API_KEY={}", config::env_quote(&api_key))
                 } else {
                     l.to_string()
                 }
@@ -685,10 +1411,14 @@ fn clawdtalk_configure(api_key: String) -> Result<(), String> {
             .join("\n")
     } else {
         // Append to end
-        format!("{}\n# ClawdTalk Voice\nCLAWDTALK_API_KEY={}\n", content.trim_end(), api_key)
+        format!(
+            "{}\n# ClawdTalk Voice\nCLAWDTALK_API_KEY={}\n",
+            content.trim_end(),
+            config::env_quote(&api_key)
+        )
     };
 
-    std::fs::write(&env_path, updated)
+    config::write_atomic(&env_path, updated)
         .map_err(|e| format!("Failed to update docker.env: {}", e))?;
 
     // chmod 600
@@ -727,7 +1457,7 @@ fn clawdtalk_remove() -> Result<(), String> {
         let updated: Vec<&str> = content.lines()
             .filter(|l| !l.trim().starts_with("CLAWDTALK_API_KEY=") && l.trim() != "# ClawdTalk Voice")
             .collect();
-        let _ = std::fs::write(&env_path, updated.join("\n") + "\n");
+        let _ = config::write_atomic(&env_path, updated.join("\n") + "\n");
     }
 
     Ok(())
@@ -738,9 +1468,22 @@ async fn clawdtalk_start() -> Result<clawdtalk::ClawdTalkStatus, String> {
     clawdtalk::start_connection().await
 }
 
+/// Tail the ClawdTalk log, emitting each new line as `clawdtalk:log`.
+#[tauri::command]
+async fn clawdtalk_start_log_stream(app: tauri::AppHandle) -> Result<(), String> {
+    clawdtalk::start_log_stream(move |line| {
+        let _ = app.emit("clawdtalk:log", line);
+    })
+}
+
 #[tauri::command]
-fn clawdtalk_stop() -> Result<clawdtalk::ClawdTalkStatus, String> {
-    clawdtalk::stop_connection()
+fn clawdtalk_stop_log_stream() -> Result<(), String> {
+    clawdtalk::stop_log_stream()
+}
+
+#[tauri::command]
+async fn clawdtalk_stop() -> Result<clawdtalk::ClawdTalkStatus, String> {
+    clawdtalk::stop_connection().await
 }
 
 #[tauri::command]
@@ -766,11 +1509,26 @@ fn main() {
             check_setup_complete,
             run_setup,
             run_setup_v2,
+            validate_setup_v2,
             // Wallets
             generate_wallet,
             generate_near_wallet_full,
+            recover_near_wallet,
             validate_wallet_address,
             import_wallet,
+            list_orphaned_wallet_keys,
+            prune_orphaned_wallet_keys,
+            is_wallet_key_encrypted,
+            unlock_wallet_key,
+            encrypt_wallet_key_file,
+            get_wallet_balance,
+            set_rpc_endpoint,
+            get_proxy,
+            set_proxy,
+            get_gateway_retry,
+            set_gateway_retry,
+            sign_wallet_message,
+            verify_wallet_signature,
             // Security
             get_guardrails_preset,
             // Google
@@ -780,77 +1538,137 @@ fn main() {
             install_gog,
             // Portfolio & Chat
             get_portfolio,
+            pause_portfolio_watcher,
+            resume_portfolio_watcher,
+            portfolio_watcher_status,
             send_chat_message,
             send_chat_message_to_session,
+            send_chat_message_stream,
+            set_session_context_budget,
+            estimate_spend,
+            get_model_prices,
+            set_model_prices,
+            // Audit Log
+            get_audit_log,
+            verify_audit_log,
+            // Pending Messages
+            list_pending_messages,
+            approve_message,
+            reject_message,
             // Sessions & Folders
             list_chat_sessions,
             create_chat_session,
             rename_chat_session,
+            delete_chat_session,
+            export_chat_session,
             move_session_to_folder,
             get_chat_folders,
             create_chat_folder,
             rename_chat_folder,
             delete_chat_folder,
+            reorder_chat_folders,
             // Source Intelligence
             verify_source,
             // 1Click API
             get_supported_tokens,
             get_cross_chain_quote,
             get_swap_status,
+            watch_swap_status,
+            list_swaps,
+            list_pending_swaps,
+            dismiss_pending_swap,
             resolve_asset_id,
             // ZEC Privacy Shield
             get_zec_shield_quote,
             get_zec_unshield_quote,
             get_shieldable_assets,
+            refresh_quote,
             execute_zec_shield,
             execute_zec_unshield,
+            set_zec_asset_id,
             // Container
             docker_start,
             docker_stop,
             docker_status,
+            system_health,
+            docker_logs,
+            docker_start_log_stream,
+            docker_stop_log_stream,
             // Ollama (local models)
             check_ollama,
             install_ollama,
             list_ollama_models,
             pull_ollama_model,
+            pull_ollama_model_stream,
             delete_ollama_model,
             chat_ollama,
+            chat_ollama_stream,
+            cancel_ollama_chat,
             get_system_ram,
             // Agent identity
             get_agent_name,
             // Settings
             read_current_config,
+            preview_soul_personalization,
             save_settings,
+            preview_settings,
+            list_config_backups,
+            restore_config_backup,
+            preview_capability_change,
             restart_container,
+            set_autostart,
+            is_autostart_enabled,
             // ClawdTalk (voice)
             clawdtalk_status,
             clawdtalk_configure,
             clawdtalk_remove,
             clawdtalk_start,
             clawdtalk_stop,
+            clawdtalk_start_log_stream,
+            clawdtalk_stop_log_stream,
             clawdtalk_logs,
             // Claude Code
             claude_code_status,
             claude_code_register_mcp,
             claude_code_unregister_mcp,
+            claude_code_list_tools,
             // PTY (embedded terminal)
             pty_spawn,
             pty_write,
             pty_resize,
             pty_kill,
+            pty_read_output,
+            pty_list,
             // Activity Intelligence
             get_intelligence_suggestions,
             dismiss_intelligence_suggestion,
+            snooze_intelligence_suggestion,
             accept_intelligence_suggestion,
             get_contact_insights,
+            set_contact_tags,
+            add_contact_tag,
+            remove_contact_tag,
+            list_contacts_by_tag,
+            merge_contacts,
             get_activity_stats,
             get_autonomy_settings,
             set_autonomy_level,
+            get_intelligence_tuning,
+            set_intelligence_tuning,
             clear_intelligence_data,
+            undo_last_intelligence_action,
+            backup_intelligence_db,
+            restore_intelligence_db,
+            export_intelligence_data,
+            import_intelligence_data,
+            prune_intelligence_contacts,
             // Web Browser
             browser_open,
             browser_close,
             browser_state,
+            browser_sessions,
+            get_browser_filter,
+            set_browser_filter,
             browser_navigate,
             browser_go_back,
             browser_go_forward,
@@ -861,11 +1679,31 @@ fn main() {
             browser_read_links,
             browser_read_forms,
             browser_select_option,
-            browser_execute_js,
+            browser_screenshot,
+            browser_wait_for,
             browser_execute_action,
             browser_send_message,
         ])
         .setup(|app| {
+            // Older versions kept the NEAR private key in plaintext here
+            if let Err(e) = wallet::scrub_legacy_wallet_file() {
+                eprintln!("Failed to scrub legacy near_account.json: {}", e);
+            }
+
+            // PTY sessions don't survive a restart; drop their leftover output
+            nyx_lib::pty_output::remove_all_snapshots();
+
+            // Pick up swaps whose status polling was cut short by the last exit
+            let swap_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let on_progress = move |status: &oneclick::SwapStatus| {
+                    let _ = swap_handle.emit("swap:progress", status);
+                };
+                if let Err(e) = oneclick::resume_pending_swaps(on_progress) {
+                    eprintln!("Failed to resume pending swaps: {}", e);
+                }
+            });
+
             let handle = app.handle().clone();
             // Start portfolio file watcher in background
             tauri::async_runtime::spawn(async move {
@@ -880,7 +1718,9 @@ fn main() {
                 .map(|c| c.capabilities.activity_intelligence)
                 .unwrap_or(false)
             {
-                intelligence::start_observer(intel_handle);
+                intelligence::start_observer(move |event, payload| {
+                    let _ = intel_handle.emit(event, payload);
+                });
             }
 
             Ok(())
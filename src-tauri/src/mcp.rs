@@ -12,11 +12,17 @@ use rmcp::{
     tool, tool_handler, tool_router,
 };
 
+use crate::audit;
 use crate::config;
 use crate::docker;
 use crate::gateway;
+use crate::health;
+use crate::intelligence;
+use crate::messaging;
 use crate::oneclick;
 use crate::portfolio_data;
+use crate::pty_output;
+use crate::wallet;
 
 // ---------------------------------------------------------------------------
 // Tool parameter types (must impl Deserialize + JsonSchema)
@@ -30,10 +36,28 @@ pub struct ChatParams {
     pub session_key: Option<String>,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct PortfolioParams {
+    /// Only positions on this chain (e.g. "near", "eth")
+    pub chain: Option<String>,
+    /// Only positions worth at least this many USD
+    pub min_value_usd: Option<f64>,
+    /// "value_desc" (default), "value_asc", "apy_desc" or "asset"
+    pub sort: Option<String>,
+    /// Maximum number of positions to return
+    pub limit: Option<usize>,
+    /// Include recent activity (default true)
+    pub include_activity: Option<bool>,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct VerifySourceParams {
     /// URL or claim to analyse for credibility
     pub query: String,
+    /// Seconds allowed for fetching the page (default 30)
+    pub fetch_timeout_secs: Option<u64>,
+    /// Maximum page content to consider, in KB (default 200)
+    pub max_content_kb: Option<u32>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -50,10 +74,87 @@ pub struct ZecQuoteParams {
     pub direction: String,
     /// Asset identifier (e.g. "eth:ETH", "near:NEAR", "sol:USDC")
     pub asset: String,
-    /// Amount to swap
-    pub amount: String,
+    /// Human-readable decimal amount (e.g. "1.5", not base units), in the
+    /// origin asset, or the destination asset for EXACT_OUTPUT
+    #[serde(alias = "amount")]
+    pub amount_decimal: String,
     /// Recipient address (required for unshield direction)
     pub recipient: Option<String>,
+    /// Slippage tolerance in basis points (clamped to the guardrail maximum)
+    pub slippage_bps: Option<u32>,
+    /// Quote deadline in minutes (default 30 for ZEC, 5–120)
+    pub deadline_mins: Option<u32>,
+    /// "EXACT_INPUT" (default; amount_decimal is what you send) or
+    /// "EXACT_OUTPUT" (amount_decimal is what the recipient receives)
+    pub swap_type: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ZecExecuteParams {
+    /// Correlation id returned by nyx_zec_quote
+    pub correlation_id: String,
+    /// The phrase the user read from the Nyx app, e.g. "CONFIRM 3FA92C1D"
+    pub confirmation: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GuardrailsParams {
+    /// "get" (default) or "set"
+    pub action: Option<String>,
+    /// Fields below are only used with "set"; omitted ones keep their value.
+    /// "set" may only tighten limits.
+    pub max_transaction_usd: Option<f64>,
+    pub daily_loss_percent: Option<f64>,
+    pub weekly_loss_percent: Option<f64>,
+    pub daily_tx_limit: Option<u32>,
+    pub require_confirmation: Option<bool>,
+    pub max_slippage_percent: Option<f64>,
+    pub max_concentration_percent: Option<f64>,
+    pub min_health_factor: Option<f64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct DraftMessageParams {
+    /// Channel: "gmail", "whatsapp", "telegram", "slack" or "signal"
+    pub channel: String,
+    /// Recipient (email address, phone number, handle or channel)
+    pub recipient: String,
+    /// Message body
+    pub body: String,
+    /// Optional subject (email only)
+    pub subject: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SuggestionsParams {
+    /// Action to perform: "run" (detect now, default), "list", "accept", "dismiss" or "snooze"
+    pub action: Option<String>,
+    /// Suggestion id (required for "accept", "dismiss" and "snooze")
+    pub id: Option<i64>,
+    /// RFC 3339 time to snooze until (only used with action "snooze")
+    pub until: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListSwapsParams {
+    /// Maximum number of swaps to return, newest first (default 20)
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct PtyOutputParams {
+    /// PTY session id. Omit for the most recently active terminal.
+    pub session_id: Option<String>,
+    /// Number of trailing lines to return (default 100)
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct WalletBalanceParams {
+    /// Chain: "NEAR" or "ETH". Omit for all supported chains.
+    pub chain: Option<String>,
+    /// Address to look up. Omit to use the configured wallets.
+    pub address: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -94,14 +195,40 @@ impl NyxMcpServer {
         }
     }
 
-    /// Get the current DeFi portfolio data.
-    #[tool(description = "Get the current DeFi portfolio data including positions, allocation, health status, and recent activity.")]
-    async fn nyx_portfolio(&self) -> String {
-        match portfolio_data::read_portfolio().await {
-            Ok(data) => serde_json::to_string_pretty(&data)
-                .unwrap_or_else(|_| "Failed to serialize portfolio".to_string()),
-            Err(e) => format!("Error: {}", e),
+    /// Get the current DeFi portfolio data, optionally filtered.
+    #[tool(description = "Get the current DeFi portfolio data including positions, allocation, health status, and recent activity. Positions can be filtered by chain and minimum USD value, sorted, and limited; positions_total and positions_matched report how many were filtered out.")]
+    async fn nyx_portfolio(&self, Parameters(params): Parameters<PortfolioParams>) -> String {
+        let sort = match params.sort.as_deref().map(portfolio_data::PositionSort::parse) {
+            Some(Ok(s)) => s,
+            Some(Err(e)) => return format!("Error: {}", e),
+            None => portfolio_data::PositionSort::default(),
+        };
+        let filter = portfolio_data::PositionFilter {
+            chain: params.chain,
+            min_value_usd: params.min_value_usd,
+            sort,
+            limit: params.limit,
+        };
+        let mut data = match portfolio_data::read_portfolio().await {
+            Ok(data) => data,
+            Err(e) => return format!("Error: {}", e),
+        };
+
+        let positions_total = data.positions.len();
+        data.positions = portfolio_data::filter_positions(&data.positions, &filter);
+        let positions_matched = data.positions.len();
+        if !params.include_activity.unwrap_or(true) {
+            data.recent_activity.clear();
         }
+
+        let mut value = match serde_json::to_value(&data) {
+            Ok(v) => v,
+            Err(_) => return "Failed to serialize portfolio".to_string(),
+        };
+        value["positions_total"] = positions_total.into();
+        value["positions_matched"] = positions_matched.into();
+        serde_json::to_string_pretty(&value)
+            .unwrap_or_else(|_| "Failed to serialize portfolio".to_string())
     }
 
     /// Analyse a URL or claim for credibility.
@@ -110,12 +237,33 @@ impl NyxMcpServer {
         &self,
         Parameters(params): Parameters<VerifySourceParams>,
     ) -> String {
-        match gateway::verify_source(params.query).await {
+        let opts = gateway::VerifyOptions {
+            fetch_timeout_secs: params.fetch_timeout_secs,
+            max_content_kb: params.max_content_kb,
+        };
+        match gateway::verify_source(params.query, opts).await {
             Ok(analysis) => analysis,
             Err(e) => format!("Error: {}", e),
         }
     }
 
+    /// Analyse a URL for credibility and return the parsed report.
+    #[tool(description = "Analyse a URL for credibility and return a validated JSON report: per-dimension scores, overall_score, grade (A-F), and key claims with their verification status.")]
+    async fn nyx_verify_source_structured(
+        &self,
+        Parameters(params): Parameters<VerifySourceParams>,
+    ) -> String {
+        let opts = gateway::VerifyOptions {
+            fetch_timeout_secs: params.fetch_timeout_secs,
+            max_content_kb: params.max_content_kb,
+        };
+        match gateway::verify_source_structured(params.query, opts).await {
+            Ok(report) => serde_json::to_string_pretty(&report)
+                .unwrap_or_else(|_| "Failed to serialize report".to_string()),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
     /// Check the Nyx Docker container status.
     #[tool(description = "Check the Nyx Docker container status including whether it's running, the image version, and system health.")]
     async fn nyx_docker_status(&self) -> String {
@@ -126,6 +274,13 @@ impl NyxMcpServer {
         }
     }
 
+    /// Report the health of every Nyx subsystem.
+    #[tool(description = "Report Nyx system health: setup, Docker, the OpenClaw container, gateway reachability, Ollama, gog (Google) auth and ClawdTalk. Each check is ok, degraded or error with a short message, plus an overall status.")]
+    async fn nyx_health(&self) -> String {
+        serde_json::to_string_pretty(&health::system_health().await)
+            .unwrap_or_else(|_| "Failed to serialize health report".to_string())
+    }
+
     /// List or create chat sessions.
     #[tool(description = "List or create chat sessions. Use action 'list' to get all sessions, or 'create' with an optional title to start a new session.")]
     async fn nyx_sessions(&self, Parameters(params): Parameters<SessionsParams>) -> String {
@@ -143,10 +298,38 @@ impl NyxMcpServer {
         }
     }
 
+    /// Queue a drafted message for the user to approve before it is sent.
+    #[tool(description = "Queue a drafted outbound message on a channel set to send-with-confirm. The message is NOT sent until the user approves it in the Nyx app.")]
+    async fn nyx_draft_message(
+        &self,
+        Parameters(params): Parameters<DraftMessageParams>,
+    ) -> String {
+        match messaging::queue_message(
+            params.channel,
+            params.recipient,
+            params.body,
+            params.subject,
+            None,
+        ) {
+            Ok(msg) => format!("Queued message {} for approval", msg.id),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
     /// Get a cross-chain swap quote for shielding or unshielding ZEC.
-    #[tool(description = "Get a cross-chain swap quote for shielding assets into Zcash (ZEC) or unshielding from ZEC to any supported crypto. Uses NEAR Intents for cross-chain routing.")]
+    #[tool(description = "Get a cross-chain swap quote for shielding assets into Zcash (ZEC) or unshielding from ZEC to any supported crypto. Uses NEAR Intents for cross-chain routing. Pass 'amount_decimal' in whole units (e.g. \"1.5\" ETH), never in base units. This is a dry run; the response includes a correlationId for nyx_zec_execute. The confirmation phrase is shown only to the user in the Nyx app; ask them for it before executing.")]
     async fn nyx_zec_quote(&self, Parameters(params): Parameters<ZecQuoteParams>) -> String {
-        let result = match params.direction.as_str() {
+        let swap_type = match params.swap_type.as_deref().map(oneclick::SwapType::parse) {
+            Some(Ok(t)) => Some(t),
+            Some(Err(e)) => return format!("Error: {}", e),
+            None => None,
+        };
+        let overrides = oneclick::QuoteOverrides {
+            slippage_bps: params.slippage_bps,
+            deadline_mins: params.deadline_mins,
+            swap_type,
+        };
+        let (direction, recipient, refund_to) = match params.direction.as_str() {
             "shield" => {
                 let zec_address = match config::get_zec_address() {
                     Some(addr) => addr,
@@ -154,8 +337,7 @@ impl NyxMcpServer {
                 };
                 let refund_to = config::get_near_account()
                     .unwrap_or_else(|| "nyx.near".to_string());
-                oneclick::get_zec_quote(&params.asset, &params.amount, &zec_address, &refund_to)
-                    .await
+                (oneclick::ZecDirection::Shield, zec_address, refund_to)
             }
             "unshield" => {
                 let zec_refund = match config::get_zec_address() {
@@ -166,30 +348,396 @@ impl NyxMcpServer {
                     Some(r) => r,
                     None => return "Error: recipient address required for unshield".to_string(),
                 };
+                (oneclick::ZecDirection::Unshield, recipient, zec_refund)
+            }
+            other => {
+                return format!(
+                    "Unknown direction '{}'. Use 'shield' or 'unshield'.",
+                    other
+                );
+            }
+        };
+        let zec_asset = config::get_zec_asset_id();
+        let (origin, destination) = match direction {
+            oneclick::ZecDirection::Shield => (params.asset.as_str(), zec_asset.as_str()),
+            oneclick::ZecDirection::Unshield => (zec_asset.as_str(), params.asset.as_str()),
+        };
+        let amount = match oneclick::swap_amount_to_base_units(
+            origin,
+            destination,
+            &params.amount_decimal,
+            swap_type,
+        ) {
+            Ok(a) => a,
+            Err(e) => return format!("Error: {}", e),
+        };
+        let result = match direction {
+            oneclick::ZecDirection::Shield => {
+                oneclick::get_zec_quote(&params.asset, &amount, &recipient, &refund_to, overrides)
+                    .await
+            }
+            oneclick::ZecDirection::Unshield => {
                 oneclick::get_quote_from_zec(
                     &params.asset,
-                    &params.amount,
+                    &amount,
                     &recipient,
-                    &zec_refund,
+                    &refund_to,
+                    overrides,
                 )
                 .await
             }
+        };
+
+        let quote = match result {
+            Ok(quote) => quote,
+            Err(e) => return format!("Error: {}", e),
+        };
+        let correlation_id = match oneclick::register_pending_quote(
+            &quote,
+            direction,
+            &params.asset,
+            &amount,
+            &recipient,
+            &refund_to,
+            overrides,
+        ) {
+            Ok(id) => id,
+            Err(e) => return format!("Error: {}", e),
+        };
+        serde_json::to_string_pretty(&serde_json::json!({
+            "quote": quote,
+            "correlationId": correlation_id,
+            "confirmation": "Ask the user for the confirmation phrase shown in the Nyx app (Privacy page).",
+            "expiresInSecs": oneclick::PENDING_QUOTE_TTL_SECS,
+        }))
+        .unwrap_or_else(|_| "Failed to serialize quote".to_string())
+    }
+
+    /// Execute a previously quoted ZEC shield/unshield after explicit confirmation.
+    #[tool(description = "Execute a ZEC shield/unshield swap quoted by nyx_zec_quote. Pass the quote's 'correlation_id' and the exact 'confirmation' phrase the user read from the Nyx app (\"CONFIRM XXXXXXXX\"). Three wrong phrases drop the quote. The swap is refused if the current quote is worse than the approved one beyond the slippage guardrail. Only quotes from the last few minutes can be executed, each at most once. This moves real funds; only call it after the user has approved the quote.")]
+    async fn nyx_zec_execute(&self, Parameters(params): Parameters<ZecExecuteParams>) -> String {
+        match oneclick::execute_pending_quote(&params.correlation_id, &params.confirmation).await {
+            Ok(quote) => serde_json::to_string_pretty(&quote)
+                .unwrap_or_else(|_| "Failed to serialize swap".to_string()),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    /// Read or update the DeFi guardrails.
+    #[tool(description = "Read or update the DeFi guardrails (transaction, loss, slippage, concentration and health-factor limits). Action 'get' (default) returns the current guardrails; 'set' applies only the fields given, switches the preset to Custom, and returns the saved guardrails with restart_required. 'set' can only tighten limits (lower caps, higher min_health_factor, require_confirmation on); any change that loosens a limit is rejected and must be made by the user in Nyx Settings. Percentages must be 0-100 and max_transaction_usd positive; invalid values are rejected without writing.")]
+    async fn nyx_guardrails(&self, Parameters(params): Parameters<GuardrailsParams>) -> String {
+        let current = match config::read_current_config() {
+            Ok(settings) => settings.guardrails,
+            Err(e) => return format!("Error: {}", e),
+        };
+        match params.action.as_deref().unwrap_or("get") {
+            "get" => serde_json::to_string_pretty(&current)
+                .unwrap_or_else(|_| "Failed to serialize guardrails".to_string()),
+            "set" => match set_guardrails(current, params) {
+                Ok(value) => serde_json::to_string_pretty(&value)
+                    .unwrap_or_else(|_| "Failed to serialize guardrails".to_string()),
+                Err(e) => format!("Error: {}", e),
+            },
+            other => format!("Unknown action '{}'. Use 'get' or 'set'.", other),
+        }
+    }
+
+    /// Read recent output from an embedded terminal session.
+    #[tool(description = "Read the last lines of output from a Nyx embedded terminal (PTY) session, with ANSI escapes stripped. Omit session_id for the most recently active terminal. Returns the session id, command, whether it is still running, and the lines.")]
+    async fn nyx_pty_output(&self, Parameters(params): Parameters<PtyOutputParams>) -> String {
+        let lines = params.lines.unwrap_or(pty_output::DEFAULT_OUTPUT_LINES);
+        match pty_output::read_snapshot(params.session_id.as_deref(), lines) {
+            Ok(output) => serde_json::to_string_pretty(&output)
+                .unwrap_or_else(|_| "Failed to serialize terminal output".to_string()),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    /// Run, list, accept or dismiss Activity Intelligence suggestions.
+    #[tool(description = "Activity Intelligence suggestions. Action 'run' (default) runs the detectors now and returns newly created plus pending suggestions; 'list' returns pending ones; 'accept', 'dismiss' or 'snooze' (with an RFC 3339 'until') acts on a suggestion by id.")]
+    async fn nyx_suggestions(&self, Parameters(params): Parameters<SuggestionsParams>) -> String {
+        let result = match params.action.as_deref().unwrap_or("run") {
+            "run" => intelligence::run_suggestions_now()
+                .and_then(|run| serde_json::to_string_pretty(&run).map_err(|e| e.to_string())),
+            "list" => intelligence::get_suggestions()
+                .and_then(|s| serde_json::to_string_pretty(&s).map_err(|e| e.to_string())),
+            action @ ("accept" | "dismiss" | "snooze") => match (params.id, action) {
+                (None, _) => Err(format!("id required for action '{}'", action)),
+                (Some(id), "accept") => intelligence::accept_suggestion(id)
+                    .and_then(|s| serde_json::to_string_pretty(&s).map_err(|e| e.to_string())),
+                (Some(id), "dismiss") => intelligence::dismiss_suggestion(id)
+                    .map(|_| format!("Dismissed suggestion {}", id)),
+                (Some(id), _) => match params.until {
+                    Some(until) => intelligence::snooze_suggestion(id, &until)
+                        .map(|_| format!("Snoozed suggestion {} until {}", id, until)),
+                    None => Err("until required for action 'snooze'".to_string()),
+                },
+            },
             other => {
                 return format!(
-                    "Unknown direction '{}'. Use 'shield' or 'unshield'.",
+                    "Unknown action '{}'. Use 'run', 'list', 'accept', 'dismiss' or 'snooze'.",
                     other
-                );
+                )
             }
         };
+        result.unwrap_or_else(|e| format!("Error: {}", e))
+    }
 
-        match result {
-            Ok(quote) => serde_json::to_string_pretty(&quote)
-                .unwrap_or_else(|_| "Failed to serialize quote".to_string()),
+    /// On-chain balances for configured wallets or a given address.
+    #[tool(description = "Look up on-chain native balances (NEAR, ETH) via public RPC. With no address, returns every configured wallet's balance, optionally filtered by chain.")]
+    async fn nyx_wallet_balance(
+        &self,
+        Parameters(params): Parameters<WalletBalanceParams>,
+    ) -> String {
+        let chain = match params.chain.as_deref().map(parse_chain).transpose() {
+            Ok(c) => c,
+            Err(e) => return format!("Error: {}", e),
+        };
+        let result = match (params.address, chain) {
+            (Some(address), Some(chain)) => wallet::get_balance(&chain, &address)
+                .await
+                .and_then(|b| serde_json::to_string_pretty(&b).map_err(|e| e.to_string())),
+            (Some(_), None) => Err("chain required when address is given".to_string()),
+            (None, chain) => wallet::get_configured_balances(chain.as_ref())
+                .await
+                .and_then(|b| serde_json::to_string_pretty(&b).map_err(|e| e.to_string())),
+        };
+        result.unwrap_or_else(|e| format!("Error: {}", e))
+    }
+
+    /// List executed ZEC shield/unshield swaps.
+    #[tool(description = "List executed ZEC shield/unshield swaps, newest first, with assets, amounts, timestamp and last-known status.")]
+    async fn nyx_list_swaps(&self, Parameters(params): Parameters<ListSwapsParams>) -> String {
+        match oneclick::list_swaps(params.limit.unwrap_or(20)) {
+            Ok(swaps) => serde_json::to_string_pretty(&swaps)
+                .unwrap_or_else(|_| "Failed to serialize swaps".to_string()),
             Err(e) => format!("Error: {}", e),
         }
     }
 }
 
+/// Merge the fields given in `params` over `current`, validate, and save.
+fn set_guardrails(
+    current: config::GuardrailsConfig,
+    params: GuardrailsParams,
+) -> Result<serde_json::Value, String> {
+    let updated = match tightened_guardrails(&current, params) {
+        Ok(updated) => updated,
+        Err(GuardrailsRefusal::Invalid(problems)) => {
+            return Err(format!("Invalid guardrails: {}", problems.join("; ")))
+        }
+        Err(GuardrailsRefusal::Loosens(loosened)) => {
+            audit::record_audit_lossy(
+                "guardrails_update",
+                serde_json::json!({ "loosened": &loosened, "source": "mcp" }),
+                "rejected: loosens limits",
+            );
+            return Err(format!(
+                "Refusing to loosen {} via MCP; the user can relax guardrails in Nyx Settings",
+                loosened.join(", ")
+            ));
+        }
+    };
+    save_guardrails(current, updated)
+}
+
+/// Why a `set` was refused.
+#[derive(Debug, PartialEq)]
+enum GuardrailsRefusal {
+    /// `validate` problems
+    Invalid(Vec<String>),
+    /// Limits the update would relax
+    Loosens(Vec<&'static str>),
+}
+
+/// `current` with the fields given in `params` applied, if the result is
+/// valid and no looser than `current`. The agent may only tighten limits;
+/// relaxing them is for the user in Settings.
+fn tightened_guardrails(
+    current: &config::GuardrailsConfig,
+    params: GuardrailsParams,
+) -> Result<config::GuardrailsConfig, GuardrailsRefusal> {
+    let mut updated = current.clone();
+    if let Some(v) = params.max_transaction_usd {
+        updated.max_transaction_usd = v;
+    }
+    if let Some(v) = params.daily_loss_percent {
+        updated.daily_loss_percent = v;
+    }
+    if let Some(v) = params.weekly_loss_percent {
+        updated.weekly_loss_percent = v;
+    }
+    if let Some(v) = params.daily_tx_limit {
+        updated.daily_tx_limit = v;
+    }
+    if let Some(v) = params.require_confirmation {
+        updated.require_confirmation = v;
+    }
+    if let Some(v) = params.max_slippage_percent {
+        updated.max_slippage_percent = v;
+    }
+    if let Some(v) = params.max_concentration_percent {
+        updated.max_concentration_percent = v;
+    }
+    if let Some(v) = params.min_health_factor {
+        updated.min_health_factor = v;
+    }
+
+    let problems = updated.validate();
+    if !problems.is_empty() {
+        return Err(GuardrailsRefusal::Invalid(problems));
+    }
+    let loosened = current.loosened_by(&updated);
+    if !loosened.is_empty() {
+        return Err(GuardrailsRefusal::Loosens(loosened));
+    }
+    Ok(updated)
+}
+
+/// Save vetted guardrails as the Custom preset, unless nothing changed.
+fn save_guardrails(
+    current: config::GuardrailsConfig,
+    mut updated: config::GuardrailsConfig,
+) -> Result<serde_json::Value, String> {
+    let before = serde_json::to_value(&current).map_err(|e| e.to_string())?;
+    if serde_json::to_value(&updated).map_err(|e| e.to_string())? == before {
+        return Ok(serde_json::json!({
+            "guardrails": current,
+            "restart_required": false,
+            "message": "Guardrails unchanged.",
+        }));
+    }
+    updated.preset = config::SecurityPreset::Custom;
+
+    let result = config::save_settings(config::SettingsUpdate {
+        guardrails: Some(updated.clone()),
+        ..Default::default()
+    })?;
+    audit::record_audit_lossy(
+        "guardrails_update",
+        serde_json::json!({ "before": before, "after": &updated, "source": "mcp" }),
+        "ok",
+    );
+
+    Ok(serde_json::json!({
+        "guardrails": updated,
+        "restart_required": result.restart_required,
+        "message": result.message,
+    }))
+}
+
+/// "near" / "NEAR" -> Chain::NEAR, etc.
+fn parse_chain(chain: &str) -> Result<config::Chain, String> {
+    serde_json::from_value(serde_json::json!(chain.trim().to_uppercase()))
+        .map_err(|_| format!("Unknown chain '{}'", chain))
+}
+
+// ---------------------------------------------------------------------------
+// Tool manifest — what the server exposes, and what works right now
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ToolSummary {
+    pub name: String,
+    pub description: String,
+    pub available: bool,
+    /// Why the tool won't work with the current config (e.g. capability off)
+    pub unavailable_reason: Option<String>,
+}
+
+/// Config facts that decide whether each tool is functional.
+#[derive(Debug, Clone, Default)]
+pub struct ToolContext {
+    /// docker.env exists and parses (setup completed)
+    pub configured: bool,
+    pub capabilities: config::CapabilitiesConfig,
+    pub has_zec_address: bool,
+    /// At least one enabled channel is set to send-with-confirm
+    pub send_with_confirm: bool,
+}
+
+impl ToolContext {
+    /// Build the context from the on-disk config.
+    pub fn load() -> Self {
+        match config::read_current_config() {
+            Ok(settings) => {
+                let m = &settings.messaging;
+                let send_with_confirm = [&m.gmail, &m.whatsapp, &m.telegram, &m.slack, &m.signal]
+                    .iter()
+                    .any(|c| {
+                        c.enabled
+                            && matches!(c.autonomy, config::MessagingAutonomy::SendWithConfirm)
+                    });
+                ToolContext {
+                    configured: true,
+                    capabilities: settings.capabilities,
+                    has_zec_address: config::get_zec_address().is_some(),
+                    send_with_confirm,
+                }
+            }
+            Err(_) => ToolContext::default(),
+        }
+    }
+}
+
+/// Why `tool` can't work under `ctx`, or None if it should.
+pub fn tool_gate(tool: &str, ctx: &ToolContext) -> Option<String> {
+    if tool == "nyx_docker_status" || tool == "nyx_health" {
+        return None;
+    }
+    if !ctx.configured {
+        return Some("Nyx setup not completed".to_string());
+    }
+    let caps = &ctx.capabilities;
+    match tool {
+        "nyx_portfolio" if !caps.defi_crypto => Some("DeFi capability disabled".to_string()),
+        "nyx_zec_quote" | "nyx_zec_execute" | "nyx_list_swaps" | "nyx_guardrails"
+            if !caps.defi_crypto =>
+        {
+            Some("DeFi capability disabled".to_string())
+        }
+        "nyx_zec_quote" | "nyx_zec_execute" if !ctx.has_zec_address => Some("No ZEC wallet configured".to_string()),
+        "nyx_verify_source" | "nyx_verify_source_structured" if !caps.source_intelligence => {
+            Some("Source intelligence disabled".to_string())
+        }
+        "nyx_suggestions" if !caps.activity_intelligence => {
+            Some("Activity Intelligence disabled".to_string())
+        }
+        "nyx_draft_message" if !caps.communications => {
+            Some("Communications capability disabled".to_string())
+        }
+        "nyx_draft_message" if !ctx.send_with_confirm => {
+            Some("No channel set to send-with-confirm".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Every registered tool with its availability under `ctx`.
+pub fn tool_manifest_with(ctx: &ToolContext) -> Vec<ToolSummary> {
+    let mut tools: Vec<ToolSummary> = NyxMcpServer::tool_router()
+        .list_all()
+        .into_iter()
+        .map(|tool| {
+            let name = tool.name.to_string();
+            let unavailable_reason = tool_gate(&name, ctx);
+            ToolSummary {
+                description: tool.description.map(|d| d.to_string()).unwrap_or_default(),
+                available: unavailable_reason.is_none(),
+                unavailable_reason,
+                name,
+            }
+        })
+        .collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    tools
+}
+
+/// Every registered tool with its availability under the current config.
+pub fn tool_manifest() -> Vec<ToolSummary> {
+    tool_manifest_with(&ToolContext::load())
+}
+
 #[tool_handler]
 impl ServerHandler for NyxMcpServer {
     fn get_info(&self) -> ServerInfo {
@@ -197,7 +745,9 @@ impl ServerHandler for NyxMcpServer {
             instructions: Some(
                 "Nyx is a private AI chief of staff. Tools include chatting with the agent, \
                  DeFi portfolio data, source credibility analysis, Docker container status, \
-                 session management, and ZEC privacy shield quotes."
+                 system health, session management, send-with-confirm message drafts, ZEC \
+                 privacy shield quotes with confirmed execution, swap history, DeFi guardrail \
+                 settings, wallet balances, embedded terminal output, and Activity Intelligence suggestions."
                     .to_string(),
             ),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
@@ -205,3 +755,54 @@ impl ServerHandler for NyxMcpServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(fields: serde_json::Value) -> GuardrailsParams {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    #[test]
+    fn guardrails_set_rejects_loosening() {
+        let current = config::GuardrailsConfig::from_preset(config::SecurityPreset::Conservative);
+        let refusal = tightened_guardrails(
+            &current,
+            params(serde_json::json!({
+                "action": "set",
+                "max_transaction_usd": 5000.0,
+                "max_slippage_percent": 0.5,
+                "min_health_factor": 1.2,
+                "require_confirmation": false,
+            })),
+        )
+        .unwrap_err();
+        assert_eq!(
+            refusal,
+            GuardrailsRefusal::Loosens(vec![
+                "max_transaction_usd",
+                "min_health_factor",
+                "require_confirmation",
+            ])
+        );
+    }
+
+    #[test]
+    fn guardrails_set_allows_tightening_and_validates() {
+        let current = config::GuardrailsConfig::from_preset(config::SecurityPreset::Balanced);
+        let updated = tightened_guardrails(
+            &current,
+            params(serde_json::json!({ "max_transaction_usd": 50.0, "require_confirmation": true })),
+        )
+        .unwrap();
+        assert_eq!(updated.max_transaction_usd, 50.0);
+        assert!(updated.require_confirmation);
+        assert_eq!(updated.daily_tx_limit, current.daily_tx_limit);
+
+        let refusal =
+            tightened_guardrails(&current, params(serde_json::json!({ "daily_tx_limit": 0 })))
+                .unwrap_err();
+        assert!(matches!(refusal, GuardrailsRefusal::Invalid(p) if p.len() == 1));
+    }
+}
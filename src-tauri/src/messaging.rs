@@ -0,0 +1,189 @@
+// ---------------------------------------------------------------------------
+// Pending outbound messages — the confirm step for SendWithConfirm channels
+// ---------------------------------------------------------------------------
+// Drafts for a channel set to SendWithConfirm are queued here instead of
+// being sent. The UI lists the queue; only approve_message hands the draft
+// to the agent gateway for delivery. Rejected drafts are dropped.
+// ---------------------------------------------------------------------------
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::audit;
+use crate::config::{self, MessagingAutonomy};
+use crate::gateway;
+
+/// Session used to deliver approved messages (keeps them out of chat history).
+const DELIVERY_SESSION: &str = "agent:default:outbox";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMessage {
+    pub id: String,
+    /// Channel name: gmail, whatsapp, telegram, slack or signal
+    pub channel: String,
+    pub recipient: String,
+    pub body: String,
+    /// Optional subject line (email only)
+    pub subject: Option<String>,
+    /// Session the draft originated from, if any
+    pub session_key: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// Serialises read-modify-write of the queue file in this process, so a
+/// double-clicked approve can't take the same draft twice.
+static PENDING_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn pending_path() -> PathBuf {
+    config::openclaw_home().join("agents/default/pending_messages.json")
+}
+
+fn load_pending() -> Vec<PendingMessage> {
+    fs::read_to_string(pending_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_pending(messages: &[PendingMessage]) -> Result<(), String> {
+    let path = pending_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create agent directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(messages)
+        .map_err(|e| format!("Failed to serialize pending messages: {}", e))?;
+    config::write_atomic(&path, content).map_err(|e| format!("Failed to write pending messages: {}", e))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Queue a drafted message for confirmation. Only channels that are enabled
+/// and set to SendWithConfirm accept drafts.
+pub fn queue_message(
+    channel: String,
+    recipient: String,
+    body: String,
+    subject: Option<String>,
+    session_key: Option<String>,
+) -> Result<PendingMessage, String> {
+    let settings = config::read_current_config()?;
+    let cfg = settings
+        .messaging
+        .channel(&channel)
+        .ok_or_else(|| format!("Unknown messaging channel '{}'", channel))?;
+    if !cfg.enabled {
+        return Err(format!("Messaging channel '{}' is disabled", channel));
+    }
+    if !matches!(cfg.autonomy, MessagingAutonomy::SendWithConfirm) {
+        return Err(format!(
+            "Channel '{}' is not set to send-with-confirm ({:?})",
+            channel, cfg.autonomy
+        ));
+    }
+    if recipient.trim().is_empty() || body.trim().is_empty() {
+        return Err("Recipient and message body are required".to_string());
+    }
+
+    let message = PendingMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        channel,
+        recipient,
+        body,
+        subject,
+        session_key,
+        created_at: now_secs(),
+    };
+
+    put_pending(message.clone())?;
+    Ok(message)
+}
+
+/// Add a draft to the queue.
+fn put_pending(message: PendingMessage) -> Result<(), String> {
+    let _guard = PENDING_LOCK
+        .lock()
+        .map_err(|e| format!("Pending message lock poisoned: {}", e))?;
+    let mut pending = load_pending();
+    pending.push(message);
+    save_pending(&pending)
+}
+
+/// All drafts awaiting confirmation, oldest first.
+pub fn list_pending_messages() -> Vec<PendingMessage> {
+    let mut pending = load_pending();
+    pending.sort_by_key(|m| m.created_at);
+    pending
+}
+
+/// Remove a draft from the queue and return it.
+fn take_pending(id: &str) -> Result<PendingMessage, String> {
+    let _guard = PENDING_LOCK
+        .lock()
+        .map_err(|e| format!("Pending message lock poisoned: {}", e))?;
+    let mut pending = load_pending();
+    let idx = pending
+        .iter()
+        .position(|m| m.id == id)
+        .ok_or_else(|| format!("No pending message with id '{}'", id))?;
+    let message = pending.remove(idx);
+    save_pending(&pending)?;
+    Ok(message)
+}
+
+fn delivery_prompt(m: &PendingMessage) -> String {
+    let subject = m
+        .subject
+        .as_deref()
+        .map(|s| format!("Subject: {}\n", s))
+        .unwrap_or_default();
+    format!(
+        "The user has approved the following {} message. Send it now exactly as written, \
+         without changes, then reply with a one-line confirmation.\n\n\
+         To: {}\n{}\n{}",
+        m.channel, m.recipient, subject, m.body
+    )
+}
+
+/// Approve a draft: take it out of the queue, then send it via the gateway.
+/// Taking it first means a second approve can't send it again while the
+/// first is in flight. If delivery fails the draft is put back so it can be
+/// retried.
+pub async fn approve_message(id: &str) -> Result<String, String> {
+    let message = take_pending(id)?;
+
+    let result =
+        gateway::send_message_to_session(delivery_prompt(&message), DELIVERY_SESSION.to_string())
+            .await;
+
+    audit::record_audit_lossy(
+        "message_sent",
+        serde_json::json!({
+            "id": message.id,
+            "channel": message.channel,
+            "recipient": message.recipient,
+        }),
+        result.as_ref().map_or_else(|e| e.as_str(), |_| "ok"),
+    );
+
+    match result {
+        Ok(reply) => Ok(reply),
+        Err(e) => match put_pending(message) {
+            Ok(()) => Err(e),
+            Err(restore) => Err(format!("{} (and the draft could not be re-queued: {})", e, restore)),
+        },
+    }
+}
+
+/// Reject a draft: remove it from the queue without sending.
+pub fn reject_message(id: &str) -> Result<(), String> {
+    take_pending(id).map(|_| ())
+}
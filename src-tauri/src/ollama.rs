@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::process::Command;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use crate::config;
 
 const OLLAMA_BASE_URL: &str = "http://localhost:11434";
 
@@ -21,6 +26,15 @@ pub struct OllamaModel {
     pub quantization: String,
 }
 
+/// One progress line from a streaming `/api/pull`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PullProgress {
+    pub status: String,
+    /// Bytes downloaded of the current layer (absent for non-download steps)
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ChatMessage {
     pub role: String,
@@ -33,7 +47,7 @@ pub struct ChatMessage {
 
 /// Check if Ollama is running on localhost:11434.
 pub async fn check_ollama() -> Result<OllamaStatus, String> {
-    let client = reqwest::Client::builder()
+    let client = config::http_client_builder(config::HttpDestination::Local)?
         .timeout(std::time::Duration::from_secs(3))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
@@ -71,15 +85,57 @@ pub async fn check_ollama() -> Result<OllamaStatus, String> {
 // Installation
 // ---------------------------------------------------------------------------
 
-/// Download and install Ollama from the official macOS zip.
+/// Download and install Ollama for the current platform, start it, and
+/// report whether the server came up.
 pub async fn install_ollama() -> Result<String, String> {
-    let url = "https://ollama.com/download/Ollama-darwin.zip";
-    let tmp_zip = "/tmp/Ollama-darwin.zip";
-    let tmp_unzip_dir = "/tmp/Ollama-unzipped";
+    install_platform()?;
 
-    // 1. Download
+    // Brief wait then verify via HTTP
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    let client = config::http_client_builder(config::HttpDestination::Local)?
+        .timeout(std::time::Duration::from_secs(3))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    match client.get(OLLAMA_BASE_URL).send().await {
+        Ok(resp) => {
+            let text = resp.text().await.unwrap_or_default();
+            if text.contains("Ollama") {
+                Ok("Ollama installed and running".to_string())
+            } else {
+                Ok("Ollama installed — starting up...".to_string())
+            }
+        }
+        Err(_) => Ok("Ollama installed — please wait for it to finish starting.".to_string()),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn install_platform() -> Result<(), String> {
+    install_macos()
+}
+
+#[cfg(target_os = "linux")]
+fn install_platform() -> Result<(), String> {
+    install_linux()
+}
+
+#[cfg(target_os = "windows")]
+fn install_platform() -> Result<(), String> {
+    install_windows()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn install_platform() -> Result<(), String> {
+    Err("Ollama install is not supported on this platform".to_string())
+}
+
+/// Download `url` to `dest` with curl.
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+fn download(url: &str, dest: &str) -> Result<(), String> {
     let output = Command::new("curl")
-        .args(["-fSL", "--progress-bar", "-o", tmp_zip, url])
+        .args(["-fSL", "--progress-bar", "-o", dest, url])
         .output()
         .map_err(|e| format!("Failed to download Ollama: {}", e))?;
 
@@ -87,6 +143,18 @@ pub async fn install_ollama() -> Result<String, String> {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(format!("Download failed: {}", stderr));
     }
+    Ok(())
+}
+
+/// macOS: official zip, copied to /Applications and launched.
+#[cfg(target_os = "macos")]
+fn install_macos() -> Result<(), String> {
+    let url = "https://ollama.com/download/Ollama-darwin.zip";
+    let tmp_zip = "/tmp/Ollama-darwin.zip";
+    let tmp_unzip_dir = "/tmp/Ollama-unzipped";
+
+    // 1. Download
+    download(url, tmp_zip)?;
 
     // 2. Unzip — clean up any previous extraction first
     let _ = std::fs::remove_dir_all(tmp_unzip_dir);
@@ -128,26 +196,73 @@ pub async fn install_ollama() -> Result<String, String> {
     let _ = Command::new("open")
         .args(["/Applications/Ollama.app"])
         .output();
+    Ok(())
+}
 
-    // 6. Brief wait then verify via HTTP
-    std::thread::sleep(std::time::Duration::from_secs(3));
+/// Linux: release tarball unpacked into ~/.local (no root needed), then
+/// `ollama serve` started in the background.
+#[cfg(target_os = "linux")]
+fn install_linux() -> Result<(), String> {
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        other => return Err(format!("Unsupported CPU architecture for Ollama: {}", other)),
+    };
+    let url = format!("https://ollama.com/download/ollama-linux-{}.tgz", arch);
+    let tmp_tgz = "/tmp/ollama-linux.tgz";
+
+    // Real HOME, not NYX_HOME: the binary belongs to the user's own PATH
+    let home = std::env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+    let prefix = std::path::Path::new(&home).join(".local");
+    std::fs::create_dir_all(&prefix)
+        .map_err(|e| format!("Failed to create {}: {}", prefix.display(), e))?;
+
+    download(&url, tmp_tgz)?;
+
+    // The tarball contains bin/ollama and lib/ollama/
+    let output = Command::new("tar")
+        .args(["-xzf", tmp_tgz, "-C"])
+        .arg(&prefix)
+        .output()
+        .map_err(|e| format!("Failed to extract Ollama: {}", e))?;
+    let _ = std::fs::remove_file(tmp_tgz);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(3))
-        .build()
-        .map_err(|e| format!("HTTP client error: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Extract failed: {}", stderr));
+    }
 
-    match client.get(OLLAMA_BASE_URL).send().await {
-        Ok(resp) => {
-            let text = resp.text().await.unwrap_or_default();
-            if text.contains("Ollama") {
-                Ok("Ollama installed and running".to_string())
-            } else {
-                Ok("Ollama installed — starting up...".to_string())
-            }
-        }
-        Err(_) => Ok("Ollama installed — please wait for it to finish starting.".to_string()),
+    let binary = prefix.join("bin/ollama");
+    Command::new(&binary)
+        .arg("serve")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", binary.display(), e))?;
+    Ok(())
+}
+
+/// Windows: official setup executable, run silently. The installer starts
+/// the Ollama tray app (and server) when it finishes.
+#[cfg(target_os = "windows")]
+fn install_windows() -> Result<(), String> {
+    let url = "https://ollama.com/download/OllamaSetup.exe";
+    let installer = std::env::temp_dir().join("OllamaSetup.exe");
+    let installer_str = installer.to_string_lossy().to_string();
+
+    download(url, &installer_str)?;
+
+    let status = Command::new(&installer)
+        .args(["/VERYSILENT", "/NORESTART", "/SUPPRESSMSGBOXES"])
+        .status()
+        .map_err(|e| format!("Failed to run Ollama installer: {}", e))?;
+    let _ = std::fs::remove_file(&installer);
+
+    if !status.success() {
+        return Err(format!("Ollama installer exited with {}", status));
     }
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -156,7 +271,7 @@ pub async fn install_ollama() -> Result<String, String> {
 
 /// List locally installed Ollama models.
 pub async fn list_models() -> Result<Vec<OllamaModel>, String> {
-    let client = reqwest::Client::builder()
+    let client = config::http_client_builder(config::HttpDestination::Local)?
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
@@ -205,10 +320,36 @@ pub async fn list_models() -> Result<Vec<OllamaModel>, String> {
     Ok(models)
 }
 
+/// Whether `installed` (an `/api/tags` name) is `model`; untagged names mean `:latest`.
+fn model_matches(installed: &str, model: &str) -> bool {
+    installed == model || (!model.contains(':') && installed == format!("{}:latest", model))
+}
+
+/// Check that `model` is installed locally. With `auto_pull`, a missing
+/// model is pulled instead of rejected.
+pub async fn ensure_model_installed(model: &str, auto_pull: bool) -> Result<(), String> {
+    let models = list_models().await.map_err(|e| {
+        format!(
+            "Can't check Ollama models ({}). Start Ollama or choose another provider.",
+            e
+        )
+    })?;
+    if models.iter().any(|m| model_matches(&m.name, model)) {
+        return Ok(());
+    }
+    if auto_pull {
+        return pull_model(model.to_string()).await.map(|_| ());
+    }
+    Err(format!(
+        "Ollama model '{}' isn't installed. Download it under Local Models first, or save with auto-pull.",
+        model
+    ))
+}
+
 /// Pull (download) a model from the Ollama library.
 /// This blocks until the download is complete — models can be 2-8GB.
 pub async fn pull_model(model: String) -> Result<String, String> {
-    let client = reqwest::Client::builder()
+    let client = config::http_client_builder(config::HttpDestination::Local)?
         .timeout(std::time::Duration::from_secs(1800)) // 30 min max
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
@@ -238,9 +379,82 @@ pub async fn pull_model(model: String) -> Result<String, String> {
     }
 }
 
+/// Longest gap allowed between progress lines while pulling.
+const PULL_IDLE_TIMEOUT_SECS: u64 = 120;
+
+/// Pull a model with `stream: true`, calling `on_progress` for each NDJSON
+/// progress line. Completes on the final `{"status":"success"}` line.
+pub async fn pull_model_stream<F>(model: String, mut on_progress: F) -> Result<String, String>
+where
+    F: FnMut(&PullProgress),
+{
+    let client = config::http_client_builder(config::HttpDestination::Local)?
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let body = serde_json::json!({
+        "name": model,
+        "stream": true
+    });
+
+    let mut resp = client
+        .post(format!("{}/api/pull", OLLAMA_BASE_URL))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to pull model: {}", e))?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("Pull failed ({}): {}", status, text));
+    }
+
+    let idle = std::time::Duration::from_secs(PULL_IDLE_TIMEOUT_SECS);
+    // Bytes after the last newline — a line can span chunk boundaries
+    let mut pending: Vec<u8> = Vec::new();
+
+    loop {
+        let chunk = tokio::time::timeout(idle, resp.chunk())
+            .await
+            .map_err(|_| format!("Pull stalled: no progress for {}s", PULL_IDLE_TIMEOUT_SECS))?
+            .map_err(|e| format!("Failed to read pull response: {}", e))?;
+        let chunk = match chunk {
+            Some(c) => c,
+            None => return Err("Pull ended before Ollama reported success".to_string()),
+        };
+        pending.extend_from_slice(&chunk);
+
+        while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            let json: serde_json::Value = match serde_json::from_slice(&line) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
+                return Err(format!("Pull failed: {}", error));
+            }
+            let progress = PullProgress {
+                status: json
+                    .get("status")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                completed: json.get("completed").and_then(|v| v.as_u64()),
+                total: json.get("total").and_then(|v| v.as_u64()),
+            };
+            on_progress(&progress);
+            if progress.status == "success" {
+                return Ok("ok".to_string());
+            }
+        }
+    }
+}
+
 /// Delete a locally installed model.
 pub async fn delete_model(model: String) -> Result<String, String> {
-    let client = reqwest::Client::builder()
+    let client = config::http_client_builder(config::HttpDestination::Local)?
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
@@ -273,7 +487,7 @@ pub async fn chat_ollama(
     message: String,
     history: Vec<ChatMessage>,
 ) -> Result<String, String> {
-    let client = reqwest::Client::builder()
+    let client = config::http_client_builder(config::HttpDestination::Local)?
         .timeout(std::time::Duration::from_secs(300)) // 5 min max for generation
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
@@ -331,21 +545,222 @@ pub async fn chat_ollama(
     }
 }
 
+/// Cancel signals for in-flight streaming chats, keyed by request id.
+static CHAT_CANCELS: std::sync::LazyLock<Mutex<HashMap<String, Arc<Notify>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Removes the request's cancel entry however the stream ends.
+struct CancelGuard(String);
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Ok(mut cancels) = CHAT_CANCELS.lock() {
+            cancels.remove(&self.0);
+        }
+    }
+}
+
+/// Longest gap allowed between streamed tokens.
+const CHAT_IDLE_TIMEOUT_SECS: u64 = 120;
+
+/// Stream a chat reply, calling `on_token` for each content delta. Returns
+/// the full reply, or the text generated so far if `cancel_chat(request_id)`
+/// was called — the response is dropped on cancel, which closes the
+/// connection and makes Ollama stop generating.
+pub async fn chat_ollama_stream<F>(
+    model: String,
+    message: String,
+    history: Vec<ChatMessage>,
+    request_id: String,
+    mut on_token: F,
+) -> Result<String, String>
+where
+    F: FnMut(&str),
+{
+    let cancel = Arc::new(Notify::new());
+    CHAT_CANCELS
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .insert(request_id.clone(), cancel.clone());
+    let _guard = CancelGuard(request_id);
+
+    let client = config::http_client_builder(config::HttpDestination::Local)?
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let mut messages: Vec<serde_json::Value> = history
+        .iter()
+        .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+        .collect();
+    messages.push(serde_json::json!({ "role": "user", "content": message }));
+
+    let body = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": true
+    });
+
+    let request = client
+        .post(format!("{}/api/chat", OLLAMA_BASE_URL))
+        .json(&body)
+        .send();
+    let mut resp = tokio::select! {
+        r = request => r.map_err(|e| format!("Ollama chat failed: {}", e))?,
+        _ = cancel.notified() => return Ok(String::new()),
+    };
+
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        let error = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|j| j.get("error").and_then(|e| e.as_str()).map(String::from))
+            .unwrap_or(text);
+        return Err(format!("Ollama error: {}", error));
+    }
+
+    let idle = std::time::Duration::from_secs(CHAT_IDLE_TIMEOUT_SECS);
+    let mut text = String::new();
+    // Bytes after the last newline — a line can span chunk boundaries
+    let mut pending: Vec<u8> = Vec::new();
+
+    loop {
+        let chunk = tokio::select! {
+            c = tokio::time::timeout(idle, resp.chunk()) => c
+                .map_err(|_| format!("Ollama stopped responding for {}s", CHAT_IDLE_TIMEOUT_SECS))?
+                .map_err(|e| format!("Failed to read Ollama stream: {}", e))?,
+            _ = cancel.notified() => return Ok(text),
+        };
+        let chunk = match chunk {
+            Some(c) => c,
+            None => return Ok(text),
+        };
+        pending.extend_from_slice(&chunk);
+
+        while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            let json: serde_json::Value = match serde_json::from_slice(&line) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
+                return Err(format!("Ollama error: {}", error));
+            }
+            if let Some(token) = json
+                .pointer("/message/content")
+                .and_then(|c| c.as_str())
+                .filter(|t| !t.is_empty())
+            {
+                on_token(token);
+                text.push_str(token);
+            }
+            if json.get("done").and_then(|d| d.as_bool()) == Some(true) {
+                return Ok(text);
+            }
+        }
+    }
+}
+
+/// Stop a streaming chat started with `request_id`. Returns false if it
+/// already finished.
+pub fn cancel_chat(request_id: &str) -> bool {
+    match CHAT_CANCELS.lock() {
+        Ok(cancels) => match cancels.get(request_id) {
+            Some(cancel) => {
+                // notify_one stores a permit, so a cancel that lands between
+                // awaits is still seen
+                cancel.notify_one();
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // System Info
 // ---------------------------------------------------------------------------
 
-/// Get total system RAM in GB (macOS via sysctl).
+/// Get total system RAM in GB.
 pub async fn get_system_ram() -> Result<u64, String> {
+    total_ram_bytes().map(|bytes| bytes / (1024 * 1024 * 1024)) // Convert bytes to GB
+}
+
+/// macOS: `sysctl hw.memsize`.
+#[cfg(target_os = "macos")]
+fn total_ram_bytes() -> Result<u64, String> {
     let output = Command::new("sysctl")
         .args(["-n", "hw.memsize"])
         .output()
         .map_err(|e| format!("Failed to run sysctl: {}", e))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    let bytes: u64 = stdout
+    stdout
+        .parse()
+        .map_err(|e| format!("Failed to parse RAM size: {}", e))
+}
+
+/// Linux: `MemTotal` from /proc/meminfo (reported in kB).
+#[cfg(target_os = "linux")]
+fn total_ram_bytes() -> Result<u64, String> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo")
+        .map_err(|e| format!("Failed to read /proc/meminfo: {}", e))?;
+    let kb: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or_else(|| "MemTotal not found in /proc/meminfo".to_string())?
         .parse()
         .map_err(|e| format!("Failed to parse RAM size: {}", e))?;
+    Ok(kb * 1024)
+}
+
+/// Windows: `GlobalMemoryStatusEx` from kernel32.
+#[cfg(target_os = "windows")]
+fn total_ram_bytes() -> Result<u64, String> {
+    #[repr(C)]
+    struct MemoryStatusEx {
+        length: u32,
+        memory_load: u32,
+        total_phys: u64,
+        avail_phys: u64,
+        total_page_file: u64,
+        avail_page_file: u64,
+        total_virtual: u64,
+        avail_virtual: u64,
+        avail_extended_virtual: u64,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GlobalMemoryStatusEx(buffer: *mut MemoryStatusEx) -> i32;
+    }
+
+    let mut status = MemoryStatusEx {
+        length: std::mem::size_of::<MemoryStatusEx>() as u32,
+        memory_load: 0,
+        total_phys: 0,
+        avail_phys: 0,
+        total_page_file: 0,
+        avail_page_file: 0,
+        total_virtual: 0,
+        avail_virtual: 0,
+        avail_extended_virtual: 0,
+    };
+    // SAFETY: `status` is a correctly sized MEMORYSTATUSEX with dwLength set.
+    let ok = unsafe { GlobalMemoryStatusEx(&mut status) };
+    if ok == 0 {
+        return Err(format!(
+            "GlobalMemoryStatusEx failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(status.total_phys)
+}
 
-    Ok(bytes / (1024 * 1024 * 1024)) // Convert bytes to GB
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn total_ram_bytes() -> Result<u64, String> {
+    Err("RAM detection is not supported on this platform".to_string())
 }
@@ -1,7 +1,22 @@
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::audit;
+use crate::config;
+
 const ONECLICK_BASE_URL: &str = "https://1click.chaindefuser.com/v0";
+/// Applies to every 1Click request (tokens, quote, status).
+const ONECLICK_TIMEOUT_SECS: u64 = 30;
+
+/// HTTP client shared by all 1Click calls, so timeout and user-agent stay consistent.
+fn oneclick_client() -> Result<reqwest::Client, String> {
+    config::http_client_builder(config::HttpDestination::Remote)?
+        .timeout(std::time::Duration::from_secs(ONECLICK_TIMEOUT_SECS))
+        .user_agent(concat!("nyx/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))
+}
 
 // ---------------------------------------------------------------------------
 // Types — updated for 1Click API v2 schema (2026-02)
@@ -21,11 +36,34 @@ pub struct TokenInfo {
     pub price: Option<f64>,
 }
 
+/// How the quote's `amount` is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SwapType {
+    /// `amount` is the origin amount; the output floats with price/slippage.
+    #[default]
+    ExactInput,
+    /// `amount` is the destination amount (e.g. exactly 1 ZEC shielded);
+    /// the required input is quoted in `amount_in`.
+    ExactOutput,
+}
+
+impl SwapType {
+    /// Parse "EXACT_INPUT" / "EXACT_OUTPUT" (case-insensitive).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_uppercase().as_str() {
+            "EXACT_INPUT" => Ok(SwapType::ExactInput),
+            "EXACT_OUTPUT" => Ok(SwapType::ExactOutput),
+            other => Err(format!("Unknown swap type '{}'. Use EXACT_INPUT or EXACT_OUTPUT.", other)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QuoteRequest {
     pub dry: bool,
-    pub swap_type: String,
+    pub swap_type: SwapType,
     pub slippage_tolerance: u32,
     pub origin_asset: String,
     pub deposit_type: String,
@@ -45,6 +83,64 @@ pub struct QuoteResponse {
     pub signature: Option<String>,
     pub timestamp: Option<String>,
     pub correlation_id: Option<String>,
+    /// Display-ready amounts for the confirmation dialog (filled locally)
+    #[serde(default)]
+    pub summary: Option<QuoteSummary>,
+    /// The request deadline, RFC 3339 (filled locally)
+    #[serde(default)]
+    pub deadline: Option<String>,
+    /// Seconds left before `deadline` when the quote was returned (filled locally)
+    #[serde(default)]
+    pub valid_for_secs: Option<u64>,
+    /// What was requested, so `refresh_quote` can ask again (filled locally)
+    #[serde(default)]
+    pub params: Option<QuoteParams>,
+}
+
+/// The inputs of a quote request, kept on the response for refreshing.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteParams {
+    pub origin_asset: String,
+    pub destination_asset: String,
+    pub amount: String,
+    pub recipient: String,
+    pub refund_to: String,
+    pub dry_run: bool,
+    pub options: QuoteOptions,
+}
+
+impl QuoteResponse {
+    /// When the quote's deadline passes, if known.
+    pub fn expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let deadline = self.deadline.as_deref()?;
+        chrono::DateTime::parse_from_rfc3339(deadline)
+            .ok()
+            .map(|d| d.with_timezone(&chrono::Utc))
+    }
+
+    /// Seconds until the deadline (0 once passed), if known.
+    pub fn seconds_remaining(&self) -> Option<u64> {
+        let left = self.expires_at()? - chrono::Utc::now();
+        Some(left.num_seconds().max(0) as u64)
+    }
+
+    /// True once the deadline has passed. Quotes without one never expire.
+    pub fn is_expired(&self) -> bool {
+        self.seconds_remaining() == Some(0)
+    }
+}
+
+/// Human-readable amounts for a shield/unshield confirmation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteSummary {
+    pub amount_in: String,
+    pub amount_in_usd: String,
+    pub amount_out: String,
+    pub amount_out_usd: String,
+    pub min_amount_out: String,
+    pub time_estimate_secs: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -87,18 +183,245 @@ pub struct SwapStatus {
     pub amount_out: Option<String>,
 }
 
+/// Swap lifecycle state reported by `/status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapState {
+    PendingDeposit,
+    KnownDepositTx,
+    IncompleteDeposit,
+    Processing,
+    Success,
+    Refunded,
+    Failed,
+    /// Given up on locally: polling was abandoned (see `resume_pending_swaps`)
+    Unknown,
+    /// Any status string this client doesn't recognise
+    Other(String),
+}
+
+impl SwapState {
+    pub fn parse(status: &str) -> Self {
+        match status.to_ascii_uppercase().as_str() {
+            "PENDING_DEPOSIT" => SwapState::PendingDeposit,
+            "KNOWN_DEPOSIT_TX" => SwapState::KnownDepositTx,
+            "INCOMPLETE_DEPOSIT" => SwapState::IncompleteDeposit,
+            "PROCESSING" => SwapState::Processing,
+            "SUCCESS" => SwapState::Success,
+            "REFUNDED" => SwapState::Refunded,
+            "FAILED" => SwapState::Failed,
+            SWAP_STATUS_UNKNOWN => SwapState::Unknown,
+            _ => SwapState::Other(status.to_string()),
+        }
+    }
+
+    /// The swap will not change state again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            SwapState::Success | SwapState::Refunded | SwapState::Failed | SwapState::Unknown
+        )
+    }
+}
+
+impl SwapStatus {
+    pub fn state(&self) -> SwapState {
+        SwapState::parse(&self.status)
+    }
+}
+
+/// Structured 1Click API error parsed from an error response body.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OneClickError {
+    pub status: u16,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for OneClickError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match friendly_error_message(self) {
+            Some(friendly) => write!(f, "{} ({})", friendly, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Parse a 1Click error body. Accepts `{code, message}`, `{error, message}`
+/// and `{message: [..]}` shapes; falls back to the raw body otherwise.
+pub fn parse_error(status: u16, body: &str) -> OneClickError {
+    let raw = || OneClickError {
+        status,
+        code: None,
+        message: if body.trim().is_empty() {
+            format!("HTTP {}", status)
+        } else {
+            body.trim().to_string()
+        },
+    };
+
+    let json = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(v) if v.is_object() => v,
+        _ => return raw(),
+    };
+
+    let code = json
+        .get("code")
+        .or_else(|| json.get("error"))
+        .and_then(|v| match v {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        });
+
+    let message = match json.get("message") {
+        Some(serde_json::Value::String(s)) => Some(s.clone()),
+        Some(serde_json::Value::Array(items)) => Some(
+            items
+                .iter()
+                .filter_map(|i| i.as_str())
+                .collect::<Vec<_>>()
+                .join("; "),
+        ),
+        _ => None,
+    };
+
+    match (code, message) {
+        (code, Some(message)) if !message.is_empty() => OneClickError { status, code, message },
+        (Some(code), _) => OneClickError { status, message: code.clone(), code: Some(code) },
+        _ => raw(),
+    }
+}
+
+/// Map well-known 1Click failures to a message the UI can show directly.
+fn friendly_error_message(err: &OneClickError) -> Option<&'static str> {
+    let haystack = format!(
+        "{} {}",
+        err.code.as_deref().unwrap_or_default(),
+        err.message
+    )
+    .to_lowercase();
+
+    if haystack.contains("too low") || haystack.contains("minimum") {
+        Some("Amount is below the minimum for this route")
+    } else if haystack.contains("not supported")
+        || haystack.contains("unsupported")
+        || haystack.contains("unknown asset")
+    {
+        Some("This asset or route is not supported by 1Click")
+    } else if haystack.contains("liquidity") || haystack.contains("no quote") {
+        Some("No route available for this swap right now")
+    } else if haystack.contains("deadline") {
+        Some("Quote deadline is invalid or has passed")
+    } else if haystack.contains("recipient") || haystack.contains("refund") {
+        Some("Recipient or refund address was rejected")
+    } else if err.status == 401 || err.status == 403 {
+        Some("1Click rejected the request as unauthorized")
+    } else if err.status == 429 {
+        Some("1Click rate limit reached, try again shortly")
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Quote options
+// ---------------------------------------------------------------------------
+
+/// Deadline for generic quotes.
+pub const DEFAULT_DEADLINE_MINS: u32 = 10;
+/// Shielded ZEC transactions confirm slowly; the generic 10 minutes causes
+/// avoidable refunds.
+pub const ZEC_DEFAULT_DEADLINE_MINS: u32 = 30;
+const MIN_DEADLINE_MINS: u32 = 5;
+const MAX_DEADLINE_MINS: u32 = 120;
+
+/// Caller overrides for slippage, deadline and swap type. `None` uses the defaults.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct QuoteOverrides {
+    pub slippage_bps: Option<u32>,
+    pub deadline_mins: Option<u32>,
+    pub swap_type: Option<SwapType>,
+}
+
+/// Effective slippage/deadline/swap type sent in a quote request.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteOptions {
+    pub slippage_bps: u32,
+    pub deadline_mins: u32,
+    pub swap_type: SwapType,
+}
+
+/// Convert a slippage percentage to basis points (0.5% -> 50). Minimum 1 bps.
+pub fn slippage_percent_to_bps(percent: f64) -> u32 {
+    if !percent.is_finite() {
+        return 1;
+    }
+    (percent * 100.0).round().max(1.0) as u32
+}
+
+/// Slippage from the configured guardrails (`max_slippage_percent`), in bps.
+/// An unreadable config is an error: quoting without the user's limit could
+/// allow more slippage than they set.
+fn guardrail_slippage_bps() -> Result<u32, String> {
+    let guardrails = read_guardrails()?;
+    Ok(slippage_percent_to_bps(guardrails.max_slippage_percent))
+}
+
+/// The configured guardrails. Swaps are refused when they can't be read.
+fn read_guardrails() -> Result<config::GuardrailsConfig, String> {
+    config::read_current_config()
+        .map(|c| c.guardrails)
+        .map_err(|e| format!("Can't read your guardrails, so the swap was refused: {}", e))
+}
+
+impl QuoteOverrides {
+    /// Apply defaults: slippage from the override or `guardrail_bps`, and
+    /// the deadline clamped to 5–120 minutes.
+    pub fn resolve(&self, default_deadline_mins: u32, guardrail_bps: u32) -> QuoteOptions {
+        QuoteOptions {
+            slippage_bps: self.slippage_bps.unwrap_or(guardrail_bps).max(1),
+            deadline_mins: self
+                .deadline_mins
+                .unwrap_or(default_deadline_mins)
+                .clamp(MIN_DEADLINE_MINS, MAX_DEADLINE_MINS),
+            swap_type: self.swap_type.unwrap_or_default(),
+        }
+    }
+
+    /// Resolve for a generic quote. An explicit slippage overrides the guardrail.
+    pub fn resolve_generic(&self) -> Result<QuoteOptions, String> {
+        Ok(self.resolve_generic_with(guardrail_slippage_bps()?))
+    }
+
+    fn resolve_generic_with(&self, guardrail_bps: u32) -> QuoteOptions {
+        self.resolve(DEFAULT_DEADLINE_MINS, guardrail_bps)
+    }
+
+    /// Resolve for a ZEC shield/unshield. Slippage may not exceed the guardrail.
+    pub fn resolve_zec(&self) -> Result<QuoteOptions, String> {
+        Ok(self.resolve_zec_with(guardrail_slippage_bps()?))
+    }
+
+    fn resolve_zec_with(&self, guardrail_bps: u32) -> QuoteOptions {
+        let mut options = self.resolve(ZEC_DEFAULT_DEADLINE_MINS, guardrail_bps);
+        options.slippage_bps = options.slippage_bps.min(guardrail_bps);
+        options
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
-/// Generate a deadline 10 minutes from now in ISO 8601 format.
-fn deadline_10min() -> String {
+/// Generate a deadline `mins` minutes from now in ISO 8601 format.
+fn deadline_in_mins(mins: u32) -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
-    let future = now + 600; // 10 minutes
+    let future = now + mins as u64 * 60;
     // Format as ISO 8601: YYYY-MM-DDTHH:MM:SS.000Z
     let secs_per_day = 86400u64;
     let secs_per_hour = 3600u64;
@@ -151,9 +474,79 @@ fn is_leap(year: i64) -> bool {
 // Client
 // ---------------------------------------------------------------------------
 
-/// Fetch the list of supported tokens from the 1Click API.
+/// How long a fetched token list is used before `get_tokens` refetches it.
+const TOKEN_CACHE_TTL_SECS: u64 = 60 * 60;
+
+/// The last `/tokens` response, kept in memory and in ~/.nyx/oneclick_tokens.json.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TokenCache {
+    /// Unix seconds
+    fetched_at: u64,
+    tokens: Vec<TokenInfo>,
+}
+
+impl TokenCache {
+    fn is_fresh(&self) -> bool {
+        unix_now().saturating_sub(self.fetched_at) < TOKEN_CACHE_TTL_SECS
+    }
+}
+
+static TOKEN_CACHE: std::sync::LazyLock<std::sync::Mutex<Option<TokenCache>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(None));
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn token_cache_path() -> std::path::PathBuf {
+    config::nyx_home().join("oneclick_tokens.json")
+}
+
+/// The cached token list, loading it from disk on first use. May be stale.
+fn load_token_cache() -> Option<TokenCache> {
+    let mut cache = TOKEN_CACHE.lock().ok()?;
+    if cache.is_none() {
+        *cache = std::fs::read_to_string(token_cache_path())
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok());
+    }
+    cache.clone()
+}
+
+fn store_token_cache(tokens: &[TokenInfo]) {
+    let entry = TokenCache { fetched_at: unix_now(), tokens: tokens.to_vec() };
+    if let Ok(json) = serde_json::to_string(&entry) {
+        if let Err(e) = config::write_atomic(&token_cache_path(), json) {
+            eprintln!("Failed to write token cache: {}", e);
+        }
+    }
+    if let Ok(mut cache) = TOKEN_CACHE.lock() {
+        *cache = Some(entry);
+    }
+}
+
+/// Supported tokens from the 1Click API, cached for an hour. If the API is
+/// unreachable a stale cached list is returned instead of an error.
 pub async fn get_tokens() -> Result<Vec<TokenInfo>, String> {
-    let client = reqwest::Client::new();
+    let cached = load_token_cache();
+    if let Some(cache) = cached.as_ref().filter(|c| c.is_fresh()) {
+        return Ok(cache.tokens.clone());
+    }
+    match fetch_tokens().await {
+        Ok(tokens) => {
+            store_token_cache(&tokens);
+            Ok(tokens)
+        }
+        Err(e) => cached.map(|c| c.tokens).ok_or(e),
+    }
+}
+
+/// Fetch the list of supported tokens from the 1Click API.
+async fn fetch_tokens() -> Result<Vec<TokenInfo>, String> {
+    let client = oneclick_client()?;
     let url = format!("{}/tokens", ONECLICK_BASE_URL);
 
     let response = client
@@ -165,7 +558,11 @@ pub async fn get_tokens() -> Result<Vec<TokenInfo>, String> {
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
-        return Err(format!("1Click tokens error ({}): {}", status, body));
+        return Err(format!(
+            "1Click tokens error ({}): {}",
+            status,
+            parse_error(status.as_u16(), &body)
+        ));
     }
 
     let tokens: Vec<TokenInfo> = response
@@ -176,7 +573,9 @@ pub async fn get_tokens() -> Result<Vec<TokenInfo>, String> {
     Ok(tokens)
 }
 
-/// Request a swap quote from the 1Click API (v2 schema).
+/// Request a swap quote from the 1Click API (v2 schema). Slippage defaults
+/// to the guardrail maximum. With `SwapType::ExactOutput`, `amount` is the
+/// destination amount.
 pub async fn get_quote(
     origin_asset: &str,
     destination_asset: &str,
@@ -184,18 +583,38 @@ pub async fn get_quote(
     recipient: &str,
     refund_to: &str,
     dry_run: bool,
+    overrides: QuoteOverrides,
 ) -> Result<QuoteResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| format!("HTTP client error: {}", e))?;
+    get_quote_with_options(
+        origin_asset,
+        destination_asset,
+        amount,
+        recipient,
+        refund_to,
+        dry_run,
+        overrides.resolve_generic()?,
+    )
+    .await
+}
+
+/// Request a swap quote with explicit slippage and deadline.
+pub async fn get_quote_with_options(
+    origin_asset: &str,
+    destination_asset: &str,
+    amount: &str,
+    recipient: &str,
+    refund_to: &str,
+    dry_run: bool,
+    options: QuoteOptions,
+) -> Result<QuoteResponse, String> {
+    let client = oneclick_client()?;
 
     let url = format!("{}/quote", ONECLICK_BASE_URL);
 
     let body = QuoteRequest {
         dry: dry_run,
-        swap_type: "EXACT_INPUT".to_string(),
-        slippage_tolerance: 100, // 1%
+        swap_type: options.swap_type,
+        slippage_tolerance: options.slippage_bps,
         origin_asset: origin_asset.to_string(),
         deposit_type: "INTENTS".to_string(),
         destination_asset: destination_asset.to_string(),
@@ -204,7 +623,7 @@ pub async fn get_quote(
         refund_type: "INTENTS".to_string(),
         recipient: recipient.to_string(),
         recipient_type: "DESTINATION_CHAIN".to_string(),
-        deadline: deadline_10min(),
+        deadline: deadline_in_mins(options.deadline_mins),
     };
 
     let response = client
@@ -217,33 +636,78 @@ pub async fn get_quote(
     if !response.status().is_success() {
         let status = response.status();
         let err_body = response.text().await.unwrap_or_default();
-        return Err(format!("1Click quote error ({}): {}", status, err_body));
+        return Err(format!(
+            "1Click quote error ({}): {}",
+            status,
+            parse_error(status.as_u16(), &err_body)
+        ));
     }
 
-    let quote: QuoteResponse = response
+    let mut quote: QuoteResponse = response
         .json()
         .await
         .map_err(|e| format!("Failed to parse quote response: {}", e))?;
 
+    quote.deadline = Some(body.deadline);
+    quote.valid_for_secs = quote.seconds_remaining();
+    quote.params = Some(QuoteParams {
+        origin_asset: body.origin_asset,
+        destination_asset: body.destination_asset,
+        amount: body.amount,
+        recipient: body.recipient,
+        refund_to: body.refund_to,
+        dry_run,
+        options,
+    });
     Ok(quote)
 }
 
+/// Re-request a dry-run quote with the same parameters and a new deadline.
+/// Live quotes can't be refreshed, since that would execute the swap again.
+pub async fn refresh_quote(quote: &QuoteResponse) -> Result<QuoteResponse, String> {
+    let params = quote
+        .params
+        .as_ref()
+        .ok_or_else(|| "Quote has no request parameters to refresh".to_string())?;
+    if !params.dry_run {
+        return Err("Only dry-run quotes can be refreshed".to_string());
+    }
+    let fresh = get_quote_with_options(
+        &params.origin_asset,
+        &params.destination_asset,
+        &params.amount,
+        &params.recipient,
+        &params.refund_to,
+        true,
+        params.options,
+    )
+    .await?;
+    Ok(if quote.summary.is_some() {
+        with_summary(fresh, &params.origin_asset, &params.destination_asset)
+    } else {
+        fresh
+    })
+}
+
 /// Get a quote to shield assets into ZEC (any asset → ZEC).
 pub async fn get_zec_quote(
     from_asset: &str,
     amount: &str,
     zec_address: &str,
     refund_to: &str,
+    overrides: QuoteOverrides,
 ) -> Result<QuoteResponse, String> {
-    get_quote(
+    let quote = get_quote_with_options(
         from_asset,
-        "nep141:zec.omft.near",
+        &config::get_zec_asset_id(),
         amount,
         zec_address,
         refund_to,
         true, // dry run — user must confirm before executing
+        overrides.resolve_zec()?,
     )
-    .await
+    .await?;
+    Ok(with_summary(quote, from_asset, &config::get_zec_asset_id()))
 }
 
 /// Get a quote to unshield from ZEC to any asset (ZEC → any).
@@ -252,57 +716,547 @@ pub async fn get_quote_from_zec(
     zec_amount: &str,
     recipient: &str,
     zec_refund: &str,
+    overrides: QuoteOverrides,
 ) -> Result<QuoteResponse, String> {
-    get_quote(
-        "nep141:zec.omft.near",
+    let quote = get_quote_with_options(
+        &config::get_zec_asset_id(),
         to_asset,
         zec_amount,
         recipient,
         zec_refund,
         true,
+        overrides.resolve_zec()?,
     )
-    .await
+    .await?;
+    Ok(with_summary(quote, &config::get_zec_asset_id(), to_asset))
+}
+
+/// Why a shield/unshield execution failed. Serialised with a `kind` tag so
+/// the UI can turn `guardrailExceeded` into a confirm dialog.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SwapError {
+    /// The dry-run quote is above `max_transaction_usd`. `amount_usd` is None
+    /// when the quote had no USD value. `confirmable` means retrying with
+    /// `confirm_over_limit` will go through (`require_confirmation` is on).
+    #[serde(rename_all = "camelCase")]
+    GuardrailExceeded {
+        message: String,
+        amount_usd: Option<f64>,
+        limit_usd: f64,
+        confirmable: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failed { message: String },
+}
+
+impl std::fmt::Display for SwapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SwapError::GuardrailExceeded { message, .. } | SwapError::Failed { message } => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl From<String> for SwapError {
+    fn from(message: String) -> Self {
+        SwapError::Failed { message }
+    }
+}
+
+/// USD value of a quote: the larger of amount in and amount out, or None if
+/// neither parses.
+fn quote_usd_value(q: &QuoteDetails) -> Option<f64> {
+    [&q.amount_in_usd, &q.amount_out_usd]
+        .iter()
+        .filter_map(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .reduce(f64::max)
+}
+
+/// Check a dry-run quote against `max_transaction_usd`. Over the limit (or
+/// with no USD value) it is rejected, unless `require_confirmation` is on and
+/// the user confirmed via `confirm_over_limit`. An unreadable guardrail
+/// config refuses the swap.
+fn check_transaction_limit(
+    kind: &str,
+    dry_run: &QuoteResponse,
+    confirm_over_limit: bool,
+) -> Result<(), SwapError> {
+    let guardrails = read_guardrails()?;
+    let limit_usd = guardrails.max_transaction_usd;
+    let amount_usd = quote_usd_value(&dry_run.quote);
+    if matches!(amount_usd, Some(v) if v <= limit_usd) {
+        return Ok(());
+    }
+    let confirmable = guardrails.require_confirmation;
+    if confirmable && confirm_over_limit {
+        return Ok(());
+    }
+
+    let value = amount_usd.map_or_else(
+        || "an unknown USD amount".to_string(),
+        |v| format_usd(&v.to_string()),
+    );
+    let message = if confirmable {
+        format!(
+            "This swap is worth {}, above your {} per-transaction limit. Confirm to proceed.",
+            value,
+            format_usd(&limit_usd.to_string())
+        )
+    } else {
+        format!(
+            "This swap is worth {}, above your {} per-transaction limit. Raise the limit in Settings → Guardrails to proceed.",
+            value,
+            format_usd(&limit_usd.to_string())
+        )
+    };
+    audit::record_audit_lossy(
+        "guardrail_blocked",
+        serde_json::json!({
+            "kind": kind,
+            "guardrail": "max_transaction_usd",
+            "amountUsd": amount_usd,
+            "limitUsd": limit_usd,
+            "correlationId": dry_run.correlation_id,
+        }),
+        &message,
+    );
+    Err(SwapError::GuardrailExceeded { message, amount_usd, limit_usd, confirmable })
+}
+
+/// Refuse a confirmed swap whose current quote has drifted from the one the
+/// user approved by more than the slippage guardrail: less out for
+/// EXACT_INPUT, more in for EXACT_OUTPUT.
+fn check_against_approved(
+    kind: &str,
+    approved: &QuoteDetails,
+    current: &QuoteResponse,
+    overrides: QuoteOverrides,
+) -> Result<(), SwapError> {
+    let parse = |v: &str| v.trim().parse::<u128>().ok();
+    let bps = overrides.resolve_zec()?.slippage_bps as u128;
+    let exact_output = overrides.swap_type == Some(SwapType::ExactOutput);
+    let (field, approved_amount, current_amount) = if exact_output {
+        ("amountIn", parse(&approved.amount_in), parse(&current.quote.amount_in))
+    } else {
+        ("amountOut", parse(&approved.amount_out), parse(&current.quote.amount_out))
+    };
+    let within = match (approved_amount, current_amount) {
+        (Some(a), Some(c)) => {
+            let tolerance = (a / 10_000).saturating_mul(bps);
+            if exact_output {
+                c <= a.saturating_add(tolerance)
+            } else {
+                c >= a.saturating_sub(tolerance)
+            }
+        }
+        _ => false,
+    };
+    if within {
+        return Ok(());
+    }
+    let message = format!(
+        "The current quote is worse than the one you approved by more than the {}% slippage limit. Request a new quote.",
+        bps as f64 / 100.0
+    );
+    audit::record_audit_lossy(
+        "guardrail_blocked",
+        serde_json::json!({
+            "kind": kind,
+            "guardrail": "approved_quote",
+            "field": field,
+            "approved": approved_amount.map(|v| v.to_string()),
+            "current": current_amount.map(|v| v.to_string()),
+            "slippageBps": bps,
+        }),
+        &message,
+    );
+    Err(SwapError::Failed { message })
 }
 
-/// Execute a shield swap (any asset → ZEC) — live, not dry run.
+/// Execute a shield swap (any asset → ZEC) — live, not dry run. A dry-run
+/// quote is checked against the transaction limit first.
 pub async fn execute_zec_shield(
     from_asset: &str,
     amount: &str,
     zec_address: &str,
     refund_to: &str,
-) -> Result<QuoteResponse, String> {
-    get_quote(
+    overrides: QuoteOverrides,
+    confirm_over_limit: bool,
+) -> Result<QuoteResponse, SwapError> {
+    shield(from_asset, amount, zec_address, refund_to, overrides, confirm_over_limit, None).await
+}
+
+/// `execute_zec_shield`, refusing the swap if `approved` is given and the
+/// current quote is worse than it beyond the slippage guardrail.
+async fn shield(
+    from_asset: &str,
+    amount: &str,
+    zec_address: &str,
+    refund_to: &str,
+    overrides: QuoteOverrides,
+    confirm_over_limit: bool,
+    approved: Option<&QuoteDetails>,
+) -> Result<QuoteResponse, SwapError> {
+    let dry_run = get_zec_quote(from_asset, amount, zec_address, refund_to, overrides).await?;
+    check_transaction_limit("zec_shield", &dry_run, confirm_over_limit)?;
+    if let Some(approved) = approved {
+        check_against_approved("zec_shield", approved, &dry_run, overrides)?;
+    }
+
+    let result = get_quote_with_options(
         from_asset,
-        "nep141:zec.omft.near",
+        &config::get_zec_asset_id(),
         amount,
         zec_address,
         refund_to,
         false, // live execution
+        overrides.resolve_zec()?,
     )
-    .await
+    .await;
+    let zec_asset = config::get_zec_asset_id();
+    audit_swap("zec_shield", from_asset, &zec_asset, amount, zec_address, &result);
+    let quote = result?;
+    record_swap_lossy("shield", from_asset, &zec_asset, &quote);
+    Ok(with_summary(quote, from_asset, &zec_asset))
 }
 
-/// Execute an unshield swap (ZEC → any asset) — live, not dry run.
+/// Execute an unshield swap (ZEC → any asset) — live, not dry run. A dry-run
+/// quote is checked against the transaction limit first.
 pub async fn execute_zec_unshield(
     to_asset: &str,
     zec_amount: &str,
     recipient: &str,
     zec_refund: &str,
-) -> Result<QuoteResponse, String> {
-    get_quote(
-        "nep141:zec.omft.near",
+    overrides: QuoteOverrides,
+    confirm_over_limit: bool,
+) -> Result<QuoteResponse, SwapError> {
+    unshield(to_asset, zec_amount, recipient, zec_refund, overrides, confirm_over_limit, None).await
+}
+
+/// `execute_zec_unshield`, refusing the swap if `approved` is given and the
+/// current quote is worse than it beyond the slippage guardrail.
+async fn unshield(
+    to_asset: &str,
+    zec_amount: &str,
+    recipient: &str,
+    zec_refund: &str,
+    overrides: QuoteOverrides,
+    confirm_over_limit: bool,
+    approved: Option<&QuoteDetails>,
+) -> Result<QuoteResponse, SwapError> {
+    let dry_run = get_quote_from_zec(to_asset, zec_amount, recipient, zec_refund, overrides).await?;
+    check_transaction_limit("zec_unshield", &dry_run, confirm_over_limit)?;
+    if let Some(approved) = approved {
+        check_against_approved("zec_unshield", approved, &dry_run, overrides)?;
+    }
+
+    let result = get_quote_with_options(
+        &config::get_zec_asset_id(),
         to_asset,
         zec_amount,
         recipient,
         zec_refund,
         false, // live execution
+        overrides.resolve_zec()?,
     )
-    .await
+    .await;
+    let zec_asset = config::get_zec_asset_id();
+    audit_swap("zec_unshield", &zec_asset, to_asset, zec_amount, recipient, &result);
+    let quote = result?;
+    record_swap_lossy("unshield", &zec_asset, to_asset, &quote);
+    Ok(with_summary(quote, &zec_asset, to_asset))
+}
+
+/// Append a live swap execution to the audit log.
+fn audit_swap(
+    kind: &str,
+    origin_asset: &str,
+    destination_asset: &str,
+    amount: &str,
+    recipient: &str,
+    result: &Result<QuoteResponse, String>,
+) {
+    let correlation_id = result.as_ref().ok().and_then(|q| q.correlation_id.clone());
+    audit::record_audit_lossy(
+        "swap_executed",
+        serde_json::json!({
+            "kind": kind,
+            "originAsset": origin_asset,
+            "destinationAsset": destination_asset,
+            "amount": amount,
+            "recipient": recipient,
+            "correlationId": correlation_id,
+        }),
+        result.as_ref().map_or_else(|e| e.as_str(), |_| "ok"),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Confirmed execution — dry-run quote first, then execute by correlation id
+// ---------------------------------------------------------------------------
+// Agent-initiated swaps go through two steps: a dry-run quote is registered
+// here, and execution only happens when the caller echoes back its
+// correlation id with the matching confirmation phrase. The phrase is random
+// and only shown to the user in the Nyx app (Privacy page); the MCP tools
+// never return it, so the agent can't confirm on its own. Pending quotes
+// live in ~/.nyx/pending_swaps.json (0600) so the app can display them.
+// Each quote can be executed once and expires after PENDING_QUOTE_TTL_SECS.
+
+/// How long a dry-run quote stays executable.
+pub const PENDING_QUOTE_TTL_SECS: u64 = 300;
+/// Wrong confirmations allowed before a pending quote is dropped.
+const MAX_CONFIRMATION_ATTEMPTS: u32 = 3;
+
+/// Which way a pending ZEC swap goes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZecDirection {
+    Shield,
+    Unshield,
+}
+
+/// A dry-run quote awaiting the user's confirmation. The request is replayed
+/// live on confirmation and checked against the approved amounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingQuote {
+    pub correlation_id: String,
+    pub direction: ZecDirection,
+    pub asset: String,
+    pub amount: String,
+    pub recipient: String,
+    pub refund_to: String,
+    pub overrides: QuoteOverrides,
+    /// The quote the user approved
+    pub approved: QuoteDetails,
+    pub summary: Option<QuoteSummary>,
+    /// The phrase the user gives the agent to execute this quote
+    pub confirmation: String,
+    /// Unix seconds
+    pub created_at: u64,
+    #[serde(default)]
+    pub failed_attempts: u32,
+}
+
+impl PendingQuote {
+    fn is_expired(&self) -> bool {
+        unix_now().saturating_sub(self.created_at) >= PENDING_QUOTE_TTL_SECS
+    }
+}
+
+/// Serialises read-modify-write of the pending quotes file in this process;
+/// `lock_pending_quotes_file` does the same across processes.
+static PENDING_QUOTES_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn pending_quotes_path() -> std::path::PathBuf {
+    config::nyx_home().join("pending_swaps.json")
+}
+
+/// Take an exclusive lock (flock on Unix) on ~/.nyx/pending_swaps.lock, held
+/// until the returned file is dropped. The app and the MCP server both
+/// update the pending quotes, so a process-local mutex isn't enough.
+fn lock_pending_quotes_file() -> Result<std::fs::File, String> {
+    let path = config::nyx_home().join("pending_swaps.lock");
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open pending swaps lock: {}", e))?;
+    file.lock()
+        .map_err(|e| format!("Failed to lock pending swaps: {}", e))?;
+    Ok(file)
+}
+
+/// Unexpired pending quotes. A missing file means none.
+fn read_pending_quotes() -> Result<Vec<PendingQuote>, String> {
+    let content = match std::fs::read_to_string(pending_quotes_path()) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read pending swaps: {}", e)),
+    };
+    let quotes: Vec<PendingQuote> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse pending swaps: {}", e))?;
+    Ok(quotes.into_iter().filter(|q| !q.is_expired()).collect())
+}
+
+fn write_pending_quotes(quotes: &[PendingQuote]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(quotes)
+        .map_err(|e| format!("Failed to serialize pending swaps: {}", e))?;
+    config::write_atomic(&pending_quotes_path(), json)
+        .map_err(|e| format!("Failed to write pending swaps: {}", e))
+}
+
+/// Run `f` on the pending quotes and write them back.
+fn update_pending_quotes<T>(
+    f: impl FnOnce(&mut Vec<PendingQuote>) -> T,
+) -> Result<T, String> {
+    let _guard = PENDING_QUOTES_LOCK
+        .lock()
+        .map_err(|e| format!("Pending quote lock poisoned: {}", e))?;
+    let _file_lock = lock_pending_quotes_file()?;
+    let mut quotes = read_pending_quotes()?;
+    let result = f(&mut quotes);
+    write_pending_quotes(&quotes)?;
+    Ok(result)
+}
+
+/// A fresh random confirmation phrase, e.g. "CONFIRM 3FA92C1D".
+fn new_confirmation_phrase() -> String {
+    use rand::Rng;
+    format!("CONFIRM {:08X}", rand::thread_rng().gen::<u32>())
+}
+
+/// Pending agent swaps with their confirmation phrases, for the app to show.
+pub fn list_pending_quotes() -> Result<Vec<PendingQuote>, String> {
+    read_pending_quotes()
+}
+
+/// Drop a pending quote the user rejected.
+pub fn dismiss_pending_quote(correlation_id: &str) -> Result<(), String> {
+    update_pending_quotes(|quotes| quotes.retain(|q| q.correlation_id != correlation_id))?;
+    audit::record_audit_lossy(
+        "swap_confirmation",
+        serde_json::json!({ "quoteCorrelationId": correlation_id }),
+        "rejected by user",
+    );
+    Ok(())
+}
+
+/// Remember a dry-run quote so it can be executed with
+/// `execute_pending_quote`. Returns the correlation id to confirm with
+/// (the quote's own, or a local one if 1Click didn't return any). The
+/// confirmation phrase is only readable through `list_pending_quotes`.
+pub fn register_pending_quote(
+    quote: &QuoteResponse,
+    direction: ZecDirection,
+    asset: &str,
+    amount: &str,
+    recipient: &str,
+    refund_to: &str,
+    overrides: QuoteOverrides,
+) -> Result<String, String> {
+    let correlation_id = quote
+        .correlation_id
+        .clone()
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let pending = PendingQuote {
+        correlation_id: correlation_id.clone(),
+        direction,
+        asset: asset.to_string(),
+        amount: amount.to_string(),
+        recipient: recipient.to_string(),
+        refund_to: refund_to.to_string(),
+        overrides,
+        approved: quote.quote.clone(),
+        summary: quote.summary.clone(),
+        confirmation: new_confirmation_phrase(),
+        created_at: unix_now(),
+        failed_attempts: 0,
+    };
+    update_pending_quotes(|quotes| {
+        quotes.retain(|q| q.correlation_id != correlation_id);
+        quotes.push(pending);
+    })?;
+    Ok(correlation_id)
+}
+
+/// Execute a quote registered by `register_pending_quote`. `confirmation`
+/// must equal the phrase shown to the user. Once confirmed the quote is
+/// consumed whether or not execution succeeds, and the swap is refused if
+/// the current quote is worse than the approved one by more than the
+/// slippage guardrail. Every attempt is audited. Agent-initiated swaps
+/// can't confirm past `max_transaction_usd`.
+pub async fn execute_pending_quote(
+    correlation_id: &str,
+    confirmation: &str,
+) -> Result<QuoteResponse, String> {
+    let pending = take_pending_quote(correlation_id, confirmation);
+    let detail = |q: Option<&PendingQuote>| {
+        serde_json::json!({
+            "quoteCorrelationId": correlation_id,
+            "direction": q.map(|q| q.direction),
+            "asset": q.map(|q| q.asset.as_str()),
+            "amount": q.map(|q| q.amount.as_str()),
+            "recipient": q.map(|q| q.recipient.as_str()),
+        })
+    };
+    let pending = match pending {
+        Ok(q) => q,
+        Err(e) => {
+            audit::record_audit_lossy("swap_confirmation", detail(None), &e);
+            return Err(e);
+        }
+    };
+    audit::record_audit_lossy("swap_confirmation", detail(Some(&pending)), "ok");
+
+    match pending.direction {
+        ZecDirection::Shield => {
+            shield(
+                &pending.asset,
+                &pending.amount,
+                &pending.recipient,
+                &pending.refund_to,
+                pending.overrides,
+                false,
+                Some(&pending.approved),
+            )
+            .await
+        }
+        ZecDirection::Unshield => {
+            unshield(
+                &pending.asset,
+                &pending.amount,
+                &pending.recipient,
+                &pending.refund_to,
+                pending.overrides,
+                false,
+                Some(&pending.approved),
+            )
+            .await
+        }
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Check the confirmation and remove the matching quote. A wrong phrase
+/// counts towards MAX_CONFIRMATION_ATTEMPTS and never echoes the right one.
+fn take_pending_quote(correlation_id: &str, confirmation: &str) -> Result<PendingQuote, String> {
+    update_pending_quotes(|quotes| {
+        let index = quotes
+            .iter()
+            .position(|q| q.correlation_id == correlation_id)
+            .ok_or_else(|| {
+                format!(
+                    "No recent dry-run quote with correlation id '{}' (quotes expire after {} minutes). Request a new quote first.",
+                    correlation_id,
+                    PENDING_QUOTE_TTL_SECS / 60
+                )
+            })?;
+        if confirmation.trim() == quotes[index].confirmation {
+            return Ok(quotes.remove(index));
+        }
+        quotes[index].failed_attempts += 1;
+        if quotes[index].failed_attempts >= MAX_CONFIRMATION_ATTEMPTS {
+            quotes.remove(index);
+            return Err("Confirmation does not match. Too many attempts; request a new quote.".to_string());
+        }
+        Err("Confirmation does not match. Ask the user for the phrase shown in the Nyx app.".to_string())
+    })?
 }
 
 /// Get the status of a swap.
 pub async fn get_status(swap_id: &str) -> Result<SwapStatus, String> {
-    let client = reqwest::Client::new();
+    let client = oneclick_client()?;
     let url = format!("{}/status/{}", ONECLICK_BASE_URL, swap_id);
 
     let response = client
@@ -314,7 +1268,11 @@ pub async fn get_status(swap_id: &str) -> Result<SwapStatus, String> {
     if !response.status().is_success() {
         let status = response.status();
         let err_body = response.text().await.unwrap_or_default();
-        return Err(format!("1Click status error ({}): {}", status, err_body));
+        return Err(format!(
+            "1Click status error ({}): {}",
+            status,
+            parse_error(status.as_u16(), &err_body)
+        ));
     }
 
     let swap_status: SwapStatus = response
@@ -325,15 +1283,359 @@ pub async fn get_status(swap_id: &str) -> Result<SwapStatus, String> {
     Ok(swap_status)
 }
 
+/// Default overall timeout for poll_until_complete.
+pub const DEFAULT_POLL_TIMEOUT_SECS: u64 = 30 * 60;
+const POLL_INITIAL_DELAY_SECS: u64 = 2;
+const POLL_MAX_DELAY_SECS: u64 = 30;
+/// Consecutive status-request failures tolerated before giving up.
+const POLL_MAX_ERRORS: u32 = 5;
+
+/// Poll `/status/{id}` with exponential backoff (2s doubling to 30s) until
+/// the swap reaches a terminal state or `timeout_secs` elapses.
+/// `on_progress` is called whenever the status or a tx hash changes.
+pub async fn poll_until_complete<F>(
+    swap_id: &str,
+    timeout_secs: u64,
+    mut on_progress: F,
+) -> Result<SwapStatus, String>
+where
+    F: FnMut(&SwapStatus),
+{
+    let started = std::time::Instant::now();
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    let mut delay = POLL_INITIAL_DELAY_SECS;
+    let mut errors = 0;
+    let mut last: Option<SwapStatus> = None;
+
+    loop {
+        match get_status(swap_id).await {
+            Ok(status) => {
+                errors = 0;
+                let changed = match &last {
+                    Some(prev) => {
+                        prev.status != status.status
+                            || prev.tx_hash_in != status.tx_hash_in
+                            || prev.tx_hash_out != status.tx_hash_out
+                    }
+                    None => true,
+                };
+                // Persisted every tick so an interrupted poll can be resumed
+                // and the history keeps the tx hashes after a restart
+                let persisted = SwapStatus {
+                    id: swap_id.to_string(),
+                    ..status.clone()
+                };
+                if let Err(e) = update_swap_progress(&persisted) {
+                    eprintln!("Failed to update swap history for {}: {}", swap_id, e);
+                }
+                if changed {
+                    on_progress(&status);
+                }
+                if status.state().is_terminal() {
+                    return Ok(status);
+                }
+                last = Some(status);
+            }
+            Err(e) => {
+                errors += 1;
+                if errors >= POLL_MAX_ERRORS {
+                    return Err(format!("Swap status polling failed {} times: {}", errors, e));
+                }
+            }
+        }
+
+        if started.elapsed() + std::time::Duration::from_secs(delay) > timeout {
+            let state = last.map_or_else(|| "unknown".to_string(), |s| s.status);
+            return Err(format!(
+                "Timed out after {}s waiting for swap {} (last status: {})",
+                timeout_secs, swap_id, state
+            ));
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+        delay = (delay * 2).min(POLL_MAX_DELAY_SECS);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Swap history — executed shield/unshield swaps in ~/.nyx/swaps.db
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapRecord {
+    /// 1Click correlation id (what `/status/{id}` is queried with)
+    pub id: String,
+    /// "shield" or "unshield"
+    pub direction: String,
+    pub origin_asset: String,
+    pub destination_asset: String,
+    pub amount_in: String,
+    pub amount_out: String,
+    /// Unix seconds
+    pub created_at: i64,
+    /// Last status seen from `/status`, "SUBMITTED" until the first poll,
+    /// "UNKNOWN" once polling was abandoned
+    pub status: String,
+    pub updated_at: i64,
+    /// Launches that resumed polling this swap
+    #[serde(default)]
+    pub resume_attempts: u32,
+    /// Deposit and payout tx hashes, once `/status` reports them
+    #[serde(default)]
+    pub tx_hash_in: Option<String>,
+    #[serde(default)]
+    pub tx_hash_out: Option<String>,
+}
+
+/// Status recorded for a swap whose outcome was never seen.
+pub const SWAP_STATUS_UNKNOWN: &str = "UNKNOWN";
+/// Swaps still not finished this long after they were submitted are marked
+/// unknown instead of being polled again.
+const SWAP_ABANDON_AFTER_SECS: i64 = 24 * 60 * 60;
+/// Launches that resume polling a swap before it is marked unknown.
+const MAX_SWAP_RESUMES: u32 = 5;
+
+fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn open_swap_db() -> Result<Connection, String> {
+    let path = config::nyx_home().join("swaps.db");
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create ~/.nyx/ directory: {}", e))?;
+    }
+    let conn =
+        Connection::open(&path).map_err(|e| format!("Failed to open swaps.db: {}", e))?;
+    init_swap_db(&conn)?;
+    Ok(conn)
+}
+
+fn init_swap_db(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS swaps (
+            id                TEXT PRIMARY KEY,
+            direction         TEXT NOT NULL,
+            origin_asset      TEXT NOT NULL,
+            destination_asset TEXT NOT NULL,
+            amount_in         TEXT NOT NULL,
+            amount_out        TEXT NOT NULL,
+            created_at        INTEGER NOT NULL,
+            status            TEXT NOT NULL,
+            updated_at        INTEGER NOT NULL,
+            resume_attempts   INTEGER NOT NULL DEFAULT 0,
+            tx_hash_in        TEXT,
+            tx_hash_out       TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_swaps_created ON swaps(created_at);",
+    )
+    .map_err(|e| format!("Failed to initialise swaps.db: {}", e))?;
+    // Databases from before these columns existed
+    for column in [
+        "resume_attempts INTEGER NOT NULL DEFAULT 0",
+        "tx_hash_in TEXT",
+        "tx_hash_out TEXT",
+    ] {
+        if let Err(e) = conn.execute_batch(&format!("ALTER TABLE swaps ADD COLUMN {}", column)) {
+            if !e.to_string().contains("duplicate column") {
+                return Err(format!("Failed to migrate swaps.db: {}", e));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Store an executed swap. Quotes without a correlation id get a local one
+/// so the swap is still listed, though it can't be polled.
+pub fn record_swap(
+    direction: &str,
+    origin_asset: &str,
+    destination_asset: &str,
+    quote: &QuoteResponse,
+) -> Result<SwapRecord, String> {
+    let now = now_secs();
+    let record = SwapRecord {
+        id: quote
+            .correlation_id
+            .clone()
+            .unwrap_or_else(|| format!("local-{}", uuid::Uuid::new_v4())),
+        direction: direction.to_string(),
+        origin_asset: origin_asset.to_string(),
+        destination_asset: destination_asset.to_string(),
+        amount_in: quote.quote.amount_in.clone(),
+        amount_out: quote.quote.amount_out.clone(),
+        created_at: now,
+        status: "SUBMITTED".to_string(),
+        updated_at: now,
+        resume_attempts: 0,
+        tx_hash_in: None,
+        tx_hash_out: None,
+    };
+    let conn = open_swap_db()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO swaps
+            (id, direction, origin_asset, destination_asset, amount_in, amount_out,
+             created_at, status, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            record.id,
+            record.direction,
+            record.origin_asset,
+            record.destination_asset,
+            record.amount_in,
+            record.amount_out,
+            record.created_at,
+            record.status,
+            record.updated_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to record swap: {}", e))?;
+    Ok(record)
+}
+
+/// Record a swap, logging failures — the swap itself already went through.
+fn record_swap_lossy(
+    direction: &str,
+    origin_asset: &str,
+    destination_asset: &str,
+    quote: &QuoteResponse,
+) {
+    if let Err(e) = record_swap(direction, origin_asset, destination_asset, quote) {
+        eprintln!("Swap history write failed: {}", e);
+    }
+}
+
+fn set_swap_status(conn: &Connection, swap_id: &str, status: &str, now: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE swaps SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        params![status, now, swap_id],
+    )
+    .map_err(|e| format!("Failed to update swap status: {}", e))?;
+    Ok(())
+}
+
+/// Persist what a status poll saw: the status and any tx hashes. Unknown
+/// ids are ignored; hashes already stored are kept when a later response
+/// omits them.
+pub fn update_swap_progress(status: &SwapStatus) -> Result<(), String> {
+    let conn = open_swap_db()?;
+    set_swap_progress(&conn, status, now_secs())
+}
+
+fn set_swap_progress(conn: &Connection, status: &SwapStatus, now: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE swaps SET status = ?1, updated_at = ?2,
+                tx_hash_in = COALESCE(?3, tx_hash_in),
+                tx_hash_out = COALESCE(?4, tx_hash_out)
+         WHERE id = ?5",
+        params![status.status, now, status.tx_hash_in, status.tx_hash_out, status.id],
+    )
+    .map_err(|e| format!("Failed to update swap status: {}", e))?;
+    Ok(())
+}
+
+/// The most recent `limit` swaps, newest first.
+pub fn list_swaps(limit: u32) -> Result<Vec<SwapRecord>, String> {
+    let conn = open_swap_db()?;
+    query_swaps(&conn, "ORDER BY created_at DESC LIMIT ?1", params![limit])
+}
+
+fn query_swaps(
+    conn: &Connection,
+    clause: &str,
+    args: impl rusqlite::Params,
+) -> Result<Vec<SwapRecord>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, direction, origin_asset, destination_asset, amount_in, amount_out,
+                    created_at, status, updated_at, resume_attempts, tx_hash_in, tx_hash_out
+             FROM swaps {}",
+            clause
+        ))
+        .map_err(|e| format!("Failed to query swaps: {}", e))?;
+    let rows = stmt
+        .query_map(args, |row| {
+            Ok(SwapRecord {
+                id: row.get(0)?,
+                direction: row.get(1)?,
+                origin_asset: row.get(2)?,
+                destination_asset: row.get(3)?,
+                amount_in: row.get(4)?,
+                amount_out: row.get(5)?,
+                created_at: row.get(6)?,
+                status: row.get(7)?,
+                updated_at: row.get(8)?,
+                resume_attempts: row.get(9)?,
+                tx_hash_in: row.get(10)?,
+                tx_hash_out: row.get(11)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query swaps: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read swap row: {}", e))
+}
+
+/// Decide which unfinished swaps to poll again at launch. Swaps past
+/// SWAP_ABANDON_AFTER_SECS, resumed MAX_SWAP_RESUMES times already, or with
+/// a local id that can't be polled are marked "UNKNOWN"; the rest have their
+/// resume count bumped and are returned.
+fn claim_resumable_swaps(conn: &Connection, now: i64) -> Result<Vec<String>, String> {
+    let mut resume = Vec::new();
+    for swap in query_swaps(conn, "ORDER BY created_at", params![])? {
+        if SwapState::parse(&swap.status).is_terminal() {
+            continue;
+        }
+        let abandoned = now - swap.created_at > SWAP_ABANDON_AFTER_SECS
+            || swap.resume_attempts >= MAX_SWAP_RESUMES
+            || swap.id.starts_with("local-");
+        if abandoned {
+            set_swap_status(conn, &swap.id, SWAP_STATUS_UNKNOWN, now)?;
+            continue;
+        }
+        conn.execute(
+            "UPDATE swaps SET resume_attempts = resume_attempts + 1 WHERE id = ?1",
+            params![swap.id],
+        )
+        .map_err(|e| format!("Failed to update swap resume count: {}", e))?;
+        resume.push(swap.id);
+    }
+    Ok(resume)
+}
+
+/// Resume polling swaps left unfinished when the app last exited, in the
+/// background. Returns how many were resumed. See `claim_resumable_swaps`.
+pub fn resume_pending_swaps<F>(on_progress: F) -> Result<usize, String>
+where
+    F: Fn(&SwapStatus) + Clone + Send + 'static,
+{
+    let ids = claim_resumable_swaps(&open_swap_db()?, now_secs())?;
+    let count = ids.len();
+    for id in ids {
+        let on_progress = on_progress.clone();
+        tokio::spawn(async move {
+            if let Err(e) = poll_until_complete(&id, DEFAULT_POLL_TIMEOUT_SECS, on_progress).await {
+                eprintln!("Stopped polling resumed swap {}: {}", id, e);
+            }
+        });
+    }
+    Ok(count)
+}
+
 // ---------------------------------------------------------------------------
 // Asset ID mapping helpers
 // ---------------------------------------------------------------------------
 
-/// Map a chain+symbol pair to a defuse asset identifier.
+/// Map a chain+symbol pair to a defuse asset identifier. Pairs not in the
+/// built-in map are looked up in the 1Click token list (fetched if nothing
+/// is cached). A symbol that matches several tokens on the chain is an
+/// error rather than a guess.
 /// Examples: ("eth", "ETH") -> "nep141:eth.omft.near"
 ///           ("near", "NEAR") -> "nep141:wrap.near"
 ///           ("sol", "SOL") -> "nep141:sol.omft.near"
-pub fn resolve_asset_id(chain: &str, symbol: &str) -> Result<String, String> {
+pub async fn resolve_asset_id(chain: &str, symbol: &str) -> Result<String, String> {
     let key = format!("{}:{}", chain.to_lowercase(), symbol.to_uppercase());
 
     let known: HashMap<&str, &str> = HashMap::from([
@@ -346,17 +1648,225 @@ pub fn resolve_asset_id(chain: &str, symbol: &str) -> Result<String, String> {
         ("eth:USDT", "nep141:usdt.eth.omft.near"),
         ("sol:SOL", "nep141:sol.omft.near"),
         ("btc:BTC", "btc:btc"),
-        ("zec:ZEC", "nep141:zec.omft.near"),
         ("base:ETH", "nep141:eth.base.omft.near"),
         ("arbitrum:ETH", "nep141:eth.arb.omft.near"),
     ]);
 
-    known
-        .get(key.as_str())
-        .map(|s| s.to_string())
+    if key == "zec:ZEC" {
+        return Ok(config::get_zec_asset_id());
+    }
+
+    if let Some(id) = known.get(key.as_str()) {
+        return Ok(id.to_string());
+    }
+    let tokens = match load_token_cache() {
+        Some(cache) => cache.tokens,
+        None => get_tokens().await?,
+    };
+    match_token(&tokens, chain, symbol)?
         .ok_or_else(|| format!("Unknown asset: {}:{}", chain, symbol))
 }
 
+/// The asset id of the one token with this chain and symbol, if any.
+fn match_token(tokens: &[TokenInfo], chain: &str, symbol: &str) -> Result<Option<String>, String> {
+    let mut ids: Vec<&str> = tokens
+        .iter()
+        .filter(|t| t.blockchain.eq_ignore_ascii_case(chain) && t.symbol.eq_ignore_ascii_case(symbol))
+        .map(|t| t.asset_id.as_str())
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    match ids.as_slice() {
+        [] => Ok(None),
+        [id] => Ok(Some(id.to_string())),
+        several => Err(format!(
+            "{}:{} matches several tokens ({}); pass the asset id instead",
+            chain,
+            symbol,
+            several.join(", ")
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Amount formatting
+// ---------------------------------------------------------------------------
+
+/// Fractional digits shown for token amounts.
+const DISPLAY_PRECISION: u32 = 6;
+/// Shown when the API omitted a field.
+const MISSING: &str = "—";
+
+/// Insert thousands separators into a string of integer digits.
+fn group_thousands(digits: &str) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Render a base-unit amount (e.g. yoctoNEAR, wei) with grouping and a fixed
+/// precision of up to 6 fractional digits, rounded half-up.
+/// `"1234567890"`, 6, `"USDC"` -> `"1,234.567890 USDC"`. Errors for
+/// decimals too large for u128 arithmetic.
+pub fn format_amount(raw: &str, decimals: u32, symbol: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(MISSING.to_string());
+    }
+    let value: u128 = match raw.parse() {
+        Ok(v) => v,
+        Err(_) => return Ok(format!("{} {}", raw, symbol).trim_end().to_string()),
+    };
+
+    let precision = decimals.min(DISPLAY_PRECISION);
+    let scale = 10u128
+        .checked_pow(decimals - precision)
+        .ok_or_else(|| format!("Unsupported token decimals: {}", decimals))?;
+    let rounded = value / scale + u128::from(value % scale >= scale / 2 && scale > 1);
+    let unit = 10u128.pow(precision);
+    let whole = group_thousands(&(rounded / unit).to_string());
+
+    let number = if precision == 0 {
+        whole
+    } else {
+        format!("{}.{:0width$}", whole, rounded % unit, width = precision as usize)
+    };
+    Ok(if symbol.is_empty() {
+        number
+    } else {
+        format!("{} {}", number, symbol)
+    })
+}
+
+/// Render a decimal USD string from the API as `$1,234.57`.
+pub fn format_usd(raw: &str) -> String {
+    let value: f64 = match raw.trim().parse() {
+        Ok(v) if f64::is_finite(v) => v,
+        _ => return MISSING.to_string(),
+    };
+    let cents = (value.abs() * 100.0).round() as u128;
+    let sign = if value < 0.0 && cents > 0 { "-" } else { "" };
+    format!(
+        "{}${}.{:02}",
+        sign,
+        group_thousands(&(cents / 100).to_string()),
+        cents % 100
+    )
+}
+
+/// Decimals and symbol for an asset id (ZEC plus the shieldable list).
+fn asset_display(asset_id: &str) -> (u32, String) {
+    if asset_id == config::get_zec_asset_id() {
+        return (8, "ZEC".to_string());
+    }
+    get_shieldable_assets()
+        .into_iter()
+        .find(|a| a.asset_id == asset_id)
+        .map(|a| (a.decimals, a.symbol))
+        .unwrap_or((0, String::new()))
+}
+
+/// Decimals for an asset id: ZEC, the shieldable list, then the cached
+/// 1Click token list.
+pub fn asset_decimals(asset_id: &str) -> Option<u32> {
+    match asset_display(asset_id) {
+        (decimals, symbol) if !symbol.is_empty() => Some(decimals),
+        _ => load_token_cache()?
+            .tokens
+            .into_iter()
+            .find(|t| t.asset_id == asset_id)
+            .map(|t| t.decimals),
+    }
+}
+
+/// Convert a decimal amount such as `"1.5"` to integer base units
+/// (`"1.5"`, 8 -> `"150000000"`). Exact: no floating point is involved.
+pub fn to_base_units(amount_decimal: &str, decimals: u32) -> Result<String, String> {
+    let amount = amount_decimal.trim();
+    let (whole, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && frac.is_empty()) || !is_digits(whole) || !is_digits(frac) {
+        return Err(format!("Invalid amount '{}': expected a number like 1.5", amount_decimal));
+    }
+    let significant = frac.trim_end_matches('0');
+    if significant.len() > decimals as usize {
+        return Err(format!(
+            "Amount '{}' has more than {} decimal places",
+            amount_decimal, decimals
+        ));
+    }
+
+    let digits = format!("{}{:0<width$}", whole, significant, width = decimals as usize);
+    let trimmed = digits.trim_start_matches('0');
+    if trimmed.is_empty() {
+        return Ok("0".to_string());
+    }
+    trimmed
+        .parse::<u128>()
+        .map(|v| v.to_string())
+        .map_err(|_| format!("Amount '{}' is too large", amount_decimal))
+}
+
+/// Convert integer base units back to a plain decimal string, without
+/// trailing zeros (`"150000000"`, 8 -> `"1.5"`).
+pub fn from_base_units(raw: &str, decimals: u32) -> Result<String, String> {
+    let value: u128 = raw
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid base-unit amount '{}'", raw))?;
+    let digits = format!("{:0>width$}", value, width = decimals as usize + 1);
+    let (whole, frac) = digits.split_at(digits.len() - decimals as usize);
+    let frac = frac.trim_end_matches('0');
+    Ok(if frac.is_empty() { whole.to_string() } else { format!("{}.{}", whole, frac) })
+}
+
+/// Base units for a human decimal amount in a swap from `origin_asset` to
+/// `destination_asset`. EXACT_OUTPUT amounts are in the destination asset.
+pub fn swap_amount_to_base_units(
+    origin_asset: &str,
+    destination_asset: &str,
+    amount_decimal: &str,
+    swap_type: Option<SwapType>,
+) -> Result<String, String> {
+    let asset = match swap_type.unwrap_or_default() {
+        SwapType::ExactInput => origin_asset,
+        SwapType::ExactOutput => destination_asset,
+    };
+    let decimals = asset_decimals(asset)
+        .ok_or_else(|| format!("Unknown decimals for asset '{}'", asset))?;
+    to_base_units(amount_decimal, decimals)
+}
+
+/// Build the confirmation summary from base-unit amounts. Unknown assets (and
+/// ones with unsupported decimals) fall back to the API's pre-formatted strings.
+fn build_summary(q: &QuoteDetails, origin_asset: &str, destination_asset: &str) -> QuoteSummary {
+    let fmt = |raw: &str, formatted: &str, asset: &str| -> String {
+        let fallback = || if formatted.is_empty() { MISSING.to_string() } else { formatted.to_string() };
+        match asset_display(asset) {
+            (_, symbol) if symbol.is_empty() => fallback(),
+            (decimals, symbol) => format_amount(raw, decimals, &symbol).unwrap_or_else(|_| fallback()),
+        }
+    };
+    QuoteSummary {
+        amount_in: fmt(&q.amount_in, &q.amount_in_formatted, origin_asset),
+        amount_in_usd: format_usd(&q.amount_in_usd),
+        amount_out: fmt(&q.amount_out, &q.amount_out_formatted, destination_asset),
+        amount_out_usd: format_usd(&q.amount_out_usd),
+        min_amount_out: fmt(&q.min_amount_out, "", destination_asset),
+        time_estimate_secs: q.time_estimate,
+    }
+}
+
+fn with_summary(mut quote: QuoteResponse, origin_asset: &str, destination_asset: &str) -> QuoteResponse {
+    quote.summary = Some(build_summary(&quote.quote, origin_asset, destination_asset));
+    quote
+}
+
 /// Get the list of shieldable assets (human-readable for the UI).
 pub fn get_shieldable_assets() -> Vec<ShieldableAsset> {
     vec![
@@ -378,3 +1888,381 @@ pub struct ShieldableAsset {
     pub decimals: u32,
     pub icon: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_swap_db(&conn).unwrap();
+        conn
+    }
+
+    fn insert_swap(conn: &Connection, id: &str, status: &str, created_at: i64, resumes: u32) {
+        conn.execute(
+            "INSERT INTO swaps
+                (id, direction, origin_asset, destination_asset, amount_in, amount_out,
+                 created_at, status, updated_at, resume_attempts)
+             VALUES (?1, 'shield', 'nep141:wrap.near', 'nep141:zec.omft.near', '1', '1',
+                     ?2, ?3, ?2, ?4)",
+            params![id, created_at, status, resumes],
+        )
+        .unwrap();
+    }
+
+    fn swap(conn: &Connection, id: &str) -> SwapRecord {
+        query_swaps(conn, "WHERE id = ?1", params![id]).unwrap().remove(0)
+    }
+
+    #[test]
+    fn poll_progress_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("nyx-swaps-{}.db", uuid::Uuid::new_v4()));
+        let conn = Connection::open(&path).unwrap();
+        init_swap_db(&conn).unwrap();
+        insert_swap(&conn, "polled", "SUBMITTED", 1_700_000_000, 0);
+        let mut status = SwapStatus {
+            id: "polled".to_string(),
+            status: "PROCESSING".to_string(),
+            tx_hash_in: Some("0xdeposit".to_string()),
+            tx_hash_out: None,
+            amount_in: None,
+            amount_out: None,
+        };
+        set_swap_progress(&conn, &status, 1_700_000_010).unwrap();
+        status.status = "SUCCESS".to_string();
+        status.tx_hash_in = None;
+        status.tx_hash_out = Some("payout".to_string());
+        set_swap_progress(&conn, &status, 1_700_000_020).unwrap();
+        drop(conn);
+
+        let conn = Connection::open(&path).unwrap();
+        init_swap_db(&conn).unwrap();
+        let record = swap(&conn, "polled");
+        assert_eq!(record.status, "SUCCESS");
+        assert_eq!(record.updated_at, 1_700_000_020);
+        assert_eq!(record.tx_hash_in.as_deref(), Some("0xdeposit"));
+        assert_eq!(record.tx_hash_out.as_deref(), Some("payout"));
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn resumes_recent_unfinished_swap() {
+        let conn = swap_db();
+        let now = 1_700_000_000;
+        insert_swap(&conn, "recent", "PENDING_DEPOSIT", now - 60, 0);
+
+        assert_eq!(claim_resumable_swaps(&conn, now).unwrap(), vec!["recent".to_string()]);
+        let record = swap(&conn, "recent");
+        assert_eq!(record.status, "PENDING_DEPOSIT");
+        assert_eq!(record.resume_attempts, 1);
+    }
+
+    #[test]
+    fn marks_too_old_swap_unknown() {
+        let conn = swap_db();
+        let now = 1_700_000_000;
+        insert_swap(&conn, "old", "PROCESSING", now - SWAP_ABANDON_AFTER_SECS - 1, 0);
+
+        assert!(claim_resumable_swaps(&conn, now).unwrap().is_empty());
+        assert_eq!(swap(&conn, "old").status, SWAP_STATUS_UNKNOWN);
+    }
+
+    #[test]
+    fn marks_swap_unknown_after_resume_cap() {
+        let conn = swap_db();
+        let now = 1_700_000_000;
+        insert_swap(&conn, "stuck", "SUBMITTED", now - 60, MAX_SWAP_RESUMES);
+        insert_swap(&conn, "local-1", "SUBMITTED", now - 60, 0);
+
+        assert!(claim_resumable_swaps(&conn, now).unwrap().is_empty());
+        assert_eq!(swap(&conn, "stuck").status, SWAP_STATUS_UNKNOWN);
+        assert_eq!(swap(&conn, "local-1").status, SWAP_STATUS_UNKNOWN);
+    }
+
+    fn token(chain: &str, symbol: &str, asset_id: &str) -> TokenInfo {
+        TokenInfo {
+            asset_id: asset_id.to_string(),
+            decimals: 18,
+            symbol: symbol.to_string(),
+            blockchain: chain.to_string(),
+            chain_name: None,
+            address: None,
+            price: None,
+        }
+    }
+
+    #[test]
+    fn match_token_finds_a_unique_symbol() {
+        let tokens = vec![
+            token("arb", "ARB", "nep141:arb.omft.near"),
+            token("eth", "ARB", "nep141:arb.eth.omft.near"),
+        ];
+        assert_eq!(
+            match_token(&tokens, "ARB", "arb").unwrap().as_deref(),
+            Some("nep141:arb.omft.near")
+        );
+        assert_eq!(match_token(&tokens, "sol", "ARB").unwrap(), None);
+    }
+
+    #[test]
+    fn match_token_rejects_ambiguous_symbol() {
+        let tokens = vec![
+            token("eth", "USDC", "nep141:usdc.eth.omft.near"),
+            token("eth", "usdc", "nep141:usdc-bridged.eth.omft.near"),
+            token("eth", "USDC", "nep141:usdc.eth.omft.near"),
+        ];
+        let err = match_token(&tokens, "eth", "USDC").unwrap_err();
+        assert!(err.contains("several tokens"), "{}", err);
+    }
+
+    #[test]
+    fn leaves_finished_swaps_alone() {
+        let conn = swap_db();
+        let now = 1_700_000_000;
+        insert_swap(&conn, "done", "SUCCESS", now - SWAP_ABANDON_AFTER_SECS * 2, 0);
+
+        assert!(claim_resumable_swaps(&conn, now).unwrap().is_empty());
+        let record = swap(&conn, "done");
+        assert_eq!(record.status, "SUCCESS");
+        assert_eq!(record.resume_attempts, 0);
+    }
+
+    #[test]
+    fn base_units_for_near_use_24_decimals() {
+        assert_eq!(to_base_units("1", 24).unwrap(), "1000000000000000000000000");
+        assert_eq!(to_base_units("0.5", 24).unwrap(), "500000000000000000000000");
+        assert_eq!(to_base_units(".000000000000000000000001", 24).unwrap(), "1");
+        assert_eq!(to_base_units("0.000", 24).unwrap(), "0");
+        assert_eq!(from_base_units("1000000000000000000000000", 24).unwrap(), "1");
+        assert_eq!(from_base_units("1", 24).unwrap(), "0.000000000000000000000001");
+    }
+
+    #[test]
+    fn base_units_round_trip() {
+        for (amount, decimals) in [("1.5", 8), ("0.000001", 6), ("123456.789", 18), ("42", 0), ("7.25", 24)] {
+            let raw = to_base_units(amount, decimals).unwrap();
+            assert_eq!(from_base_units(&raw, decimals).unwrap(), amount);
+        }
+        assert_eq!(to_base_units("1.50", 8).unwrap(), "150000000");
+        assert_eq!(from_base_units("150000000", 8).unwrap(), "1.5");
+    }
+
+    #[test]
+    fn base_units_reject_bad_amounts() {
+        let max = u128::MAX.to_string();
+        assert_eq!(to_base_units(&max, 0).unwrap(), max);
+        assert_eq!(from_base_units(&max, 0).unwrap(), max);
+
+        let err = to_base_units("340282366920938463463374607431768211456", 0).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+        let err = to_base_units("1000000000000000", 24).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+        let err = to_base_units("0.0000001", 6).unwrap_err();
+        assert!(err.contains("decimal places"), "{}", err);
+        assert!(from_base_units("340282366920938463463374607431768211456", 0).is_err());
+
+        for bad in ["", ".", "1.2.3", "-1", "1e18", "abc"] {
+            assert!(to_base_units(bad, 8).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn format_amount_handles_wei_and_zero() {
+        assert_eq!(
+            format_amount("1000000000000000000", 18, "ETH").unwrap(),
+            "1.000000 ETH"
+        );
+        assert_eq!(
+            format_amount("1234567890123456789012", 18, "ETH").unwrap(),
+            "1,234.567890 ETH"
+        );
+        // Half a unit of the last shown digit rounds up
+        assert_eq!(
+            format_amount("500000000000", 18, "ETH").unwrap(),
+            "0.000001 ETH"
+        );
+        assert_eq!(
+            format_amount("499999999999", 18, "ETH").unwrap(),
+            "0.000000 ETH"
+        );
+        assert_eq!(format_amount("0", 18, "ETH").unwrap(), "0.000000 ETH");
+        assert_eq!(format_amount("0", 0, "").unwrap(), "0");
+        assert_eq!(format_amount("  ", 18, "ETH").unwrap(), MISSING);
+    }
+
+    #[test]
+    fn parse_error_reads_code_and_message() {
+        let err = parse_error(
+            400,
+            r#"{"code": "AMOUNT_TOO_LOW", "message": "Amount is too low"}"#,
+        );
+        assert_eq!(
+            (err.status, err.code.as_deref(), err.message.as_str()),
+            (400, Some("AMOUNT_TOO_LOW"), "Amount is too low")
+        );
+
+        let err = parse_error(
+            422,
+            r#"{"error": 17, "message": ["bad recipient", "bad refundTo"]}"#,
+        );
+        assert_eq!(err.code.as_deref(), Some("17"));
+        assert_eq!(err.message, "bad recipient; bad refundTo");
+
+        // Code only: it doubles as the message
+        let err = parse_error(500, r#"{"error": "internal"}"#);
+        assert_eq!(
+            (err.code.as_deref(), err.message.as_str()),
+            (Some("internal"), "internal")
+        );
+
+        let err = parse_error(502, "  <html>Bad Gateway</html> ");
+        assert_eq!(
+            (err.code, err.message.as_str()),
+            (None, "<html>Bad Gateway</html>")
+        );
+        assert_eq!(parse_error(503, "").message, "HTTP 503");
+        assert_eq!(
+            parse_error(400, r#"["not", "an object"]"#).message,
+            r#"["not", "an object"]"#
+        );
+    }
+
+    #[test]
+    fn friendly_errors_match_known_failures() {
+        let friendly = |status, body: &str| friendly_error_message(&parse_error(status, body));
+        assert_eq!(
+            friendly(400, r#"{"message": "Amount is too low"}"#),
+            Some("Amount is below the minimum for this route")
+        );
+        assert_eq!(
+            friendly(400, r#"{"code": "UNSUPPORTED_ASSET"}"#),
+            Some("This asset or route is not supported by 1Click")
+        );
+        assert_eq!(
+            friendly(404, r#"{"message": "No quote found"}"#),
+            Some("No route available for this swap right now")
+        );
+        assert_eq!(
+            friendly(400, r#"{"message": "deadline is in the past"}"#),
+            Some("Quote deadline is invalid or has passed")
+        );
+        assert_eq!(
+            friendly(400, r#"{"message": "invalid refundTo"}"#),
+            Some("Recipient or refund address was rejected")
+        );
+        assert_eq!(
+            friendly(401, ""),
+            Some("1Click rejected the request as unauthorized")
+        );
+        assert_eq!(
+            friendly(429, ""),
+            Some("1Click rate limit reached, try again shortly")
+        );
+        assert_eq!(friendly(500, r#"{"message": "boom"}"#), None);
+    }
+
+    #[test]
+    fn quote_overrides_default_and_clamp_deadlines() {
+        let none = QuoteOverrides::default();
+        let generic = none.resolve_generic_with(50);
+        assert_eq!(
+            generic,
+            QuoteOptions {
+                slippage_bps: 50,
+                deadline_mins: DEFAULT_DEADLINE_MINS,
+                swap_type: SwapType::ExactInput
+            }
+        );
+        assert_eq!(
+            none.resolve_zec_with(50).deadline_mins,
+            ZEC_DEFAULT_DEADLINE_MINS
+        );
+
+        let short = QuoteOverrides {
+            deadline_mins: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(short.resolve_generic_with(50).deadline_mins, 5);
+        assert_eq!(short.resolve_zec_with(50).deadline_mins, 5);
+        let long = QuoteOverrides {
+            deadline_mins: Some(600),
+            ..Default::default()
+        };
+        assert_eq!(long.resolve_generic_with(50).deadline_mins, 120);
+        assert_eq!(long.resolve_zec_with(50).deadline_mins, 120);
+        let exact = QuoteOverrides {
+            deadline_mins: Some(45),
+            ..Default::default()
+        };
+        assert_eq!(exact.resolve_zec_with(50).deadline_mins, 45);
+    }
+
+    #[test]
+    fn zec_slippage_never_exceeds_the_guardrail() {
+        let loose = QuoteOverrides {
+            slippage_bps: Some(300),
+            ..Default::default()
+        };
+        assert_eq!(loose.resolve_generic_with(50).slippage_bps, 300);
+        assert_eq!(loose.resolve_zec_with(50).slippage_bps, 50);
+
+        let tight = QuoteOverrides {
+            slippage_bps: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(tight.resolve_zec_with(50).slippage_bps, 10);
+        let zero = QuoteOverrides {
+            slippage_bps: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(zero.resolve_generic_with(50).slippage_bps, 1);
+    }
+
+    #[test]
+    fn slippage_percent_converts_to_bounded_bps() {
+        assert_eq!(slippage_percent_to_bps(0.5), 50);
+        assert_eq!(slippage_percent_to_bps(1.0), 100);
+        assert_eq!(slippage_percent_to_bps(0.004), 1);
+        assert_eq!(slippage_percent_to_bps(0.0), 1);
+        assert_eq!(slippage_percent_to_bps(-2.0), 1);
+        assert_eq!(slippage_percent_to_bps(f64::NAN), 1);
+        assert_eq!(slippage_percent_to_bps(f64::INFINITY), 1);
+        assert_eq!(slippage_percent_to_bps(100.0), 10_000);
+    }
+
+    #[test]
+    fn swap_type_uses_the_api_spelling() {
+        assert_eq!(
+            serde_json::to_string(&SwapType::ExactInput).unwrap(),
+            r#""EXACT_INPUT""#
+        );
+        assert_eq!(
+            serde_json::to_string(&SwapType::ExactOutput).unwrap(),
+            r#""EXACT_OUTPUT""#
+        );
+        assert_eq!(
+            serde_json::from_str::<SwapType>(r#""EXACT_OUTPUT""#).unwrap(),
+            SwapType::ExactOutput
+        );
+        assert_eq!(
+            SwapType::parse("exact_output").unwrap(),
+            SwapType::ExactOutput
+        );
+        assert!(SwapType::parse("FLEX_INPUT").is_err());
+        assert_eq!(SwapType::default(), SwapType::ExactInput);
+    }
+
+    #[test]
+    fn format_amount_rounds_and_rejects_huge_decimals() {
+        assert_eq!(format_amount("1234567890", 6, "USDC").unwrap(), "1,234.567890 USDC");
+        assert_eq!(format_amount("1500000000000000000000000", 24, "NEAR").unwrap(), "1.500000 NEAR");
+        assert_eq!(format_amount("1999999500", 9, "").unwrap(), "2.000000");
+        assert_eq!(format_amount("42", 0, "X").unwrap(), "42 X");
+        assert_eq!(format_amount(&u128::MAX.to_string(), 44, "BIG").unwrap(), "0.000003 BIG");
+        let err = format_amount("1", 45, "BIG").unwrap_err();
+        assert!(err.contains("Unsupported token decimals: 45"), "{}", err);
+        assert!(format_amount("1", u32::MAX, "BIG").is_err());
+    }
+}
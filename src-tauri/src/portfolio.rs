@@ -4,13 +4,133 @@
 // ---------------------------------------------------------------------------
 
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
-use std::sync::mpsc;
+use std::sync::{mpsc, Mutex};
 use tauri::{AppHandle, Emitter};
 
+use nyx_lib::config;
+
 // Re-export shared types so existing code (`portfolio::PortfolioData`) still works
 pub use nyx_lib::portfolio_data::*;
 
+// ---------------------------------------------------------------------------
+// Watcher state
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+struct WatcherState {
+    started: bool,
+    paused: bool,
+    last_event_at: Option<u64>,
+    watched_path: Option<String>,
+    app: Option<AppHandle>,
+    /// Last emitted snapshot, diffed against for portfolio:changed
+    last_snapshot: Option<PortfolioData>,
+    /// Keys of guardrail breaches already alerted on
+    active_alerts: HashSet<String>,
+}
+
+static WATCHER: std::sync::LazyLock<Mutex<WatcherState>> =
+    std::sync::LazyLock::new(|| Mutex::new(WatcherState::default()));
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub paused: bool,
+    /// Unix seconds of the last emitted portfolio-update
+    pub last_event_at: Option<u64>,
+    pub watched_path: Option<String>,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn current_guardrails() -> config::GuardrailsConfig {
+    config::read_current_config()
+        .map(|c| c.guardrails)
+        .unwrap_or_default()
+}
+
+/// Emit a portfolio-update unless the watcher is paused, plus a
+/// portfolio:changed diff when something differs from the last snapshot and
+/// a guardrail:alert for each newly breached guardrail.
+async fn emit_update(app: &AppHandle) {
+    if WATCHER.lock().map(|s| s.paused).unwrap_or(false) {
+        return;
+    }
+    if let Ok(data) = read_portfolio().await {
+        let _ = app.emit("portfolio-update", &data);
+        let guardrails = current_guardrails();
+        let alerts = check_guardrails(&data, &guardrails);
+        let alert_keys: HashSet<String> = alerts.iter().map(|a| a.key()).collect();
+
+        let (previous, already_alerted) = match WATCHER.lock() {
+            Ok(mut state) => {
+                state.last_event_at = Some(now_secs());
+                let previous = state.last_snapshot.replace(data.clone());
+                (previous, std::mem::replace(&mut state.active_alerts, alert_keys))
+            }
+            Err(_) => return,
+        };
+
+        if let Some(previous) = previous {
+            let diff = diff_portfolio(&previous, &data, guardrails.min_health_factor);
+            if !diff.is_empty() {
+                let _ = app.emit("portfolio:changed", &diff);
+            }
+        }
+        for alert in alerts.iter().filter(|a| !already_alerted.contains(&a.key())) {
+            let _ = app.emit("guardrail:alert", alert);
+        }
+    }
+}
+
+/// Stop emitting portfolio-update events (file changes are ignored).
+pub fn pause_watcher() -> Result<(), String> {
+    let mut state = WATCHER.lock().map_err(|e| format!("Lock error: {}", e))?;
+    if !state.started {
+        return Err("Portfolio watcher is not running".to_string());
+    }
+    state.paused = true;
+    Ok(())
+}
+
+/// Resume emitting events and push a fresh snapshot immediately.
+pub async fn resume_watcher() -> Result<(), String> {
+    let app = {
+        let mut state = WATCHER.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if !state.started {
+            return Err("Portfolio watcher is not running".to_string());
+        }
+        state.paused = false;
+        state.app.clone()
+    };
+    if let Some(app) = app {
+        emit_update(&app).await;
+    }
+    Ok(())
+}
+
+pub fn watcher_status() -> Result<WatcherStatus, String> {
+    let state = WATCHER.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(WatcherStatus {
+        running: state.started && !state.paused,
+        paused: state.paused,
+        last_event_at: state.last_event_at,
+        watched_path: state.watched_path.clone(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// File watcher
+// ---------------------------------------------------------------------------
+
 /// Start file watcher for real-time updates.
 pub async fn start_watcher(app: AppHandle) -> Result<(), String> {
     let dir = defi_state_dir();
@@ -27,14 +147,18 @@ pub async fn start_watcher(app: AppHandle) -> Result<(), String> {
         .watch(&dir, RecursiveMode::NonRecursive)
         .map_err(|e| format!("Watch failed: {}", e))?;
 
+    if let Ok(mut state) = WATCHER.lock() {
+        state.started = true;
+        state.watched_path = Some(dir.display().to_string());
+        state.app = Some(app.clone());
+    }
+
     // Also poll every 30 seconds as fallback
     let app_clone = app.clone();
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
-            if let Ok(data) = read_portfolio().await {
-                let _ = app_clone.emit("portfolio-update", &data);
-            }
+            emit_update(&app_clone).await;
         }
     });
 
@@ -44,9 +168,7 @@ pub async fn start_watcher(app: AppHandle) -> Result<(), String> {
             Ok(Ok(_event)) => {
                 // Small debounce
                 std::thread::sleep(std::time::Duration::from_millis(200));
-                if let Ok(data) = read_portfolio().await {
-                    let _ = app.emit("portfolio-update", &data);
-                }
+                emit_update(&app).await;
             }
             Ok(Err(e)) => {
                 eprintln!("Watch error: {}", e);
@@ -55,5 +177,9 @@ pub async fn start_watcher(app: AppHandle) -> Result<(), String> {
         }
     }
 
+    if let Ok(mut state) = WATCHER.lock() {
+        state.started = false;
+    }
+
     Ok(())
 }
@@ -6,8 +6,19 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+/// Portfolio file format written by the current container release.
+pub const PORTFOLIO_SCHEMA_VERSION: u32 = 2;
+/// Oldest format still parsed. v1 files predate the version field.
+pub const PORTFOLIO_MIN_SCHEMA_VERSION: u32 = 1;
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct PortfolioData {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub total_value_usd: f64,
     pub change_24h_pct: f64,
     pub change_24h_usd: f64,
@@ -25,6 +36,9 @@ pub struct Position {
     pub amount: f64,
     pub value_usd: f64,
     pub apy: Option<f64>,
+    /// e.g. "near", "eth". Absent in files from older container releases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,9 +63,251 @@ pub struct HealthStatus {
     pub daily_loss_limit_pct: f64,
 }
 
+/// Order for `filter_positions`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PositionSort {
+    #[default]
+    ValueDesc,
+    ValueAsc,
+    ApyDesc,
+    Asset,
+}
+
+impl PositionSort {
+    /// Parse "value_desc", "value_asc", "apy_desc" or "asset".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "value_desc" | "value" => Ok(PositionSort::ValueDesc),
+            "value_asc" => Ok(PositionSort::ValueAsc),
+            "apy_desc" | "apy" => Ok(PositionSort::ApyDesc),
+            "asset" => Ok(PositionSort::Asset),
+            other => Err(format!(
+                "Unknown sort '{}'. Use value_desc, value_asc, apy_desc or asset.",
+                other
+            )),
+        }
+    }
+}
+
+/// Which positions to return. `None` fields don't filter.
+#[derive(Debug, Clone, Default)]
+pub struct PositionFilter {
+    /// Case-insensitive chain match. Positions without a chain never match.
+    pub chain: Option<String>,
+    pub min_value_usd: Option<f64>,
+    pub sort: PositionSort,
+    pub limit: Option<usize>,
+}
+
+/// Positions matching `filter`, sorted and truncated.
+pub fn filter_positions(positions: &[Position], filter: &PositionFilter) -> Vec<Position> {
+    let mut matched: Vec<Position> = positions
+        .iter()
+        .filter(|p| match &filter.chain {
+            Some(chain) => p.chain.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(chain)),
+            None => true,
+        })
+        .filter(|p| filter.min_value_usd.is_none_or(|min| p.value_usd >= min))
+        .cloned()
+        .collect();
+
+    match filter.sort {
+        PositionSort::ValueDesc => matched.sort_by(|a, b| b.value_usd.total_cmp(&a.value_usd)),
+        PositionSort::ValueAsc => matched.sort_by(|a, b| a.value_usd.total_cmp(&b.value_usd)),
+        PositionSort::ApyDesc => matched.sort_by(|a, b| {
+            b.apy.unwrap_or(f64::MIN).total_cmp(&a.apy.unwrap_or(f64::MIN))
+        }),
+        PositionSort::Asset => matched.sort_by(|a, b| a.asset.cmp(&b.asset)),
+    }
+    if let Some(limit) = filter.limit {
+        matched.truncate(limit);
+    }
+    matched
+}
+
+// ---------------------------------------------------------------------------
+// Snapshot diff
+// ---------------------------------------------------------------------------
+
+/// A value move smaller than this (percent) isn't reported as a change, so
+/// price ticks alone don't flood `portfolio:changed`.
+const POSITION_VALUE_CHANGE_PCT: f64 = 1.0;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PositionChange {
+    pub before: Position,
+    pub after: Position,
+}
+
+/// The Burrow health factor moved across `min_health_factor`.
+#[derive(Debug, Serialize, Clone)]
+pub struct HealthCrossing {
+    pub previous: Option<f64>,
+    pub current: f64,
+    pub threshold: f64,
+    /// true when it dropped below the threshold, false when it recovered
+    pub below: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PortfolioDiff {
+    pub added: Vec<Position>,
+    pub removed: Vec<Position>,
+    pub changed: Vec<PositionChange>,
+    pub total_value_usd_before: f64,
+    pub total_value_usd_after: f64,
+    pub health_crossing: Option<HealthCrossing>,
+}
+
+impl PortfolioDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.health_crossing.is_none()
+    }
+}
+
+/// Positions are matched on asset, protocol, type and chain.
+fn position_key(p: &Position) -> (&str, &str, &str, Option<&str>) {
+    (&p.asset, &p.protocol, &p.position_type, p.chain.as_deref())
+}
+
+fn position_changed(before: &Position, after: &Position) -> bool {
+    if before.amount != after.amount {
+        return true;
+    }
+    let base = before.value_usd.abs().max(f64::EPSILON);
+    (after.value_usd - before.value_usd).abs() / base * 100.0 >= POSITION_VALUE_CHANGE_PCT
+}
+
+/// What changed between two snapshots. A health crossing is reported when
+/// the health factor moves from at-or-above `min_health_factor` to below it,
+/// or back.
+pub fn diff_portfolio(
+    before: &PortfolioData,
+    after: &PortfolioData,
+    min_health_factor: f64,
+) -> PortfolioDiff {
+    let find = |list: &[Position], p: &Position| {
+        list.iter().find(|q| position_key(q) == position_key(p)).cloned()
+    };
+
+    let mut diff = PortfolioDiff {
+        total_value_usd_before: before.total_value_usd,
+        total_value_usd_after: after.total_value_usd,
+        ..Default::default()
+    };
+    for p in &after.positions {
+        match find(&before.positions, p) {
+            None => diff.added.push(p.clone()),
+            Some(old) if position_changed(&old, p) => {
+                diff.changed.push(PositionChange { before: old, after: p.clone() })
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = before
+        .positions
+        .iter()
+        .filter(|p| find(&after.positions, p).is_none())
+        .cloned()
+        .collect();
+
+    let previous = before.health.burrow_health_factor;
+    if let Some(current) = after.health.burrow_health_factor {
+        let was_below = previous.is_some_and(|h| h < min_health_factor);
+        let is_below = current < min_health_factor;
+        if was_below != is_below {
+            diff.health_crossing = Some(HealthCrossing {
+                previous,
+                current,
+                threshold: min_health_factor,
+                below: is_below,
+            });
+        }
+    }
+    diff
+}
+
+// ---------------------------------------------------------------------------
+// Guardrail checks
+// ---------------------------------------------------------------------------
+
+/// A live portfolio value past one of the configured guardrails.
+#[derive(Debug, Serialize, Clone)]
+pub struct GuardrailAlert {
+    /// "daily_loss_percent", "min_health_factor" or "max_concentration_percent"
+    pub guardrail: String,
+    /// The asset, for concentration alerts
+    pub asset: Option<String>,
+    pub current: f64,
+    pub limit: f64,
+    pub message: String,
+}
+
+impl GuardrailAlert {
+    /// Identifies the breach, so a watcher can alert once per crossing.
+    pub fn key(&self) -> String {
+        match &self.asset {
+            Some(asset) => format!("{}:{}", self.guardrail, asset),
+            None => self.guardrail.clone(),
+        }
+    }
+}
+
+/// Every guardrail the snapshot currently violates.
+pub fn check_guardrails(
+    data: &PortfolioData,
+    guardrails: &crate::config::GuardrailsConfig,
+) -> Vec<GuardrailAlert> {
+    let mut alerts = Vec::new();
+
+    if data.health.daily_loss_pct >= guardrails.daily_loss_percent {
+        alerts.push(GuardrailAlert {
+            guardrail: "daily_loss_percent".to_string(),
+            asset: None,
+            current: data.health.daily_loss_pct,
+            limit: guardrails.daily_loss_percent,
+            message: format!(
+                "Daily loss is {:.2}%, at or above the {:.2}% limit",
+                data.health.daily_loss_pct, guardrails.daily_loss_percent
+            ),
+        });
+    }
+    if let Some(hf) = data.health.burrow_health_factor {
+        if hf < guardrails.min_health_factor {
+            alerts.push(GuardrailAlert {
+                guardrail: "min_health_factor".to_string(),
+                asset: None,
+                current: hf,
+                limit: guardrails.min_health_factor,
+                message: format!(
+                    "Burrow health factor is {:.2}, below the {:.2} minimum",
+                    hf, guardrails.min_health_factor
+                ),
+            });
+        }
+    }
+    for a in &data.allocation {
+        if a.percentage > guardrails.max_concentration_percent {
+            alerts.push(GuardrailAlert {
+                guardrail: "max_concentration_percent".to_string(),
+                asset: Some(a.asset.clone()),
+                current: a.percentage,
+                limit: guardrails.max_concentration_percent,
+                message: format!(
+                    "{} is {:.1}% of the portfolio, above the {:.1}% concentration limit",
+                    a.asset, a.percentage, guardrails.max_concentration_percent
+                ),
+            });
+        }
+    }
+    alerts
+}
+
 pub fn defi_state_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(home).join(".openclaw/defi-state")
+    crate::config::openclaw_home().join("defi-state")
 }
 
 /// Read current portfolio data from defi-state files.
@@ -63,17 +319,54 @@ pub async fn read_portfolio() -> Result<PortfolioData, String> {
     if portfolio_path.exists() {
         let content = fs::read_to_string(&portfolio_path)
             .map_err(|e| format!("Failed to read portfolio: {}", e))?;
-        let data: PortfolioData = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse portfolio: {}", e))?;
-        return Ok(data);
+        return parse_portfolio(&content);
     }
 
     // Return empty data if no portfolio file exists yet
     Ok(demo_portfolio())
 }
 
+/// Parse portfolio.json, checking the schema version before the shape so a
+/// newer container format yields a clear error instead of a parse failure.
+pub fn parse_portfolio(content: &str) -> Result<PortfolioData, String> {
+    let value: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| format!("Failed to parse portfolio: {}", e))?;
+
+    let version = match value.get("schema_version") {
+        None => legacy_schema_version(),
+        Some(v) => {
+            let raw = v
+                .as_u64()
+                .ok_or_else(|| format!("Invalid portfolio schema_version: {}", v))?;
+            u32::try_from(raw).map_err(|_| {
+                format!(
+                    "Portfolio format v{} unsupported (this app reads up to v{}) — update the app",
+                    raw, PORTFOLIO_SCHEMA_VERSION
+                )
+            })?
+        }
+    };
+
+    if version > PORTFOLIO_SCHEMA_VERSION {
+        return Err(format!(
+            "Portfolio format v{} unsupported (this app reads up to v{}) — update the app",
+            version, PORTFOLIO_SCHEMA_VERSION
+        ));
+    }
+    if version < PORTFOLIO_MIN_SCHEMA_VERSION {
+        return Err(format!("Portfolio format v{} is no longer supported", version));
+    }
+
+    // v1 and v2 share the same shape; v2 only adds the explicit version field
+    let mut data: PortfolioData = serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse portfolio (format v{}): {}", version, e))?;
+    data.schema_version = version;
+    Ok(data)
+}
+
 pub fn demo_portfolio() -> PortfolioData {
     PortfolioData {
+        schema_version: PORTFOLIO_SCHEMA_VERSION,
         total_value_usd: 0.0,
         change_24h_pct: 0.0,
         change_24h_usd: 0.0,
@@ -88,3 +381,17 @@ pub fn demo_portfolio() -> PortfolioData {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_schema_version_beyond_u32() {
+        // 2^32 would wrap to 0 with a plain cast
+        let err = parse_portfolio(r#"{"schema_version": 4294967296}"#).unwrap_err();
+        assert!(err.contains("v4294967296 unsupported"), "{}", err);
+        let err = parse_portfolio(r#"{"schema_version": -1}"#).unwrap_err();
+        assert!(err.contains("Invalid portfolio schema_version"), "{}", err);
+    }
+}
@@ -3,14 +3,23 @@
 // ---------------------------------------------------------------------------
 // Spawns Claude Code (or any command) in a PTY and streams output to the
 // frontend via Tauri events. Frontend sends keystrokes back via commands.
+// Output is also captured (pty_output) so the agent can read it back.
 // ---------------------------------------------------------------------------
 
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::path::PathBuf;
+use serde::Serialize;
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter};
 
+use nyx_lib::config;
+use nyx_lib::pty_output::{self, OutputBuffer, PtyOutput};
+
+/// How often captured output is flushed to its on-disk snapshot.
+const SNAPSHOT_INTERVAL_MS: u64 = 250;
+
 // ---------------------------------------------------------------------------
 // Types
 // ---------------------------------------------------------------------------
@@ -18,8 +27,38 @@ use tauri::{AppHandle, Emitter};
 struct PtySession {
     writer: Box<dyn Write + Send>,
     master: Box<dyn portable_pty::MasterPty + Send>,
-    _command: String,
+    command: String,
     running: Arc<std::sync::atomic::AtomicBool>,
+    /// Set when the child exits (None if killed or the wait failed)
+    exit_code: Arc<Mutex<Option<u32>>>,
+    output: Arc<Mutex<OutputBuffer>>,
+}
+
+impl PtySession {
+    fn alive(&self) -> bool {
+        self.running.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn exit_code(&self) -> Option<u32> {
+        self.exit_code.lock().ok().and_then(|c| *c)
+    }
+}
+
+/// A PTY session as listed by `list`.
+#[derive(Debug, Serialize, Clone)]
+pub struct PtySessionInfo {
+    pub session_id: String,
+    pub command: String,
+    pub alive: bool,
+    pub exit_code: Option<u32>,
+}
+
+/// Payload of the `pty:exit` event.
+#[derive(Debug, Serialize, Clone)]
+pub struct PtyExit {
+    pub session_id: String,
+    /// None when the exit status couldn't be read
+    pub exit_code: Option<u32>,
 }
 
 // ---------------------------------------------------------------------------
@@ -33,15 +72,48 @@ static SESSIONS: std::sync::LazyLock<Mutex<HashMap<String, PtySession>>> =
 // Public API
 // ---------------------------------------------------------------------------
 
-/// Spawn a command in a new PTY session.
+/// Resolve and check a working directory (`~` expands to the home dir).
+fn resolve_cwd(cwd: &str) -> Result<PathBuf, String> {
+    let path = match cwd.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            config::home_dir().join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(cwd),
+    };
+    if !path.is_dir() {
+        return Err(format!("Working directory '{}' does not exist", path.display()));
+    }
+    Ok(path)
+}
+
+/// Reject env var names the OS would refuse or misparse.
+fn validate_env_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.contains('=') || key.contains('\0') {
+        return Err(format!("Invalid environment variable name '{}'", key));
+    }
+    Ok(())
+}
+
+/// Spawn a command in a new PTY session, optionally in `cwd` and with `env`
+/// merged over the inherited environment.
 /// Returns a session ID. Output is streamed via `pty:output` Tauri events.
 pub fn spawn(
     app: AppHandle,
     command: Option<String>,
     cols: u16,
     rows: u16,
+    cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
 ) -> Result<String, String> {
     let cmd = command.unwrap_or_else(|| "claude".to_string());
+    let cwd = cwd.filter(|c| !c.trim().is_empty()).map(|c| resolve_cwd(&c)).transpose()?;
+    let env = env.unwrap_or_default();
+    for (key, value) in &env {
+        validate_env_key(key)?;
+        if value.contains('\0') {
+            return Err(format!("Environment variable '{}' contains a NUL byte", key));
+        }
+    }
     let session_id = uuid::Uuid::new_v4().to_string();
 
     let pty_system = native_pty_system();
@@ -74,6 +146,14 @@ pub fn spawn(
     cmd_builder.env("TERM", "xterm-256color");
     cmd_builder.env("COLORTERM", "truecolor");
 
+    // Caller-supplied variables win over the inherited and defaults above
+    for (key, value) in &env {
+        cmd_builder.env(key, value);
+    }
+    if let Some(dir) = &cwd {
+        cmd_builder.cwd(dir);
+    }
+
     // Spawn the child process
     let mut child = pair
         .slave
@@ -93,6 +173,8 @@ pub fn spawn(
     let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
     let running_clone = running.clone();
     let sid = session_id.clone();
+    let output = Arc::new(Mutex::new(OutputBuffer::default()));
+    let output_reader = output.clone();
 
     // Background thread: read PTY output and emit Tauri events
     let app_clone = app.clone();
@@ -103,6 +185,9 @@ pub fn spawn(
                 Ok(0) => break, // EOF
                 Ok(n) => {
                     let data = String::from_utf8_lossy(&buf[..n]).to_string();
+                    if let Ok(mut output) = output_reader.lock() {
+                        output.push(&data);
+                    }
                     let _ = app_clone.emit("pty:output", (&sid, &data));
                 }
                 Err(e) => {
@@ -112,26 +197,44 @@ pub fn spawn(
             }
         }
         running_clone.store(false, std::sync::atomic::Ordering::Relaxed);
-        let _ = app_clone.emit("pty:exit", &sid);
     });
 
-    // Background thread: wait for child process exit
+    // Background thread: wait for child process exit and report its code
     let running_exit = running.clone();
+    let exit_code = Arc::new(Mutex::new(None));
+    let exit_code_wait = exit_code.clone();
     let sid_exit = session_id.clone();
     let app_exit = app;
     std::thread::spawn(move || {
-        let _ = child.wait();
+        let code = child.wait().ok().map(|status| status.exit_code());
+        if let Ok(mut slot) = exit_code_wait.lock() {
+            *slot = code;
+        }
         running_exit.store(false, std::sync::atomic::Ordering::Relaxed);
-        let _ = app_exit.emit("pty:exit", &sid_exit);
+        let _ = app_exit.emit(
+            "pty:exit",
+            PtyExit { session_id: sid_exit, exit_code: code },
+        );
     });
 
+    // Background thread: mirror captured output to disk for the MCP server
+    spawn_snapshot_writer(
+        session_id.clone(),
+        cmd.clone(),
+        running.clone(),
+        exit_code.clone(),
+        output.clone(),
+    );
+
     // Store session (we need the master for resize)
     let master = pair.master;
     let session = PtySession {
         writer,
         master,
-        _command: cmd,
+        command: cmd,
         running,
+        exit_code,
+        output,
     };
 
     SESSIONS
@@ -142,15 +245,111 @@ pub fn spawn(
     Ok(session_id)
 }
 
+/// Write the session's snapshot whenever its output changed, until the
+/// session has exited and the final output is on disk. That final snapshot
+/// is removed after EXITED_SNAPSHOT_SECS.
+fn spawn_snapshot_writer(
+    session_id: String,
+    command: String,
+    running: Arc<std::sync::atomic::AtomicBool>,
+    exit_code: Arc<Mutex<Option<u32>>>,
+    output: Arc<Mutex<OutputBuffer>>,
+) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_millis(SNAPSHOT_INTERVAL_MS));
+        let alive = running.load(std::sync::atomic::Ordering::Relaxed);
+        // Killed sessions are gone from SESSIONS and their snapshot removed
+        let killed = SESSIONS.lock().map(|s| !s.contains_key(&session_id)).unwrap_or(true);
+        if killed && !alive {
+            break;
+        }
+        if let Ok(mut buffer) = output.lock() {
+            if buffer.dirty || !alive {
+                let code = exit_code.lock().ok().and_then(|c| *c);
+                if let Err(e) =
+                    pty_output::save_snapshot(&session_id, &command, alive, code, &buffer)
+                {
+                    eprintln!("PTY snapshot failed for {}: {}", session_id, e);
+                }
+                buffer.dirty = false;
+            }
+        }
+        if !alive {
+            std::thread::sleep(std::time::Duration::from_secs(pty_output::EXITED_SNAPSHOT_SECS));
+            pty_output::remove_snapshot(&session_id);
+            break;
+        }
+    });
+}
+
+/// The last `lines` lines of a session's output, ANSI escapes stripped.
+pub fn read_output(session_id: &str, lines: usize) -> Result<PtyOutput, String> {
+    let sessions = SESSIONS
+        .lock()
+        .map_err(|_| "Session lock poisoned".to_string())?;
+
+    let session = sessions.get(session_id).ok_or_else(|| not_found(session_id))?;
+
+    let buffer = session
+        .output
+        .lock()
+        .map_err(|_| "Output lock poisoned".to_string())?;
+
+    Ok(PtyOutput {
+        session_id: session_id.to_string(),
+        command: session.command.clone(),
+        alive: session.alive(),
+        exit_code: session.exit_code(),
+        updated_at: buffer.updated_at(),
+        lines: buffer.tail(lines),
+    })
+}
+
+fn not_found(session_id: &str) -> String {
+    format!("PTY session '{}' not found", session_id)
+}
+
+/// The session, or a "not found" / "has exited" error.
+fn live_session<'a>(
+    sessions: &'a mut HashMap<String, PtySession>,
+    session_id: &str,
+) -> Result<&'a mut PtySession, String> {
+    let session = sessions.get_mut(session_id).ok_or_else(|| not_found(session_id))?;
+    if !session.alive() {
+        return Err(match session.exit_code() {
+            Some(code) => format!("PTY session '{}' has exited (code {})", session_id, code),
+            None => format!("PTY session '{}' has exited", session_id),
+        });
+    }
+    Ok(session)
+}
+
+/// Every session that hasn't been killed, including exited ones.
+pub fn list() -> Result<Vec<PtySessionInfo>, String> {
+    let sessions = SESSIONS
+        .lock()
+        .map_err(|_| "Session lock poisoned".to_string())?;
+
+    let mut list: Vec<PtySessionInfo> = sessions
+        .iter()
+        .map(|(id, session)| PtySessionInfo {
+            session_id: id.clone(),
+            command: session.command.clone(),
+            alive: session.alive(),
+            exit_code: session.exit_code(),
+        })
+        .collect();
+    list.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    Ok(list)
+}
+
 /// Write data (keystrokes) to a PTY session.
 pub fn write_to(session_id: &str, data: &str) -> Result<(), String> {
     let mut sessions = SESSIONS
         .lock()
         .map_err(|_| "Session lock poisoned".to_string())?;
 
-    let session = sessions
-        .get_mut(session_id)
-        .ok_or_else(|| format!("PTY session '{}' not found", session_id))?;
+    let session = live_session(&mut sessions, session_id)?;
 
     session
         .writer
@@ -167,13 +366,11 @@ pub fn write_to(session_id: &str, data: &str) -> Result<(), String> {
 
 /// Resize a PTY session.
 pub fn resize(session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
-    let sessions = SESSIONS
+    let mut sessions = SESSIONS
         .lock()
         .map_err(|_| "Session lock poisoned".to_string())?;
 
-    let session = sessions
-        .get(session_id)
-        .ok_or_else(|| format!("PTY session '{}' not found", session_id))?;
+    let session = live_session(&mut sessions, session_id)?;
 
     session
         .master
@@ -201,6 +398,7 @@ pub fn kill(session_id: &str) -> Result<(), String> {
         // Dropping the session closes the PTY
         drop(session);
     }
+    pty_output::remove_snapshot(session_id);
 
     Ok(())
 }
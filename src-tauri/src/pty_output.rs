@@ -0,0 +1,253 @@
+// ---------------------------------------------------------------------------
+// PTY output capture (shared, no Tauri dependency)
+// ---------------------------------------------------------------------------
+// The Tauri binary owns the PTY sessions; the MCP server runs as a separate
+// process. Each session's recent output is kept in an OutputBuffer (ANSI
+// escapes stripped, carriage returns applied) and mirrored to
+// ~/.nyx/pty/<session_id>.json so agents can read what a command printed.
+// That is the only thing persisted: the last OUTPUT_BUFFER_LINES lines, the
+// command and its exit status, in a 0600 file inside a 0700 directory. A
+// snapshot is deleted when its session is killed, EXITED_SNAPSHOT_SECS after
+// the process exits, and at the next launch if the app didn't exit cleanly.
+// ---------------------------------------------------------------------------
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+/// Lines kept per session.
+pub const OUTPUT_BUFFER_LINES: usize = 2000;
+/// Lines returned when the caller doesn't ask for a number.
+pub const DEFAULT_OUTPUT_LINES: usize = 100;
+/// How long an exited session's final output stays readable.
+pub const EXITED_SNAPSHOT_SECS: u64 = 5 * 60;
+
+/// Where the escape-sequence stripper is between chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Escape {
+    #[default]
+    None,
+    /// Just saw ESC
+    Start,
+    /// Inside `ESC [ ...` until a final byte
+    Csi,
+    /// Inside `ESC ] ...` until BEL or `ESC \`
+    Osc,
+    /// Saw ESC inside an OSC (possible `ESC \` terminator)
+    OscEsc,
+}
+
+/// Recent terminal output as plain text lines.
+#[derive(Debug, Default)]
+pub struct OutputBuffer {
+    lines: VecDeque<String>,
+    partial: String,
+    escape: Escape,
+    /// A `\r` was seen; the next printable character starts the line over
+    carriage_return: bool,
+    /// Unix seconds of the last push
+    updated_at: u64,
+    /// Changed since the last snapshot was written
+    pub dirty: bool,
+}
+
+impl OutputBuffer {
+    /// Feed a chunk of raw PTY output.
+    pub fn push(&mut self, data: &str) {
+        for c in data.chars() {
+            match self.escape {
+                Escape::Start => {
+                    self.escape = match c {
+                        '[' => Escape::Csi,
+                        ']' => Escape::Osc,
+                        _ => Escape::None,
+                    };
+                    continue;
+                }
+                Escape::Csi => {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        self.escape = Escape::None;
+                    }
+                    continue;
+                }
+                Escape::Osc => {
+                    match c {
+                        '\u{07}' => self.escape = Escape::None,
+                        '\u{1b}' => self.escape = Escape::OscEsc,
+                        _ => {}
+                    }
+                    continue;
+                }
+                Escape::OscEsc => {
+                    self.escape = if c == '\\' { Escape::None } else { Escape::Osc };
+                    continue;
+                }
+                Escape::None => {}
+            }
+
+            match c {
+                '\u{1b}' => self.escape = Escape::Start,
+                '\n' => {
+                    self.carriage_return = false;
+                    let line = std::mem::take(&mut self.partial);
+                    self.lines.push_back(line);
+                    if self.lines.len() > OUTPUT_BUFFER_LINES {
+                        self.lines.pop_front();
+                    }
+                }
+                '\r' => self.carriage_return = true,
+                '\u{08}' => {
+                    self.partial.pop();
+                }
+                '\t' => self.push_char(c),
+                c if c.is_control() => {}
+                c => self.push_char(c),
+            }
+        }
+        self.updated_at = now_secs();
+        self.dirty = true;
+    }
+
+    /// Unix seconds of the last output (0 before any).
+    pub fn updated_at(&self) -> u64 {
+        self.updated_at
+    }
+
+    fn push_char(&mut self, c: char) {
+        if self.carriage_return {
+            self.partial.clear();
+            self.carriage_return = false;
+        }
+        self.partial.push(c);
+    }
+
+    /// The last `n` lines, including an unterminated last line (e.g. a prompt).
+    pub fn tail(&self, n: usize) -> Vec<String> {
+        let mut all: Vec<&String> = self.lines.iter().collect();
+        if !self.partial.is_empty() {
+            all.push(&self.partial);
+        }
+        let start = all.len().saturating_sub(n);
+        all[start..].iter().map(|l| l.to_string()).collect()
+    }
+}
+
+/// A session's output as written to disk and returned to callers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PtyOutput {
+    pub session_id: String,
+    pub command: String,
+    pub alive: bool,
+    /// The process exit code, once it has exited
+    #[serde(default)]
+    pub exit_code: Option<u32>,
+    /// Unix seconds of the last output
+    pub updated_at: u64,
+    pub lines: Vec<String>,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn output_dir() -> PathBuf {
+    config::nyx_home().join("pty")
+}
+
+fn output_path(session_id: &str) -> PathBuf {
+    output_dir().join(format!("{}.json", session_id))
+}
+
+/// Write a session's buffer to ~/.nyx/pty/<session_id>.json (mode 600, in a
+/// mode 700 directory).
+pub fn save_snapshot(
+    session_id: &str,
+    command: &str,
+    alive: bool,
+    exit_code: Option<u32>,
+    buffer: &OutputBuffer,
+) -> Result<(), String> {
+    let snapshot = PtyOutput {
+        session_id: session_id.to_string(),
+        command: command.to_string(),
+        alive,
+        exit_code,
+        updated_at: buffer.updated_at(),
+        lines: buffer.tail(OUTPUT_BUFFER_LINES),
+    };
+    let dir = output_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to set permissions on {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string(&snapshot)
+        .map_err(|e| format!("Failed to serialize PTY output: {}", e))?;
+    // write_atomic creates new files 0600
+    config::write_atomic(&output_path(session_id), json)
+        .map_err(|e| format!("Failed to write PTY output: {}", e))
+}
+
+/// Delete a session's snapshot (when the session is killed or has exited).
+pub fn remove_snapshot(session_id: &str) {
+    let _ = fs::remove_file(output_path(session_id));
+}
+
+/// Delete every snapshot. Sessions don't outlive the app, so at launch any
+/// snapshot left on disk belongs to a session that is gone.
+pub fn remove_all_snapshots() {
+    let Ok(entries) = fs::read_dir(output_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.path().extension().is_some_and(|x| x == "json") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Every session snapshot on disk, most recently updated first.
+pub fn list_snapshots() -> Vec<PtyOutput> {
+    let Ok(entries) = fs::read_dir(output_dir()) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<PtyOutput> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|c| serde_json::from_str(&c).ok())
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    snapshots
+}
+
+/// The last `lines` lines of a session's output from disk. Without a
+/// session id, the most recently active session is used.
+pub fn read_snapshot(session_id: Option<&str>, lines: usize) -> Result<PtyOutput, String> {
+    let mut snapshot = match session_id {
+        Some(id) => {
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(format!("Invalid PTY session id '{}'", id));
+            }
+            let content = fs::read_to_string(output_path(id))
+                .map_err(|_| format!("No captured output for PTY session '{}'", id))?;
+            serde_json::from_str::<PtyOutput>(&content)
+                .map_err(|e| format!("Failed to parse PTY output: {}", e))?
+        }
+        None => list_snapshots()
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No PTY sessions have produced output".to_string())?,
+    };
+    let start = snapshot.lines.len().saturating_sub(lines);
+    snapshot.lines.drain(..start);
+    Ok(snapshot)
+}
@@ -1,15 +1,13 @@
 use crate::config;
 use crate::docker;
 use crate::wallet;
+use serde::Serialize;
 use std::path::PathBuf;
 use tauri::Manager;
 
 /// Check if Nyx has been set up (openclaw.json exists).
 pub async fn is_setup_complete() -> Result<bool, String> {
-    let home = config::home_dir();
-    let config_path = home.join(".openclaw/openclaw.json");
-    let env_path = home.join("openclaw/docker.env");
-    Ok(config_path.exists() && env_path.exists())
+    Ok(config::is_setup_complete())
 }
 
 /// Resolve the bundled resources directory.
@@ -45,29 +43,52 @@ pub fn resolve_resources_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, S
     Err("Could not find bundled resources directory".to_string())
 }
 
+/// What `run_setup` produced.
+#[derive(Debug, Serialize)]
+pub struct SetupResult {
+    pub account_id: String,
+    /// Recovery phrase of the wallet generated by this run, returned only
+    /// this once for the user to write down. None when an existing wallet
+    /// was kept.
+    pub mnemonic: Option<String>,
+}
+
 /// Run the full setup process.
+///
+/// On an install that is already set up this merges like `run_setup_v2`
+/// without `force`: the existing wallet, settings and workspace files are
+/// kept, only the keys passed here are updated, and the previous config
+/// files are backed up first.
 pub async fn run_setup(
     app_handle: tauri::AppHandle,
     anthropic_key: String,
     openai_key: Option<String>,
     telegram_token: Option<String>,
-) -> Result<String, String> {
+) -> Result<SetupResult, String> {
     let gateway_token = config::generate_token();
+    let home = config::home_dir();
+    let rerun = config::is_setup_complete();
+    let existing = if rerun { Some(config::read_current_config()?) } else { None };
 
     // Step 1: Create directory structure
     config::create_directories()?;
 
-    // Step 2: Generate NEAR wallet
-    let (wallet_info, wallet_config) = wallet::generate_near_wallet().await?;
-    let home = config::home_dir();
-    wallet::save_wallet(&wallet_info, &home.join(".openclaw/secrets"))?;
-    wallet::save_wallet_key(&wallet_config.id, &wallet_info)?;
+    // Step 2: Generate a NEAR wallet, unless the install already has one
+    let existing_account = config::get_near_account().filter(|_| rerun);
+    let (wallets, active_wallet_id, mnemonic) = if existing_account.is_some() {
+        (Vec::new(), None, None)
+    } else {
+        let (wallet_info, wallet_config) = wallet::generate_near_wallet().await?;
+        wallet::save_wallet(&wallet_info, &home.join(".openclaw/secrets"))?;
+        wallet::save_wallet_key(&wallet_config.id, &wallet_info, None)?;
+        (vec![wallet_config], Some(wallet_info.account_id), wallet_info.mnemonic)
+    };
 
     // Step 3: Write config files — credentials injected via env vars (IronClaw pattern)
-    let guardrails = config::GuardrailsConfig::default();
-
     let setup_config = config::SetupConfig {
-        agent_name: "Nyx".to_string(),
+        agent_name: existing
+            .as_ref()
+            .map_or_else(|| "Nyx".to_string(), |c| c.agent_name.clone()),
         anthropic_key,
         openai_key,
         venice_key: None,
@@ -76,49 +97,79 @@ pub async fn run_setup(
         telegram_token,
         slack_token: None,
         whatsapp_phone: None,
-        gateway_token: gateway_token.clone(),
-        wallets: vec![wallet_config],
-        active_wallet_id: Some(wallet_info.account_id.clone()),
-        guardrails: guardrails.clone(),
-        messaging: config::MessagingConfig::default(),
+        gateway_token,
+        wallets,
+        active_wallet_id: active_wallet_id.clone(),
+        guardrails: existing.as_ref().map(|c| c.guardrails.clone()).unwrap_or_default(),
+        messaging: existing.as_ref().map(|c| c.messaging.clone()).unwrap_or_default(),
         google_authenticated: false,
-        email_notifications: config::EmailNotificationsConfig::default(),
-        capabilities: config::CapabilitiesConfig::default(),
+        email_notifications: existing
+            .as_ref()
+            .map(|c| c.email_notifications.clone())
+            .unwrap_or_default(),
+        capabilities: existing.as_ref().map(|c| c.capabilities.clone()).unwrap_or_default(),
+    };
+    let setup_config = if rerun {
+        let (merged, _) = config::merge_setup_config(setup_config)?;
+        config::backup_config_files()?;
+        merged
+    } else {
+        setup_config
     };
 
     config::write_docker_env(&setup_config)?;
     config::write_openclaw_config(&setup_config)?;
-    config::write_guardrails(&guardrails)?;
+    config::write_guardrails(&setup_config.guardrails)?;
     config::write_cron_jobs(&setup_config)?;
 
-    // Step 4: Write empty function call keys
+    // Step 4: Write empty function call keys (existing keys are kept)
     let keys_path = home.join(".openclaw/secrets/function_call_keys.json");
-    std::fs::write(&keys_path, "{}").map_err(|e| format!("Failed to write keys: {}", e))?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        std::fs::set_permissions(&keys_path, std::fs::Permissions::from_mode(0o600))
-            .map_err(|e| format!("Failed to set keys permissions: {}", e))?;
+    if !keys_path.exists() {
+        config::write_atomic(&keys_path, "{}").map_err(|e| format!("Failed to write keys: {}", e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&keys_path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to set keys permissions: {}", e))?;
+        }
     }
 
     // Step 5: Copy bundled resources (resolved via Tauri at runtime)
     let resources_dir = resolve_resources_dir(&app_handle)?;
-    config::copy_resources(&resources_dir)?;
+    config::copy_resources(&resources_dir, false)?;
 
     // Step 6: Pull Docker image
-    docker::pull_image("ghcr.io/openclaw/openclaw:2026.2.21").await?;
+    docker::pull_image(&config::openclaw_image()).await?;
 
     // Step 7: Start container
     docker::start_container().await?;
 
-    // Step 8: Write LaunchAgent
-    write_launch_agent()?;
+    // Step 8: Install the launch-at-login entry
+    install_autostart()?;
+
+    Ok(SetupResult {
+        account_id: existing_account.or(active_wallet_id).unwrap_or_default(),
+        mnemonic,
+    })
+}
 
-    Ok(wallet_info.account_id)
+/// What `run_setup_v2` produced.
+#[derive(Debug, Serialize)]
+pub struct SetupV2Result {
+    /// Address of the active wallet, or "setup_complete" when there is none
+    pub address: String,
+    /// Outcome of applying the agent name to SOUL.md; its `warning` is shown
+    /// by the wizard
+    pub soul: config::SoulPersonalization,
 }
 
 /// Extended setup that accepts the full v2 configuration from the setup wizard.
 /// Wallets are passed in directly (already generated/imported by the UI).
+///
+/// On an install that is already set up, the wizard's config is merged over
+/// the existing one (see `config::merge_setup_config`) so re-running it can't
+/// drop a funded wallet. `force` regenerates everything from the wizard's
+/// inputs instead. Either way the previous config files are backed up first.
 pub async fn run_setup_v2(
     app_handle: tauri::AppHandle,
     agent_name: String,
@@ -137,9 +188,11 @@ pub async fn run_setup_v2(
     google_authenticated: bool,
     email_notifications: config::EmailNotificationsConfig,
     capabilities: config::CapabilitiesConfig,
-) -> Result<String, String> {
+    force: bool,
+) -> Result<SetupV2Result, String> {
     let gateway_token = config::generate_token();
     let home = config::home_dir();
+    let rerun = config::is_setup_complete();
 
     // Step 1: Create directory structure
     config::create_directories()?;
@@ -154,7 +207,7 @@ pub async fn run_setup_v2(
     });
 
     let setup_config = config::SetupConfig {
-        agent_name: agent_name.clone(),
+        agent_name,
         anthropic_key,
         openai_key,
         venice_key,
@@ -163,53 +216,60 @@ pub async fn run_setup_v2(
         telegram_token,
         slack_token,
         whatsapp_phone,
-        gateway_token: gateway_token.clone(),
+        gateway_token,
         wallets,
         active_wallet_id: active_id,
-        guardrails: guardrails.clone(),
+        guardrails,
         messaging,
         google_authenticated,
         email_notifications,
         capabilities,
     };
 
+    // Re-running over an existing install: keep what's there unless forced
+    let (setup_config, previous_agent_name) = if rerun && !force {
+        config::merge_setup_config(setup_config)?
+    } else {
+        (setup_config, "Nyx".to_string())
+    };
+    if rerun {
+        config::backup_config_files()?;
+    }
+    let agent_name = setup_config.agent_name.clone();
+
     config::write_docker_env(&setup_config)?;
     config::write_openclaw_config(&setup_config)?;
-    config::write_guardrails(&guardrails)?;
+    config::write_guardrails(&setup_config.guardrails)?;
     config::write_cron_jobs(&setup_config)?;
 
-    // Step 4: Write empty function call keys
+    // Step 4: Write empty function call keys (existing keys are kept unless forced)
     let keys_path = home.join(".openclaw/secrets/function_call_keys.json");
-    std::fs::write(&keys_path, "{}").map_err(|e| format!("Failed to write keys: {}", e))?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        std::fs::set_permissions(&keys_path, std::fs::Permissions::from_mode(0o600))
-            .map_err(|e| format!("Failed to set keys permissions: {}", e))?;
+    if force || !keys_path.exists() {
+        config::write_atomic(&keys_path, "{}").map_err(|e| format!("Failed to write keys: {}", e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&keys_path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to set keys permissions: {}", e))?;
+        }
     }
 
-    // Step 5: Copy bundled resources
+    // Step 5: Copy bundled resources (workspace files the user may have
+    // edited, like SOUL.md, are only replaced when forced)
     let resources_dir = resolve_resources_dir(&app_handle)?;
-    config::copy_resources(&resources_dir)?;
+    config::copy_resources(&resources_dir, force || !rerun)?;
 
     // Step 5b: Personalize SOUL.md with the configured agent name
-    let soul_path = home.join("openclaw/workspace/SOUL.md");
-    if soul_path.exists() {
-        let soul_content = std::fs::read_to_string(&soul_path)
-            .map_err(|e| format!("Failed to read SOUL.md: {}", e))?;
-        let personalized = soul_content.replace("You're Nyx", &format!("You're {}", agent_name));
-        std::fs::write(&soul_path, personalized)
-            .map_err(|e| format!("Failed to write SOUL.md: {}", e))?;
-    }
+    let soul = config::apply_soul_personalization(&previous_agent_name, &agent_name)?;
 
     // Step 6: Pull Docker image
-    docker::pull_image("ghcr.io/openclaw/openclaw:2026.2.21").await?;
+    docker::pull_image(&config::openclaw_image()).await?;
 
     // Step 7: Start container
     docker::start_container().await?;
 
-    // Step 8: Write LaunchAgent
-    write_launch_agent()?;
+    // Step 8: Install the launch-at-login entry
+    install_autostart()?;
 
     // Return the active wallet address as confirmation
     let active_address = setup_config
@@ -219,10 +279,218 @@ pub async fn run_setup_v2(
         .map(|w| w.address.clone())
         .unwrap_or_else(|| "setup_complete".to_string());
 
-    Ok(active_address)
+    Ok(SetupV2Result {
+        address: active_address,
+        soul,
+    })
+}
+
+/// Validate the setup wizard inputs without writing anything.
+/// Mirrors `run_setup_v2` and returns every problem found (empty = valid).
+pub fn validate_setup_v2(
+    agent_name: &str,
+    anthropic_key: &str,
+    openai_key: Option<&str>,
+    venice_key: Option<&str>,
+    nearai_key: Option<&str>,
+    perplexity_key: Option<&str>,
+    telegram_token: Option<&str>,
+    slack_token: Option<&str>,
+    whatsapp_phone: Option<&str>,
+    wallets: &[config::WalletConfig],
+    active_wallet_id: Option<&str>,
+    guardrails: &config::GuardrailsConfig,
+    messaging: &config::MessagingConfig,
+    google_authenticated: bool,
+    email_notifications: &config::EmailNotificationsConfig,
+    capabilities: &config::CapabilitiesConfig,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let present = |v: Option<&str>| v.map(|s| !s.trim().is_empty()).unwrap_or(false);
+
+    // Agent identity
+    if agent_name.trim().is_empty() {
+        problems.push("Agent name cannot be empty".to_string());
+    }
+
+    // LLM provider keys
+    let has_anthropic = !anthropic_key.trim().is_empty();
+    let has_ollama = capabilities
+        .ollama_model
+        .as_deref()
+        .map(|m| !m.is_empty())
+        .unwrap_or(false);
+    if !has_anthropic
+        && !present(openai_key)
+        && !present(venice_key)
+        && !present(nearai_key)
+        && !has_ollama
+    {
+        problems.push("At least one LLM API key (or a local Ollama model) is required".to_string());
+    }
+    let provider_ready = match capabilities.default_llm_provider.as_str() {
+        "anthropic" => has_anthropic,
+        "openai" => present(openai_key),
+        "venice" => present(venice_key),
+        "nearai" => present(nearai_key),
+        "ollama" => has_ollama,
+        other => {
+            problems.push(format!("Unknown default LLM provider '{}'", other));
+            true
+        }
+    };
+    if !provider_ready {
+        problems.push(format!(
+            "Default provider '{}' has no API key or model configured",
+            capabilities.default_llm_provider
+        ));
+    }
+    // Optional: backs web search, not an LLM provider on its own
+    if let Some(key) = perplexity_key.filter(|k| !k.trim().is_empty()) {
+        if !key.trim().starts_with("pplx-") {
+            problems.push("Perplexity API key should start with 'pplx-'".to_string());
+        }
+    }
+
+    // Wallets
+    let mut seen_ids = std::collections::HashSet::new();
+    for w in wallets {
+        if !seen_ids.insert(w.id.as_str()) {
+            problems.push(format!("Duplicate wallet id '{}'", w.id));
+        }
+        if let Err(e) = wallet::validate_address(&w.chain, &w.address) {
+            let label = if w.label.is_empty() { &w.address } else { &w.label };
+            problems.push(format!("Wallet '{}': {}", label, e));
+        }
+    }
+    if let Some(id) = active_wallet_id {
+        if !wallets.iter().any(|w| w.id == id) {
+            problems.push(format!("Active wallet '{}' is not in the wallet list", id));
+        }
+    }
+
+    // Guardrails
+    problems.extend(guardrails.validate());
+
+    // Messaging channels
+    if messaging.whatsapp.enabled && !present(whatsapp_phone) {
+        problems.push("WhatsApp is enabled but no phone number was provided".to_string());
+    }
+    if let Some(phone) = whatsapp_phone.filter(|p| !p.trim().is_empty()) {
+        if let Err(e) = config::normalize_phone(phone) {
+            problems.push(e);
+        }
+    }
+    if messaging.telegram.enabled && !present(telegram_token) {
+        problems.push("Telegram is enabled but no bot token was provided".to_string());
+    }
+    if messaging.slack.enabled && !present(slack_token) {
+        problems.push("Slack is enabled but no token was provided".to_string());
+    }
+    if messaging.gmail.enabled && !google_authenticated {
+        problems.push("Gmail is enabled but Google is not connected".to_string());
+    }
+
+    // Email notifications
+    if email_notifications.enabled {
+        if let Err(e) = config::validate_timezone(&email_notifications.timezone) {
+            problems.push(e);
+        }
+        if email_notifications.digest_hour > 23 || email_notifications.digest_minute > 59 {
+            problems.push("Digest time must be a valid HH:MM".to_string());
+        }
+        if email_notifications.triage_start_hour > 23 || email_notifications.triage_end_hour > 23 {
+            problems.push("Triage hours must be between 0 and 23".to_string());
+        } else if email_notifications.triage_start_hour > email_notifications.triage_end_hour {
+            problems.push("Triage start hour must be before the end hour".to_string());
+        }
+    }
+
+    problems
+}
+
+// ---------------------------------------------------------------------------
+// Autostart (launch at login)
+// ---------------------------------------------------------------------------
+
+/// Where the autostart entry lives on this platform.
+#[cfg(target_os = "macos")]
+fn autostart_path() -> PathBuf {
+    config::home_dir().join("Library/LaunchAgents/com.nyx.agent.plist")
+}
+
+#[cfg(target_os = "linux")]
+fn autostart_path() -> PathBuf {
+    config::home_dir().join(".config/systemd/user").join(SYSTEMD_UNIT)
+}
+
+#[cfg(target_os = "windows")]
+fn autostart_path() -> PathBuf {
+    let appdata = std::env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config::home_dir().join("AppData/Roaming"));
+    appdata.join("Microsoft/Windows/Start Menu/Programs/Startup/nyx-agent.cmd")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn autostart_path() -> PathBuf {
+    config::home_dir().join(".nyx-autostart-unsupported")
+}
+
+#[cfg(target_os = "linux")]
+const SYSTEMD_UNIT: &str = "nyx-agent.service";
+
+/// Install or remove the autostart entry. Returns what was done.
+pub fn set_autostart(enabled: bool) -> Result<String, String> {
+    if enabled {
+        return install_autostart();
+    }
+
+    let path = autostart_path();
+    if !path.exists() {
+        return Ok("Autostart was not installed".to_string());
+    }
+    // Unload first so the agent doesn't stay loaded until logout; failure
+    // just means it wasn't loaded
+    #[cfg(target_os = "macos")]
+    let _ = launchctl_unload(&path);
+    #[cfg(target_os = "linux")]
+    let _ = systemctl_user(&["disable", SYSTEMD_UNIT]);
+    std::fs::remove_file(&path)
+        .map_err(|e| format!("Failed to remove autostart entry: {}", e))?;
+    #[cfg(target_os = "linux")]
+    let _ = systemctl_user(&["daemon-reload"]);
+    Ok(format!("Removed {}", path.display()))
 }
 
-fn write_launch_agent() -> Result<(), String> {
+/// Check whether the autostart entry is installed.
+pub fn is_autostart_enabled() -> Result<bool, String> {
+    Ok(autostart_path().exists())
+}
+
+#[cfg(target_os = "macos")]
+fn install_autostart() -> Result<String, String> {
+    write_launch_agent()
+}
+
+#[cfg(target_os = "linux")]
+fn install_autostart() -> Result<String, String> {
+    write_systemd_unit()
+}
+
+#[cfg(target_os = "windows")]
+fn install_autostart() -> Result<String, String> {
+    write_startup_script()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn install_autostart() -> Result<String, String> {
+    Err("Autostart is not supported on this platform".to_string())
+}
+
+/// macOS: a LaunchAgent that runs start-nyx.sh at login.
+#[cfg(target_os = "macos")]
+fn write_launch_agent() -> Result<String, String> {
     let home = config::home_dir();
     let plist_dir = home.join("Library/LaunchAgents");
     std::fs::create_dir_all(&plist_dir)
@@ -261,9 +529,144 @@ fn write_launch_agent() -> Result<(), String> {
         home.display()
     );
 
-    let path = plist_dir.join("com.nyx.agent.plist");
-    std::fs::write(&path, plist)
+    let path = autostart_path();
+    config::write_atomic(&path, plist)
         .map_err(|e| format!("Failed to write LaunchAgent: {}", e))?;
 
+    Ok(format!("Installed LaunchAgent {}", path.display()))
+}
+
+#[cfg(target_os = "macos")]
+fn launchctl_unload(plist: &std::path::Path) -> Result<(), String> {
+    let output = std::process::Command::new("launchctl")
+        .arg("unload")
+        .arg(plist)
+        .output()
+        .map_err(|e| format!("Failed to run launchctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "launchctl unload failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Linux: a systemd user unit that brings the compose stack up at login.
+#[cfg(target_os = "linux")]
+fn write_systemd_unit() -> Result<String, String> {
+    let home = config::home_dir();
+    let path = autostart_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create systemd user dir: {}", e))?;
+    }
+
+    let unit = format!(
+        "[Unit]\n\
+         Description=Start the Nyx agent via Docker Compose\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         RemainAfterExit=yes\n\
+         Environment=HOME={home}\n\
+         WorkingDirectory={home}/openclaw\n\
+         ExecStart=/usr/bin/env docker compose up -d\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        home = home.display()
+    );
+    config::write_atomic(&path, unit)
+        .map_err(|e| format!("Failed to write systemd unit: {}", e))?;
+
+    // Without a user systemd (containers, some WSL setups) the unit is
+    // still written; say so rather than failing setup
+    let enabled = systemctl_user(&["daemon-reload"])
+        .and_then(|_| systemctl_user(&["enable", SYSTEMD_UNIT]));
+    match enabled {
+        Ok(()) => Ok(format!("Installed and enabled systemd user unit {}", path.display())),
+        Err(e) => Ok(format!(
+            "Installed systemd user unit {} but could not enable it: {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn systemctl_user(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run systemctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
     Ok(())
 }
+
+/// Windows: a script in the user's Startup folder that brings the compose
+/// stack up at login.
+#[cfg(target_os = "windows")]
+fn write_startup_script() -> Result<String, String> {
+    let home = config::home_dir();
+    let path = autostart_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create Startup folder: {}", e))?;
+    }
+
+    let script = format!(
+        "@echo off\r\n\
+         rem Start Nyx agent via Docker Compose (installed by Nyx)\r\n\
+         cd /d \"{}\\openclaw\" && docker compose up -d\r\n",
+        home.display()
+    );
+    config::write_atomic(&path, script)
+        .map_err(|e| format!("Failed to write startup script: {}", e))?;
+
+    Ok(format!("Installed startup script {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perplexity_problems(key: Option<&str>) -> Vec<String> {
+        validate_setup_v2(
+            "Nyx",
+            "sk-ant-REDACTED",
+            None,
+            None,
+            None,
+            key,
+            None,
+            None,
+            None,
+            &[],
+            None,
+            &config::GuardrailsConfig::default(),
+            &config::MessagingConfig::default(),
+            false,
+            &config::EmailNotificationsConfig::default(),
+            &config::CapabilitiesConfig::default(),
+        )
+        .into_iter()
+        .filter(|p| p.contains("Perplexity"))
+        .collect()
+    }
+
+    #[test]
+    fn perplexity_key_is_validated_when_given() {
+        assert!(perplexity_problems(None).is_empty());
+        assert!(perplexity_problems(Some("  ")).is_empty());
+        assert!(perplexity_problems(Some("pplx-0123456789abcdef")).is_empty());
+        assert_eq!(perplexity_problems(Some("sk-not-perplexity")).len(), 1);
+    }
+}
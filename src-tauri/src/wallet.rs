@@ -6,24 +6,102 @@ use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::PathBuf;
 
-use crate::config::{Chain, WalletConfig};
+use crate::config::{write_atomic, Chain, WalletConfig};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletInfo {
     pub account_id: String,
     pub public_key: String,
     pub secret_key: String,
+    /// BIP-39 phrase, only returned when a wallet is first generated so the
+    /// user can write it down. Never written to the key file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
 // NEAR wallet generation
 // ---------------------------------------------------------------------------
 
-/// Generate a new NEAR ed25519 keypair.
-/// Returns a `WalletInfo` (contains the private key for secret storage) and a
-/// `WalletConfig` (safe to persist in the main config file).
+/// SLIP-0010 path prefix for NEAR; the account index is the last
+/// (hardened) component, so index 0 is `m/44'/397'/0'`.
+const NEAR_DERIVATION_PREFIX: [u32; 2] = [44, 397];
+
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Mnemonic length for new wallets, matching NEAR's own wallets.
+const MNEMONIC_WORDS: usize = 12;
+
+/// Generate a new recoverable NEAR wallet from a fresh 12-word mnemonic.
+/// Returns a `WalletInfo` (contains the private key and mnemonic for secret
+/// storage / backup) and a `WalletConfig` (safe to persist in the main config
+/// file).
 pub async fn generate_near_wallet() -> Result<(WalletInfo, WalletConfig), String> {
-    let signing_key = SigningKey::generate(&mut OsRng);
+    let mut entropy = [0u8; MNEMONIC_WORDS / 3 * 4];
+    OsRng.fill(&mut entropy);
+    let mnemonic = bip39::Mnemonic::from_entropy(&entropy)
+        .map_err(|e| format!("Failed to generate mnemonic: {}", e))?;
+
+    let (mut wallet_info, wallet_config) = near_wallet_from_mnemonic(&mnemonic, 0)?;
+    wallet_info.mnemonic = Some(mnemonic.to_string());
+    Ok((wallet_info, wallet_config))
+}
+
+/// Re-derive the NEAR wallet at `index` from a BIP-39 mnemonic and save its
+/// private key. A wallet that is already configured keeps its id and label,
+/// so the key file lands where the config expects it; an existing key file
+/// for the account (possibly encrypted) is left alone.
+pub fn recover_near_wallet(mnemonic: &str, index: u32) -> Result<(WalletInfo, WalletConfig), String> {
+    let (info, mut config) = derive_near_wallet(mnemonic, index)?;
+    let existing = crate::config::read_configured_wallets()
+        .ok()
+        .and_then(|ws| {
+            ws.into_iter()
+                .find(|w| w.chain == Chain::NEAR && w.address == info.account_id)
+        });
+    if let Some(existing) = existing {
+        config.id = existing.id;
+        config.label = existing.label;
+        config.is_active = existing.is_active;
+    }
+    if !has_key_file_for_account(&info.account_id)? {
+        save_wallet_key(&config.id, &info, None)?;
+    }
+    Ok((info, config))
+}
+
+/// Derive the NEAR wallet at `index` from a BIP-39 mnemonic, without saving.
+fn derive_near_wallet(mnemonic: &str, index: u32) -> Result<(WalletInfo, WalletConfig), String> {
+    let normalized = mnemonic
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, &normalized)
+        .map_err(|e| format!("Invalid recovery phrase: {}", e))?;
+    near_wallet_from_mnemonic(&mnemonic, index)
+}
+
+fn near_wallet_from_mnemonic(
+    mnemonic: &bip39::Mnemonic,
+    index: u32,
+) -> Result<(WalletInfo, WalletConfig), String> {
+    if index >= HARDENED_OFFSET {
+        return Err(format!("Account index {} is out of range", index));
+    }
+    let seed = mnemonic.to_seed("");
+    let path = [NEAR_DERIVATION_PREFIX[0], NEAR_DERIVATION_PREFIX[1], index];
+    let signing_key = SigningKey::from_bytes(&slip10_ed25519(&seed, &path));
+
+    let label = if index == 0 {
+        "NEAR wallet".to_string()
+    } else {
+        format!("NEAR wallet #{}", index)
+    };
+    Ok(near_wallet_from_key(&signing_key, label))
+}
+
+fn near_wallet_from_key(signing_key: &SigningKey, label: String) -> (WalletInfo, WalletConfig) {
     let verifying_key = signing_key.verifying_key();
 
     let public_bytes = verifying_key.as_bytes();
@@ -50,18 +128,47 @@ pub async fn generate_near_wallet() -> Result<(WalletInfo, WalletConfig), String
         account_id: account_id.clone(),
         public_key,
         secret_key,
+        mnemonic: None,
     };
 
     let wallet_config = WalletConfig {
         id: wallet_id,
         chain: Chain::NEAR,
         address: account_id,
-        label: "NEAR wallet".to_string(),
+        label,
         has_private_key: true,
         is_active: true,
     };
 
-    Ok((wallet_info, wallet_config))
+    (wallet_info, wallet_config)
+}
+
+/// SLIP-0010 ed25519 derivation. ed25519 only supports hardened children,
+/// so every path component is hardened.
+fn slip10_ed25519(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    type HmacSha512 = Hmac<sha2::Sha512>;
+
+    let split = |data: &[u8], key: &[u8]| -> ([u8; 32], [u8; 32]) {
+        let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(data);
+        let out = mac.finalize().into_bytes();
+        let mut k = [0u8; 32];
+        let mut c = [0u8; 32];
+        k.copy_from_slice(&out[..32]);
+        c.copy_from_slice(&out[32..]);
+        (k, c)
+    };
+
+    let (mut key, mut chain_code) = split(seed, b"ed25519 seed");
+    for &index in path {
+        let mut data = Vec::with_capacity(37);
+        data.push(0u8);
+        data.extend_from_slice(&key);
+        data.extend_from_slice(&(index | HARDENED_OFFSET).to_be_bytes());
+        (key, chain_code) = split(&data, &chain_code);
+    }
+    key
 }
 
 // ---------------------------------------------------------------------------
@@ -76,29 +183,78 @@ pub fn validate_address(chain: &Chain, address: &str) -> Result<(), String> {
         Chain::SOL => validate_sol_address(address),
         Chain::BTC => validate_btc_address(address),
         Chain::ZEC => validate_zec_address(address),
+        Chain::LTC => validate_ltc_address(address),
+        Chain::DOGE => validate_doge_address(address),
     }
 }
 
+const NEAR_ACCOUNT_MIN_LEN: usize = 2;
+const NEAR_ACCOUNT_MAX_LEN: usize = 64;
+
 fn validate_near_address(address: &str) -> Result<(), String> {
-    // Named accounts: *.near or *.testnet
-    if address.ends_with(".near") || address.ends_with(".testnet") {
-        let prefix = if address.ends_with(".near") {
-            &address[..address.len() - 5]
-        } else {
-            &address[..address.len() - 8]
-        };
-        if prefix.is_empty() {
-            return Err("NEAR named account has empty prefix".to_string());
+    // Implicit account: 64-char lowercase hex
+    if address.len() == 64 && address.chars().all(|c| c.is_ascii_hexdigit()) {
+        if address.chars().any(|c| c.is_ascii_uppercase()) {
+            return Err("NEAR implicit account must be lowercase hex".to_string());
         }
         return Ok(());
     }
 
-    // Implicit account: 64-char hex
-    if address.len() == 64 && address.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Ok(());
+    // Named accounts: *.near or *.testnet
+    if !address.ends_with(".near") && !address.ends_with(".testnet") {
+        return Err("Invalid NEAR address: must be a .near/.testnet name or 64-char hex implicit account".to_string());
     }
+    validate_near_account_id(address)
+}
 
-    Err("Invalid NEAR address: must be a .near/.testnet name or 64-char hex implicit account".to_string())
+/// NEAR account-id rules: 2–64 chars of lowercase a-z, 0-9, `-`, `_` and
+/// `.`, where `.` separates parts and `-`/`_` may only sit between two
+/// alphanumerics (no leading, trailing or doubled separators).
+fn validate_near_account_id(account: &str) -> Result<(), String> {
+    let len = account.len();
+    if len < NEAR_ACCOUNT_MIN_LEN {
+        return Err(format!(
+            "NEAR account id must be at least {} characters",
+            NEAR_ACCOUNT_MIN_LEN
+        ));
+    }
+    if len > NEAR_ACCOUNT_MAX_LEN {
+        return Err(format!(
+            "NEAR account id must be at most {} characters, got {}",
+            NEAR_ACCOUNT_MAX_LEN, len
+        ));
+    }
+    if let Some(c) = account.chars().find(|c| c.is_ascii_uppercase()) {
+        return Err(format!("NEAR account id must be lowercase (found '{}')", c));
+    }
+    if let Some(c) = account
+        .chars()
+        .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(format!("NEAR account id contains invalid character '{}'", c));
+    }
+
+    let mut prev: Option<char> = None;
+    for c in account.chars() {
+        let is_sep = matches!(c, '-' | '_' | '.');
+        match prev {
+            None if is_sep => {
+                return Err(format!("NEAR account id cannot start with '{}'", c));
+            }
+            Some(p) if is_sep && matches!(p, '-' | '_' | '.') => {
+                return Err(format!(
+                    "NEAR account id cannot have consecutive separators ('{}{}')",
+                    p, c
+                ));
+            }
+            _ => {}
+        }
+        prev = Some(c);
+    }
+    if let Some(c) = prev.filter(|c| matches!(c, '-' | '_' | '.')) {
+        return Err(format!("NEAR account id cannot end with '{}'", c));
+    }
+    Ok(())
 }
 
 fn validate_eth_address(address: &str) -> Result<(), String> {
@@ -108,12 +264,43 @@ fn validate_eth_address(address: &str) -> Result<(), String> {
     if address.len() != 42 {
         return Err(format!("ETH address must be 42 characters, got {}", address.len()));
     }
-    if !address[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+    let hex_part = &address[2..];
+    if !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
         return Err("ETH address contains non-hex characters after 0x".to_string());
     }
+    // Single-case addresses carry no checksum; mixed case must match EIP-55
+    let has_lower = hex_part.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex_part.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper && eip55_checksum(hex_part) != hex_part {
+        return Err(
+            "ETH address checksum mismatch — the capitalisation doesn't match, so this is likely a typo"
+                .to_string(),
+        );
+    }
     Ok(())
 }
 
+/// EIP-55 mixed-case encoding of a 40-char hex address (without 0x): each
+/// letter is uppercased when the matching nibble of keccak256(lowercase hex)
+/// is >= 8.
+fn eip55_checksum(hex_part: &str) -> String {
+    use sha3::{Digest, Keccak256};
+    let lower = hex_part.to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
 fn validate_sol_address(address: &str) -> Result<(), String> {
     if address.len() < 32 || address.len() > 44 {
         return Err(format!(
@@ -191,6 +378,158 @@ fn validate_zec_address(address: &str) -> Result<(), String> {
     Err("ZEC address must start with t1/t3 (transparent), zs1 (shielded), or u1 (unified)".to_string())
 }
 
+/// Base58check payload length: 1 version byte + 20-byte hash + 4-byte checksum.
+const BASE58CHECK_LEN: usize = 25;
+
+/// Litecoin version bytes: P2PKH (L), P2SH (M) and the legacy P2SH byte it
+/// shared with Bitcoin (3).
+const LTC_VERSIONS: &[u8] = &[0x30, 0x32, 0x05];
+/// Dogecoin version bytes: P2PKH (D) and P2SH (9 or A).
+const DOGE_VERSIONS: &[u8] = &[0x1e, 0x16];
+
+/// Decode a base58check address, verify its double-SHA256 checksum and
+/// return the version byte.
+fn base58check_version(address: &str) -> Result<u8, &'static str> {
+    let bytes = bs58::decode(address)
+        .into_vec()
+        .map_err(|_| "is not valid base58")?;
+    if bytes.len() != BASE58CHECK_LEN {
+        return Err("has the wrong decoded length");
+    }
+    let (payload, checksum) = bytes.split_at(BASE58CHECK_LEN - 4);
+    let digest = Sha256::digest(Sha256::digest(payload));
+    if &digest[..4] != checksum {
+        return Err("has a bad checksum");
+    }
+    Ok(payload[0])
+}
+
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Checksum constants: BIP-173 for witness v0, BIP-350 (bech32m) for v1+.
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    let mut chk: u32 = 1;
+    for &v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+/// Validate a segwit address for `hrp` (BIP-173/BIP-350): charset, checksum
+/// variant for the witness version, and witness program length.
+fn validate_segwit_address(hrp: &str, address: &str) -> Result<(), &'static str> {
+    if address.chars().any(|c| c.is_ascii_lowercase()) && address.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("mixes upper and lower case");
+    }
+    let address = address.to_ascii_lowercase();
+    let data_part = address
+        .strip_prefix(hrp)
+        .and_then(|rest| rest.strip_prefix('1'))
+        .ok_or("has the wrong prefix")?;
+    let data: Vec<u8> = data_part
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&x| x == c).map(|p| p as u8))
+        .collect::<Option<_>>()
+        .ok_or("contains characters outside the Bech32 alphabet")?;
+    if data.len() < 7 {
+        return Err("is too short");
+    }
+
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 31));
+    values.extend(&data);
+    let version = data[0];
+    let expected = if version == 0 { BECH32_CONST } else { BECH32M_CONST };
+    if version > 16 || bech32_polymod(&values) != expected {
+        return Err("has a bad checksum");
+    }
+
+    // Regroup the 5-bit words after the version into the witness program
+    let words = &data[1..data.len() - 6];
+    let mut program = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &w in words {
+        acc = (acc << 5) | u32::from(w);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            program.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || (acc & ((1 << bits) - 1)) != 0 {
+        return Err("has invalid padding");
+    }
+    let length_ok = match version {
+        0 => program.len() == 20 || program.len() == 32,
+        _ => (2..=40).contains(&program.len()),
+    };
+    if !length_ok {
+        return Err("has the wrong witness program length");
+    }
+    Ok(())
+}
+
+fn validate_ltc_address(address: &str) -> Result<(), String> {
+    let len = address.len();
+
+    // Native SegWit: ltc1... (Bech32/Bech32m)
+    if address.to_ascii_lowercase().starts_with("ltc1") {
+        if !(43..=63).contains(&len) {
+            return Err(format!(
+                "LTC SegWit address must be 43-63 characters, got {}",
+                len
+            ));
+        }
+        return validate_segwit_address("ltc", address)
+            .map_err(|e| format!("LTC SegWit address {}", e));
+    }
+
+    // Legacy: L... (P2PKH), M... or 3... (P2SH)
+    if address.starts_with('L') || address.starts_with('M') || address.starts_with('3') {
+        if !(26..=34).contains(&len) {
+            return Err(format!(
+                "LTC legacy address must be 26-34 characters, got {}",
+                len
+            ));
+        }
+        let version = base58check_version(address)
+            .map_err(|e| format!("LTC legacy address {}", e))?;
+        if !LTC_VERSIONS.contains(&version) {
+            return Err("LTC legacy address has a non-Litecoin version byte".to_string());
+        }
+        return Ok(());
+    }
+
+    Err("LTC address must start with L, M, 3, or ltc1".to_string())
+}
+
+fn validate_doge_address(address: &str) -> Result<(), String> {
+    if !(address.starts_with('D') || address.starts_with('A') || address.starts_with('9')) {
+        return Err("DOGE address must start with D, A, or 9".to_string());
+    }
+    if address.len() != 34 {
+        return Err(format!(
+            "DOGE address must be 34 characters, got {}",
+            address.len()
+        ));
+    }
+    let version = base58check_version(address).map_err(|e| format!("DOGE address {}", e))?;
+    if !DOGE_VERSIONS.contains(&version) {
+        return Err("DOGE address has a non-Dogecoin version byte".to_string());
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Import an external (watch-only) wallet
 // ---------------------------------------------------------------------------
@@ -219,20 +558,20 @@ pub fn import_wallet(chain: Chain, address: String, label: String) -> Result<Wal
 
 /// Return the base secrets directory: `~/.openclaw/secrets`
 fn secrets_dir() -> Result<PathBuf, String> {
-    let home = std::env::var("HOME").map_err(|_| "HOME not set".to_string())?;
-    Ok(PathBuf::from(home).join(".openclaw/secrets"))
+    Ok(crate::config::try_openclaw_home()?.join("secrets"))
 }
 
-/// Save wallet credentials to the legacy `near_account.json` location.
+/// Save the wallet's public identity (account id and public key) to the
+/// legacy `near_account.json` location. The private key is only ever kept in
+/// `wallets/{wallet_id}.json` (see `save_wallet_key`).
 pub fn save_wallet(wallet: &WalletInfo, secrets_dir: &std::path::Path) -> Result<(), String> {
     let wallet_json = serde_json::json!({
         "account_id": wallet.account_id,
         "public_key": wallet.public_key,
-        "private_key": wallet.secret_key
     });
 
     let path = secrets_dir.join("near_account.json");
-    std::fs::write(&path, serde_json::to_string_pretty(&wallet_json).unwrap())
+    write_atomic(&path, serde_json::to_string_pretty(&wallet_json).unwrap())
         .map_err(|e| format!("Failed to write wallet: {}", e))?;
 
     // chmod 600
@@ -246,46 +585,972 @@ pub fn save_wallet(wallet: &WalletInfo, secrets_dir: &std::path::Path) -> Result
     Ok(())
 }
 
-/// Save a wallet's private-key material to
-/// `~/.openclaw/secrets/wallets/{wallet_id}.json`.
-pub fn save_wallet_key(wallet_id: &str, wallet_info: &WalletInfo) -> Result<(), String> {
+/// Earlier versions wrote the private key in plaintext to
+/// `near_account.json`. Move it into a per-wallet key file, then securely
+/// replace the legacy file with public data only. Returns true when a legacy
+/// file was scrubbed.
+pub fn scrub_legacy_wallet_file() -> Result<bool, String> {
+    scrub_legacy_wallet_file_in(&secrets_dir()?, |account_id| {
+        // Key it by the configured wallet's id when there is one
+        crate::config::read_configured_wallets()
+            .ok()
+            .and_then(|ws| ws.into_iter().find(|w| w.address == account_id))
+            .map(|w| w.id)
+    })
+}
+
+/// `scrub_legacy_wallet_file` for a given secrets directory. The legacy key
+/// is only scrubbed once it is held by the key file it was migrated to, or
+/// by an existing one for the same key pair; a key file under the wanted id
+/// holding a different key is never overwritten.
+fn scrub_legacy_wallet_file_in(
+    dir: &std::path::Path,
+    configured_id: impl FnOnce(&str) -> Option<String>,
+) -> Result<bool, String> {
+    let path = dir.join("near_account.json");
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    let legacy: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse near_account.json: {}", e))?;
+    let Some(secret_key) = legacy.get("private_key").and_then(|v| v.as_str()) else {
+        return Ok(false);
+    };
+    let field = |name: &str| {
+        legacy
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .ok_or_else(|| format!("near_account.json has no {}", name))
+    };
+    let info = WalletInfo {
+        account_id: field("account_id")?,
+        public_key: field("public_key")?,
+        secret_key: secret_key.to_string(),
+        mnemonic: None,
+    };
+
+    let wallets_dir = dir.join("wallets");
+    if key_file_holding(&wallets_dir, &info).is_none() {
+        let wallet_id = configured_id(&info.account_id).unwrap_or_else(|| info.account_id.clone());
+        let key_path = wallets_dir.join(format!("{}.json", wallet_id));
+        if key_path.exists() {
+            return Err(format!(
+                "A different key is already stored for wallet {}; near_account.json was left in place",
+                wallet_id
+            ));
+        }
+        write_wallet_key(&key_path, &info, None)?;
+        if key_file_holding(&wallets_dir, &info).as_deref() != Some(wallet_id.as_str()) {
+            return Err(format!(
+                "Migrated key for wallet {} failed to verify; near_account.json was left in place",
+                wallet_id
+            ));
+        }
+    }
+
+    secure_delete(&path)?;
+    save_wallet(&info, dir)?;
+    Ok(true)
+}
+
+/// Id of the key file in `wallets_dir` holding the same key pair as `info`
+/// (matching account id and public key, which stay readable when encrypted).
+fn key_file_holding(wallets_dir: &std::path::Path, info: &WalletInfo) -> Option<String> {
+    fs::read_dir(wallets_dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|x| x.to_str()) == Some("json"))
+        .find(|p| {
+            read_stored_key(p).is_ok_and(|stored| {
+                stored.account_id() == info.account_id && stored.public_key() == info.public_key
+            })
+        })
+        .and_then(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
+}
+
+/// Whether any key file in `wallets/` holds `account_id`.
+fn has_key_file_for_account(account_id: &str) -> Result<bool, String> {
     let wallets_dir = secrets_dir()?.join("wallets");
+    let Ok(entries) = fs::read_dir(&wallets_dir) else {
+        return Ok(false);
+    };
+    Ok(entries
+        .flatten()
+        .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("json"))
+        .filter_map(|e| read_stored_key(&e.path()).ok())
+        .any(|stored| stored.account_id() == account_id))
+}
+
+/// Save a wallet's private-key material to
+/// `~/.openclaw/secrets/wallets/{wallet_id}.json`. With a passphrase the
+/// secret key is encrypted (see `encrypt_wallet_key`); the passphrase itself
+/// is never stored.
+pub fn save_wallet_key(
+    wallet_id: &str,
+    wallet_info: &WalletInfo,
+    passphrase: Option<&str>,
+) -> Result<(), String> {
+    let path = secrets_dir()?.join("wallets").join(format!("{}.json", wallet_id));
+    write_wallet_key(&path, wallet_info, passphrase)
+}
 
-    fs::create_dir_all(&wallets_dir)
-        .map_err(|e| format!("Failed to create wallets dir: {}", e))?;
+/// Write a key file (0600), creating its directory.
+fn write_wallet_key(
+    path: &std::path::Path,
+    wallet_info: &WalletInfo,
+    passphrase: Option<&str>,
+) -> Result<(), String> {
+    if let Some(wallets_dir) = path.parent() {
+        fs::create_dir_all(wallets_dir)
+            .map_err(|e| format!("Failed to create wallets dir: {}", e))?;
+    }
 
-    let path = wallets_dir.join(format!("{}.json", wallet_id));
-    let content = serde_json::to_string_pretty(wallet_info)
-        .map_err(|e| format!("Failed to serialize wallet key: {}", e))?;
+    let content = match passphrase {
+        Some(passphrase) => serde_json::to_string_pretty(&encrypt_wallet_key(wallet_info, passphrase)?),
+        None => serde_json::to_string_pretty(&WalletInfo {
+            account_id: wallet_info.account_id.clone(),
+            public_key: wallet_info.public_key.clone(),
+            secret_key: wallet_info.secret_key.clone(),
+            mnemonic: None,
+        }),
+    }
+    .map_err(|e| format!("Failed to serialize wallet key: {}", e))?;
 
-    fs::write(&path, content)
+    write_atomic(path, content)
         .map_err(|e| format!("Failed to write wallet key: {}", e))?;
 
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
             .map_err(|e| format!("Failed to set wallet key permissions: {}", e))?;
     }
 
     Ok(())
 }
 
+/// Stems of key files in `~/.openclaw/secrets/wallets/` not referenced by any
+/// configured wallet. A key counts as referenced if its file name matches a
+/// wallet id or its account id matches a wallet address.
+pub fn list_orphaned_wallet_keys() -> Result<Vec<String>, String> {
+    let wallets_dir = secrets_dir()?.join("wallets");
+    if !wallets_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Fails (and so never prunes) if docker.env can't be read
+    let wallets = crate::config::read_configured_wallets()?;
+
+    let entries = fs::read_dir(&wallets_dir)
+        .map_err(|e| format!("Failed to read wallets dir: {}", e))?;
+
+    let mut orphaned = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if wallets.iter().any(|w| w.id == stem) {
+            continue;
+        }
+        // Unparseable key files are treated as referenced — never guess
+        let account_id = match read_stored_key(&path) {
+            Ok(stored) => stored.account_id().to_string(),
+            Err(_) => continue,
+        };
+        if wallets.iter().any(|w| w.address == account_id) {
+            continue;
+        }
+        orphaned.push(stem.to_string());
+    }
+    orphaned.sort();
+    Ok(orphaned)
+}
+
+/// Overwrite a file with zeros, flush it to disk, then delete it.
+fn secure_delete(path: &std::path::Path) -> Result<(), String> {
+    use std::io::Write;
+
+    let len = fs::metadata(path)
+        .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?
+        .len() as usize;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(&vec![0u8; len])
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to overwrite {}: {}", path.display(), e))?;
+    drop(file);
+    fs::remove_file(path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))
+}
+
+/// Securely delete orphaned wallet key files. Requires `confirm = true`.
+/// Returns the ids that were removed.
+pub fn prune_orphaned_wallet_keys(confirm: bool) -> Result<Vec<String>, String> {
+    if !confirm {
+        return Err("Pruning deletes private keys permanently; pass confirm=true".to_string());
+    }
+
+    let wallets_dir = secrets_dir()?.join("wallets");
+    let orphaned = list_orphaned_wallet_keys()?;
+    for id in &orphaned {
+        secure_delete(&wallets_dir.join(format!("{}.json", id)))?;
+    }
+    Ok(orphaned)
+}
+
 /// Load a wallet's private-key material from
 /// `~/.openclaw/secrets/wallets/{wallet_id}.json`.
-/// Returns `Ok(None)` when the file does not exist.
-pub fn load_wallet_key(wallet_id: &str) -> Result<Option<WalletInfo>, String> {
+/// Returns `Ok(None)` when the file does not exist. Encrypted keys need the
+/// passphrase. The file is never rewritten here; use
+/// `encrypt_wallet_key_file` to protect a plaintext key.
+pub fn load_wallet_key(
+    wallet_id: &str,
+    passphrase: Option<&str>,
+) -> Result<Option<WalletInfo>, String> {
     let path = secrets_dir()?.join("wallets").join(format!("{}.json", wallet_id));
 
     if !path.exists() {
         return Ok(None);
     }
 
-    let content = fs::read_to_string(&path)
+    match (read_stored_key(&path)?, passphrase) {
+        (StoredWalletKey::Plain(info), _) => Ok(Some(info)),
+        (StoredWalletKey::Encrypted(_), None) => Err(format!(
+            "Wallet key {} is encrypted; a passphrase is required",
+            wallet_id
+        )),
+        (StoredWalletKey::Encrypted(enc), Some(passphrase)) => {
+            decrypt_wallet_key(&enc, passphrase).map(Some)
+        }
+    }
+}
+
+/// Encrypt a plaintext key file under `passphrase`. The passphrase must be
+/// entered twice and match, since a typo would make the key unrecoverable;
+/// the new file is decrypted once before returning to prove it opens.
+pub fn encrypt_wallet_key_file(
+    wallet_id: &str,
+    passphrase: &str,
+    passphrase_confirm: &str,
+) -> Result<(), String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+    if passphrase != passphrase_confirm {
+        return Err("Passphrases do not match".to_string());
+    }
+
+    let path = secrets_dir()?.join("wallets").join(format!("{}.json", wallet_id));
+    if !path.exists() {
+        return Err(format!("No key file for wallet {}", wallet_id));
+    }
+    let info = match read_stored_key(&path)? {
+        StoredWalletKey::Plain(info) => info,
+        StoredWalletKey::Encrypted(_) => {
+            return Err(format!("Wallet key {} is already encrypted", wallet_id))
+        }
+    };
+
+    save_wallet_key(wallet_id, &info, Some(passphrase))?;
+    match load_wallet_key(wallet_id, Some(passphrase))? {
+        Some(loaded) if loaded.secret_key == info.secret_key => Ok(()),
+        _ => {
+            // Never leave the key in a state we can't open
+            save_wallet_key(wallet_id, &info, None)?;
+            Err(format!("Encrypted key for wallet {} failed to verify; left unencrypted", wallet_id))
+        }
+    }
+}
+
+/// Whether a wallet's key file is passphrase-protected. `None` when there is
+/// no key file.
+pub fn is_wallet_key_encrypted(wallet_id: &str) -> Result<Option<bool>, String> {
+    let path = secrets_dir()?.join("wallets").join(format!("{}.json", wallet_id));
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(matches!(read_stored_key(&path)?, StoredWalletKey::Encrypted(_))))
+}
+
+// ---------------------------------------------------------------------------
+// Key encryption at rest
+// ---------------------------------------------------------------------------
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Encrypted key file. Only the secret key is sealed; account and public key
+/// stay readable so orphan detection works without unlocking.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedWalletKey {
+    account_id: String,
+    public_key: String,
+    /// Always "argon2id"
+    kdf: String,
+    /// Argon2 memory cost in KiB
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    /// hex
+    salt: String,
+    /// Always "xchacha20poly1305"
+    cipher: String,
+    /// hex, 24 bytes
+    nonce: String,
+    /// hex; authenticated with account_id as associated data
+    ciphertext: String,
+}
+
+/// Either on-disk format. Encrypted is tried first since it never has a
+/// `secret_key` field.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredWalletKey {
+    Encrypted(EncryptedWalletKey),
+    Plain(WalletInfo),
+}
+
+impl StoredWalletKey {
+    fn account_id(&self) -> &str {
+        match self {
+            StoredWalletKey::Encrypted(enc) => &enc.account_id,
+            StoredWalletKey::Plain(info) => &info.account_id,
+        }
+    }
+
+    fn public_key(&self) -> &str {
+        match self {
+            StoredWalletKey::Encrypted(enc) => &enc.public_key,
+            StoredWalletKey::Plain(info) => &info.public_key,
+        }
+    }
+}
+
+fn read_stored_key(path: &std::path::Path) -> Result<StoredWalletKey, String> {
+    let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read wallet key: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse wallet key: {}", e))
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+) -> Result<[u8; 32], String> {
+    let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = [0u8; 32];
+    argon
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt_wallet_key(info: &WalletInfo, passphrase: &str) -> Result<EncryptedWalletKey, String> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 24];
+    OsRng.fill(&mut salt);
+    OsRng.fill(&mut nonce);
+
+    let params = argon2::Params::default();
+    let key = derive_key(passphrase, &salt, params.m_cost(), params.t_cost(), params.p_cost())?;
+    let ciphertext = XChaCha20Poly1305::new((&key).into())
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: info.secret_key.as_bytes(),
+                aad: info.account_id.as_bytes(),
+            },
+        )
+        .map_err(|_| "Failed to encrypt wallet key".to_string())?;
+
+    Ok(EncryptedWalletKey {
+        account_id: info.account_id.clone(),
+        public_key: info.public_key.clone(),
+        kdf: "argon2id".to_string(),
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        salt: hex::encode(salt),
+        cipher: "xchacha20poly1305".to_string(),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn decrypt_wallet_key(enc: &EncryptedWalletKey, passphrase: &str) -> Result<WalletInfo, String> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+    if enc.kdf != "argon2id" || enc.cipher != "xchacha20poly1305" {
+        return Err(format!("Unsupported wallet key format: {} / {}", enc.kdf, enc.cipher));
+    }
+    let unhex = |field: &str, v: &str| {
+        hex::decode(v).map_err(|e| format!("Corrupt wallet key {}: {}", field, e))
+    };
+    let salt = unhex("salt", &enc.salt)?;
+    let nonce = unhex("nonce", &enc.nonce)?;
+    let ciphertext = unhex("ciphertext", &enc.ciphertext)?;
+    if nonce.len() != 24 {
+        return Err("Corrupt wallet key nonce".to_string());
+    }
+
+    let key = derive_key(passphrase, &salt, enc.m_cost, enc.t_cost, enc.p_cost)?;
+    let secret = XChaCha20Poly1305::new((&key).into())
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: enc.account_id.as_bytes(),
+            },
+        )
+        .map_err(|_| "Incorrect passphrase for wallet key".to_string())?;
+
+    Ok(WalletInfo {
+        account_id: enc.account_id.clone(),
+        public_key: enc.public_key.clone(),
+        secret_key: String::from_utf8(secret)
+            .map_err(|_| "Corrupt wallet key contents".to_string())?,
+        mnemonic: None,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Message signing
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignedMessage {
+    /// "ed25519:<base58>"
+    pub signature: String,
+    /// "ed25519:<base58>"
+    pub public_key: String,
+}
+
+/// Decode an "ed25519:<base58>" key or signature, requiring one of `lens`.
+fn decode_ed25519(value: &str, what: &str, lens: &[usize]) -> Result<Vec<u8>, String> {
+    let encoded = value.strip_prefix("ed25519:").unwrap_or(value);
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| format!("Invalid {}: {}", what, e))?;
+    if !lens.contains(&bytes.len()) {
+        return Err(format!("Invalid {}: unexpected length {}", what, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+/// Sign `message` (as UTF-8 bytes) with a wallet's stored ed25519 key, to
+/// prove control of the account. Encrypted keys need the passphrase.
+pub fn sign_message(
+    wallet_id: &str,
+    message: &str,
+    passphrase: Option<&str>,
+) -> Result<SignedMessage, String> {
+    let info = load_wallet_key(wallet_id, passphrase)?
+        .ok_or_else(|| format!("No private key stored for wallet {}", wallet_id))?;
+    let signed = sign_with_key(&info, message)
+        .map_err(|e| format!("Failed to sign with wallet {}: {}", wallet_id, e))?;
+    crate::audit::record_audit_lossy(
+        "message_signed",
+        serde_json::json!({ "wallet_id": wallet_id, "public_key": signed.public_key }),
+        "ok",
+    );
+    Ok(signed)
+}
+
+/// Sign `message` with a decoded key, checking it matches its public key.
+fn sign_with_key(info: &WalletInfo, message: &str) -> Result<SignedMessage, String> {
+    use ed25519_dalek::Signer;
+
+    // NEAR secret keys are secret || public; the first 32 bytes are the seed
+    let secret = decode_ed25519(&info.secret_key, "secret key", &[32, 64])?;
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&secret[..32]);
+    let signing_key = SigningKey::from_bytes(&seed);
+
+    let public_key = format!(
+        "ed25519:{}",
+        bs58::encode(signing_key.verifying_key().as_bytes()).into_string()
+    );
+    if public_key != info.public_key {
+        return Err("the stored key does not match its public key".to_string());
+    }
+
+    let signature = signing_key.sign(message.as_bytes());
+    Ok(SignedMessage {
+        signature: format!("ed25519:{}", bs58::encode(signature.to_bytes()).into_string()),
+        public_key,
+    })
+}
+
+/// Check a signature produced by `sign_message` (or any NEAR ed25519 signer).
+/// `Ok(false)` means well-formed but not valid for this key and message.
+pub fn verify_signature(public_key: &str, message: &str, signature: &str) -> Result<bool, String> {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 
-    let info: WalletInfo = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse wallet key: {}", e))?;
+    let key_bytes = decode_ed25519(public_key, "public key", &[32])?;
+    let sig_bytes = decode_ed25519(signature, "signature", &[64])?;
+    let verifying_key = VerifyingKey::from_bytes(&key_bytes.try_into().expect("length checked"))
+        .map_err(|e| format!("Invalid public key: {}", e))?;
+    let signature = Signature::from_slice(&sig_bytes)
+        .map_err(|e| format!("Invalid signature: {}", e))?;
 
-    Ok(Some(info))
+    Ok(verifying_key.verify(message.as_bytes(), &signature).is_ok())
+}
+
+// ---------------------------------------------------------------------------
+// Balance lookup
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Balance {
+    pub chain: Chain,
+    pub address: String,
+    /// e.g. "NEAR", "ETH"
+    pub symbol: String,
+    /// Human-readable amount, e.g. "12.5"
+    pub amount: String,
+    /// Amount in the chain's smallest unit (yoctoNEAR, wei)
+    pub raw: String,
+    pub decimals: u32,
+    pub rpc_url: String,
+}
+
+/// Render a base-unit amount with `decimals` places, trimming trailing zeros.
+fn format_base_units(raw: u128, decimals: u32) -> String {
+    let digits = format!("{:0>width$}", raw, width = decimals as usize + 1);
+    let (whole, frac) = digits.split_at(digits.len() - decimals as usize);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, frac)
+    }
+}
+
+async fn json_rpc(
+    url: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let client = crate::config::http_client_builder(crate::config::HttpDestination::Remote)?
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let resp = client
+        .post(url)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "nyx",
+            "method": method,
+            "params": params,
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("RPC request to {} failed: {}", url, e))?;
+    if !resp.status().is_success() {
+        return Err(format!("RPC {} returned HTTP {}", url, resp.status()));
+    }
+    resp.json()
+        .await
+        .map_err(|e| format!("Failed to parse RPC response: {}", e))
+}
+
+/// On-chain native balance of `address`. Supports NEAR and ETH; endpoints
+/// come from `config::get_rpc_endpoint`.
+pub async fn get_balance(chain: &Chain, address: &str) -> Result<Balance, String> {
+    validate_address(chain, address)?;
+    let rpc_url = crate::config::get_rpc_endpoint(chain)
+        .ok_or_else(|| format!("Balance lookup is not supported for {} yet", chain))?;
+
+    let (symbol, decimals, raw) = match chain {
+        Chain::NEAR => {
+            let resp = json_rpc(
+                &rpc_url,
+                "query",
+                serde_json::json!({
+                    "request_type": "view_account",
+                    "finality": "final",
+                    "account_id": address,
+                }),
+            )
+            .await?;
+            let raw = match resp.pointer("/result/amount").and_then(|v| v.as_str()) {
+                Some(amount) => amount
+                    .parse::<u128>()
+                    .map_err(|e| format!("Invalid NEAR balance '{}': {}", amount, e))?,
+                // Implicit accounts don't exist on chain until first funded
+                None if resp.pointer("/error/cause/name").and_then(|v| v.as_str())
+                    == Some("UNKNOWN_ACCOUNT") => 0,
+                None => return Err(format!("NEAR RPC error: {}", resp.get("error").unwrap_or(&resp))),
+            };
+            ("NEAR", 24, raw)
+        }
+        Chain::ETH => {
+            let resp = json_rpc(&rpc_url, "eth_getBalance", serde_json::json!([address, "latest"]))
+                .await?;
+            let hex_amount = resp
+                .get("result")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("ETH RPC error: {}", resp.get("error").unwrap_or(&resp)))?;
+            let raw = u128::from_str_radix(hex_amount.trim_start_matches("0x"), 16)
+                .map_err(|e| format!("Invalid ETH balance '{}': {}", hex_amount, e))?;
+            ("ETH", 18, raw)
+        }
+        _ => return Err(format!("Balance lookup not supported for {:?}", chain)),
+    };
+
+    Ok(Balance {
+        chain: chain.clone(),
+        address: address.to_string(),
+        symbol: symbol.to_string(),
+        amount: format_base_units(raw, decimals),
+        raw: raw.to_string(),
+        decimals,
+        rpc_url,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletBalance {
+    pub wallet_id: String,
+    pub label: String,
+    pub balance: Option<Balance>,
+    /// Set when this wallet's lookup failed
+    pub error: Option<String>,
+}
+
+/// Balances of every configured wallet on a chain with lookup support,
+/// optionally limited to one chain. A failed lookup is reported on its
+/// wallet instead of failing the whole call.
+pub async fn get_configured_balances(chain: Option<&Chain>) -> Result<Vec<WalletBalance>, String> {
+    let wallets = crate::config::read_configured_wallets()?;
+    let mut out = Vec::new();
+    for w in wallets {
+        if chain.is_some_and(|c| *c != w.chain) || crate::config::get_rpc_endpoint(&w.chain).is_none() {
+            continue;
+        }
+        let (balance, error) = match get_balance(&w.chain, &w.address).await {
+            Ok(b) => (Some(b), None),
+            Err(e) => (None, Some(e)),
+        };
+        out.push(WalletBalance {
+            wallet_id: w.id,
+            label: w.label,
+            balance,
+            error,
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABANDON_MNEMONIC: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    // RFC 8032 section 7.1, test 1 (empty message)
+    const RFC8032_PUBLIC: &str = "ed25519:FVen3X669xLzsi6N2V91DoiyzHzg1uAgqiT8jZ9nS96Z";
+    const RFC8032_SECRET: &str = "ed25519:49W385L4rePHy6PAaQUovbD2aacgN4HsKXSMeUzRg4fmwXszN91JuMFrQRj3vMDpZuRF3ZknQBuRBoWQJEfXstMw";
+    const RFC8032_SIGNATURE: &str = "ed25519:5awYiUvGiDFA33EJjj4TXJG44a5afJc8QjWRpGgQiu6b23jCr7yndW2fmp9ujwqJVe32J456wV3VF78Asb1obnTc";
+
+    fn legacy_secrets_dir(
+        account_id: &str,
+        public_key: &str,
+        private_key: &str,
+    ) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("nyx-secrets-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let legacy = serde_json::json!({
+            "account_id": account_id,
+            "public_key": public_key,
+            "private_key": private_key,
+        });
+        fs::write(dir.join("near_account.json"), legacy.to_string()).unwrap();
+        dir
+    }
+
+    fn key_info(public_key: &str, secret_key: &str) -> WalletInfo {
+        WalletInfo {
+            account_id: "alice.near".to_string(),
+            public_key: public_key.to_string(),
+            secret_key: secret_key.to_string(),
+            mnemonic: None,
+        }
+    }
+
+    #[test]
+    fn legacy_key_moves_to_the_configured_wallet() {
+        let dir = legacy_secrets_dir("alice.near", "ed25519:pub", "ed25519:secret");
+        assert!(scrub_legacy_wallet_file_in(&dir, |_| Some("w1".to_string())).unwrap());
+
+        match read_stored_key(&dir.join("wallets/w1.json")).unwrap() {
+            StoredWalletKey::Plain(info) => assert_eq!(info.secret_key, "ed25519:secret"),
+            StoredWalletKey::Encrypted(_) => panic!("migrated key should be plaintext"),
+        }
+        let legacy = fs::read_to_string(dir.join("near_account.json")).unwrap();
+        assert!(!legacy.contains("private_key"), "{}", legacy);
+        // Nothing left to scrub
+        assert!(!scrub_legacy_wallet_file_in(&dir, |_| None).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn legacy_key_is_kept_when_the_wallet_id_holds_another_key() {
+        let dir = legacy_secrets_dir("alice.near", "ed25519:pub", "ed25519:secret");
+        let other = dir.join("wallets/w1.json");
+        write_wallet_key(
+            &other,
+            &key_info("ed25519:other", "ed25519:other-secret"),
+            None,
+        )
+        .unwrap();
+
+        let err = scrub_legacy_wallet_file_in(&dir, |_| Some("w1".to_string())).unwrap_err();
+        assert!(err.contains("left in place"), "{}", err);
+        let legacy = fs::read_to_string(dir.join("near_account.json")).unwrap();
+        assert!(legacy.contains("ed25519:secret"));
+        match read_stored_key(&other).unwrap() {
+            StoredWalletKey::Plain(info) => assert_eq!(info.secret_key, "ed25519:other-secret"),
+            StoredWalletKey::Encrypted(_) => panic!("key file should be untouched"),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn legacy_key_already_held_by_another_file_is_scrubbed() {
+        let dir = legacy_secrets_dir("alice.near", "ed25519:pub", "ed25519:secret");
+        write_wallet_key(
+            &dir.join("wallets/old-id.json"),
+            &key_info("ed25519:pub", "ed25519:secret"),
+            None,
+        )
+        .unwrap();
+
+        assert!(scrub_legacy_wallet_file_in(&dir, |_| Some("w1".to_string())).unwrap());
+        assert!(!dir.join("wallets/w1.json").exists());
+        let legacy = fs::read_to_string(dir.join("near_account.json")).unwrap();
+        assert!(!legacy.contains("private_key"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ltc_addresses_need_a_valid_checksum_and_version() {
+        for ok in [
+            "LhV2arStU45wCohKcyheHWMM16NZe85jMP",
+            "MWAEZ53TtR1gqfyVfpNHFktvG6tT5nxR1H",
+            "3Px6FBdVwJAG3AhbZwNwS7eWwQJ16Q4f5b",
+            "ltc1q7skf6twj283gwjmaxj775akkhh8an6zafhy252",
+            "LTC1Q7SKF6TWJ283GWJMAXJ775AKKHH8AN6ZAFHY252",
+            "ltc1q5td4l9zsefjgqk9v9wjn06aqsj30zgsnn5atea43n7vc7thrrcgq8s8xgc",
+            "ltc1p5td4l9zsefjgqk9v9wjn06aqsj30zgsnn5atea43n7vc7thrrcgqd880sy",
+        ] {
+            assert!(validate_ltc_address(ok).is_ok(), "{}", ok);
+        }
+        for bad in [
+            // Last character changed: checksum mismatch
+            "LhV2arStU45wCohKcyheHWMM16NZe85jMQ",
+            // Valid base58check, but a Dogecoin version byte
+            "DTQAru5hgokAV1BmBRhuZFTBg1jatbANM7",
+            // Bitcoin P2PKH
+            "1PG5Ke94PPqsx11ASqiM1VHant1HYcUKpN",
+            "ltc1q7skf6twj283gwjmaxj775akkhh8an6zafhy253",
+            // Witness v0 with a bech32m checksum
+            "ltc1q7skf6twj283gwjmaxj775akkhh8an6zaut5x3g",
+            "ltc1Q7skf6twj283gwjmaxj775akkhh8an6zafhy252",
+            "bc1q7skf6twj283gwjmaxj775akkhh8an6zadt7wv6",
+        ] {
+            assert!(validate_ltc_address(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn doge_addresses_need_a_valid_checksum_and_version() {
+        assert!(validate_doge_address("DTQAru5hgokAV1BmBRhuZFTBg1jatbANM7").is_ok());
+        assert!(validate_doge_address("AEhLz2hQ1N39wY54z53MgFGtdyg3As8UeV").is_ok());
+
+        assert!(validate_doge_address("DTQAru5hgokAV1BmBRhuZFTBg1jatbANM8").is_err());
+        assert!(validate_doge_address("LhV2arStU45wCohKcyheHWMM16NZe85jMP").is_err());
+        assert!(validate_doge_address("3Px6FBdVwJAG3AhbZwNwS7eWwQJ16Q4f5b").is_err());
+        assert!(validate_doge_address("DTQAru5hgokAV1BmBRhuZFTBg1jatbANM").is_err());
+    }
+
+    #[test]
+    fn slip10_matches_spec_vectors() {
+        // SLIP-0010 ed25519 test vector 1
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let cases: [(&[u32], &str); 4] = [
+            (&[], "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"),
+            (&[0], "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"),
+            (&[0, 1], "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2"),
+            (&[0, 1, 2], "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(hex::encode(slip10_ed25519(&seed, path)), expected, "path {:?}", path);
+        }
+    }
+
+    #[test]
+    fn bip39_seed_matches_reference_vector() {
+        let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, ABANDON_MNEMONIC).unwrap();
+        assert_eq!(
+            hex::encode(mnemonic.to_seed("")),
+            "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4"
+        );
+    }
+
+    #[test]
+    fn derives_known_near_account() {
+        let (info, config) = derive_near_wallet(ABANDON_MNEMONIC, 0).unwrap();
+        assert_eq!(info.account_id, "572dd46d19e80993d9c45d4989bf47b4cee83124edf950026b271b0f4fb33cb4");
+        assert_eq!(info.public_key, "ed25519:6j4b6zUaty6fD1awqcGCCU9JYGCWYUgdJhQrzfZhqE25");
+        assert_eq!(config.address, info.account_id);
+        assert_eq!(info.mnemonic, None);
+
+        // Case and spacing don't matter; the index does
+        let messy = format!("  {}  ", ABANDON_MNEMONIC.to_uppercase().replace(' ', "   "));
+        assert_eq!(derive_near_wallet(&messy, 0).unwrap().0.account_id, info.account_id);
+        let (second, second_config) = derive_near_wallet(ABANDON_MNEMONIC, 1).unwrap();
+        assert_eq!(second.account_id, "ab67d76f59e71a87c09f075e3405267822d22a6f1286979d7a239f1a6530fc9a");
+        assert_eq!(second_config.label, "NEAR wallet #1");
+
+        assert!(derive_near_wallet("abandon abandon abandon", 0).is_err());
+        assert!(derive_near_wallet(ABANDON_MNEMONIC, HARDENED_OFFSET).is_err());
+    }
+
+    #[test]
+    fn signs_rfc8032_vector() {
+        let info = WalletInfo {
+            account_id: String::new(),
+            public_key: RFC8032_PUBLIC.to_string(),
+            secret_key: RFC8032_SECRET.to_string(),
+            mnemonic: None,
+        };
+        let signed = sign_with_key(&info, "").unwrap();
+        assert_eq!(signed.signature, RFC8032_SIGNATURE);
+        assert_eq!(signed.public_key, RFC8032_PUBLIC);
+        assert_eq!(verify_signature(RFC8032_PUBLIC, "", RFC8032_SIGNATURE), Ok(true));
+        assert_eq!(verify_signature(RFC8032_PUBLIC, "tampered", RFC8032_SIGNATURE), Ok(false));
+    }
+
+    #[test]
+    fn signs_with_derived_wallet() {
+        let (info, _) = derive_near_wallet(ABANDON_MNEMONIC, 0).unwrap();
+        let signed = sign_with_key(&info, "nyx proves ownership").unwrap();
+        assert_eq!(
+            signed.signature,
+            "ed25519:2ABxLFtinwtVNqDZo1KgEPVHNz9Bf2616VJG5jLHxELjfaRa7Cd2sw4doS4aYvxFXZtwnKFJmMGVxK1sDX92gMCG"
+        );
+        assert_eq!(verify_signature(&info.public_key, "nyx proves ownership", &signed.signature), Ok(true));
+
+        // A key that doesn't match its public key is refused
+        let mismatched = WalletInfo { public_key: RFC8032_PUBLIC.to_string(), ..info };
+        assert!(sign_with_key(&mismatched, "x").is_err());
+        assert!(verify_signature("ed25519:abc", "x", &signed.signature).is_err());
+    }
+
+    #[test]
+    fn near_address_rules() {
+        let implicit = "572dd46d19e80993d9c45d4989bf47b4cee83124edf950026b271b0f4fb33cb4";
+        let longest = format!("{}.near", "a".repeat(NEAR_ACCOUNT_MAX_LEN - 5));
+        let too_long = format!("{}.near", "a".repeat(NEAR_ACCOUNT_MAX_LEN - 4));
+        let upper_implicit = implicit.to_uppercase();
+        let short_hex = &implicit[..63];
+
+        // (address, expected error substring; None means valid)
+        let cases: Vec<(&str, Option<&str>)> = vec![
+            (implicit, None),
+            ("alice.near", None),
+            ("a-b_c.near", None),
+            ("app.alice.testnet", None),
+            ("0.near", None),
+            (&longest, None),
+            (&too_long, Some("at most 64")),
+            (&upper_implicit, Some("lowercase hex")),
+            (short_hex, Some("must be a .near/.testnet name")),
+            ("alice.nea", Some("must be a .near/.testnet name")),
+            ("Foo.near", Some("must be lowercase")),
+            ("foo..bar.near", Some("consecutive separators")),
+            ("a__b.near", Some("consecutive separators")),
+            ("alice-.near", Some("consecutive separators")),
+            ("-alice.near", Some("cannot start with '-'")),
+            (".near", Some("cannot start with '.'")),
+            ("al ice.near", Some("invalid character ' '")),
+            ("alice@x.near", Some("invalid character '@'")),
+        ];
+        for (address, expected) in cases {
+            match (validate_address(&Chain::NEAR, address), expected) {
+                (Ok(()), None) => {}
+                (Err(e), Some(fragment)) => {
+                    assert!(e.contains(fragment), "{:?}: {:?} should mention {:?}", address, e, fragment)
+                }
+                (result, expected) => panic!("{:?}: got {:?}, expected {:?}", address, result, expected),
+            }
+        }
+    }
+
+    #[test]
+    fn near_account_id_edges() {
+        assert!(validate_near_account_id("ab").is_ok());
+        assert!(validate_near_account_id(&"a".repeat(NEAR_ACCOUNT_MAX_LEN)).is_ok());
+        assert!(validate_near_account_id("a").unwrap_err().contains("at least 2"));
+        assert!(validate_near_account_id("").unwrap_err().contains("at least 2"));
+        assert!(validate_near_account_id("alice_").unwrap_err().contains("cannot end with '_'"));
+        assert!(validate_near_account_id("alice.").unwrap_err().contains("cannot end with '.'"));
+        assert!(validate_near_account_id("_alice").unwrap_err().contains("cannot start with '_'"));
+    }
+
+    #[test]
+    fn eip55_matches_spec_vectors() {
+        // From EIP-55: all-caps, all-lower and mixed-case examples
+        for address in [
+            "0x52908400098527886E0F7030069857D2E4169EE7",
+            "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+            "0xde709f2102306220921060314715629080e2fb77",
+            "0x27b1fdb04752bbc536007a920d24acb045561c26",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let hex_part = &address[2..];
+            let has_lower = hex_part.chars().any(|c| c.is_ascii_lowercase());
+            let has_upper = hex_part.chars().any(|c| c.is_ascii_uppercase());
+            if has_lower && has_upper {
+                assert_eq!(eip55_checksum(hex_part), hex_part);
+            }
+            assert!(validate_address(&Chain::ETH, address).is_ok(), "{}", address);
+        }
+        assert_eq!(
+            eip55_checksum("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"),
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+
+        let err = validate_address(&Chain::ETH, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").unwrap_err();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+        assert!(validate_address(&Chain::ETH, "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!(validate_address(&Chain::ETH, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+        assert!(validate_address(&Chain::ETH, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg").is_err());
+    }
 }